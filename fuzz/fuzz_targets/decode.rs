@@ -0,0 +1,12 @@
+//! Decoding arbitrary bytes must fail or wait for more input, but never panic
+
+#![no_main]
+
+use instant_epp::codec::{Decoder, EppCodec};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let mut codec = EppCodec::new().max_frame_len(64 * 1024);
+    let mut buf = data.to_vec();
+    while let Ok(Some(_)) = codec.decode(&mut buf) {}
+});
@@ -0,0 +1,23 @@
+//! Every encoded payload decodes to itself, also when split across reads
+
+#![no_main]
+
+use instant_epp::codec::{Decoder, Encoder, EppCodec};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: (&str, u8)| {
+    let (payload, split) = input;
+    let mut codec = EppCodec::new();
+    let mut encoded = Vec::new();
+    codec.encode(payload, &mut encoded).unwrap();
+
+    let split = (split as usize).min(encoded.len());
+    let mut buf = encoded[..split].to_vec();
+    if split < encoded.len() {
+        assert_eq!(codec.decode(&mut buf).unwrap(), None);
+        buf.extend_from_slice(&encoded[split..]);
+    }
+
+    assert_eq!(codec.decode(&mut buf).unwrap().as_deref(), Some(payload));
+    assert!(buf.is_empty());
+});
@@ -0,0 +1,282 @@
+//! A blocking wrapper around the async [`EppClient`](crate::EppClient)
+//!
+//! For scripts and command-line tools that don't otherwise use async code. Each client owns a
+//! single-threaded tokio runtime on which it drives the async client; calls block the current
+//! thread until they complete.
+//!
+//! The methods of this client must not be called from within an async runtime, as blocking
+//! there would stall the runtime; they panic if they are. Use the async client instead.
+
+use std::fmt::Debug;
+use std::time::{Duration, Instant};
+
+use instant_xml::FromXmlOwned;
+use tokio::runtime::{self, Runtime};
+use tokio::sync::broadcast;
+
+#[cfg(feature = "__rustls")]
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+
+use crate::circuit_breaker::CircuitBreaker;
+#[cfg(feature = "__rustls")]
+use crate::client::RustlsConnector;
+use crate::client::{
+    Archiver, ConnectionEvent, Connector, Credentials, Keepalive, PendingStore, RequestData,
+};
+use crate::common::LanguageTag;
+use crate::error::Error;
+use crate::hello::{Greeting, GreetingDiff};
+use crate::layer::Layer;
+#[cfg(feature = "prometheus")]
+use crate::metrics::Metrics;
+use crate::poll::PollData;
+use crate::rate_limit::RateLimiter;
+use crate::request::{Command, Extension, Transaction};
+use crate::response::Response;
+use crate::xml::NamespacePrefixes;
+
+/// A blocking `EppClient`, see the [module documentation](self)
+pub struct EppClient<C: Connector> {
+    inner: crate::EppClient<C>,
+    runtime: Runtime,
+}
+
+#[cfg(feature = "__rustls")]
+impl EppClient<RustlsConnector> {
+    /// Connect to the specified `server` over TLS, see [`crate::EppClient::connect()`]
+    pub fn connect(
+        registry: String,
+        server: (String, u16),
+        identity: Option<(Vec<CertificateDer<'static>>, PrivateKeyDer<'static>)>,
+        timeout: Duration,
+    ) -> Result<Self, Error> {
+        let runtime = new_runtime()?;
+        let inner = runtime.block_on(crate::EppClient::connect(
+            registry, server, identity, timeout,
+        ))?;
+        Ok(Self { inner, runtime })
+    }
+
+    /// Replace the TLS client certificate and key, see [`crate::EppClient::set_identity()`]
+    pub fn set_identity(
+        &mut self,
+        certs: Vec<CertificateDer<'static>>,
+        key: PrivateKeyDer<'static>,
+    ) -> Result<(), Error> {
+        self.inner.set_identity(certs, key)
+    }
+}
+
+impl<C: Connector> EppClient<C> {
+    /// Create an `EppClient` from a `connector`, see [`crate::EppClient::new()`]
+    pub fn new(connector: C, registry: String, timeout: Duration) -> Result<Self, Error> {
+        let runtime = new_runtime()?;
+        let inner = runtime.block_on(crate::EppClient::new(connector, registry, timeout))?;
+        Ok(Self { inner, runtime })
+    }
+
+    /// Executes an EPP Hello call and returns the response as a `Greeting`
+    pub fn hello(&mut self) -> Result<Greeting, Error> {
+        self.runtime.block_on(self.inner.hello())
+    }
+
+    /// Compare a new greeting to the first, see [`crate::EppClient::greeting_changed()`]
+    pub fn greeting_changed(&mut self) -> Result<Option<GreetingDiff>, Error> {
+        self.runtime.block_on(self.inner.greeting_changed())
+    }
+
+    /// Send a command, see [`crate::EppClient::transact()`]
+    pub fn transact<'c, 'e, Cmd, Ext>(
+        &mut self,
+        data: impl Into<RequestData<'c, 'e, Cmd, Ext>>,
+        id: &str,
+    ) -> Result<Response<Cmd::Response, Ext::Response>, Error>
+    where
+        Cmd: Transaction<Ext> + Command + 'c,
+        Ext: Extension + 'e,
+    {
+        self.runtime.block_on(self.inner.transact(data, id))
+    }
+
+    /// Poll with typed extension data, see [`crate::EppClient::poll_with_ext()`]
+    pub fn poll_with_ext<T: FromXmlOwned + Debug>(
+        &mut self,
+        id: &str,
+    ) -> Result<Response<PollData, T>, Error> {
+        self.runtime.block_on(self.inner.poll_with_ext(id))
+    }
+
+    /// Accepts raw EPP XML and returns the raw EPP XML response to it
+    pub fn transact_xml(&mut self, xml: &str) -> Result<String, Error> {
+        self.runtime.block_on(self.inner.transact_xml(xml))
+    }
+
+    /// Send the keepalive command, see [`crate::EppClient::keepalive()`]
+    pub fn keepalive(&mut self, id: &str) -> Result<(), Error> {
+        self.runtime.block_on(self.inner.keepalive(id))
+    }
+
+    /// Choose the command sent by `keepalive()`, see [`crate::EppClient::set_keepalive()`]
+    pub fn set_keepalive(&mut self, keepalive: Keepalive) {
+        self.inner.set_keepalive(keepalive);
+    }
+
+    /// How long ago the last response was received
+    pub fn idle(&self) -> Duration {
+        self.inner.idle()
+    }
+
+    /// Returns the greeting received on establishment of the connection in raw xml form
+    pub fn xml_greeting(&self) -> String {
+        self.inner.xml_greeting()
+    }
+
+    /// Returns the greeting received on establishment of the connection as an `Greeting`
+    pub fn greeting(&self) -> Result<Greeting, Error> {
+        self.inner.greeting()
+    }
+
+    /// Subscribe to state transitions, see [`crate::EppClient::events()`]
+    ///
+    /// Use `blocking_recv()` to wait for events outside of an async runtime.
+    pub fn events(&self) -> broadcast::Receiver<ConnectionEvent> {
+        self.inner.events()
+    }
+
+    /// The client's Prometheus metrics, see [`crate::EppClient::metrics()`]
+    #[cfg(feature = "prometheus")]
+    pub fn metrics(&self) -> &Metrics {
+        self.inner.metrics()
+    }
+
+    /// Limit how long a request may wait, see [`crate::EppClient::set_queue_ttl()`]
+    pub fn set_queue_ttl(&mut self, ttl: Option<Duration>) {
+        self.inner.set_queue_ttl(ttl);
+    }
+
+    /// Compress payloads with deflate, see [`crate::EppClient::set_deflate()`]
+    #[cfg(feature = "deflate")]
+    pub fn set_deflate(&mut self, enabled: bool) {
+        self.inner.set_deflate(enabled);
+    }
+
+    /// Log frame headers and previews, see [`crate::EppClient::set_wire_dump()`]
+    pub fn set_wire_dump(&mut self, enabled: bool) {
+        self.inner.set_wire_dump(enabled);
+    }
+
+    /// Write requests with namespace prefixes, see [`crate::EppClient::set_namespace_prefixes()`]
+    pub fn set_namespace_prefixes(&mut self, prefixes: NamespacePrefixes) {
+        self.inner.set_namespace_prefixes(prefixes);
+    }
+
+    /// Serialize empty containers, see [`crate::EppClient::set_keep_empty_elements()`]
+    pub fn set_keep_empty_elements(&mut self, keep: bool) {
+        self.inner.set_keep_empty_elements(keep);
+    }
+
+    /// Only accept RFC 3339 dates in UTC, see [`crate::EppClient::set_strict_dates()`]
+    pub fn set_strict_dates(&mut self, strict: bool) {
+        self.inner.set_strict_dates(strict);
+    }
+
+    /// Start generated transaction IDs with `prefix`, see [`crate::EppClient::set_transaction_prefix()`]
+    pub fn set_transaction_prefix(&mut self, prefix: &str) -> Result<(), Error> {
+        self.inner.set_transaction_prefix(prefix)
+    }
+
+    /// Check the echoed transaction IDs, see [`crate::EppClient::set_verify_transaction_ids()`]
+    pub fn set_verify_transaction_ids(&mut self, verify: bool) {
+        self.inner.set_verify_transaction_ids(verify);
+    }
+
+    /// Check commands against the greeting, see [`crate::EppClient::set_check_services()`]
+    pub fn set_check_services(&mut self, check: bool) {
+        self.inner.set_check_services(check);
+    }
+
+    /// Log in again after the server ends the session, see [`crate::EppClient::set_relogin()`]
+    pub fn set_relogin(&mut self, enabled: bool) {
+        self.inner.set_relogin(enabled);
+    }
+
+    /// Tag generated IDs with the current span, see [`crate::EppClient::set_transaction_span_tag()`]
+    pub fn set_transaction_span_tag(&mut self, enabled: bool) {
+        self.inner.set_transaction_span_tag(enabled);
+    }
+
+    /// Generate a client transaction ID, see [`crate::EppClient::next_transaction_id()`]
+    pub fn next_transaction_id(&mut self) -> String {
+        self.inner.next_transaction_id()
+    }
+
+    /// Set the credentials used to log in again after `reconnect()`
+    pub fn set_credentials(&mut self, credentials: Option<Credentials>) {
+        self.inner.set_credentials(credentials);
+    }
+
+    /// Prefer responses in one of `languages`, see [`crate::EppClient::set_languages()`]
+    pub fn set_languages(&mut self, languages: Vec<LanguageTag>) {
+        self.inner.set_languages(languages);
+    }
+
+    /// Archive every request and its response, see [`crate::EppClient::set_archiver()`]
+    pub fn set_archiver(&mut self, archiver: Option<Box<dyn Archiver>>) {
+        self.inner.set_archiver(archiver);
+    }
+
+    /// Record transform commands, see [`crate::EppClient::set_pending_store()`]
+    pub fn set_pending_store(&mut self, store: Option<Box<dyn PendingStore>>) {
+        self.inner.set_pending_store(store);
+    }
+
+    /// Add a layer commands are sent through, see [`crate::EppClient::layer()`]
+    pub fn layer(&mut self, layer: impl Layer + 'static) {
+        self.inner.layer(layer);
+    }
+
+    /// Limit the rate of commands, see [`crate::EppClient::set_rate_limiter()`]
+    pub fn set_rate_limiter(&mut self, limiter: Option<RateLimiter>) {
+        self.inner.set_rate_limiter(limiter);
+    }
+
+    /// The rate limiter, if one is set
+    pub fn rate_limiter(&self) -> Option<&RateLimiter> {
+        self.inner.rate_limiter()
+    }
+
+    /// Fail fast after connection failures, see [`crate::EppClient::set_circuit_breaker()`]
+    pub fn set_circuit_breaker(&mut self, breaker: Option<CircuitBreaker>) {
+        self.inner.set_circuit_breaker(breaker);
+    }
+
+    /// The circuit breaker, if one is set
+    pub fn circuit_breaker(&self) -> Option<&CircuitBreaker> {
+        self.inner.circuit_breaker()
+    }
+
+    /// Reconnect to the server, logging in if credentials were set
+    pub fn reconnect(&mut self) -> Result<(), Error> {
+        self.runtime.block_on(self.inner.reconnect())
+    }
+
+    /// Close the connection without logging out, see [`crate::EppClient::shutdown()`]
+    ///
+    /// Blocks until the connection is shut down or the client's timeout elapses. Use
+    /// `close_gracefully()` to log out first.
+    pub fn shutdown(self) -> Result<(), Error> {
+        self.runtime.block_on(self.inner.shutdown())
+    }
+
+    /// Finish any in-flight request, log out and close the connection
+    pub fn close_gracefully(self, id: &str, deadline: Instant) -> Result<(), Error> {
+        self.runtime
+            .block_on(self.inner.close_gracefully(id, deadline))
+    }
+}
+
+fn new_runtime() -> Result<Runtime, Error> {
+    Ok(runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?)
+}
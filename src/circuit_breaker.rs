@@ -0,0 +1,125 @@
+//! Failing fast while a registry is unreachable
+//!
+//! When a registry is down for maintenance, every command and reconnect waits for a timeout or
+//! a refused connection, and many clients reconnecting at once only add to the load once it
+//! comes back. A [`CircuitBreaker`] counts consecutive connection failures: I/O errors and
+//! timeouts from requests, and failed reconnects. Once `threshold` of them have happened in a
+//! row, the circuit opens and commands and reconnects fail immediately with
+//! [`Error::CircuitOpen`] instead.
+//!
+//! While the circuit is open, one attempt is let through every `probe_interval`: a `<hello>`
+//! sent before the command, or the reconnect itself. If it succeeds, the circuit closes again.
+
+use std::time::{Duration, Instant};
+
+use tracing::{debug, warn};
+
+use crate::Error;
+
+/// Fails commands fast after repeated connection failures, see the [module
+/// documentation](self)
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    threshold: u32,
+    probe_interval: Duration,
+    failures: u32,
+    // When the next probe may be sent, while the circuit is open
+    probe_at: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    /// Open the circuit after `threshold` consecutive failures, probing every `probe_interval`
+    pub fn new(threshold: u32, probe_interval: Duration) -> Self {
+        Self {
+            threshold: threshold.max(1),
+            probe_interval,
+            failures: 0,
+            probe_at: None,
+        }
+    }
+
+    /// Whether commands currently fail fast
+    pub fn is_open(&self) -> bool {
+        self.probe_at.is_some()
+    }
+
+    /// The number of consecutive failures
+    pub fn failures(&self) -> u32 {
+        self.failures
+    }
+
+    /// Check whether an attempt may be made
+    ///
+    /// Returns `Ok(true)` if the circuit is open and the attempt is a probe.
+    pub(crate) fn acquire(&mut self, now: Instant) -> Result<bool, Error> {
+        match self.probe_at {
+            None => Ok(false),
+            Some(at) if now >= at => {
+                debug!("probing after {} failures", self.failures);
+                self.probe_at = Some(now + self.probe_interval);
+                Ok(true)
+            }
+            Some(_) => Err(Error::CircuitOpen),
+        }
+    }
+
+    pub(crate) fn success(&mut self) {
+        if self.probe_at.take().is_some() {
+            debug!("circuit closed after {} failures", self.failures);
+        }
+
+        self.failures = 0;
+    }
+
+    pub(crate) fn failure(&mut self, now: Instant) {
+        self.failures += 1;
+        if self.failures < self.threshold {
+            return;
+        }
+
+        if self.probe_at.is_none() {
+            warn!("circuit opened after {} failures", self.failures);
+        }
+
+        self.probe_at = Some(now + self.probe_interval);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::CircuitBreaker;
+    use crate::Error;
+
+    #[test]
+    fn breaker() {
+        let start = Instant::now();
+        let mut breaker = CircuitBreaker::new(2, Duration::from_secs(10));
+        assert!(!breaker.acquire(start).unwrap());
+
+        breaker.failure(start);
+        assert!(!breaker.is_open());
+        breaker.success();
+        breaker.failure(start);
+        assert!(!breaker.is_open());
+        breaker.failure(start);
+        assert!(breaker.is_open());
+        assert_eq!(breaker.failures(), 2);
+
+        // Fail fast until the probe interval has passed, then let one probe through
+        assert!(matches!(breaker.acquire(start), Err(Error::CircuitOpen)));
+        let later = start + Duration::from_secs(10);
+        assert!(breaker.acquire(later).unwrap());
+        assert!(matches!(breaker.acquire(later), Err(Error::CircuitOpen)));
+
+        // A failed probe keeps the circuit open, a successful one closes it
+        breaker.failure(later);
+        assert!(breaker.is_open());
+        let later = later + Duration::from_secs(10);
+        assert!(breaker.acquire(later).unwrap());
+        breaker.success();
+        assert!(!breaker.is_open());
+        assert!(!breaker.acquire(later).unwrap());
+    }
+}
@@ -1,17 +1,43 @@
-use std::time::Duration;
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::{self, Debug};
+use std::future::Future;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use instant_xml::FromXmlOwned;
+use tokio::io::{ReadHalf, WriteHalf};
+use tokio::sync::{broadcast, oneshot, Mutex, MutexGuard};
 
 #[cfg(feature = "__rustls")]
 use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
-use tracing::{debug, error};
+use tracing::{debug, error, warn, Span};
 
-use crate::common::NoExtension;
-pub use crate::connection::Connector;
+use crate::circuit_breaker::CircuitBreaker;
+use crate::codec::EppCodec;
+use crate::common::{LanguageTag, NoExtension};
 use crate::connection::EppConnection;
-use crate::error::Error;
-use crate::hello::{Greeting, Hello};
-use crate::request::{Command, CommandWrapper, Extension, Transaction};
-use crate::response::{Response, ResponseStatus};
-use crate::xml;
+pub use crate::connection::{
+    CloseReason, ConnectionEvent, Connector, FrameReader, FrameWriter, GreetingRetry,
+    ReceivedGreeting, ReconnectBackoff,
+};
+use crate::error::{CommandError, Error};
+use crate::hello::{Greeting, GreetingDiff, Hello};
+use crate::layer::{Incoming, Layer, Outgoing};
+use crate::login::Login;
+use crate::logout::Logout;
+#[cfg(feature = "prometheus")]
+use crate::metrics::Metrics;
+use crate::poll::{Poll, PollData, PollExtension};
+use crate::rate_limit::{CommandClass, RateLimiter};
+pub use crate::request::RequestData;
+use crate::request::{Command, Extension, Transaction};
+use crate::response::{Response, ResultClass, ResultCode};
+use crate::runtime;
+#[cfg(feature = "async-io")]
+pub use crate::runtime::Compat;
+use crate::xml::{self, NamespacePrefixes};
 
 /// An `EppClient` provides an interface to sending EPP requests to a registry
 ///
@@ -25,9 +51,7 @@ use crate::xml;
 /// # use std::net::ToSocketAddrs;
 /// # use std::time::Duration;
 /// #
-/// use instant_epp::EppClient;
-/// use instant_epp::domain::DomainCheck;
-/// use instant_epp::common::NoExtension;
+/// use instant_epp::prelude::*;
 ///
 /// # #[cfg(feature = "rustls")]
 /// # #[tokio::main]
@@ -66,6 +90,25 @@ use crate::xml;
 /// ```
 pub struct EppClient<C: Connector> {
     connection: EppConnection<C>,
+    credentials: Option<Credentials>,
+    languages: Vec<LanguageTag>,
+    archiver: Option<Box<dyn Archiver>>,
+    pending_store: Option<Box<dyn PendingStore>>,
+    circuit_breaker: Option<CircuitBreaker>,
+    rate_limiter: Option<RateLimiter>,
+    layers: Vec<Box<dyn Layer>>,
+    keepalive: Keepalive,
+    prefixes: NamespacePrefixes,
+    xml_options: xml::Options,
+    // Prefix and counter for `next_transaction_id()`
+    transaction_prefix: String,
+    transaction_count: u64,
+    transaction_span_tag: bool,
+    verify_transaction_ids: bool,
+    check_services: bool,
+    relogin: bool,
+    // When the last request was answered
+    last_exchange: Instant,
 }
 
 #[cfg(feature = "__rustls")]
@@ -76,6 +119,7 @@ impl EppClient<RustlsConnector> {
     /// and port to connect to), `hostname` is sent as the TLS server name indication and
     /// `identity` provides optional TLS client authentication (using) rustls as the TLS
     /// implementation. The `timeout` limits the time spent on any underlying network operations.
+    /// An [`Identity`] loads the certificates and key from PEM files or a PKCS#12 bundle.
     ///
     /// Alternatively, use `EppClient::new()` with any established `AsyncRead + AsyncWrite + Unpin`
     /// implementation.
@@ -95,6 +139,21 @@ impl EppClient<RustlsConnector> {
         let connector = builder.build().map_err(|err| Error::Other(Box::new(err)))?;
         Self::new(connector, registry, timeout).await
     }
+
+    /// Replace the TLS client certificate and key
+    ///
+    /// The current connection is not affected; the new identity is used from the next
+    /// `reconnect()` onwards.
+    pub fn set_identity(
+        &mut self,
+        certs: Vec<CertificateDer<'static>>,
+        key: PrivateKeyDer<'static>,
+    ) -> Result<(), Error> {
+        self.connection
+            .connector
+            .set_identity(certs, key)
+            .map_err(|err| Error::Other(Box::new(err)))
+    }
 }
 
 impl<C: Connector> EppClient<C> {
@@ -102,6 +161,23 @@ impl<C: Connector> EppClient<C> {
     pub async fn new(connector: C, registry: String, timeout: Duration) -> Result<Self, Error> {
         Ok(Self {
             connection: EppConnection::new(connector, registry, timeout).await?,
+            credentials: None,
+            languages: Vec::new(),
+            archiver: None,
+            pending_store: None,
+            circuit_breaker: None,
+            rate_limiter: None,
+            layers: Vec::new(),
+            keepalive: Keepalive::Hello,
+            prefixes: NamespacePrefixes::default(),
+            xml_options: xml::Options::default(),
+            transaction_prefix: String::new(),
+            transaction_count: 0,
+            transaction_span_tag: false,
+            verify_transaction_ids: false,
+            check_services: false,
+            relogin: false,
+            last_exchange: Instant::now(),
         })
     }
 
@@ -110,10 +186,11 @@ impl<C: Connector> EppClient<C> {
         let xml = xml::serialize(Hello)?;
 
         debug!("{}: hello: {}", self.connection.registry, &xml);
-        let response = self.connection.transact(&xml)?.await?;
+        let response = self.exchange(&xml).await?;
         debug!("{}: greeting: {}", self.connection.registry, &response);
 
-        xml::deserialize::<Greeting>(&response)
+        self.xml_options
+            .scope(|| xml::deserialize::<Greeting>(&response))
     }
 
     pub async fn transact<'c, 'e, Cmd, Ext>(
@@ -126,26 +203,168 @@ impl<C: Connector> EppClient<C> {
         Ext: Extension + 'e,
     {
         let data = data.into();
-        let document = CommandWrapper::new(data.command, data.extension, id);
-        let xml = xml::serialize(&document)?;
+        let class = CommandClass::of(Cmd::COMMAND);
+        let mut replayed = false;
+        loop {
+            let data = RequestData::new(data.command, data.extension);
+            let error = match self.send(data, id).await {
+                Err(Error::Command(error))
+                    if self.relogin
+                        && self.credentials.is_some()
+                        && class != CommandClass::Session
+                        && error.result.code.classify() == ResultClass::SessionFatal
+                        // A plain authentication error leaves the session open
+                        && error.result.code != ResultCode::AuthenticationError =>
+                {
+                    error
+                }
+                result => return result,
+            };
+
+            warn!(
+                "{}: session ended by {} command ({}), logging in again",
+                self.connection.registry,
+                error.command,
+                error.result.code.code()
+            );
+
+            // `reconnect()` logs in through `transact()`, so box the future to allow recursion
+            let relogin = Box::pin(self.reconnect()).await.err().map(Box::new);
+            if relogin.is_some() || replayed || class != CommandClass::Query {
+                return Err(Error::SessionEnded { error, relogin });
+            }
+
+            replayed = true;
+        }
+    }
+
+    async fn send<'c, 'e, Cmd, Ext>(
+        &mut self,
+        data: RequestData<'c, 'e, Cmd, Ext>,
+        id: &str,
+    ) -> Result<Response<Cmd::Response, Ext::Response>, Error>
+    where
+        Cmd: Transaction<Ext> + Command + 'c,
+        Ext: Extension + 'e,
+    {
+        let command = data.command;
+        let mut outgoing = Outgoing::new(
+            Cmd::COMMAND,
+            id,
+            command.object_ids(),
+            command.object_uris(),
+            data.extension.is_some(),
+        );
+        for layer in &self.layers {
+            layer.request(&mut outgoing).await?;
+        }
+
+        let xml = match xml::serialize_request_with(data, id, &outgoing.attached, self.xml_options)
+        {
+            Ok(xml) => self.prefixes.apply(xml),
+            Err(Error::Request(mut err)) => {
+                err.registry = Some(self.connection.registry.clone());
+                error!("{err}");
+                return Err(Error::Request(err));
+            }
+            Err(err) => return Err(err),
+        };
+
+        if let (true, Some(menu)) = (self.check_services, &self.connection.svc_menu) {
+            if let Err(err) = menu.check(Cmd::COMMAND, &command.object_uris()) {
+                error!("{}: {err}", self.connection.registry);
+                return Err(err);
+            }
+        }
+
+        let class = CommandClass::of(Cmd::COMMAND);
+        if let Some(limiter) = &mut self.rate_limiter {
+            limiter.acquire(class).await;
+        }
+
+        let pending = match (&self.pending_store, class) {
+            (Some(store), CommandClass::Transform) => {
+                let transaction = PendingTransaction {
+                    transaction_id: id.to_owned(),
+                    command: Cmd::COMMAND,
+                    objects: command
+                        .object_ids()
+                        .into_iter()
+                        .map(str::to_owned)
+                        .collect(),
+                };
+                store.begin(&transaction).await?;
+                Some(transaction)
+            }
+            _ => None,
+        };
 
         debug!("{}: request: {}", self.connection.registry, &xml);
-        let response = self.connection.transact(&xml)?.await?;
+        let response = self.exchange(&xml).await?;
         debug!("{}: response: {}", self.connection.registry, &response);
 
-        let rsp = match xml::deserialize::<Response<Cmd::Response, Ext::Response>>(&response) {
+        let rsp = match self
+            .xml_options
+            .scope(|| xml::deserialize_response::<Cmd, Ext>(&response))
+        {
             Ok(rsp) => rsp,
             Err(e) => {
+                let response = xml::pretty(&response);
                 error!(%response, "failed to deserialize response for transaction: {e}");
                 return Err(e);
             }
         };
 
+        if self.verify_transaction_ids {
+            if let Some(received) = &rsp.tr_ids.client_tr_id {
+                if received != id {
+                    error!(
+                        "{}: response has clTRID {received:?}, expected {id:?}",
+                        self.connection.registry
+                    );
+                    return Err(Error::TransactionMismatch {
+                        expected: id.to_owned(),
+                        received: received.clone(),
+                    });
+                }
+            }
+        }
+
+        if let (Some(store), Some(transaction)) = (&self.pending_store, &pending) {
+            store.complete(transaction, rsp.result.code).await?;
+        }
+
+        #[cfg(feature = "prometheus")]
+        self.connection.metrics.response(
+            Cmd::COMMAND,
+            rsp.result.code,
+            rsp.message_queue().and_then(|queue| queue.count()),
+        );
+
+        let incoming = Incoming {
+            command: Cmd::COMMAND,
+            transaction_id: id,
+            result: &rsp.result,
+            message_queue: rsp.message_queue(),
+            tr_ids: &rsp.tr_ids,
+            xml: &response,
+            options: self.xml_options,
+        };
+        for layer in &self.layers {
+            layer.response(&incoming).await?;
+        }
+
         if rsp.result.code.is_success() {
             return Ok(rsp);
         }
 
-        let err = crate::error::Error::Command(Box::new(ResponseStatus {
+        let err = crate::error::Error::Command(Box::new(CommandError {
+            command: Cmd::COMMAND,
+            objects: command
+                .object_ids()
+                .into_iter()
+                .map(str::to_owned)
+                .collect(),
             result: rsp.result,
             tr_ids: rsp.tr_ids,
         }));
@@ -153,10 +372,121 @@ impl<C: Connector> EppClient<C> {
         Err(err)
     }
 
+    /// Poll the message queue, parsing the extension data of the message as `T`
+    ///
+    /// Sends the same request as `transact(&Poll, id)`. See [`PollExtension`] for details.
+    pub async fn poll_with_ext<T: FromXmlOwned + Debug>(
+        &mut self,
+        id: &str,
+    ) -> Result<Response<PollData, T>, Error> {
+        let data = RequestData::new(&Poll, None::<&PollExtension<T>>);
+        self.transact(data, id).await
+    }
+
     /// Accepts raw EPP XML and returns the raw EPP XML response to it.
     /// Not recommended for direct use but sometimes can be useful for debugging
     pub async fn transact_xml(&mut self, xml: &str) -> Result<String, Error> {
-        self.connection.transact(xml)?.await
+        self.exchange(xml).await
+    }
+
+    /// Send `xml` and read the response, handing both to the archiver if there is one
+    async fn exchange(&mut self, xml: &str) -> Result<String, Error> {
+        if let Some(breaker) = &mut self.circuit_breaker {
+            if breaker.acquire(Instant::now())? {
+                self.probe().await?;
+            }
+        }
+
+        let result = match self.connection.transact(xml) {
+            Ok(future) => future.await,
+            Err(err) => Err(err),
+        };
+
+        if let Some(breaker) = &mut self.circuit_breaker {
+            match &result {
+                Ok(_) => breaker.success(),
+                Err(Error::Io(_) | Error::Timeout | Error::Framing { .. }) => {
+                    breaker.failure(Instant::now())
+                }
+                Err(_) => {}
+            }
+        }
+
+        let response = result?;
+        self.last_exchange = Instant::now();
+        if let Some(archiver) = &self.archiver {
+            archiver.archive(xml, &response).await?;
+        }
+
+        Ok(response)
+    }
+
+    /// Send a `<hello>` to find out whether the server is reachable again
+    async fn probe(&mut self) -> Result<(), Error> {
+        let xml = xml::serialize(Hello)?;
+        let result = match self.connection.transact(&xml) {
+            Ok(future) => future.await,
+            Err(err) => Err(err),
+        };
+
+        let Some(breaker) = &mut self.circuit_breaker else {
+            return Ok(());
+        };
+
+        match result {
+            Ok(_) => {
+                breaker.success();
+                Ok(())
+            }
+            Err(err) => {
+                debug!("{}: probe failed: {err}", self.connection.registry);
+                breaker.failure(Instant::now());
+                Err(Error::CircuitOpen)
+            }
+        }
+    }
+
+    /// Send the keepalive command, see [`EppClient::set_keepalive()`]
+    ///
+    /// Call this periodically, for example when `idle()` exceeds the registry's idle timeout
+    /// minus some margin. The `id` is used as the client transaction ID if the command has one.
+    pub async fn keepalive(&mut self, id: &str) -> Result<(), Error> {
+        match &self.keepalive {
+            Keepalive::Hello => {
+                self.hello().await?;
+            }
+            Keepalive::Poll => {
+                self.transact(&Poll, id).await?;
+            }
+            Keepalive::Xml(xml) => {
+                let xml = xml.clone();
+                self.transact_xml(&xml).await?;
+            }
+        }
+
+        self.connection.emit(ConnectionEvent::KeepaliveSent);
+        Ok(())
+    }
+
+    /// Choose the command sent by `keepalive()`, `Keepalive::Hello` by default
+    pub fn set_keepalive(&mut self, keepalive: Keepalive) {
+        self.keepalive = keepalive;
+    }
+
+    /// How long ago the last response was received
+    pub fn idle(&self) -> Duration {
+        self.last_exchange.elapsed()
+    }
+
+    /// Send a `<hello>` and compare the greeting to the one received on connecting
+    ///
+    /// Returns `None` if the service menu and data collection policy are unchanged. During long
+    /// sessions, this detects changes in the registry's capabilities, for example after a
+    /// maintenance window, which may call for a reconnect.
+    pub async fn greeting_changed(&mut self) -> Result<Option<GreetingDiff>, Error> {
+        let current = self.hello().await?;
+        let diff = self.greeting()?.diff(&current);
+        Ok((!diff.is_empty()).then_some(diff))
     }
 
     /// Returns the greeting received on establishment of the connection in raw xml form
@@ -166,79 +496,667 @@ impl<C: Connector> EppClient<C> {
 
     /// Returns the greeting received on establishment of the connection as an `Greeting`
     pub fn greeting(&self) -> Result<Greeting, Error> {
-        xml::deserialize::<Greeting>(&self.connection.greeting)
+        self.xml_options
+            .scope(|| xml::deserialize::<Greeting>(&self.connection.greeting))
     }
 
+    /// The most recent greetings, oldest first, across reconnects
+    ///
+    /// A few greetings are kept, so that registry-side restarts and maintenance windows can be
+    /// correlated with the client's reconnects.
+    pub fn last_greetings(&self) -> impl DoubleEndedIterator<Item = &ReceivedGreeting> {
+        self.connection.greetings.iter()
+    }
+
+    /// The client's Prometheus metrics, see the [`metrics`](crate::metrics) module
+    #[cfg(feature = "prometheus")]
+    pub fn metrics(&self) -> &Metrics {
+        &self.connection.metrics
+    }
+
+    /// Subscribe to state transitions of the connection
+    ///
+    /// Only events after subscribing are received, so the `Connected` and `GreetingReceived`
+    /// for the initial connection are never seen. A subscriber that falls more than a few
+    /// events behind gets `RecvError::Lagged` and misses the oldest ones.
+    pub fn events(&self) -> broadcast::Receiver<ConnectionEvent> {
+        self.connection.events()
+    }
+
+    /// Limit how long a request may wait for an in-flight request to finish
+    ///
+    /// A request started while the future for an earlier request was dropped before its
+    /// response was read must wait for that response first. If the connection is stalled, this
+    /// can take arbitrarily long; with a `ttl`, a request that waited longer is not sent, and
+    /// fails with `Error::QueueTimeout` instead. Defaults to `None` (no limit).
+    pub fn set_queue_ttl(&mut self, ttl: Option<Duration>) {
+        self.connection.queue_ttl = ttl;
+    }
+
+    /// Compress request and response payloads with deflate
+    ///
+    /// No EPP standard covers compression, so this is off by default: only enable it for
+    /// registries that document support, after negotiating it the way they require. Each frame's
+    /// payload is compressed with raw deflate (RFC 1951), and the frame header carries the
+    /// compressed length. The greeting is never compressed, so this stays in effect across
+    /// `reconnect()`.
+    #[cfg(feature = "deflate")]
+    pub fn set_deflate(&mut self, enabled: bool) {
+        self.connection.deflate = enabled;
+    }
+
+    /// Log each frame's header and a preview of its contents at TRACE level
+    ///
+    /// The frame header is logged with its value, followed by the first bytes of the frame as
+    /// hex and as UTF-8. This helps diagnose framing problems, such as a server sending a
+    /// length prefix that doesn't match the payload, which otherwise surface as confusing EOF
+    /// or UTF-8 errors. Frames may contain credentials and personal data, so this is off by
+    /// default.
+    pub fn set_wire_dump(&mut self, enabled: bool) {
+        self.connection.wire_dump = enabled;
+    }
+
+    /// Write the elements of requests with these namespace prefixes
+    ///
+    /// Only needed for registries that reject default namespace declarations, see
+    /// [`NamespacePrefixes`]. Applies to commands sent with `transact()`, not to raw XML.
+    pub fn set_namespace_prefixes(&mut self, prefixes: NamespacePrefixes) {
+        self.prefixes = prefixes;
+    }
+
+    /// Serialize empty containers such as `<domain:ns/>` or `<domain:add/>` in requests
+    ///
+    /// By default, optional elements without content are left out of requests, since some
+    /// registries reject them. Enable this for registries that require the empty elements
+    /// instead. Defaults to `false`.
+    pub fn set_keep_empty_elements(&mut self, keep: bool) {
+        self.xml_options.keep_empty_elements = keep;
+    }
+
+    /// Only accept dates in responses that are in RFC 3339 format, in UTC
+    ///
+    /// By default, dates are parsed leniently: offsets other than UTC are converted, and dates
+    /// without a timezone, seconds or time of day are taken to be in UTC. Defaults to `false`.
+    pub fn set_strict_dates(&mut self, strict: bool) {
+        self.xml_options.strict_dates = strict;
+    }
+
+    /// Start the IDs from `next_transaction_id()` with `prefix`
+    ///
+    /// A prefix identifying the originating system (such as a company identifier and the
+    /// environment) makes it easier to correlate the registry's logs with the client's. The
+    /// prefix can be up to 32 characters long, leaving room for the generated part of the ID.
+    pub fn set_transaction_prefix(&mut self, prefix: &str) -> Result<(), Error> {
+        if prefix.chars().count() > 32 {
+            return Err(Error::Other(
+                format!("transaction ID prefix {prefix:?} is longer than 32 characters").into(),
+            ));
+        }
+
+        // Check the prefix with a generated part of the same length, including the span tag
+        xml::check_transaction_id(&format!("{prefix}1234567890-1-01234567"))?;
+        self.transaction_prefix = prefix.to_owned();
+        Ok(())
+    }
+
+    /// Check that responses echo the client transaction ID of their request
+    ///
+    /// With this enabled, `transact()` fails with `Error::TransactionMismatch` if a response
+    /// carries a different `<clTRID>`, which would mean it belongs to another request. Responses
+    /// without a `<clTRID>` are accepted, as registries omit it when they could not parse the
+    /// request. Defaults to `false`.
+    pub fn set_verify_transaction_ids(&mut self, verify: bool) {
+        self.verify_transaction_ids = verify;
+    }
+
+    /// Check commands against the services offered in the server's greeting
+    ///
+    /// With this enabled, `transact()` fails with `Error::Unsupported` without sending the
+    /// command if the greeting advertises an EPP version other than 1.0 or does not list the
+    /// object mappings the command uses, including those requested in a `<login>`. See
+    /// [`ServiceMenu::check()`](crate::hello::ServiceMenu::check). Defaults to `false`.
+    pub fn set_check_services(&mut self, check: bool) {
+        self.check_services = check;
+    }
+
+    /// Log in again after the server ends the session in response to a command
+    ///
+    /// With this enabled, a command failing with a result code from 2500 to 2502 makes the client
+    /// reconnect and log in with the credentials from `set_credentials()`. Queries (check, info
+    /// and poll commands) are then sent once more and their response returned as usual; for
+    /// other commands, or if logging in fails, `transact()` returns [`Error::SessionEnded`].
+    /// Defaults to `false`.
+    pub fn set_relogin(&mut self, enabled: bool) {
+        self.relogin = enabled;
+    }
+
+    /// Tag the IDs from `next_transaction_id()` with the current tracing span
+    ///
+    /// With this enabled, a short hash of the current span's ID is appended to generated IDs,
+    /// and the ID is logged at debug level within the span. When investigating an incident
+    /// with the registry's support, the transaction IDs from their logs can then be traced
+    /// back to the requests that sent them. Defaults to `false`.
+    pub fn set_transaction_span_tag(&mut self, enabled: bool) {
+        self.transaction_span_tag = enabled;
+    }
+
+    /// Generate a client transaction ID to pass to `transact()`
+    ///
+    /// IDs consist of the prefix from `set_transaction_prefix()`, the current Unix time in
+    /// seconds and a counter, such as `acme-prod-1700000000-42`. The counter makes them unique
+    /// per client; different clients with the same prefix may generate the same ID. See
+    /// `set_transaction_span_tag()` for an optional suffix.
+    pub fn next_transaction_id(&mut self) -> String {
+        self.transaction_count += 1;
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or_default();
+        let mut id = format!(
+            "{}{secs}-{}",
+            self.transaction_prefix, self.transaction_count
+        );
+
+        if self.transaction_span_tag {
+            if let Some(span) = Span::current().id() {
+                let mut hasher = DefaultHasher::new();
+                span.into_u64().hash(&mut hasher);
+                id = format!("{id}-{:08x}", hasher.finish() as u32);
+                debug!(transaction_id = %id, "generated transaction ID");
+            }
+        }
+
+        id
+    }
+
+    /// Set the credentials used to log in again after `reconnect()`
+    ///
+    /// This does not send a `<login>` command itself, so the current session is not affected.
+    /// Setting new credentials replaces the old ones, which allows rotating the password of a
+    /// long-lived client: after changing it through the `new_password` of a [`Login`], set the
+    /// new password here. Pass `None` to stop logging in on reconnect.
+    pub fn set_credentials(&mut self, credentials: Option<Credentials>) {
+        self.credentials = credentials;
+    }
+
+    /// Prefer responses in one of `languages` when logging in after `reconnect()`
+    ///
+    /// The first of the languages that the server advertises in its greeting is requested in
+    /// the login options, falling back to `en` when none of them is supported. Use
+    /// [`ServiceMenu::select_language()`](crate::hello::ServiceMenu::select_language) to do
+    /// the same for logins sent through `transact()`.
+    pub fn set_languages(&mut self, languages: Vec<LanguageTag>) {
+        self.languages = languages;
+    }
+
+    /// Archive every request and its response with `archiver`
+    ///
+    /// See [`Archiver`] for details. Pass `None` to stop archiving.
+    pub fn set_archiver(&mut self, archiver: Option<Box<dyn Archiver>>) {
+        self.archiver = archiver;
+    }
+
+    /// Record transform commands with `store` before sending them
+    ///
+    /// See [`PendingStore`] for details. Pass `None` to stop recording.
+    pub fn set_pending_store(&mut self, store: Option<Box<dyn PendingStore>>) {
+        self.pending_store = store;
+    }
+
+    /// Add `layer` to the end of the chain of layers commands are sent through
+    ///
+    /// See [`Layer`] for details. Layers see the requests in the order they were added, and so
+    /// do their responses.
+    pub fn layer(&mut self, layer: impl Layer + 'static) {
+        self.layers.push(Box::new(layer));
+    }
+
+    /// Limit the rate of commands sent through `transact()` with `limiter`
+    ///
+    /// See [`RateLimiter`] for details. Raw requests sent through `transact_xml()` are not
+    /// limited. Pass `None` to remove the limits.
+    pub fn set_rate_limiter(&mut self, limiter: Option<RateLimiter>) {
+        self.rate_limiter = limiter;
+    }
+
+    /// The rate limiter, if one is set, for example to read its statistics
+    pub fn rate_limiter(&self) -> Option<&RateLimiter> {
+        self.rate_limiter.as_ref()
+    }
+
+    /// Fail fast after repeated connection failures with `breaker`
+    ///
+    /// See [`CircuitBreaker`] for details. Pass `None` to remove the circuit breaker.
+    pub fn set_circuit_breaker(&mut self, breaker: Option<CircuitBreaker>) {
+        self.circuit_breaker = breaker;
+    }
+
+    /// The circuit breaker, if one is set, for example to check whether it is open
+    pub fn circuit_breaker(&self) -> Option<&CircuitBreaker> {
+        self.circuit_breaker.as_ref()
+    }
+
+    /// Reconnect to the server, logging in if credentials were set
     pub async fn reconnect(&mut self) -> Result<(), Error> {
-        self.connection.reconnect().await
+        if let Some(breaker) = &mut self.circuit_breaker {
+            breaker.acquire(Instant::now())?;
+        }
+
+        let result = self.connection.reconnect().await;
+        if let Some(breaker) = &mut self.circuit_breaker {
+            match &result {
+                Ok(()) => breaker.success(),
+                Err(_) => breaker.failure(Instant::now()),
+            }
+        }
+
+        result?;
+        let Some(credentials) = self.credentials.clone() else {
+            return Ok(());
+        };
+
+        let ext_uris = credentials
+            .ext_uris
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        let mut login = Login::new(
+            &credentials.username,
+            &credentials.password,
+            None,
+            Some(&ext_uris),
+        );
+
+        if !self.languages.is_empty() {
+            let lang = self.greeting()?.svc_menu.select_language(&self.languages);
+            login.language(&lang);
+        }
+
+        self.transact(&login, &credentials.transaction_id).await?;
+        Ok(())
     }
 
+    /// Close the connection without logging out
+    ///
+    /// Any request whose future was dropped before completing is abandoned. Returns
+    /// `Error::Timeout` if the connection is not shut down within the client's timeout.
     pub async fn shutdown(mut self) -> Result<(), Error> {
         self.connection.shutdown().await
     }
+
+    /// Finish any in-flight request, log out and close the connection
+    ///
+    /// Unlike `shutdown()`, this first completes a request whose future was dropped before its
+    /// response was read, then sends a `<logout>` command with the given client transaction
+    /// `id`. Returns `Error::Timeout` if this does not complete before `deadline`.
+    pub async fn close_gracefully(mut self, id: &str, deadline: Instant) -> Result<(), Error> {
+        self.close(id, deadline).await
+    }
+
+    /// Split the connection into halves for reading and writing frames
+    ///
+    /// For schedulers that manage the requests on a session themselves, for example to have
+    /// several in flight at once. Any in-flight request is finished first. The halves frame
+    /// payloads like the client does, including deflate compression if it was negotiated, but
+    /// the client's other options, like reconnecting, rate limits and archiving, don't apply.
+    pub async fn into_split(self) -> Result<SplitConnection<C>, Error> {
+        let codec = EppCodec::new().with_deflate(self.connection.deflate);
+        let stream = self.connection.into_stream().await?;
+        let (read, write) = tokio::io::split(stream);
+        Ok((
+            FrameReader::new(read, codec),
+            FrameWriter::new(write, codec),
+        ))
+    }
+
+    async fn close(&mut self, id: &str, deadline: Instant) -> Result<(), Error> {
+        let close = async {
+            self.connection.drain().await?;
+            self.transact(&Logout, id).await?;
+            self.connection.shutdown().await
+        };
+
+        match runtime::timeout_at(deadline, close).await {
+            Ok(result) => result,
+            Err(_) => Err(Error::Timeout),
+        }
+    }
+
+    /// Turn this client into a [`SharedClient`], which can be cloned
+    ///
+    /// The returned future waits until no strong handles are left, then finishes any
+    /// in-flight request and logs out with the client transaction `id`, like
+    /// [`close_gracefully()`](Self::close_gracefully), giving up after `timeout`. It should be
+    /// spawned or otherwise polled alongside the handles; if it is dropped instead, the
+    /// connection is closed without logging out.
+    pub fn into_shared(
+        self,
+        id: String,
+        timeout: Duration,
+    ) -> (SharedClient<C>, impl Future<Output = Result<(), Error>>) {
+        let (tx, rx) = oneshot::channel();
+        let client = Arc::new(Mutex::new(self));
+        let shared = SharedClient {
+            client: client.clone(),
+            handle: Arc::new(Handle { _shutdown: tx }),
+            extension: None,
+        };
+
+        let shutdown = async move {
+            // The sender is never used; the receiver completes once the last handle drops it
+            let _ = rx.await;
+            let mut client = client.lock().await;
+            client.close(&id, Instant::now() + timeout).await
+        };
+
+        (shared, shutdown)
+    }
 }
 
-#[derive(Debug)]
-pub struct RequestData<'c, 'e, C, E> {
-    pub(crate) command: &'c C,
-    pub(crate) extension: Option<&'e E>,
+/// The halves of a connection, see [`EppClient::into_split()`]
+pub type SplitConnection<C> = (
+    FrameReader<ReadHalf<<C as Connector>::Connection>>,
+    FrameWriter<WriteHalf<<C as Connector>::Connection>>,
+);
+
+/// A handle to an `EppClient` that can be cloned and used from multiple tasks
+///
+/// All clones share the same connection, which handles one request at a time; concurrent
+/// requests wait for their turn. Handles are cheap to clone, and `Send` and `Sync` if the
+/// connector and its connection are `Send`. The connection stays open as long as any strong
+/// handle exists; a [`WeakClient`] from [`downgrade()`](Self::downgrade) does not keep it
+/// alive. Each handle can carry a default extension that is attached to
+/// every command sent through [`transact()`](Self::transact), which helps when code for
+/// multiple TLDs shares a connection:
+///
+/// ```no_run
+/// # use std::time::Duration;
+/// # use instant_epp::client::{Connector, EppClient};
+/// # use instant_epp::extensions::namestore::NameStore;
+/// # async fn example<C: Connector>(client: EppClient<C>) {
+/// let (shared, _shutdown) = client.into_shared("logout-1".to_owned(), Duration::from_secs(5));
+/// let com = shared.with_extension(NameStore::new("dotCOM"));
+/// let net = shared.with_extension(NameStore::new("dotNET"));
+/// # }
+/// ```
+pub struct SharedClient<C: Connector, E = NoExtension> {
+    // Declared before `handle`, so that the client is released before the shutdown starts
+    client: Arc<Mutex<EppClient<C>>>,
+    handle: Arc<Handle>,
+    extension: Option<Arc<E>>,
 }
 
-impl<'c, C: Command> From<&'c C> for RequestData<'c, 'static, C, NoExtension> {
-    fn from(command: &'c C) -> Self {
-        Self {
-            command,
-            extension: None,
+impl<C: Connector, E: Extension> SharedClient<C, E> {
+    /// A handle to the same client that attaches `extension` to every command
+    pub fn with_extension<F: Extension>(&self, extension: F) -> SharedClient<C, F> {
+        SharedClient {
+            client: self.client.clone(),
+            handle: self.handle.clone(),
+            extension: Some(Arc::new(extension)),
         }
     }
+
+    /// A handle to the same client that does not keep the connection alive
+    pub fn downgrade(&self) -> WeakClient<C, E> {
+        WeakClient {
+            client: Arc::downgrade(&self.client),
+            handle: Arc::downgrade(&self.handle),
+            extension: self.extension.clone(),
+        }
+    }
+
+    /// The number of strong handles to the client, including this one
+    pub fn strong_count(&self) -> usize {
+        Arc::strong_count(&self.handle)
+    }
+
+    /// Send `command` with this handle's default extension, if any
+    ///
+    /// Waits until requests from other handles have been answered.
+    pub async fn transact<Cmd>(
+        &self,
+        command: &Cmd,
+        id: &str,
+    ) -> Result<Response<Cmd::Response, E::Response>, Error>
+    where
+        Cmd: Transaction<E> + Command,
+    {
+        let data = RequestData::new(command, self.extension.as_deref());
+        self.client.lock().await.transact(data, id).await
+    }
+
+    /// Exclusive access to the client, for example to send a command with another extension
+    ///
+    /// Other handles wait until the guard is dropped.
+    pub async fn lock(&self) -> MutexGuard<'_, EppClient<C>> {
+        self.client.lock().await
+    }
 }
 
-impl<'c, 'e, C: Command, E: Extension> From<(&'c C, &'e E)> for RequestData<'c, 'e, C, E> {
-    fn from((command, extension): (&'c C, &'e E)) -> Self {
+// Manual impl because this does not depend on whether `C` and `E` are `Clone`
+impl<C: Connector, E> Clone for SharedClient<C, E> {
+    fn clone(&self) -> Self {
         Self {
-            command,
-            extension: Some(extension),
+            client: self.client.clone(),
+            handle: self.handle.clone(),
+            extension: self.extension.clone(),
+        }
+    }
+}
+
+/// A handle to a [`SharedClient`] that does not keep its connection alive
+///
+/// Created by [`SharedClient::downgrade()`]. This suits background tasks such as a poller,
+/// which should not keep the connection open after the rest of the application is done with
+/// it. Once all strong handles are dropped, [`transact()`](Self::transact) fails with
+/// [`Error::Closed`], and the task can stop:
+///
+/// ```no_run
+/// # use instant_epp::client::{Connector, WeakClient};
+/// # use instant_epp::poll::Poll;
+/// # use instant_epp::Error;
+/// async fn poller<C: Connector>(client: WeakClient<C>) {
+///     loop {
+///         match client.transact(&Poll, "poll-1").await {
+///             Ok(rsp) => { /* handle and acknowledge the message */ }
+///             Err(Error::Closed) => break,
+///             Err(err) => { /* log the error */ }
+///         }
+///
+///         // wait before polling again
+///     }
+/// }
+/// ```
+pub struct WeakClient<C: Connector, E = NoExtension> {
+    client: Weak<Mutex<EppClient<C>>>,
+    handle: Weak<Handle>,
+    extension: Option<Arc<E>>,
+}
+
+impl<C: Connector, E> WeakClient<C, E> {
+    /// A strong handle to the client, or `None` if all strong handles have been dropped
+    pub fn upgrade(&self) -> Option<SharedClient<C, E>> {
+        // The handle is upgraded first, so that a failed upgrade never holds on to the client
+        let handle = self.handle.upgrade()?;
+        Some(SharedClient {
+            client: self.client.upgrade()?,
+            handle,
+            extension: self.extension.clone(),
+        })
+    }
+
+    /// The number of strong handles to the client
+    pub fn strong_count(&self) -> usize {
+        self.handle.strong_count()
+    }
+}
+
+impl<C: Connector, E: Extension> WeakClient<C, E> {
+    /// Send `command` with this handle's default extension, if any
+    ///
+    /// The client is kept alive until the response has been received. Fails with
+    /// [`Error::Closed`] if all strong handles have been dropped.
+    pub async fn transact<Cmd>(
+        &self,
+        command: &Cmd,
+        id: &str,
+    ) -> Result<Response<Cmd::Response, E::Response>, Error>
+    where
+        Cmd: Transaction<E> + Command,
+    {
+        match self.upgrade() {
+            Some(client) => client.transact(command, id).await,
+            None => Err(Error::Closed),
         }
     }
 }
 
 // Manual impl because this does not depend on whether `C` and `E` are `Clone`
-impl<C, E> Clone for RequestData<'_, '_, C, E> {
+impl<C: Connector, E> Clone for WeakClient<C, E> {
     fn clone(&self) -> Self {
-        *self
+        Self {
+            client: self.client.clone(),
+            handle: self.handle.clone(),
+            extension: self.extension.clone(),
+        }
     }
 }
 
-// Manual impl because this does not depend on whether `C` and `E` are `Copy`
-impl<C, E> Copy for RequestData<'_, '_, C, E> {}
+/// Shared by the strong handles to a client; wakes up the shutdown future when dropped
+struct Handle {
+    _shutdown: oneshot::Sender<()>,
+}
+
+/// The command sent by [`EppClient::keepalive()`]
+///
+/// A `<hello>` keeps the connection open, but some registries expire the session unless an
+/// authenticated command is sent. Those need one of the other variants.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub enum Keepalive {
+    /// Send a `<hello>`
+    #[default]
+    Hello,
+    /// Send a `<poll op="req">`, which leaves the message queue unchanged
+    Poll,
+    /// Send this raw EPP XML request; its response is not checked
+    Xml(String),
+}
 
+/// Credentials for logging in after a reconnect, see [`EppClient::set_credentials()`]
+#[derive(Clone)]
+pub struct Credentials {
+    pub username: String,
+    pub password: String,
+    /// Extension namespace URIs to request in the `<svcs>` of the login
+    pub ext_uris: Vec<String>,
+    /// The client transaction ID to use for the `<login>` command
+    pub transaction_id: String,
+}
+
+// Manual impl to keep the password out of logs
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("username", &self.username)
+            .field("password", &"<redacted>")
+            .field("ext_uris", &self.ext_uris)
+            .field("transaction_id", &self.transaction_id)
+            .finish()
+    }
+}
+
+/// Receives every request sent by an [`EppClient`] along with the response to it
+///
+/// The archiver is called with the raw XML of both documents once the response has been read,
+/// and the request does not complete until `archive()` returns. This provides backpressure, so
+/// a slow archive slows down the client rather than buffering, and makes it safe to rely on
+/// the archive being written before the caller sees a result. If `archive()` fails, the
+/// request fails with that error, even though the server has processed the command.
+///
+/// Note that archived requests include `<login>` commands, with the credentials in them.
+#[async_trait]
+pub trait Archiver: Send + Sync {
+    async fn archive(&self, request: &str, response: &str) -> Result<(), Error>;
+}
+
+/// Write-ahead records of transform commands sent by an [`EppClient`]
+///
+/// Before a create, delete, renew, transfer or update command is sent through `transact()`,
+/// `begin()` is called with the transaction, and the command is only sent once it returns.
+/// When the server's response has been read, `complete()` is called with its result code.
+/// A transaction that was begun but never completed is in doubt: the server may or may not
+/// have processed it, for example because the connection was lost while waiting for the
+/// response. After a crash, the application can reconcile these, for example with an
+/// `<info>` command for the objects, before retrying.
+///
+/// If either method fails, the request fails with that error. Raw requests sent through
+/// `transact_xml()` are not recorded.
+#[async_trait]
+pub trait PendingStore: Send + Sync {
+    /// Record that `transaction` is about to be sent
+    async fn begin(&self, transaction: &PendingTransaction) -> Result<(), Error>;
+
+    /// Record that the server responded to `transaction` with `code`
+    async fn complete(
+        &self,
+        transaction: &PendingTransaction,
+        code: ResultCode,
+    ) -> Result<(), Error>;
+}
+
+/// A transform command passed to a [`PendingStore`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PendingTransaction {
+    /// The client transaction ID of the request
+    pub transaction_id: String,
+    /// The command, as in [`Command::COMMAND`]
+    pub command: &'static str,
+    /// The IDs of the objects in the request, as in [`Command::object_ids()`]
+    pub objects: Vec<String>,
+}
+
+#[cfg(feature = "__rustls")]
+pub use crate::identity::Identity;
 #[cfg(feature = "__rustls")]
-pub use rustls_connector::RustlsConnector;
+pub use rustls_connector::{Endpoint, Environment, RustlsConnector};
 
 #[cfg(feature = "__rustls")]
 mod rustls_connector {
     use std::io;
+    #[cfg(feature = "async-io")]
+    use std::net::ToSocketAddrs;
+    use std::net::{IpAddr, SocketAddr};
     use std::sync::Arc;
     use std::time::Duration;
 
     use async_trait::async_trait;
     use rustls_platform_verifier::BuilderVerifierExt;
-    use tokio::net::lookup_host;
-    use tokio::net::TcpStream;
+    use socket2::{Domain, Protocol, SockAddr, Socket, TcpKeepalive, Type};
+    #[cfg(not(feature = "async-io"))]
+    use tokio::net::{lookup_host, TcpSocket, TcpStream};
     use tokio_rustls::client::TlsStream;
+    use tokio_rustls::rustls::client::ResolvesClientCert;
     use tokio_rustls::rustls::pki_types::InvalidDnsNameError;
     use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName};
-    use tokio_rustls::rustls::ClientConfig;
+    use tokio_rustls::rustls::sign::CertifiedKey;
+    use tokio_rustls::rustls::{ClientConfig, SignatureScheme};
     use tokio_rustls::TlsConnector;
     use tracing::info;
 
-    use crate::connection::{self, Connector};
+    #[cfg(feature = "async-io")]
+    use super::Compat;
+    use crate::connection::{self, Connector, GreetingRetry, ReconnectBackoff};
     use crate::error::Error;
 
     pub struct RustlsConnector {
         inner: TlsConnector,
+        config: Arc<ClientConfig>,
         server_name: ServerName<'static>,
         server: (String, u16),
+        socket: SocketOptions,
+        greeting_retry: GreetingRetry,
+        reconnect_backoff: ReconnectBackoff,
+        environment: Option<Environment>,
     }
 
     impl RustlsConnector {
@@ -250,8 +1168,66 @@ mod rustls_connector {
                 server_name: ServerName::try_from(server.0.as_str())?.to_owned(),
                 server,
                 identity: None,
+                socket: SocketOptions::default(),
+                greeting_retry: GreetingRetry::default(),
+                reconnect_backoff: ReconnectBackoff::default(),
+                endpoint: None,
             })
         }
+
+        /// Create a builder for one of the environments of `endpoint`
+        ///
+        /// The builder connects to the OT&E environment unless its `environment()` method
+        /// selects production.
+        pub fn endpoint_builder(endpoint: Endpoint) -> RustlsConnectorBuilder {
+            let ote = &endpoint.ote;
+            RustlsConnectorBuilder {
+                server: ote.server.clone(),
+                server_name: ote.server_name.clone(),
+                identity: None,
+                socket: SocketOptions::default(),
+                greeting_retry: GreetingRetry::default(),
+                reconnect_backoff: ReconnectBackoff::default(),
+                endpoint: Some((endpoint, Environment::Ote)),
+            }
+        }
+
+        /// The environment this connector connects to, if created from an [`Endpoint`]
+        pub fn environment(&self) -> Option<Environment> {
+            self.environment
+        }
+
+        /// Replace the client certificate and key used for new connections
+        ///
+        /// The rest of the TLS configuration, including any set with `build_with_config()`,
+        /// is kept.
+        pub fn set_identity(
+            &mut self,
+            certs: Vec<CertificateDer<'static>>,
+            key: PrivateKeyDer<'static>,
+        ) -> Result<(), tokio_rustls::rustls::Error> {
+            let key = CertifiedKey::from_der(certs, key, self.config.crypto_provider())?;
+            let mut config = ClientConfig::clone(&self.config);
+            config.client_auth_cert_resolver = Arc::new(ClientIdentity(Arc::new(key)));
+
+            self.config = Arc::new(config);
+            self.inner = TlsConnector::from(self.config.clone());
+            Ok(())
+        }
+    }
+
+    /// Always presents the same client certificate
+    #[derive(Debug)]
+    struct ClientIdentity(Arc<CertifiedKey>);
+
+    impl ResolvesClientCert for ClientIdentity {
+        fn resolve(&self, _: &[&[u8]], _: &[SignatureScheme]) -> Option<Arc<CertifiedKey>> {
+            Some(self.0.clone())
+        }
+
+        fn has_certs(&self) -> bool {
+            true
+        }
     }
 
     #[async_trait]
@@ -260,32 +1236,267 @@ mod rustls_connector {
 
         async fn connect(&self, timeout: Duration) -> Result<Self::Connection, Error> {
             info!("connecting to server: {}:{}", self.server.0, self.server.1);
-            let addr = match lookup_host(&self.server).await?.next() {
-                Some(addr) => addr,
-                None => {
-                    return Err(Error::Io(io::Error::new(
-                        io::ErrorKind::InvalidInput,
-                        format!("invalid host: {}", &self.server.0),
-                    )))
-                }
-            };
+            // When binding to a local address, only a server address of the same family will do
+            let local = self.socket.local_addr;
+            let mut addrs = lookup_host(&self.server).await?;
+            let addr =
+                match addrs.find(|addr| local.is_none_or(|ip| ip.is_ipv4() == addr.is_ipv4())) {
+                    Some(addr) => addr,
+                    None => {
+                        return Err(Error::Io(io::Error::new(
+                            io::ErrorKind::InvalidInput,
+                            format!("invalid host: {}", &self.server.0),
+                        )))
+                    }
+                };
 
-            let stream = TcpStream::connect(addr).await?;
+            let stream = self.socket.connect(addr).await?;
             let future = self.inner.connect(self.server_name.clone(), stream);
             connection::timeout(timeout, future).await
         }
+
+        fn greeting_retry(&self) -> GreetingRetry {
+            self.greeting_retry
+        }
+
+        fn reconnect_backoff(&self) -> ReconnectBackoff {
+            self.reconnect_backoff
+        }
+    }
+
+    /// The production and OT&E (operational test and evaluation) servers of a registry
+    ///
+    /// Keeping both in one place, and selecting one through the `environment()` method of the
+    /// connector builder, makes it harder to accidentally send commands from test code to
+    /// production.
+    pub struct Endpoint {
+        production: Server,
+        ote: Server,
+    }
+
+    impl Endpoint {
+        /// The `production` and `ote` servers, each consisting of a hostname and port
+        pub fn new(
+            production: (String, u16),
+            ote: (String, u16),
+        ) -> Result<Self, InvalidDnsNameError> {
+            Ok(Self {
+                production: Server::new(production)?,
+                ote: Server::new(ote)?,
+            })
+        }
+
+        /// Authenticate to the production server with this client certificate and key
+        pub fn production_identity(
+            mut self,
+            certs: Vec<CertificateDer<'static>>,
+            key: PrivateKeyDer<'static>,
+        ) -> Self {
+            self.production.identity = Some((certs, key));
+            self
+        }
+
+        /// Authenticate to the OT&E server with this client certificate and key
+        pub fn ote_identity(
+            mut self,
+            certs: Vec<CertificateDer<'static>>,
+            key: PrivateKeyDer<'static>,
+        ) -> Self {
+            self.ote.identity = Some((certs, key));
+            self
+        }
+
+        fn server(&self, environment: Environment) -> &Server {
+            match environment {
+                Environment::Production => &self.production,
+                Environment::Ote => &self.ote,
+            }
+        }
+
+        fn server_mut(&mut self, environment: Environment) -> &mut Server {
+            match environment {
+                Environment::Production => &mut self.production,
+                Environment::Ote => &mut self.ote,
+            }
+        }
+    }
+
+    /// The environment of an [`Endpoint`] to connect to
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    pub enum Environment {
+        Production,
+        /// Operational test and evaluation
+        Ote,
+    }
+
+    struct Server {
+        server: (String, u16),
+        server_name: ServerName<'static>,
+        identity: Option<(Vec<CertificateDer<'static>>, PrivateKeyDer<'static>)>,
+    }
+
+    impl Server {
+        fn new(server: (String, u16)) -> Result<Self, InvalidDnsNameError> {
+            Ok(Self {
+                server_name: ServerName::try_from(server.0.as_str())?.to_owned(),
+                server,
+                identity: None,
+            })
+        }
+    }
+
+    /// TCP-level settings for the connection to the server
+    #[derive(Clone, Debug, Default)]
+    struct SocketOptions {
+        local_addr: Option<IpAddr>,
+        nodelay: bool,
+        keepalive: Option<(Duration, Option<Duration>)>,
+    }
+
+    impl SocketOptions {
+        #[cfg(not(feature = "async-io"))]
+        async fn connect(&self, addr: SocketAddr) -> io::Result<TcpStream> {
+            TcpSocket::from_std_stream(self.socket(addr)?.into())
+                .connect(addr)
+                .await
+        }
+
+        #[cfg(feature = "async-io")]
+        async fn connect(&self, addr: SocketAddr) -> io::Result<TcpStream> {
+            let socket = self.socket(addr)?;
+            match socket.connect(&SockAddr::from(addr)) {
+                Ok(()) => {}
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => {}
+                #[cfg(unix)]
+                Err(err) if err.raw_os_error() == Some(libc::EINPROGRESS) => {}
+                Err(err) => return Err(err),
+            }
+
+            // The socket becomes writable once the connection is established or has failed
+            let stream = async_io::Async::new_nonblocking(std::net::TcpStream::from(socket))?;
+            stream.writable().await?;
+            match stream.get_ref().take_error()? {
+                None => Ok(Compat::new(stream)),
+                Some(err) => Err(err),
+            }
+        }
+
+        /// Create a non-blocking socket for connecting to `addr`, with these options applied
+        fn socket(&self, addr: SocketAddr) -> io::Result<Socket> {
+            let socket = Socket::new(Domain::for_address(addr), Type::STREAM, Some(Protocol::TCP))?;
+            socket.set_nonblocking(true)?;
+
+            if let Some(ip) = self.local_addr {
+                socket.bind(&SockAddr::from(SocketAddr::new(ip, 0)))?;
+            }
+
+            socket.set_tcp_nodelay(self.nodelay)?;
+            if let Some((time, _interval)) = self.keepalive {
+                #[allow(unused_mut)]
+                let mut keepalive = TcpKeepalive::new().with_time(time);
+                #[cfg(any(
+                    target_os = "android",
+                    target_os = "freebsd",
+                    target_os = "ios",
+                    target_os = "linux",
+                    target_os = "macos",
+                    target_os = "netbsd",
+                    target_os = "windows",
+                ))]
+                if let Some(interval) = _interval {
+                    keepalive = keepalive.with_interval(interval);
+                }
+
+                socket.set_tcp_keepalive(&keepalive)?;
+            }
+
+            Ok(socket)
+        }
+    }
+
+    #[cfg(feature = "async-io")]
+    type TcpStream = Compat<async_io::Async<std::net::TcpStream>>;
+
+    /// Resolve `server` on a separate thread, as tokio's `lookup_host()` does
+    #[cfg(feature = "async-io")]
+    async fn lookup_host(server: &(String, u16)) -> io::Result<std::vec::IntoIter<SocketAddr>> {
+        let server = server.clone();
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        std::thread::spawn(move || {
+            let _ = tx.send(server.to_socket_addrs());
+        });
+
+        rx.await
+            .map_err(|_| io::Error::other("address resolution thread panicked"))?
     }
 
     pub struct RustlsConnectorBuilder {
         server: (String, u16),
         server_name: ServerName<'static>,
         identity: Option<(Vec<CertificateDer<'static>>, PrivateKeyDer<'static>)>,
+        socket: SocketOptions,
+        greeting_retry: GreetingRetry,
+        reconnect_backoff: ReconnectBackoff,
+        // The endpoint this builder was created from, with the selected environment
+        endpoint: Option<(Endpoint, Environment)>,
     }
 
     impl RustlsConnectorBuilder {
+        /// Connect to the given `environment` of the endpoint
+        ///
+        /// Only builders created with [`RustlsConnector::endpoint_builder()`] have an
+        /// endpoint to select an environment from; for others, this has no effect.
+        pub fn environment(mut self, environment: Environment) -> Self {
+            if let Some((endpoint, selected)) = &mut self.endpoint {
+                let server = endpoint.server(environment);
+                self.server = server.server.clone();
+                self.server_name = server.server_name.clone();
+                *selected = environment;
+            }
+
+            self
+        }
+
+        /// Connect from the given local address
+        ///
+        /// Useful on multi-homed hosts when the registry only accepts connections from
+        /// allow-listed addresses. Only server addresses of the same family are tried.
+        pub fn local_addr(mut self, addr: IpAddr) -> Self {
+            self.socket.local_addr = Some(addr);
+            self
+        }
+
+        /// Set `TCP_NODELAY` on the socket, disabling Nagle's algorithm
+        pub fn nodelay(mut self, nodelay: bool) -> Self {
+            self.socket.nodelay = nodelay;
+            self
+        }
+
+        /// Enable TCP keepalive, starting probes after the connection is idle for `time`
+        ///
+        /// If given, `interval` sets the time between probes. It is ignored on platforms that
+        /// don't support configuring it.
+        pub fn keepalive(mut self, time: Duration, interval: Option<Duration>) -> Self {
+            self.socket.keepalive = Some((time, interval));
+            self
+        }
+
+        /// Connect again if the server doesn't send a greeting, see [`GreetingRetry`]
+        pub fn greeting_retry(mut self, retry: GreetingRetry) -> Self {
+            self.greeting_retry = retry;
+            self
+        }
+
+        /// Retry failed reconnects, see [`ReconnectBackoff`]
+        pub fn reconnect_backoff(mut self, backoff: ReconnectBackoff) -> Self {
+            self.reconnect_backoff = backoff;
+            self
+        }
+
         /// Enable client authentication
         ///
-        /// Only used when `build()` is called.
+        /// Only used when `build()` is called. Takes precedence over the identity of an
+        /// [`Endpoint`].
         pub fn client_auth(
             mut self,
             certs: Vec<CertificateDer<'static>>,
@@ -303,12 +1514,21 @@ mod rustls_connector {
                 server,
                 server_name,
                 identity: _identity,
+                socket,
+                greeting_retry,
+                reconnect_backoff,
+                endpoint,
             } = self;
 
             RustlsConnector {
-                inner: TlsConnector::from(config),
+                inner: TlsConnector::from(config.clone()),
+                config,
                 server_name,
                 server,
+                socket,
+                greeting_retry,
+                reconnect_backoff,
+                environment: endpoint.map(|(_, environment)| environment),
             }
         }
 
@@ -318,19 +1538,101 @@ mod rustls_connector {
                 server,
                 server_name,
                 identity,
+                socket,
+                greeting_retry,
+                reconnect_backoff,
+                endpoint,
             } = self;
 
+            let environment = endpoint.as_ref().map(|(_, environment)| *environment);
+            let identity = identity.or_else(|| {
+                let (mut endpoint, environment) = endpoint?;
+                endpoint.server_mut(environment).identity.take()
+            });
+
             let builder = ClientConfig::builder().with_platform_verifier()?;
             let config = match identity {
                 Some((certs, key)) => builder.with_client_auth_cert(certs, key)?,
                 None => builder.with_no_client_auth(),
             };
 
+            let config = Arc::new(config);
             Ok(RustlsConnector {
-                inner: TlsConnector::from(Arc::new(config)),
+                inner: TlsConnector::from(config.clone()),
+                config,
                 server_name,
                 server,
+                socket,
+                greeting_retry,
+                reconnect_backoff,
+                environment,
             })
         }
     }
+
+    #[cfg(test)]
+    mod tests {
+        use std::net::{IpAddr, Ipv4Addr, TcpListener};
+        use std::time::Duration;
+
+        use socket2::SockRef;
+
+        use super::{Endpoint, Environment, RustlsConnector, SocketOptions};
+
+        #[test]
+        fn endpoint() {
+            let endpoint = || {
+                Endpoint::new(
+                    ("epp.example.com".to_owned(), 700),
+                    ("ote.example.com".to_owned(), 1700),
+                )
+                .unwrap()
+            };
+
+            let builder = RustlsConnector::endpoint_builder(endpoint());
+            assert_eq!(builder.server, ("ote.example.com".to_owned(), 1700));
+            assert_eq!(builder.endpoint.unwrap().1, Environment::Ote);
+
+            let builder =
+                RustlsConnector::endpoint_builder(endpoint()).environment(Environment::Production);
+            assert_eq!(builder.server, ("epp.example.com".to_owned(), 700));
+            assert_eq!(builder.endpoint.unwrap().1, Environment::Production);
+
+            let builder = RustlsConnector::builder(("epp.example.com".to_owned(), 700))
+                .unwrap()
+                .environment(Environment::Ote);
+            assert_eq!(builder.server, ("epp.example.com".to_owned(), 700));
+            assert!(builder.endpoint.is_none());
+        }
+
+        #[test]
+        fn socket_options() {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let options = SocketOptions {
+                local_addr: Some(IpAddr::V4(Ipv4Addr::LOCALHOST)),
+                nodelay: true,
+                keepalive: Some((Duration::from_secs(60), Some(Duration::from_secs(10)))),
+            };
+
+            let connect = options.connect(listener.local_addr().unwrap());
+            #[cfg(not(feature = "async-io"))]
+            let stream = tokio::runtime::Builder::new_current_thread()
+                .enable_io()
+                .build()
+                .unwrap()
+                .block_on(connect)
+                .unwrap();
+            #[cfg(feature = "async-io")]
+            let stream = futures_lite::future::block_on(connect)
+                .unwrap()
+                .into_inner();
+
+            let (_, peer) = listener.accept().unwrap();
+            let socket = SockRef::from(&stream);
+            assert_eq!(Some(peer), socket.local_addr().unwrap().as_socket());
+            assert_eq!(peer.ip(), Ipv4Addr::LOCALHOST);
+            assert!(socket.tcp_nodelay().unwrap());
+            assert!(socket.keepalive().unwrap());
+        }
+    }
 }
@@ -0,0 +1,359 @@
+//! The length-prefixed framing of EPP over TCP
+//!
+//! As described in [RFC 5734](https://tools.ietf.org/html/rfc5734), each EPP frame starts with
+//! a 4-byte big-endian header containing the length of the frame, including the header itself.
+//! [`EppCodec`] implements this framing for the client's connection, and can be used on its own
+//! to build custom transports or a server on top of this crate's types.
+//!
+//! The [`Decoder`] and [`Encoder`] traits have the same shape as the ones in
+//! `tokio_util::codec`, but work on a `Vec<u8>`, so that this crate doesn't depend on
+//! tokio-util; wrapping an `EppCodec` for use with `tokio_util::codec::Framed` takes only a few
+//! lines.
+
+use std::{fmt, io};
+
+use crate::Error;
+
+/// The largest frame an [`EppCodec`] accepts by default, 16 MiB
+pub const DEFAULT_MAX_FRAME_LEN: usize = 16 * 1024 * 1024;
+
+/// Decoding frames from a buffer of received bytes
+pub trait Decoder {
+    type Item;
+    type Error;
+
+    /// Take the first item off `src`, if it has been received completely
+    ///
+    /// Returns `Ok(None)` if more bytes are needed; `src` is left as is in that case.
+    fn decode(&mut self, src: &mut Vec<u8>) -> Result<Option<Self::Item>, Self::Error>;
+}
+
+/// Encoding frames into a buffer of bytes to send
+pub trait Encoder<Item> {
+    type Error;
+
+    /// Append the frame for `item` to `dst`
+    fn encode(&mut self, item: Item, dst: &mut Vec<u8>) -> Result<(), Self::Error>;
+}
+
+/// The EPP frame codec, see the [module documentation](self)
+///
+/// Frames longer than the maximum frame length are rejected as soon as their header is seen,
+/// so that a bad header can't make the receiver allocate gigabytes. If deflate compression was
+/// negotiated with the peer, payloads are compressed and decompressed as well.
+#[derive(Clone, Copy, Debug)]
+pub struct EppCodec {
+    deflate: bool,
+    max_frame_len: usize,
+}
+
+impl EppCodec {
+    /// A codec for uncompressed frames up to [`DEFAULT_MAX_FRAME_LEN`]
+    pub fn new() -> Self {
+        Self {
+            deflate: false,
+            max_frame_len: DEFAULT_MAX_FRAME_LEN,
+        }
+    }
+
+    /// Accept frames of up to `len` bytes, including the header
+    pub fn max_frame_len(mut self, len: usize) -> Self {
+        self.max_frame_len = len;
+        self
+    }
+
+    /// Whether payloads are compressed with raw deflate (RFC 1951)
+    #[cfg(feature = "deflate")]
+    pub fn deflate(self, deflate: bool) -> Self {
+        self.with_deflate(deflate)
+    }
+
+    #[cfg(feature = "client")]
+    pub(crate) fn with_deflate(mut self, deflate: bool) -> Self {
+        self.deflate = deflate;
+        self
+    }
+
+    /// The maximum frame length, including the header
+    #[cfg(feature = "client")]
+    pub(crate) fn limit(&self) -> usize {
+        self.max_frame_len
+    }
+
+    /// The length of the frame announced by `header`, checked against the limits
+    pub(crate) fn frame_len(&self, header: [u8; 4]) -> Result<usize, Error> {
+        let len = u32::from_be_bytes(header) as usize;
+        if len < 4 {
+            return Err(Error::Framing {
+                registry: None,
+                declared: len,
+                limit: 4,
+                received: None,
+            });
+        } else if len > self.max_frame_len {
+            return Err(Error::Framing {
+                registry: None,
+                declared: len,
+                limit: self.max_frame_len,
+                received: None,
+            });
+        }
+
+        Ok(len)
+    }
+}
+
+impl Default for EppCodec {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Decoder for EppCodec {
+    type Item = String;
+    type Error = Error;
+
+    fn decode(&mut self, src: &mut Vec<u8>) -> Result<Option<String>, Error> {
+        let Some(&header) = src.first_chunk::<4>() else {
+            return Ok(None);
+        };
+
+        let len = self.frame_len(header)?;
+        if src.len() < len {
+            return Ok(None);
+        }
+
+        let frame = src.drain(..len).skip(4).collect::<Vec<_>>();
+        let payload = match self.deflate {
+            true => inflate(&frame, self.max_frame_len)?,
+            false => frame,
+        };
+
+        match String::from_utf8(payload) {
+            Ok(payload) => Ok(Some(payload)),
+            Err(err) => Err(Error::Other(
+                format!(
+                    "frame is not valid UTF-8 ({}): {}",
+                    err.utf8_error(),
+                    Preview(err.as_bytes())
+                )
+                .into(),
+            )),
+        }
+    }
+}
+
+impl Encoder<&str> for EppCodec {
+    type Error = Error;
+
+    fn encode(&mut self, item: &str, dst: &mut Vec<u8>) -> Result<(), Error> {
+        let payload = match self.deflate {
+            true => compress(item.as_bytes())?,
+            false => item.as_bytes().to_vec(),
+        };
+
+        let len = u32::try_from(payload.len() + 4)?;
+        dst.reserve(payload.len() + 4);
+        dst.extend_from_slice(&len.to_be_bytes());
+        dst.extend_from_slice(&payload);
+        Ok(())
+    }
+}
+
+/// Compress a frame payload with raw deflate (RFC 1951)
+#[cfg(feature = "deflate")]
+fn compress(payload: &[u8]) -> io::Result<Vec<u8>> {
+    use std::io::Write;
+
+    let mut encoder =
+        flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(payload)?;
+    encoder.finish()
+}
+
+/// Decompress a raw deflate frame payload of at most `limit` bytes
+#[cfg(feature = "deflate")]
+fn inflate(payload: &[u8], limit: usize) -> Result<Vec<u8>, Error> {
+    use std::io::Read;
+
+    // Stop right after the limit, so that a small frame can't inflate into gigabytes
+    let mut decompressed = Vec::new();
+    flate2::read::DeflateDecoder::new(payload)
+        .take(limit as u64 + 1)
+        .read_to_end(&mut decompressed)?;
+    match decompressed.len() > limit {
+        true => Err(Error::Framing {
+            registry: None,
+            declared: decompressed.len(),
+            limit,
+            received: None,
+        }),
+        false => Ok(decompressed),
+    }
+}
+
+// Without the feature, `deflate` can't be enabled
+#[cfg(not(feature = "deflate"))]
+fn compress(_: &[u8]) -> io::Result<Vec<u8>> {
+    unreachable!()
+}
+
+#[cfg(not(feature = "deflate"))]
+fn inflate(_: &[u8], _: usize) -> Result<Vec<u8>, Error> {
+    unreachable!()
+}
+
+// How many bytes of a frame `Preview` shows
+const PREVIEW_LEN: usize = 64;
+
+// Formats the start of a frame as hex, followed by the same bytes as (lossy) UTF-8
+pub(crate) struct Preview<'a>(pub(crate) &'a [u8]);
+
+impl fmt::Display for Preview<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let shown = &self.0[..self.0.len().min(PREVIEW_LEN)];
+        for (i, byte) in shown.iter().enumerate() {
+            match i {
+                0 => write!(f, "{byte:02x}")?,
+                _ => write!(f, " {byte:02x}")?,
+            }
+        }
+
+        write!(f, " {:?}", String::from_utf8_lossy(shown))?;
+        match self.0.len() - shown.len() {
+            0 => Ok(()),
+            rest => write!(f, " (+{rest} bytes)"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Decoder, Encoder, EppCodec, Preview};
+    use crate::Error;
+
+    #[test]
+    fn codec() {
+        let mut codec = EppCodec::new();
+        let mut buf = Vec::new();
+        codec.encode("<epp/>", &mut buf).unwrap();
+        codec.encode("<epp>ü</epp>", &mut buf).unwrap();
+        assert_eq!(&buf[..10], b"\x00\x00\x00\x0a<epp/>");
+
+        // Frames are only taken off once they are complete
+        let mut partial = buf[..8].to_vec();
+        assert_eq!(codec.decode(&mut partial).unwrap(), None);
+        assert_eq!(partial.len(), 8);
+
+        assert_eq!(codec.decode(&mut buf).unwrap().as_deref(), Some("<epp/>"));
+        assert_eq!(
+            codec.decode(&mut buf).unwrap().as_deref(),
+            Some("<epp>ü</epp>")
+        );
+        assert!(buf.is_empty());
+        assert_eq!(codec.decode(&mut buf).unwrap(), None);
+
+        let err = codec.decode(&mut b"\x00\x00\x00\x02".to_vec()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "frame header announces 2 bytes, less than the minimum of 4"
+        );
+        let err = codec
+            .decode(&mut b"\x00\x00\x00\x05\xff".to_vec())
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            r#"error: frame is not valid UTF-8 (invalid utf-8 sequence of 1 bytes from index 0): ff "�""#
+        );
+    }
+
+    #[test]
+    fn max_frame_len() {
+        let mut codec = EppCodec::new().max_frame_len(16);
+
+        // The header is enough to reject the frame
+        let err = codec.decode(&mut b"\xff\xff\xff\xff".to_vec()).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Framing {
+                declared: 4_294_967_295,
+                limit: 16,
+                ..
+            }
+        ));
+        assert_eq!(
+            err.to_string(),
+            "frame header announces 4294967295 bytes, more than the maximum of 16"
+        );
+
+        let mut buf = Vec::new();
+        codec.encode("<epp/><epp/>", &mut buf).unwrap();
+        assert_eq!(
+            codec.decode(&mut buf).unwrap().as_deref(),
+            Some("<epp/><epp/>")
+        );
+    }
+
+    #[cfg(feature = "deflate")]
+    #[test]
+    fn inflate_limit() {
+        // The compressed frame fits, but its payload doesn't
+        let mut buf = Vec::new();
+        let payload = format!("<epp>{}</epp>", "a".repeat(1000));
+        EppCodec::new()
+            .deflate(true)
+            .encode(&payload, &mut buf)
+            .unwrap();
+        assert!(buf.len() < 64);
+
+        let mut codec = EppCodec::new().deflate(true).max_frame_len(64);
+        let err = codec.decode(&mut buf.clone()).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Framing {
+                declared: 65,
+                limit: 64,
+                ..
+            }
+        ));
+
+        let mut codec = codec.max_frame_len(payload.len());
+        assert_eq!(codec.decode(&mut buf).unwrap(), Some(payload));
+    }
+
+    #[test]
+    fn arbitrary_input() {
+        // Decoding arbitrary bytes fails or waits for more, but doesn't panic
+        let mut state = 0x2545_f491_u32;
+        for len in 0..512 {
+            let mut buf = (0..len)
+                .map(|i| {
+                    state ^= state << 13;
+                    state ^= state >> 17;
+                    state ^= state << 5;
+                    match i {
+                        0..=2 => 0,
+                        _ => state as u8,
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            let mut codec = EppCodec::new();
+            while let Ok(Some(_)) = codec.decode(&mut buf) {}
+        }
+    }
+
+    #[test]
+    fn preview() {
+        let frame = b"\x00\x00\x00\x0b<epp/>\xff";
+        assert_eq!(
+            Preview(frame).to_string(),
+            r#"00 00 00 0b 3c 65 70 70 2f 3e ff "\0\0\0\u{b}<epp/>�""#
+        );
+
+        let frame = [b'a'; 100];
+        let preview = Preview(&frame).to_string();
+        assert!(preview.ends_with(r#" (+36 bytes)"#));
+        assert_eq!(preview.matches("61").count(), 64);
+    }
+}
@@ -1,13 +1,27 @@
 //! Common data types included in EPP Requests and Responses
 
 use std::borrow::Cow;
+use std::fmt;
+use std::str::FromStr;
 
-use instant_xml::{FromXml, ToXml};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use instant_xml::ser::Context;
+use instant_xml::{
+    Accumulate, AnyElement, Deserializer, FromXml, OptionAccumulator, Serializer, ToXml,
+};
+
+use tracing::warn;
 
 use crate::request::Extension;
+use crate::response::{Response, ResultCode};
+use crate::Error;
 
 pub(crate) const EPP_XMLNS: &str = "urn:ietf:params:xml:ns:epp-1.0";
 
+/// No extension data
+///
+/// As a response type, any extension data in the response is ignored, for example the response
+/// data of an extension attached by a client layer.
 #[derive(Debug, Eq, PartialEq, ToXml)]
 pub struct NoExtension;
 
@@ -24,14 +38,466 @@ impl<'xml> FromXml<'xml> for NoExtension {
         unreachable!()
     }
 
-    type Accumulator = Option<Self>;
+    type Accumulator = NoExtensionAccumulator;
     const KIND: instant_xml::Kind = instant_xml::Kind::Element;
 }
 
+/// Accumulator for deserializing [`NoExtension`], which is complete without any data
+#[derive(Debug, Default)]
+pub struct NoExtensionAccumulator;
+
+impl Accumulate<NoExtension> for NoExtensionAccumulator {
+    fn try_done(self, _: &'static str) -> Result<NoExtension, instant_xml::Error> {
+        Ok(NoExtension)
+    }
+}
+
 impl Extension for NoExtension {
     type Response = Self;
 }
 
+/// A language tag as used in `lang` attributes and the login `<lang>` option
+///
+/// Tags are checked for the basic [RFC 5646](https://www.rfc-editor.org/rfc/rfc5646) syntax of
+/// alphanumeric subtags separated by hyphens, and compare case-insensitively. Tags received from
+/// the server are kept as sent, even if malformed, so that a bad `lang` attribute doesn't fail
+/// the whole response; use [`is_well_formed()`](Self::is_well_formed) to check them.
+#[derive(Clone, Debug)]
+pub struct LanguageTag(String);
+
+impl LanguageTag {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The primary language subtag, e.g. `de` for `de-CH`
+    pub fn primary(&self) -> &str {
+        self.0.split('-').next().unwrap_or_default()
+    }
+
+    /// Whether the tag has the RFC 5646 syntax, which is only unchecked for received tags
+    pub fn is_well_formed(&self) -> bool {
+        well_formed(&self.0)
+    }
+
+    /// Keep a tag received from the server, logging a warning if it is malformed
+    pub(crate) fn received(value: String) -> Self {
+        if !well_formed(&value) {
+            warn!("server sent invalid language tag {value:?}");
+        }
+
+        Self(value)
+    }
+}
+
+fn well_formed(tag: &str) -> bool {
+    tag.split('-').enumerate().all(|(i, subtag)| {
+        (1..=8).contains(&subtag.len())
+            && match i {
+                0 => subtag.bytes().all(|b| b.is_ascii_alphabetic()),
+                _ => subtag.bytes().all(|b| b.is_ascii_alphanumeric()),
+            }
+    })
+}
+
+/// The default language for EPP, `en`, used when no `lang` attribute is present
+impl Default for LanguageTag {
+    fn default() -> Self {
+        Self("en".to_owned())
+    }
+}
+
+impl FromStr for LanguageTag {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match well_formed(s) {
+            true => Ok(Self(s.to_owned())),
+            false => Err(Error::Other(format!("invalid language tag {s:?}").into())),
+        }
+    }
+}
+
+impl fmt::Display for LanguageTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl PartialEq for LanguageTag {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.eq_ignore_ascii_case(&other.0)
+    }
+}
+
+impl Eq for LanguageTag {}
+
+impl PartialEq<str> for LanguageTag {
+    fn eq(&self, other: &str) -> bool {
+        self.0.eq_ignore_ascii_case(other)
+    }
+}
+
+impl PartialEq<&str> for LanguageTag {
+    fn eq(&self, other: &&str) -> bool {
+        self.0.eq_ignore_ascii_case(other)
+    }
+}
+
+impl<'xml> FromXml<'xml> for LanguageTag {
+    fn matches(id: instant_xml::Id<'_>, field: Option<instant_xml::Id<'_>>) -> bool {
+        match field {
+            Some(field) => id == field,
+            None => false,
+        }
+    }
+
+    fn deserialize<'cx>(
+        into: &mut Self::Accumulator,
+        field: &'static str,
+        deserializer: &mut instant_xml::Deserializer<'cx, 'xml>,
+    ) -> Result<(), instant_xml::Error> {
+        let mut value = None;
+        String::deserialize(&mut value, field, deserializer)?;
+        if let Some(value) = value {
+            *into = Some(Self::received(value));
+        }
+
+        Ok(())
+    }
+
+    type Accumulator = Option<Self>;
+    const KIND: instant_xml::Kind = instant_xml::Kind::Scalar;
+}
+
+/// The status values of one of the object mappings, see [`StatusValue`]
+pub trait ObjectStatus: Clone + fmt::Display + FromStr {
+    /// The namespace of the object mapping
+    const XMLNS: &'static str;
+
+    /// Whether the status is set and removed by the server only
+    fn is_server_managed(&self) -> bool;
+}
+
+/// Check that an update of `object` only adds and removes statuses the client may manage
+///
+/// Update commands reject server-managed statuses before sending, unless their
+/// `allow_server_statuses()` was called: some registries let clients manage additional
+/// statuses.
+pub(crate) fn check_client_statuses<S: ObjectStatus>(
+    object: &str,
+    add: Option<&[StatusValue<S>]>,
+    remove: Option<&[StatusValue<S>]>,
+) -> Result<(), Error> {
+    match add
+        .into_iter()
+        .chain(remove)
+        .flatten()
+        .map(|value| &value.status)
+        .find(|status| status.is_server_managed())
+    {
+        Some(status) => Err(Error::Other(
+            format!("{object}: server-managed status {status} cannot be added or removed").into(),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// A `<status>` element: a status value with an optional human-readable reason
+///
+/// Clients can explain why they set a status (for example, why a domain is on `clientHold`),
+/// and servers may include such text in info responses.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StatusValue<S> {
+    pub status: S,
+    /// Text explaining the status
+    pub reason: Option<String>,
+    /// The language of the `reason`, `en` if absent
+    pub lang: Option<LanguageTag>,
+}
+
+impl<S> StatusValue<S> {
+    /// A `status` without a reason
+    pub fn new(status: S) -> Self {
+        Self {
+            status,
+            reason: None,
+            lang: None,
+        }
+    }
+
+    /// Explain the status with `reason`, written in `lang` if not English
+    pub fn reason(mut self, reason: &str, lang: Option<LanguageTag>) -> Self {
+        self.reason = Some(reason.to_owned());
+        self.lang = lang;
+        self
+    }
+}
+
+impl<S> From<S> for StatusValue<S> {
+    fn from(status: S) -> Self {
+        Self::new(status)
+    }
+}
+
+impl<S: PartialEq> PartialEq<S> for StatusValue<S> {
+    fn eq(&self, other: &S) -> bool {
+        self.status == *other
+    }
+}
+
+impl<S: ObjectStatus> ToXml for StatusValue<S> {
+    fn serialize<W: fmt::Write + ?Sized>(
+        &self,
+        _: Option<instant_xml::Id<'_>>,
+        serializer: &mut Serializer<W>,
+    ) -> Result<(), instant_xml::Error> {
+        let status = serializer.write_start("status", S::XMLNS, None::<Context<0>>)?;
+        serializer.write_attr("s", S::XMLNS, &self.status.to_string())?;
+        if let Some(lang) = &self.lang {
+            serializer.write_attr("lang", S::XMLNS, lang.as_str())?;
+        }
+
+        let Some(reason) = &self.reason else {
+            return serializer.end_empty();
+        };
+
+        serializer.end_start()?;
+        reason.serialize(None, serializer)?;
+        serializer.write_close(status)
+    }
+}
+
+impl<'xml, S: ObjectStatus> FromXml<'xml> for StatusValue<S> {
+    fn matches(id: instant_xml::Id<'_>, _: Option<instant_xml::Id<'_>>) -> bool {
+        id == instant_xml::Id {
+            ns: S::XMLNS,
+            name: "status",
+        }
+    }
+
+    fn deserialize<'cx>(
+        into: &mut Self::Accumulator,
+        field: &'static str,
+        deserializer: &mut Deserializer<'cx, 'xml>,
+    ) -> Result<(), instant_xml::Error> {
+        use instant_xml::de::Node;
+        use instant_xml::Error;
+
+        let (mut status, mut reason, mut lang) = (None, None, None);
+        while let Some(node) = deserializer.next() {
+            match node? {
+                Node::Attribute(attr) => {
+                    let id = deserializer.attribute_id(&attr)?;
+                    if !id.ns.is_empty() {
+                        continue;
+                    }
+
+                    match id.name {
+                        "s" => match attr.value.parse() {
+                            Ok(s) => status = Some(s),
+                            Err(_) => {
+                                return Err(Error::UnexpectedValue(format!(
+                                    "invalid status {:?}",
+                                    attr.value
+                                )))
+                            }
+                        },
+                        "lang" => match attr.value.parse() {
+                            Ok(tag) => lang = Some(tag),
+                            Err(_) => {
+                                return Err(Error::UnexpectedValue(format!(
+                                    "invalid language tag {:?}",
+                                    attr.value
+                                )))
+                            }
+                        },
+                        _ => {}
+                    }
+                }
+                Node::Text(text) => match text.trim() {
+                    "" => {}
+                    text => reason = Some(text.to_owned()),
+                },
+                Node::Open(element) => deserializer.nested(element).ignore()?,
+                _ => {}
+            }
+        }
+
+        let Some(status) = status else {
+            return Err(Error::MissingValue(field));
+        };
+
+        *into = Some(Self {
+            status,
+            reason,
+            lang,
+        });
+        Ok(())
+    }
+
+    type Accumulator = Option<Self>;
+    const KIND: instant_xml::Kind = instant_xml::Kind::Element;
+}
+
+/// A single result from a domain, host or contact `<check>` response
+///
+/// The object mappings use different element names for the checked object, so each has its
+/// own type. This trait gives them uniform accessors, which allows writing code for bulk
+/// checks once.
+pub trait Checked {
+    /// The domain or host name, or contact ID, that was checked
+    fn id(&self) -> &str;
+    /// Whether the object can be provisioned
+    fn available(&self) -> bool;
+    /// The server's explanation of why the object is not available, if any
+    fn reason(&self) -> Option<&str>;
+}
+
+/// The `<chkData>` of a domain, host or contact `<check>` response, see [`Checked`]
+pub trait CheckResults {
+    type Item: Checked;
+
+    /// The results, in the order the server returned them
+    fn results(&self) -> &[Self::Item];
+
+    /// The IDs of the objects that are available
+    fn available(&self) -> impl Iterator<Item = &str> {
+        self.results()
+            .iter()
+            .filter(|checked| checked.available())
+            .map(|checked| checked.id())
+    }
+}
+
+/// An arbitrary XML element, captured as-is
+///
+/// Used where a response contains data this crate has no type for. The `Display` impl
+/// renders the element back to XML, which is mostly useful for logging.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RawXml(pub AnyElement<'static>);
+
+impl RawXml {
+    /// The namespace of the element
+    pub fn ns(&self) -> &str {
+        &self.0.ns
+    }
+
+    /// The local name of the element
+    pub fn name(&self) -> &str {
+        &self.0.name
+    }
+}
+
+impl fmt::Display for RawXml {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_element(&self.0, "", f)
+    }
+}
+
+fn write_element(
+    elem: &AnyElement<'_>,
+    parent_ns: &str,
+    f: &mut fmt::Formatter<'_>,
+) -> fmt::Result {
+    write!(f, "<{}", elem.name)?;
+    if elem.ns != parent_ns {
+        write!(f, " xmlns=\"{}\"", Escaped(&elem.ns))?;
+    }
+
+    for (i, attr) in elem.attributes.iter().enumerate() {
+        match attr.ns.is_empty() {
+            true => write!(f, " {}=\"{}\"", attr.name, Escaped(&attr.value))?,
+            false => write!(
+                f,
+                " xmlns:a{i}=\"{}\" a{i}:{}=\"{}\"",
+                Escaped(&attr.ns),
+                attr.name,
+                Escaped(&attr.value)
+            )?,
+        }
+    }
+
+    if elem.text.is_none() && elem.children.is_empty() {
+        return f.write_str(" />");
+    }
+
+    f.write_str(">")?;
+    if let Some(text) = &elem.text {
+        write!(f, "{}", Escaped(text))?;
+    }
+    for child in &elem.children {
+        write_element(child, &elem.ns, f)?;
+    }
+    write!(f, "</{}>", elem.name)
+}
+
+struct Escaped<'a>(&'a str);
+
+impl fmt::Display for Escaped<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '&' => f.write_str("&amp;")?,
+                '<' => f.write_str("&lt;")?,
+                '>' => f.write_str("&gt;")?,
+                '"' => f.write_str("&quot;")?,
+                c => fmt::Write::write_char(f, c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'xml> FromXml<'xml> for RawXml {
+    /// Matches any element
+    fn matches(_: instant_xml::Id<'_>, _: Option<instant_xml::Id<'_>>) -> bool {
+        true
+    }
+
+    fn deserialize<'cx>(
+        into: &mut Self::Accumulator,
+        field: &'static str,
+        deserializer: &mut instant_xml::Deserializer<'cx, 'xml>,
+    ) -> Result<(), instant_xml::Error> {
+        let mut elem = None;
+        <AnyElement as FromXml>::deserialize(&mut elem, field, deserializer)?;
+        *into = elem.map(|elem| Self(elem.into_owned()));
+        Ok(())
+    }
+
+    type Accumulator = Option<Self>;
+    const KIND: instant_xml::Kind = instant_xml::Kind::Element;
+}
+
+/// The outcome of a domain, host or contact `<delete>`
+///
+/// Registries either delete the object right away (`1000`), or accept the request and report
+/// the outcome later through the poll queue (`1001`), typically with the object in
+/// `pendingDelete` in the meantime. RFC 5730 defines no `<resData>` for deletes, but some
+/// registries send one anyway; it is kept as is. Convert the response to a delete command into
+/// this type with `From`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DeleteData {
+    /// Whether the object has been deleted
+    pub immediate: bool,
+    /// Whether the delete is pending, to be reported on through the poll queue
+    pub pending: bool,
+    /// The `<resData>` of the response, if any
+    pub data: Option<RawXml>,
+}
+
+impl<E> From<Response<RawXml, E>> for DeleteData {
+    fn from(rsp: Response<RawXml, E>) -> Self {
+        let pending = rsp.result.code == ResultCode::CommandCompletedSuccessfullyActionPending;
+        Self {
+            immediate: rsp.result.code.is_success() && !pending,
+            pending,
+            data: rsp.res_data.map(|data| data.into_inner()),
+        }
+    }
+}
+
 /// The `<option>` type in EPP XML login requests
 #[derive(Debug, Eq, FromXml, PartialEq, ToXml)]
 #[xml(rename = "options", ns(EPP_XMLNS))]
@@ -72,3 +538,145 @@ pub struct Services<'a> {
     #[xml(rename = "svcExtension")]
     pub svc_ext: Option<ServiceExtension<'a>>,
 }
+
+/// Deserialize a date, leniently unless the client has `EppClient::set_strict_dates()` enabled
+pub(crate) fn deserialize_date(
+    into: &mut Option<DateTime<Utc>>,
+    field: &'static str,
+    deserializer: &mut Deserializer<'_, '_>,
+) -> Result<(), instant_xml::Error> {
+    if into.is_some() {
+        return Err(instant_xml::Error::DuplicateValue(field));
+    }
+
+    let Some(value) = deserializer.take_str()? else {
+        return Ok(());
+    };
+
+    match parse_date(&value, crate::xml::Options::current().strict_dates) {
+        Some(date) => *into = Some(date),
+        None => {
+            return Err(instant_xml::Error::UnexpectedValue(format!(
+                "invalid date/time '{value}' for {field}"
+            )))
+        }
+    }
+
+    deserializer.ignore()
+}
+
+/// Deserialize an optional date, see [`deserialize_date()`]
+pub(crate) fn deserialize_date_option(
+    into: &mut OptionAccumulator<DateTime<Utc>, Option<DateTime<Utc>>>,
+    field: &'static str,
+    deserializer: &mut Deserializer<'_, '_>,
+) -> Result<(), instant_xml::Error> {
+    deserialize_date(into.get_mut(), field, deserializer)
+}
+
+/// Request elements that only hold other elements, such as `<add>` or `<ns>`
+pub(crate) trait Container {
+    /// Whether the element would be serialized without any content
+    fn is_empty(&self) -> bool;
+}
+
+/// Serialize an optional container, leaving it out if it is empty
+///
+/// Empty containers are kept for clients with `EppClient::set_keep_empty_elements()` enabled.
+pub(crate) fn serialize_container<T: Container + ToXml, W: fmt::Write + ?Sized>(
+    value: &Option<T>,
+    serializer: &mut Serializer<'_, W>,
+) -> Result<(), instant_xml::Error> {
+    match value {
+        Some(value) if !value.is_empty() || crate::xml::Options::current().keep_empty_elements => {
+            value.serialize(None, serializer)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Parse an RFC 3339 date, or one of the variations registries send instead
+///
+/// In `strict` mode, only RFC 3339 dates in UTC are accepted. Otherwise, other offsets are
+/// converted to UTC, and dates without a timezone, seconds or time of day are taken to be in
+/// UTC.
+fn parse_date(value: &str, strict: bool) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+        return match strict && date.offset().utc_minus_local() != 0 {
+            true => None,
+            false => Some(date.with_timezone(&Utc)),
+        };
+    }
+
+    if strict {
+        return None;
+    }
+
+    let with_offset = match value.strip_suffix(['Z', 'z']) {
+        Some(rest) => Cow::Owned(format!("{rest}+00:00")),
+        None => Cow::Borrowed(value),
+    };
+
+    for format in [
+        "%Y-%m-%dT%H:%M%:z",
+        "%Y-%m-%d %H:%M:%S%.f%:z",
+        "%Y-%m-%d %H:%M%:z",
+    ] {
+        if let Ok(date) = DateTime::parse_from_str(&with_offset, format) {
+            return Some(date.with_timezone(&Utc));
+        }
+    }
+
+    for format in [
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%dT%H:%M",
+        "%Y-%m-%d %H:%M:%S%.f",
+        "%Y-%m-%d %H:%M",
+    ] {
+        if let Ok(date) = NaiveDateTime::parse_from_str(value, format) {
+            return Some(date.and_utc());
+        }
+    }
+
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .map(|date| date.and_hms_opt(0, 0, 0).unwrap().and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::parse_date;
+
+    #[test]
+    fn dates() {
+        let expected = Utc.with_ymd_and_hms(2022, 7, 25, 18, 11, 0).unwrap();
+        for value in [
+            "2022-07-25T18:11:00Z",
+            "2022-07-25T18:11:00.0Z",
+            "2022-07-25T20:11:00+02:00",
+            "2022-07-25T18:11:00",
+            "2022-07-25T18:11",
+            "2022-07-25T18:11Z",
+            "2022-07-25T19:11+01:00",
+            "2022-07-25 18:11:00",
+            " 2022-07-25T18:11:00.000 ",
+        ] {
+            assert_eq!(parse_date(value, false), Some(expected), "{value}");
+        }
+
+        let midnight = Utc.with_ymd_and_hms(2022, 7, 25, 0, 0, 0).unwrap();
+        assert_eq!(parse_date("2022-07-25", false), Some(midnight));
+        assert_eq!(parse_date("25/07/2022", false), None);
+    }
+
+    #[test]
+    fn strict_dates() {
+        let expected = Utc.with_ymd_and_hms(2022, 7, 25, 18, 11, 0).unwrap();
+        assert_eq!(parse_date("2022-07-25T18:11:00Z", true), Some(expected));
+        assert_eq!(parse_date("2022-07-25T20:11:00+02:00", true), None);
+        assert_eq!(parse_date("2022-07-25T18:11:00", true), None);
+    }
+}
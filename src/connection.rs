@@ -2,25 +2,41 @@
 //!
 //! See also [RFC 5734](https://tools.ietf.org/html/rfc5734).
 
+use std::collections::hash_map::RandomState;
+use std::collections::VecDeque;
 use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
 use std::pin::Pin;
 use std::task::{Context, Poll};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use std::{io, mem, str};
 
 use async_trait::async_trait;
-use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt, ReadBuf};
-use tracing::{debug, info};
+use chrono::{DateTime, Utc};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, ReadBuf};
+use tokio::sync::broadcast;
+use tracing::{debug, info, trace, warn};
 
+use crate::codec::{Decoder, Encoder, EppCodec, Preview};
 use crate::error::Error;
+use crate::hello::{Greeting, ServiceMenu};
+#[cfg(feature = "prometheus")]
+use crate::metrics::Metrics;
+use crate::runtime;
 
 /// EPP Connection struct with some metadata for the connection
 pub(crate) struct EppConnection<C: Connector> {
     pub(crate) registry: String,
-    connector: C,
+    pub(crate) connector: C,
     stream: C::Connection,
     pub(crate) greeting: String,
+    // The service menu from `greeting`
+    pub(crate) svc_menu: Option<ServiceMenu>,
+    // The most recent greetings, oldest first
+    pub(crate) greetings: VecDeque<ReceivedGreeting>,
     timeout: Duration,
+    // How long a request may wait in `next` before it is failed instead of sent
+    pub(crate) queue_ttl: Option<Duration>,
     // A request that is currently in flight
     //
     // Because the code here currently depends on only one request being in flight at a time,
@@ -30,7 +46,17 @@ pub(crate) struct EppConnection<C: Connector> {
     //
     // If we get a request while another request is in flight (because its future was dropped),
     // we will store it here until the current request is finished.
-    next: Option<RequestState>,
+    next: Option<Queued>,
+    // Subscribers to the connection's state transitions
+    events: broadcast::Sender<ConnectionEvent>,
+    // Whether frame payloads after the greeting are deflate-compressed
+    pub(crate) deflate: bool,
+    // Whether to log frame headers and a preview of each frame at TRACE level
+    pub(crate) wire_dump: bool,
+    // Set after a framing error that left the stream at an unknown position
+    closed: bool,
+    #[cfg(feature = "prometheus")]
+    pub(crate) metrics: Metrics,
 }
 
 impl<C: Connector> EppConnection<C> {
@@ -40,42 +66,208 @@ impl<C: Connector> EppConnection<C> {
         timeout: Duration,
     ) -> Result<Self, Error> {
         let mut this = Self {
-            registry,
             stream: connector.connect(timeout).await?,
             connector,
             greeting: String::new(),
+            svc_menu: None,
+            greetings: VecDeque::with_capacity(GREETINGS_CAPACITY),
             timeout,
+            queue_ttl: None,
             current: None,
             next: None,
+            events: broadcast::channel(EVENTS_CAPACITY).0,
+            deflate: false,
+            wire_dump: false,
+            closed: false,
+            #[cfg(feature = "prometheus")]
+            metrics: Metrics::new(&registry),
+            registry,
         };
 
+        this.emit(ConnectionEvent::Connected);
         this.read_greeting().await?;
         Ok(this)
     }
 
+    /// Subscribe to the connection's state transitions
+    pub(crate) fn events(&self) -> broadcast::Receiver<ConnectionEvent> {
+        self.events.subscribe()
+    }
+
+    pub(crate) fn emit(&self, event: ConnectionEvent) {
+        #[cfg(feature = "prometheus")]
+        self.metrics.event(&event);
+        // Sending only fails if there are no subscribers, which is fine
+        let _ = self.events.send(event);
+    }
+
+    // Update the request gauges from the current and next request
+    fn record_requests(&self) {
+        #[cfg(feature = "prometheus")]
+        {
+            let queued = usize::from(self.next.is_some());
+            let in_flight = usize::from(self.current.is_some()) + queued;
+            self.metrics.requests(in_flight, queued);
+        }
+    }
+
+    // Log the header and a preview of a full frame, if enabled
+    fn dump(&self, direction: &str, frame: &[u8]) {
+        if !self.wire_dump {
+            return;
+        }
+
+        let header = frame
+            .get(..4)
+            .map(|header| u32::from_be_bytes(header.try_into().unwrap()));
+        trace!(
+            "{}: {} frame with header {:?}, {} bytes: {}",
+            self.registry,
+            direction,
+            header,
+            frame.len(),
+            Preview(frame)
+        );
+    }
+
+    // Read the greeting, reconnecting to try again as configured by the connector
     async fn read_greeting(&mut self) -> Result<(), Error> {
+        let retry = self.connector.greeting_retry();
+        let mut backoff = retry.backoff;
+        let mut retries = retry.retries;
+        loop {
+            let err = match self.try_read_greeting().await {
+                Ok(()) => return Ok(()),
+                Err(err) if retries == 0 => return Err(err),
+                Err(err) => err,
+            };
+
+            debug!(
+                "{}: failed to read greeting ({err}), retrying in {backoff:?}",
+                self.registry
+            );
+            runtime::sleep(backoff).await;
+            backoff *= 2;
+            retries -= 1;
+
+            self.emit(ConnectionEvent::Reconnecting);
+            self.connect().await?;
+        }
+    }
+
+    async fn try_read_greeting(&mut self) -> Result<(), Error> {
         assert!(self.current.is_none());
         self.current = Some(RequestState::ReadLength {
             read: 0,
             buf: vec![0; 256],
         });
 
-        self.greeting = RequestFuture { conn: self }.await?;
+        // The greeting is never compressed
+        let deflate = mem::take(&mut self.deflate);
+        let greeting = RequestFuture { conn: self }.await;
+        self.deflate = deflate;
+
+        let greeting = greeting?;
+        let parsed = match crate::xml::deserialize::<Greeting>(&greeting) {
+            Ok(parsed) => parsed,
+            Err(_) => {
+                let err = Error::UnexpectedGreeting { raw: greeting };
+                self.emit(ConnectionEvent::Closed {
+                    reason: CloseReason::Failed(err.to_string()),
+                });
+                return Err(err);
+            }
+        };
+
+        if self.greetings.len() == GREETINGS_CAPACITY {
+            self.greetings.pop_front();
+        }
+        self.greetings.push_back(ReceivedGreeting {
+            received: Utc::now(),
+            service_id: parsed.service_id,
+            service_date: parsed.service_date,
+            raw: greeting.clone(),
+        });
+
+        self.greeting = greeting;
+        self.svc_menu = Some(parsed.svc_menu);
+        self.emit(ConnectionEvent::GreetingReceived);
         Ok(())
     }
 
+    // Reconnect, retrying as configured by the connector
     pub(crate) async fn reconnect(&mut self) -> Result<(), Error> {
-        debug!("{}: reconnecting", self.registry);
         let _ = self.current.take();
         let _ = self.next.take();
-        self.stream = self.connector.connect(self.timeout).await?;
-        self.read_greeting().await?;
+
+        let backoff = self.connector.reconnect_backoff();
+        let mut delay = backoff.backoff;
+        let mut attempts = 0;
+        loop {
+            debug!("{}: reconnecting", self.registry);
+            self.emit(ConnectionEvent::Reconnecting);
+            attempts += 1;
+            let err = match self.try_reconnect().await {
+                Ok(()) => return Ok(()),
+                Err(err) if backoff.retries == 0 => return Err(err),
+                Err(err) if attempts > backoff.retries => {
+                    return Err(Error::ReconnectExhausted {
+                        attempts,
+                        last: Box::new(err),
+                    })
+                }
+                Err(err) => err,
+            };
+
+            let wait = match backoff.jitter {
+                true => jitter(delay),
+                false => delay,
+            };
+
+            debug!(
+                "{}: reconnect attempt {attempts} failed ({err}), retrying in {wait:?}",
+                self.registry
+            );
+            runtime::sleep(wait).await;
+            delay *= 2;
+            if let Some(max) = backoff.max_backoff {
+                delay = delay.min(max);
+            }
+        }
+    }
+
+    async fn try_reconnect(&mut self) -> Result<(), Error> {
+        self.connect().await?;
+        self.read_greeting().await
+    }
+
+    // Replace the stream with a new connection
+    async fn connect(&mut self) -> Result<(), Error> {
+        self.stream = match self.connector.connect(self.timeout).await {
+            Ok(stream) => stream,
+            Err(err) => {
+                self.emit(ConnectionEvent::Closed {
+                    reason: CloseReason::Failed(err.to_string()),
+                });
+                return Err(err);
+            }
+        };
+
+        self.closed = false;
+        self.emit(ConnectionEvent::Connected);
         Ok(())
     }
 
     /// Sends an EPP XML request to the registry and returns the response
     pub(crate) fn transact<'a>(&'a mut self, command: &str) -> Result<RequestFuture<'a, C>, Error> {
-        let new = RequestState::new(command)?;
+        if self.closed {
+            return Err(Error::Closed);
+        }
+
+        let new = RequestState::new(command, self.codec())?;
+        if let RequestState::Writing { buf, .. } = &new {
+            self.dump("Sending", buf);
+        }
 
         // If we have a request currently in flight, finish that first
         // If another request was queued up behind the one in flight, just replace it
@@ -85,19 +277,47 @@ impl<C: Connector> EppConnection<C> {
                     "{}: Queueing up request in order to finish in-flight request",
                     self.registry
                 );
-                self.next = Some(new);
+                self.next = Some(Queued {
+                    state: new,
+                    since: Instant::now(),
+                });
             }
             false => self.current = Some(new),
         }
 
+        self.record_requests();
         Ok(RequestFuture { conn: self })
     }
 
+    /// Finishes any request that is still in flight, discarding its response
+    ///
+    /// If the future for a request was dropped before its response was read, the outcome of
+    /// that request is unknown to the caller. Draining pushes it through so that the server
+    /// sees the full exchange before the session is ended.
+    pub(crate) async fn drain(&mut self) -> Result<(), Error> {
+        if self.current.is_none() {
+            return Ok(());
+        }
+
+        debug!("{}: draining in-flight request", self.registry);
+        RequestFuture { conn: self }.await?;
+        Ok(())
+    }
+
+    /// Finish any in-flight request and hand out the stream
+    pub(crate) async fn into_stream(mut self) -> Result<C::Connection, Error> {
+        self.drain().await?;
+        Ok(self.stream)
+    }
+
     /// Closes the socket and shuts down the connection
     pub(crate) async fn shutdown(&mut self) -> Result<(), Error> {
         info!("{}: Closing connection", self.registry);
-        timeout(self.timeout, self.stream.shutdown()).await?;
-        Ok(())
+        let result = timeout(self.timeout, self.stream.shutdown()).await;
+        self.emit(ConnectionEvent::Closed {
+            reason: CloseReason::Shutdown,
+        });
+        result
     }
 
     fn handle(
@@ -167,14 +387,66 @@ impl<C: Connector> EppConnection<C> {
                     return Ok(Transition::Next(state));
                 }
 
-                let expected = u32::from_be_bytes(filled[..4].try_into()?) as usize;
+                let header = &buf[..4];
+                let expected = u32::from_be_bytes(header.try_into()?) as usize;
                 debug!("{}: Expected response length: {}", self.registry, expected);
+                if self.wire_dump {
+                    trace!(
+                        "{}: Frame header {:02x?} = {}, {} bytes read so far: {}",
+                        self.registry,
+                        header,
+                        expected,
+                        read,
+                        Preview(&buf[..read])
+                    );
+                }
+
+                // Check the length against the codec's limits before allocating for the frame
+                match self.codec().frame_len(header.try_into()?) {
+                    Ok(_) => {}
+                    // A header can't start with `<` without announcing more than the maximum,
+                    // so this is an uncompressed XML document sent without a header. Read up
+                    // to its end, which leaves the stream in sync for the next frame.
+                    Err(err) if !self.deflate && buf[0] == b'<' => {
+                        warn!("{}: {err}, reading response without header", self.registry);
+                        let buf = mem::take(buf);
+                        return Ok(Transition::Next(RequestState::Unframed {
+                            read,
+                            buf,
+                            declared: expected,
+                        }));
+                    }
+                    Err(err) => {
+                        self.dump("Malformed", &buf[..read]);
+                        self.closed = true;
+                        return Err(self.context(err));
+                    }
+                }
+
+                // The length includes the header itself, and we only ever have one request in
+                // flight, so the server can't have sent more than one frame yet
+                if read > expected {
+                    self.dump("Malformed", &buf[..read]);
+                    self.closed = true;
+                    return Err(Error::Framing {
+                        registry: Some(self.registry.clone()),
+                        declared: expected,
+                        limit: self.codec().limit(),
+                        received: Some(read),
+                    });
+                }
+
                 buf.resize(expected, 0);
-                Ok(Transition::Next(RequestState::Reading {
-                    read,
-                    buf: mem::take(buf),
-                    expected,
-                }))
+                let mut buf = mem::take(buf);
+                match read < expected {
+                    true => Ok(Transition::Next(RequestState::Reading {
+                        read,
+                        buf,
+                        expected,
+                    })),
+                    // The entire frame arrived with the header
+                    false => self.finish(&mut buf),
+                }
             }
             RequestState::Reading {
                 mut read,
@@ -190,9 +462,13 @@ impl<C: Connector> EppConnection<C> {
 
                 let filled = read_buf.filled();
                 if filled.is_empty() {
+                    self.dump("Truncated", &buf[..read]);
                     return Err(io::Error::new(
                         io::ErrorKind::UnexpectedEof,
-                        format!("{}: Unexpected EOF while reading", self.registry),
+                        format!(
+                            "{}: Unexpected EOF while reading, {} out of {} bytes done",
+                            self.registry, read, expected
+                        ),
                     )
                     .into());
                 }
@@ -206,22 +482,108 @@ impl<C: Connector> EppConnection<C> {
                     expected
                 );
 
-                //
-
-                Ok(if read < *expected {
+                match read < *expected {
                     // If we haven't received the entire response yet, stick to the `Reading` state.
-                    Transition::Next(state)
-                } else if let Some(next) = self.next.take() {
-                    // Otherwise, if we were just pushing through this request because it was already
-                    // in flight when we started a new one, ignore this response and move to the
-                    // next request (the one this `RequestFuture` is actually for).
-                    Transition::Next(next)
-                } else {
-                    // Otherwise, drain off the frame header and convert the rest to a `String`.
-                    buf.drain(..4);
-                    Transition::Done(String::from_utf8(mem::take(buf))?)
-                })
+                    true => Ok(Transition::Next(state)),
+                    false => self.finish(buf),
+                }
+            }
+            RequestState::Unframed {
+                read,
+                buf,
+                declared,
+            } => {
+                if !ends_document(&buf[..*read]) {
+                    if *read == buf.len() {
+                        if *read >= self.codec().limit() {
+                            self.closed = true;
+                            return Err(Error::Framing {
+                                registry: Some(self.registry.clone()),
+                                declared: *declared,
+                                limit: self.codec().limit(),
+                                received: Some(*read),
+                            });
+                        }
+                        buf.resize(2 * buf.len(), 0);
+                    }
+
+                    let mut read_buf = ReadBuf::new(&mut buf[*read..]);
+                    match Pin::new(&mut self.stream).poll_read(cx, &mut read_buf) {
+                        Poll::Ready(Ok(())) => {}
+                        Poll::Ready(Err(err)) => return Err(err.into()),
+                        Poll::Pending => return Ok(Transition::Pending(state)),
+                    }
+
+                    let filled = read_buf.filled().len();
+                    if filled == 0 {
+                        self.dump("Truncated", &buf[..*read]);
+                        return Err(io::Error::new(
+                            io::ErrorKind::UnexpectedEof,
+                            format!(
+                                "{}: Unexpected EOF while reading response without header",
+                                self.registry
+                            ),
+                        )
+                        .into());
+                    }
+
+                    *read += filled;
+                    return Ok(Transition::Next(state));
+                }
+
+                // Add the missing header, so that the frame is handled like any other
+                let mut frame = u32::try_from(*read + 4)?.to_be_bytes().to_vec();
+                frame.extend_from_slice(&buf[..*read]);
+                self.finish(&mut frame)
+            }
+        }
+    }
+
+    // Handle a complete response frame
+    fn finish(&mut self, buf: &mut Vec<u8>) -> Result<Transition, Error> {
+        self.dump("Received", buf);
+        if let Some(next) = self.next.take() {
+            // If we were just pushing through this request because it was already in flight
+            // when we started a new one, ignore this response and move to the next request (the
+            // one this `RequestFuture` is actually for). If that one has been waiting for too
+            // long, fail it without sending it.
+            if self.queue_ttl.is_some_and(|ttl| next.since.elapsed() > ttl) {
+                debug!("{}: Queued request expired", self.registry);
+                return Err(Error::QueueTimeout);
             }
+
+            return Ok(Transition::Next(next.state));
+        }
+
+        // Otherwise, decode the frame to a `String`
+        match self.codec().decode(buf) {
+            Ok(Some(payload)) => Ok(Transition::Done(payload)),
+            Ok(None) => unreachable!("incomplete frame"),
+            Err(err) => Err(self.context(err)),
+        }
+    }
+
+    // The codec for frames after the greeting
+    fn codec(&self) -> EppCodec {
+        EppCodec::new().with_deflate(self.deflate)
+    }
+
+    // Add the registry to a framing error
+    fn context(&self, err: Error) -> Error {
+        match err {
+            Error::Other(err) => Error::Other(format!("{}: {err}", self.registry).into()),
+            Error::Framing {
+                registry: None,
+                declared,
+                limit,
+                received,
+            } => Error::Framing {
+                registry: Some(self.registry.clone()),
+                declared,
+                limit,
+                received,
+            },
+            err => err,
         }
     }
 }
@@ -244,12 +606,22 @@ impl<C: Connector> Future for RequestFuture<'_, C> {
                 }
                 Ok(Transition::Pending(state)) => {
                     this.conn.current = Some(state);
+                    this.conn.record_requests();
                     return Poll::Pending;
                 }
-                Ok(Transition::Done(rsp)) => return Poll::Ready(Ok(rsp)),
+                Ok(Transition::Done(rsp)) => {
+                    this.conn.record_requests();
+                    return Poll::Ready(Ok(rsp));
+                }
                 Err(err) => {
                     // Assume the error means the connection can no longer be used
                     this.conn.next = None;
+                    this.conn.record_requests();
+                    if !matches!(err, Error::QueueTimeout) {
+                        this.conn.emit(ConnectionEvent::Closed {
+                            reason: CloseReason::Failed(err.to_string()),
+                        });
+                    }
                     return Poll::Ready(Err(err));
                 }
             }
@@ -257,6 +629,60 @@ impl<C: Connector> Future for RequestFuture<'_, C> {
     }
 }
 
+/// A state transition of the connection to the registry
+///
+/// Subscribe through [`EppClient::events()`](crate::EppClient::events).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ConnectionEvent {
+    /// The transport connection to the server was established
+    Connected,
+    /// The server's greeting was read, so the connection is ready for commands
+    GreetingReceived,
+    /// `EppClient::keepalive()` sent its command to keep the session alive
+    KeepaliveSent,
+    /// The client started reconnecting; no commands are sent until `GreetingReceived`
+    Reconnecting,
+    /// The connection was closed, or failed such that it can no longer be used
+    Closed { reason: CloseReason },
+}
+
+/// Why the connection was closed, see [`ConnectionEvent::Closed`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CloseReason {
+    /// The client shut down the connection
+    Shutdown,
+    /// An error occurred, described by the message
+    Failed(String),
+}
+
+// How many events a subscriber can fall behind before it misses some
+const EVENTS_CAPACITY: usize = 16;
+
+// How many greetings are kept for `EppClient::last_greetings()`
+const GREETINGS_CAPACITY: usize = 8;
+
+/// A greeting received from the server, see [`EppClient::last_greetings()`]
+///
+/// [`EppClient::last_greetings()`]: crate::EppClient::last_greetings
+#[derive(Clone, Debug)]
+pub struct ReceivedGreeting {
+    /// When the greeting was received, according to the local clock
+    pub received: DateTime<Utc>,
+    /// The `<svID>` from the greeting
+    pub service_id: String,
+    /// The `<svDate>` from the greeting, according to the server's clock
+    pub service_date: DateTime<Utc>,
+    /// The greeting in raw XML form
+    pub raw: String,
+}
+
+// A request waiting for the in-flight request to finish
+struct Queued {
+    state: RequestState,
+    // When the request was queued
+    since: Instant,
+}
+
 // Transitions between `RequestState`s
 enum Transition {
     Pending(RequestState),
@@ -292,21 +718,108 @@ enum RequestState {
         // The expected length of the response according to the frame header
         expected: usize,
     },
+    // Reading a response the server sent without a frame header, up to its closing tag
+    Unframed {
+        // The amount of bytes we've already read
+        read: usize,
+        // The buffer we're using to read into, starting with the response
+        buf: Vec<u8>,
+        // The start of the response, read as a frame header
+        declared: usize,
+    },
+}
+
+// Whether `buf` ends with the closing tag of an `<epp>` document, with or without a prefix
+fn ends_document(buf: &[u8]) -> bool {
+    let buf = buf.trim_ascii_end();
+    let Some(start) = buf.windows(2).rposition(|window| window == b"</") else {
+        return false;
+    };
+
+    let tag = &buf[start + 2..];
+    tag == b"epp>" || tag.ends_with(b":epp>")
 }
 
 impl RequestState {
-    fn new(command: &str) -> Result<Self, Error> {
-        let len = command.len();
+    fn new(command: &str, mut codec: EppCodec) -> Result<Self, Error> {
+        let mut buf = Vec::new();
+        codec.encode(command, &mut buf)?;
+        Ok(Self::Writing { start: 0, buf })
+    }
+}
 
-        let buf_size = len + 4;
-        let mut buf: Vec<u8> = vec![0u8; buf_size];
+/// The reading half of a connection, see [`EppClient::into_split()`]
+///
+/// [`EppClient::into_split()`]: crate::EppClient::into_split
+#[derive(Debug)]
+pub struct FrameReader<R> {
+    stream: R,
+    codec: EppCodec,
+    buf: Vec<u8>,
+}
 
-        let len = len + 4;
-        let len_u32: [u8; 4] = u32::to_be_bytes(len.try_into()?);
+impl<R: AsyncRead + Unpin> FrameReader<R> {
+    /// Read frames from `stream` with `codec`
+    pub fn new(stream: R, codec: EppCodec) -> Self {
+        Self {
+            stream,
+            codec,
+            buf: Vec::new(),
+        }
+    }
 
-        buf[..4].clone_from_slice(&len_u32);
-        buf[4..].clone_from_slice(command.as_bytes());
-        Ok(Self::Writing { start: 0, buf })
+    /// Read the next frame and return its payload
+    ///
+    /// Returns an `UnexpectedEof` I/O error if the stream ends before a complete frame.
+    pub async fn read_frame(&mut self) -> Result<String, Error> {
+        loop {
+            if let Some(frame) = self.codec.decode(&mut self.buf)? {
+                return Ok(frame);
+            }
+
+            if self.stream.read_buf(&mut self.buf).await? == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    format!("unexpected EOF with {} bytes buffered", self.buf.len()),
+                )
+                .into());
+            }
+        }
+    }
+
+    /// The underlying stream
+    pub fn into_inner(self) -> R {
+        self.stream
+    }
+}
+
+/// The writing half of a connection, see [`EppClient::into_split()`]
+///
+/// [`EppClient::into_split()`]: crate::EppClient::into_split
+#[derive(Debug)]
+pub struct FrameWriter<W> {
+    stream: W,
+    codec: EppCodec,
+}
+
+impl<W: AsyncWrite + Unpin> FrameWriter<W> {
+    /// Write frames to `stream` with `codec`
+    pub fn new(stream: W, codec: EppCodec) -> Self {
+        Self { stream, codec }
+    }
+
+    /// Write a frame with the `payload`, such as a serialized request
+    pub async fn write_frame(&mut self, payload: &str) -> Result<(), Error> {
+        let mut buf = Vec::with_capacity(payload.len() + 4);
+        self.codec.encode(payload, &mut buf)?;
+        self.stream.write_all(&buf).await?;
+        self.stream.flush().await?;
+        Ok(())
+    }
+
+    /// The underlying stream
+    pub fn into_inner(self) -> W {
+        self.stream
     }
 }
 
@@ -314,7 +827,7 @@ pub(crate) async fn timeout<T, E: Into<Error>>(
     timeout: Duration,
     fut: impl Future<Output = Result<T, E>>,
 ) -> Result<T, Error> {
-    match tokio::time::timeout(timeout, fut).await {
+    match runtime::timeout(timeout, fut).await {
         Ok(Ok(t)) => Ok(t),
         Ok(Err(e)) => Err(e.into()),
         Err(_) => Err(Error::Timeout),
@@ -326,4 +839,87 @@ pub trait Connector {
     type Connection: AsyncRead + AsyncWrite + Unpin;
 
     async fn connect(&self, timeout: Duration) -> Result<Self::Connection, Error>;
+
+    /// How to retry when the first frame from the server is not a greeting
+    ///
+    /// Defaults to not retrying.
+    fn greeting_retry(&self) -> GreetingRetry {
+        GreetingRetry::default()
+    }
+
+    /// How to retry when reconnecting fails
+    ///
+    /// Defaults to not retrying.
+    fn reconnect_backoff(&self) -> ReconnectBackoff {
+        ReconnectBackoff::default()
+    }
+}
+
+/// Retry policy for reading the greeting, see [`Connector::greeting_retry()`]
+///
+/// Some registries intermittently send an error instead of a greeting, or close the connection
+/// before sending one. With retries, the client connects again after waiting for `backoff`,
+/// which doubles after each attempt. Once all retries have failed, the last error is
+/// returned: `Error::UnexpectedGreeting` if a frame other than a greeting was received.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct GreetingRetry {
+    /// How many times to connect again
+    pub retries: u32,
+    /// How long to wait before the first retry
+    pub backoff: Duration,
+}
+
+/// Retry policy for reconnecting, see [`Connector::reconnect_backoff()`]
+///
+/// When connecting or reading the greeting fails during a reconnect, the client tries again
+/// after waiting for `backoff`, which doubles after each attempt up to `max_backoff`. A
+/// [`ConnectionEvent::Reconnecting`] is emitted at the start of each attempt. Once all retries
+/// have failed, `Error::ReconnectExhausted` is returned.
+///
+/// With `jitter`, each wait is a random duration between half and all of the current backoff,
+/// so that clients that lost their connections at the same time don't all reconnect at once.
+/// To stop reconnecting altogether while the registry is down, see `CircuitBreaker`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ReconnectBackoff {
+    /// How many times to try again after the first attempt
+    pub retries: u32,
+    /// How long to wait before the first retry
+    pub backoff: Duration,
+    /// The longest to wait between attempts, if any
+    pub max_backoff: Option<Duration>,
+    /// Whether to randomize the wait between attempts
+    pub jitter: bool,
+}
+
+/// A random duration between half of `delay` and `delay`
+fn jitter(delay: Duration) -> Duration {
+    // `RandomState` is seeded randomly for each instance, which suffices here
+    let random = RandomState::new().build_hasher().finish();
+    let fraction = (random >> 11) as f64 / (1u64 << 53) as f64;
+    delay.mul_f64(0.5 + fraction / 2.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{ends_document, jitter};
+
+    #[test]
+    fn document_end() {
+        assert!(ends_document(b"<epp><response/></epp>"));
+        assert!(ends_document(b"<epp:epp><epp:response/></epp:epp>\r\n"));
+        assert!(!ends_document(b"<epp><response>"));
+        assert!(!ends_document(b"<epp><response/></ep"));
+        assert!(!ends_document(b""));
+    }
+
+    #[test]
+    fn jittered() {
+        let delay = Duration::from_secs(2);
+        for _ in 0..100 {
+            let wait = jitter(delay);
+            assert!(wait >= Duration::from_secs(1) && wait <= delay, "{wait:?}");
+        }
+    }
 }
@@ -5,7 +5,7 @@ use std::fmt::{self, Debug};
 use instant_xml::{FromXml, Serializer, ToXml};
 
 use super::XMLNS;
-use crate::common::{NoExtension, EPP_XMLNS};
+use crate::common::{CheckResults, Checked, NoExtension, EPP_XMLNS};
 use crate::request::{Command, Transaction};
 
 impl Transaction<NoExtension> for ContactCheck<'_> {}
@@ -13,6 +13,14 @@ impl Transaction<NoExtension> for ContactCheck<'_> {}
 impl Command for ContactCheck<'_> {
     type Response = CheckData;
     const COMMAND: &'static str = "check";
+
+    fn object_ids(&self) -> Vec<&str> {
+        self.contact_ids.to_vec()
+    }
+
+    fn object_uris(&self) -> Vec<&str> {
+        vec![XMLNS]
+    }
 }
 
 // Request
@@ -76,6 +84,28 @@ pub struct CheckData {
     pub list: Vec<CheckedContact>,
 }
 
+impl Checked for CheckedContact {
+    fn id(&self) -> &str {
+        &self.id.value
+    }
+
+    fn available(&self) -> bool {
+        self.id.available
+    }
+
+    fn reason(&self) -> Option<&str> {
+        self.reason.as_ref().map(|reason| reason.value.as_str())
+    }
+}
+
+impl CheckResults for CheckData {
+    type Item = CheckedContact;
+
+    fn results(&self) -> &[Self::Item] {
+        &self.list
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::ContactCheck;
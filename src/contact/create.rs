@@ -3,15 +3,34 @@
 use chrono::{DateTime, Utc};
 use instant_xml::{FromXml, ToXml};
 
-use super::{ContactAuthInfo, Fax, PostalInfo, Voice, XMLNS};
-use crate::common::{NoExtension, EPP_XMLNS};
+use super::{is_valid_email, ContactAuthInfo, Fax, PostalInfo, Voice, XMLNS};
+use crate::common::{deserialize_date, NoExtension, EPP_XMLNS};
 use crate::request::{Command, Transaction};
+use crate::Error;
 
 impl Transaction<NoExtension> for ContactCreate<'_> {}
 
 impl Command for ContactCreate<'_> {
     type Response = CreateData;
     const COMMAND: &'static str = "create";
+
+    fn validate(&self) -> Result<(), Error> {
+        let ContactCreateRequest { id, email, .. } = &self.contact;
+        match is_valid_email(email) {
+            true => Ok(()),
+            false => Err(Error::Other(
+                format!("{id}: invalid email address {email:?}").into(),
+            )),
+        }
+    }
+
+    fn object_ids(&self) -> Vec<&str> {
+        vec![self.contact.id]
+    }
+
+    fn object_uris(&self) -> Vec<&str> {
+        vec![XMLNS]
+    }
 }
 
 // Request
@@ -62,6 +81,11 @@ impl<'a> ContactCreate<'a> {
         }
     }
 
+    /// The ID of the contact to create
+    pub fn id(&self) -> &'a str {
+        self.contact.id
+    }
+
     /// Sets the `<fax>` data for the request
     pub fn set_fax(&mut self, fax: Fax<'a>) {
         self.contact.fax = Some(fax);
@@ -71,12 +95,16 @@ impl<'a> ContactCreate<'a> {
 // Response
 
 /// Type that represents the `<creData>` tag for contact create response
+///
+/// Some registries echo the full contact, with its postal info and other data, in the
+/// `<creData>`. Elements other than the ID and creation date are ignored; send a contact
+/// `<info>` command for them.
 #[derive(Debug, FromXml)]
 #[xml(rename = "creData", ns(XMLNS))]
 pub struct CreateData {
     /// The contact id
     pub id: String,
-    #[xml(rename = "crDate")]
+    #[xml(rename = "crDate", deserialize_with = "deserialize_date")]
     /// The contact creation date
     pub created_at: DateTime<Utc>,
 }
@@ -89,6 +117,7 @@ mod tests {
     use crate::contact::{Address, InfoType};
     use crate::response::ResultCode;
     use crate::tests::{assert_serialized, response_from_file, CLTRID, SUCCESS_MSG, SVTRID};
+    use crate::xml;
 
     #[test]
     fn command() {
@@ -138,6 +167,42 @@ mod tests {
         assert_serialized("request/contact/create_minimal.xml", &object);
     }
 
+    #[test]
+    fn email() {
+        let create = |email| {
+            let address = Address::new(&[], "Paris", None, None, "FR".parse().unwrap());
+            let postal_info = PostalInfo::new(InfoType::International, "John Doe", None, address);
+            let object = ContactCreate::new("eppdev-contact-3", email, postal_info, None, "pw");
+            xml::serialize_request(&object, CLTRID).map_err(|err| err.to_string())
+        };
+
+        for email in [
+            "contact@eppdev.net",
+            "first.last+tag@mail.eppdev.net",
+            "δοκιμή@παράδειγμα.δοκιμή",
+        ] {
+            assert!(create(email).is_ok(), "{email}");
+        }
+
+        for email in [
+            "",
+            "contact",
+            "contact@eppdev",
+            "@eppdev.net",
+            "contact@@eppdev.net",
+            "con tact@eppdev.net",
+            ".contact@eppdev.net",
+            "con..tact@eppdev.net",
+            "contact@-eppdev.net",
+            "contact@eppdev..net",
+        ] {
+            assert_eq!(
+                create(email).unwrap_err(),
+                format!("error: eppdev-contact-3: invalid email address {email:?}")
+            );
+        }
+    }
+
     #[test]
     fn response() {
         let object = response_from_file::<ContactCreate>("response/contact/create.xml");
@@ -153,4 +218,16 @@ mod tests {
         assert_eq!(object.tr_ids.client_tr_id.unwrap(), CLTRID);
         assert_eq!(object.tr_ids.server_tr_id, SVTRID);
     }
+
+    #[test]
+    fn response_full() {
+        let object = response_from_file::<ContactCreate>("response/contact/create_full.xml");
+        let results = object.res_data().unwrap();
+
+        assert_eq!(results.id, "eppdev-contact-4");
+        assert_eq!(
+            results.created_at,
+            Utc.with_ymd_and_hms(2021, 7, 25, 16, 5, 32).unwrap(),
+        );
+    }
 }
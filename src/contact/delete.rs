@@ -3,14 +3,22 @@
 use instant_xml::ToXml;
 
 use super::XMLNS;
-use crate::common::{NoExtension, EPP_XMLNS};
+use crate::common::{NoExtension, RawXml, EPP_XMLNS};
 use crate::request::{Command, Transaction};
 
 impl Transaction<NoExtension> for ContactDelete<'_> {}
 
 impl Command for ContactDelete<'_> {
-    type Response = ();
+    type Response = RawXml;
     const COMMAND: &'static str = "delete";
+
+    fn object_ids(&self) -> Vec<&str> {
+        vec![self.contact.id]
+    }
+
+    fn object_uris(&self) -> Vec<&str> {
+        vec![XMLNS]
+    }
 }
 
 /// Type containing the data for the `<delete>` tag for contacts
@@ -40,6 +48,7 @@ impl<'a> ContactDelete<'a> {
 #[cfg(test)]
 mod tests {
     use super::ContactDelete;
+    use crate::common::DeleteData;
     use crate::response::ResultCode;
     use crate::tests::{assert_serialized, response_from_file, CLTRID, SUCCESS_MSG, SVTRID};
 
@@ -54,7 +63,10 @@ mod tests {
         let object = response_from_file::<ContactDelete>("response/contact/delete.xml");
         assert_eq!(object.result.code, ResultCode::CommandCompletedSuccessfully);
         assert_eq!(object.result.message, SUCCESS_MSG);
-        assert_eq!(object.tr_ids.client_tr_id.unwrap(), CLTRID);
+        assert_eq!(object.tr_ids.client_tr_id.as_deref(), Some(CLTRID));
         assert_eq!(object.tr_ids.server_tr_id, SVTRID);
+
+        let data = DeleteData::from(object);
+        assert!(data.immediate && !data.pending);
     }
 }
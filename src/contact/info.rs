@@ -1,48 +1,132 @@
 //! Types for EPP contact info request
 
+use std::fmt;
+
 use chrono::{DateTime, Utc};
-use instant_xml::{FromXml, ToXml};
+use instant_xml::ser::Context;
+use instant_xml::{FromXml, Serializer, ToXml};
 
 use super::{ContactAuthInfo, Fax, PostalInfo, Status, Voice, XMLNS};
-use crate::common::{NoExtension, EPP_XMLNS};
+use crate::common::{
+    deserialize_date, deserialize_date_option, NoExtension, StatusValue, EPP_XMLNS,
+};
 use crate::request::{Command, Transaction};
+use crate::Error;
 
 impl Transaction<NoExtension> for ContactInfo<'_> {}
 
 impl Command for ContactInfo<'_> {
     type Response = InfoData;
     const COMMAND: &'static str = "info";
+
+    fn validate(&self) -> Result<(), Error> {
+        match (self.info.roid, self.roid_lookup) {
+            (Some(roid), RoidLookup::Unsupported) => Err(Error::Other(
+                format!("{roid}: registry does not support contact info by ROID").into(),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn object_ids(&self) -> Vec<&str> {
+        self.info.id.into_iter().chain(self.info.roid).collect()
+    }
+
+    fn object_uris(&self) -> Vec<&str> {
+        vec![XMLNS]
+    }
 }
 
 // Request
 
+/// Whether a registry accepts contact `<info>` commands by ROID
+///
+/// RFC 5733 only identifies contacts by their ID, but some registries also look them up by the
+/// repository object ID (`<roid>`). Others reject such a command as a syntax error, so
+/// [`ContactInfo::by_roid()`] is only sent once the policy on the command allows it.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum RoidLookup {
+    /// Contacts can only be looked up by ID
+    #[default]
+    Unsupported,
+    /// Contacts can be looked up by ROID
+    Supported,
+}
+
 /// Type for elements under the contact `<info>` tag
 #[derive(Debug, ToXml)]
 #[xml(rename = "info", ns(XMLNS))]
 pub struct ContactInfoRequest<'a> {
     /// The contact id for the info command
-    id: &'a str,
+    id: Option<&'a str>,
+    /// The contact ROID, for registries that support it
+    roid: Option<&'a str>,
     /// The `<authInfo>` data
-    auth_info: ContactAuthInfo<'a>,
+    auth_info: Option<ContactAuthInfo<'a>>,
 }
 
 /// Type for EPP XML `<info>` command for contacts
-#[derive(Debug, ToXml)]
-#[xml(rename = "info", ns(EPP_XMLNS))]
+#[derive(Debug)]
 pub struct ContactInfo<'a> {
     /// Data for `<info>` command for contact
     info: ContactInfoRequest<'a>,
+    roid_lookup: RoidLookup,
 }
 
 impl<'a> ContactInfo<'a> {
-    pub fn new(id: &'a str, auth_password: &'a str) -> Self {
+    pub fn new(id: &'a str) -> Self {
         Self {
             info: ContactInfoRequest {
-                id,
-                auth_info: ContactAuthInfo::new(auth_password),
+                id: Some(id),
+                roid: None,
+                auth_info: None,
             },
+            roid_lookup: RoidLookup::Unsupported,
         }
     }
+
+    /// Look up the contact with repository object ID `roid`
+    ///
+    /// Sends a `<roid>` element instead of the `<id>`. Not all registries support this, so the
+    /// command fails before it is sent unless [`RoidLookup::Supported`] is set with
+    /// [`roid_lookup()`](Self::roid_lookup).
+    pub fn by_roid(roid: &'a str) -> Self {
+        Self {
+            info: ContactInfoRequest {
+                id: None,
+                roid: Some(roid),
+                auth_info: None,
+            },
+            roid_lookup: RoidLookup::Unsupported,
+        }
+    }
+
+    /// Set whether the registry supports looking up contacts by ROID
+    pub fn roid_lookup(mut self, lookup: RoidLookup) -> Self {
+        self.roid_lookup = lookup;
+        self
+    }
+
+    /// Authorize the request with the contact's `auth_password`
+    ///
+    /// Only needed for contacts sponsored by another registrar.
+    pub fn with_auth(mut self, auth_password: &'a str) -> Self {
+        self.info.auth_info = Some(ContactAuthInfo::new(auth_password));
+        self
+    }
+}
+
+impl ToXml for ContactInfo<'_> {
+    fn serialize<W: fmt::Write + ?Sized>(
+        &self,
+        _: Option<instant_xml::Id<'_>>,
+        serializer: &mut Serializer<W>,
+    ) -> Result<(), instant_xml::Error> {
+        let info = serializer.write_start("info", EPP_XMLNS, None::<Context<0>>)?;
+        serializer.end_start()?;
+        self.info.serialize(None, serializer)?;
+        serializer.write_close(info)
+    }
 }
 
 // Response
@@ -56,7 +140,7 @@ pub struct InfoData {
     /// The contact ROID
     pub roid: String,
     /// The list of contact statuses
-    pub statuses: Vec<Status>,
+    pub statuses: Vec<StatusValue<Status>>,
     /// The postal info for the contact
     pub postal_info: PostalInfo<'static>,
     /// The voice data for the contact
@@ -72,16 +156,16 @@ pub struct InfoData {
     #[xml(rename = "crID")]
     pub creator_id: String,
     /// The creation date
-    #[xml(rename = "crDate")]
+    #[xml(rename = "crDate", deserialize_with = "deserialize_date")]
     pub created_at: DateTime<Utc>,
     /// The epp user who last updated the contact
     #[xml(rename = "upID")]
     pub updater_id: Option<String>,
     /// The last update date
-    #[xml(rename = "upDate")]
+    #[xml(rename = "upDate", deserialize_with = "deserialize_date_option")]
     pub updated_at: Option<DateTime<Utc>>,
     /// The contact transfer date
-    #[xml(rename = "trDate")]
+    #[xml(rename = "trDate", deserialize_with = "deserialize_date_option")]
     pub transferred_at: Option<DateTime<Utc>>,
     /// The contact auth info
     #[xml(rename = "authInfo")]
@@ -92,17 +176,40 @@ pub struct InfoData {
 mod tests {
     use chrono::{TimeZone, Utc};
 
-    use super::ContactInfo;
+    use super::{ContactInfo, RoidLookup};
+    use crate::common::NoExtension;
     use crate::contact::{InfoType, Status};
     use crate::response::ResultCode;
-    use crate::tests::{assert_serialized, response_from_file, CLTRID, SUCCESS_MSG, SVTRID};
+    use crate::tests::{
+        assert_serialized, get_xml, response_from_file, CLTRID, SUCCESS_MSG, SVTRID,
+    };
+    use crate::xml;
 
     #[test]
     fn command() {
-        let object = ContactInfo::new("eppdev-contact-3", "eppdev-387323");
+        let object = ContactInfo::new("eppdev-contact-3").with_auth("eppdev-387323");
         assert_serialized("request/contact/info.xml", &object);
     }
 
+    #[test]
+    fn command_no_auth() {
+        let object = ContactInfo::new("eppdev-contact-3");
+        assert_serialized("request/contact/info_no_auth.xml", &object);
+    }
+
+    #[test]
+    fn command_roid() {
+        let object = ContactInfo::by_roid("eppdev-contact-3-REP").with_auth("eppdev-387323");
+        let err = xml::serialize_request(&object, CLTRID).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "error: eppdev-contact-3-REP: registry does not support contact info by ROID"
+        );
+
+        let object = object.roid_lookup(RoidLookup::Supported);
+        assert_serialized("request/contact/info_roid.xml", &object);
+    }
+
     #[test]
     fn response() {
         let object = response_from_file::<ContactInfo>("response/contact/info.xml");
@@ -189,4 +296,18 @@ mod tests {
         assert_eq!(object.tr_ids.client_tr_id.unwrap(), CLTRID);
         assert_eq!(object.tr_ids.server_tr_id, SVTRID);
     }
+
+    #[test]
+    fn unknown_status() {
+        let xml = get_xml("response/contact/info.xml").unwrap().replace(
+            r#"<contact:status s="ok"/>"#,
+            r#"<contact:status s="clientHold">Unverified</contact:status>"#,
+        );
+        let object = xml::deserialize_response::<ContactInfo, NoExtension>(&xml).unwrap();
+        let statuses = &object.res_data().unwrap().statuses;
+
+        assert_eq!(statuses[0], Status::Other("clientHold".into()));
+        assert!(!statuses[0].status.is_server_managed());
+        assert_eq!(statuses[0].reason.as_deref(), Some("Unverified"));
+    }
 }
@@ -6,8 +6,10 @@ use std::borrow::Cow;
 use std::fmt;
 use std::str::FromStr;
 
-use instant_xml::ser::Context;
-use instant_xml::{display_to_xml, from_xml_str, Deserializer, FromXml, Serializer, ToXml};
+use instant_xml::{display_to_xml, from_xml_str, FromXml, ToXml};
+
+use crate::common::ObjectStatus;
+use crate::Error;
 
 pub mod check;
 pub use check::ContactCheck;
@@ -19,7 +21,7 @@ pub mod delete;
 pub use delete::ContactDelete;
 
 pub mod info;
-pub use info::ContactInfo;
+pub use info::{ContactInfo, RoidLookup};
 
 pub mod update;
 pub use update::ContactUpdate;
@@ -230,7 +232,8 @@ pub enum InfoType {
 }
 
 /// The `<status>` type on contact transactions
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
 pub enum Status {
     ClientDeleteProhibited,
     ServerDeleteProhibited,
@@ -244,10 +247,12 @@ pub enum Status {
     PendingDelete,
     PendingTransfer,
     PendingUpdate,
+    /// A status not defined in the RFC, with its value as sent by the server
+    Other(String),
 }
 
 impl Status {
-    pub fn as_str(&self) -> &'static str {
+    pub fn as_str(&self) -> &str {
         use Status::*;
         match self {
             ClientDeleteProhibited => "clientDeleteProhibited",
@@ -262,56 +267,31 @@ impl Status {
             PendingDelete => "pendingDelete",
             PendingTransfer => "pendingTransfer",
             PendingUpdate => "pendingUpdate",
+            Other(status) => status,
         }
     }
-}
-
-impl ToXml for Status {
-    fn serialize<W: fmt::Write + ?Sized>(
-        &self,
-        _: Option<instant_xml::Id<'_>>,
-        serializer: &mut Serializer<W>,
-    ) -> Result<(), instant_xml::Error> {
-        serializer.write_start("status", XMLNS, None::<Context<0>>)?;
-        serializer.write_attr("s", XMLNS, &self.as_str())?;
-        serializer.end_empty()
-    }
-}
 
-impl<'xml> FromXml<'xml> for Status {
-    fn matches(id: instant_xml::Id<'_>, _: Option<instant_xml::Id<'_>>) -> bool {
-        id == instant_xml::Id {
-            ns: XMLNS,
-            name: "status",
+    /// Whether the status is set and removed by the server only
+    ///
+    /// Clients can only add and remove the `client*` statuses.
+    pub fn is_server_managed(&self) -> bool {
+        use Status::*;
+        if let Other(status) = self {
+            return !status.starts_with("client");
         }
+
+        !matches!(
+            self,
+            ClientDeleteProhibited | ClientTransferProhibited | ClientUpdateProhibited
+        )
     }
+}
 
-    fn deserialize<'cx>(
-        into: &mut Self::Accumulator,
-        field: &'static str,
-        deserializer: &mut Deserializer<'cx, 'xml>,
-    ) -> Result<(), instant_xml::Error> {
-        use instant_xml::de::Node;
-        use instant_xml::{Error, Id};
-
-        let node = match deserializer.next() {
-            Some(result) => result?,
-            None => return Err(Error::MissingValue(field)),
-        };
-
-        let attr = match node {
-            Node::Attribute(attr) => attr,
-            Node::Open(_) | Node::Text(_) => return Err(Error::MissingValue(field)),
-            node => return Err(Error::UnexpectedNode(format!("{node:?} in Status"))),
-        };
-
-        let id = deserializer.attribute_id(&attr)?;
-        let expected = Id { ns: "", name: "s" };
-        if id != expected {
-            return Err(Error::MissingValue(field));
-        }
+impl FromStr for Status {
+    type Err = Error;
 
-        *into = Some(match attr.value.as_ref() {
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
             "clientDeleteProhibited" => Self::ClientDeleteProhibited,
             "serverDeleteProhibited" => Self::ServerDeleteProhibited,
             "clientTransferProhibited" => Self::ClientTransferProhibited,
@@ -324,13 +304,49 @@ impl<'xml> FromXml<'xml> for Status {
             "pendingDelete" => Self::PendingDelete,
             "pendingTransfer" => Self::PendingTransfer,
             "pendingUpdate" => Self::PendingUpdate,
-            val => return Err(Error::UnexpectedValue(format!("invalid status {val:?}"))),
-        });
+            _ => Self::Other(s.to_owned()),
+        })
+    }
+}
 
-        deserializer.ignore()?;
-        Ok(())
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
     }
+}
 
-    type Accumulator = Option<Self>;
-    const KIND: instant_xml::Kind = instant_xml::Kind::Element;
+impl ObjectStatus for Status {
+    const XMLNS: &'static str = XMLNS;
+
+    fn is_server_managed(&self) -> bool {
+        Self::is_server_managed(self)
+    }
+}
+
+/// Whether `email` is a syntactically valid email address
+///
+/// This is a structural check: a local part of at most 64 characters, a single `@` and a
+/// domain name of at least two labels. Non-ASCII characters are accepted, as registries
+/// supporting internationalized email addresses allow them.
+pub(crate) fn is_valid_email(email: &str) -> bool {
+    match email.rsplit_once('@') {
+        Some((local, domain)) => {
+            (1..=64).contains(&local.chars().count())
+                && !local.starts_with('.')
+                && !local.ends_with('.')
+                && !local.contains("..")
+                && domain.chars().count() <= 253
+                && domain.split('.').count() >= 2
+                && domain.split('.').all(|label| {
+                    (1..=63).contains(&label.len())
+                        && !label.starts_with('-')
+                        && !label.ends_with('-')
+                        && label.chars().all(|c| c == '-' || c.is_alphanumeric())
+                })
+                && local.chars().all(|c| {
+                    !c.is_whitespace() && !c.is_control() && !matches!(c, '@' | '<' | '>' | '"')
+                })
+        }
+        None => false,
+    }
 }
@@ -1,16 +1,55 @@
 //! Types for EPP contact create request
 
-use instant_xml::ToXml;
+use std::fmt;
 
-use super::{ContactAuthInfo, Fax, PostalInfo, Status, Voice, XMLNS};
-use crate::common::{NoExtension, EPP_XMLNS};
+use instant_xml::ser::Context;
+use instant_xml::{Serializer, ToXml};
+
+use super::{is_valid_email, ContactAuthInfo, Fax, PostalInfo, Status, Voice, XMLNS};
+use crate::common::{
+    check_client_statuses, serialize_container, Container, NoExtension, StatusValue, EPP_XMLNS,
+};
 use crate::request::{Command, Transaction};
+use crate::Error;
 
 impl Transaction<NoExtension> for ContactUpdate<'_> {}
 
 impl Command for ContactUpdate<'_> {
     type Response = ();
     const COMMAND: &'static str = "update";
+
+    fn validate(&self) -> Result<(), Error> {
+        let email = self
+            .contact
+            .change_info
+            .as_ref()
+            .and_then(|info| info.email);
+        if let Some(email) = email.filter(|email| !is_valid_email(email)) {
+            return Err(Error::Other(
+                format!("{}: invalid email address {email:?}", self.contact.id).into(),
+            ));
+        }
+
+        if self.allow_server_statuses {
+            return Ok(());
+        }
+
+        let add = self.contact.add_statuses.as_ref().map(|add| add.statuses);
+        let remove = self
+            .contact
+            .remove_statuses
+            .as_ref()
+            .map(|rem| rem.statuses);
+        check_client_statuses(self.contact.id, add, remove)
+    }
+
+    fn object_ids(&self) -> Vec<&str> {
+        vec![self.contact.id]
+    }
+
+    fn object_uris(&self) -> Vec<&str> {
+        vec![XMLNS]
+    }
 }
 
 impl<'a> ContactUpdate<'a> {
@@ -22,6 +61,7 @@ impl<'a> ContactUpdate<'a> {
                 remove_statuses: None,
                 change_info: None,
             },
+            allow_server_statuses: false,
         }
     }
 
@@ -50,14 +90,19 @@ impl<'a> ContactUpdate<'a> {
     }
 
     /// Sets the data for the `<add>` tag for the contact update request
-    pub fn add(&mut self, statuses: &'a [Status]) {
+    pub fn add(&mut self, statuses: &'a [StatusValue<Status>]) {
         self.contact.add_statuses = Some(AddStatuses { statuses });
     }
 
     /// Sets the data for the `<rem>` tag for the contact update request
-    pub fn remove(&mut self, statuses: &'a [Status]) {
+    pub fn remove(&mut self, statuses: &'a [StatusValue<Status>]) {
         self.contact.remove_statuses = Some(RemoveStatuses { statuses });
     }
+
+    /// Allow adding and removing server-managed statuses, which are rejected by default
+    pub fn allow_server_statuses(&mut self) {
+        self.allow_server_statuses = true;
+    }
 }
 
 /// Type for elements under the `<chg>` tag for contact update request
@@ -74,13 +119,35 @@ pub struct ContactChangeInfo<'a> {
 #[derive(Debug, ToXml)]
 #[xml(rename = "add", ns(XMLNS))]
 struct AddStatuses<'a> {
-    statuses: &'a [Status],
+    statuses: &'a [StatusValue<Status>],
 }
 
 #[derive(Debug, ToXml)]
 #[xml(rename = "rem", ns(XMLNS))]
 struct RemoveStatuses<'a> {
-    statuses: &'a [Status],
+    statuses: &'a [StatusValue<Status>],
+}
+
+impl Container for ContactChangeInfo<'_> {
+    fn is_empty(&self) -> bool {
+        self.postal_info.is_none()
+            && self.voice.is_none()
+            && self.fax.is_none()
+            && self.email.is_none()
+            && self.auth_info.is_none()
+    }
+}
+
+impl Container for AddStatuses<'_> {
+    fn is_empty(&self) -> bool {
+        self.statuses.is_empty()
+    }
+}
+
+impl Container for RemoveStatuses<'_> {
+    fn is_empty(&self) -> bool {
+        self.statuses.is_empty()
+    }
 }
 
 /// Type for elements under the contact `<update>` tag
@@ -88,18 +155,33 @@ struct RemoveStatuses<'a> {
 #[xml(rename = "update", ns(XMLNS))]
 pub struct ContactUpdateRequest<'a> {
     id: &'a str,
+    #[xml(serialize_with = "serialize_container")]
     add_statuses: Option<AddStatuses<'a>>,
-    #[xml(rename = "rem")]
+    #[xml(serialize_with = "serialize_container")]
     remove_statuses: Option<RemoveStatuses<'a>>,
+    #[xml(serialize_with = "serialize_container")]
     change_info: Option<ContactChangeInfo<'a>>,
 }
 
 /// Type for EPP XML `<update>` command for contacts
-#[derive(Debug, ToXml)]
-#[xml(rename = "update", ns(EPP_XMLNS))]
+#[derive(Debug)]
 pub struct ContactUpdate<'a> {
     /// The data under the `<update>` tag for the contact update
     contact: ContactUpdateRequest<'a>,
+    allow_server_statuses: bool,
+}
+
+impl ToXml for ContactUpdate<'_> {
+    fn serialize<W: fmt::Write + ?Sized>(
+        &self,
+        _: Option<instant_xml::Id<'_>>,
+        serializer: &mut Serializer<W>,
+    ) -> Result<(), instant_xml::Error> {
+        let update = serializer.write_start("update", EPP_XMLNS, None::<Context<0>>)?;
+        serializer.end_start()?;
+        self.contact.serialize(None, serializer)?;
+        serializer.write_close(update)
+    }
 }
 
 #[cfg(test)]
@@ -108,6 +190,7 @@ mod tests {
     use crate::contact::{Address, InfoType};
     use crate::response::ResultCode;
     use crate::tests::{assert_serialized, response_from_file, CLTRID, SUCCESS_MSG, SVTRID};
+    use crate::xml;
 
     #[test]
     fn command() {
@@ -126,12 +209,29 @@ mod tests {
         let voice = Voice::new("+33.47237942");
 
         object.set_info("newemail@eppdev.net", postal_info, voice, "eppdev-387323");
-        object.add(&[Status::ClientTransferProhibited]);
-        object.remove(&[Status::ClientDeleteProhibited]);
+        let add = [Status::ClientTransferProhibited.into()];
+        let remove = [Status::ClientDeleteProhibited.into()];
+        object.add(&add);
+        object.remove(&remove);
 
         assert_serialized("request/contact/update.xml", &object);
     }
 
+    #[test]
+    fn invalid_email() {
+        let mut object = ContactUpdate::new("eppdev-contact-3");
+        let address = Address::new(&[], "Paris", None, None, "FR".parse().unwrap());
+        let postal_info = PostalInfo::new(InfoType::Local, "John Doe", None, address);
+        object.set_info("eppdev.net", postal_info, Voice::new("+33.47237942"), "pw");
+
+        assert_eq!(
+            xml::serialize_request(&object, CLTRID)
+                .unwrap_err()
+                .to_string(),
+            r#"error: eppdev-contact-3: invalid email address "eppdev.net""#
+        );
+    }
+
     #[test]
     fn contact_update() {
         let object = response_from_file::<ContactUpdate>("response/contact/update.xml");
@@ -0,0 +1,166 @@
+//! Support for commands this crate doesn't implement
+//!
+//! Registries define proprietary objects, such as account balance queries, that are used with
+//! the standard EPP commands. To send one, define a type for the object-specific element that
+//! goes inside the command element, and implement [`CustomObject`] for it:
+//!
+//! ```
+//! use instant_epp::custom::{CustomCommand, CustomObject};
+//! use instant_xml::{FromXml, ToXml};
+//!
+//! const XMLNS: &str = "http://www.verisign.com/epp/balance-1.0";
+//!
+//! #[derive(Debug, ToXml)]
+//! #[xml(rename = "info", ns(XMLNS))]
+//! struct BalanceInfo;
+//!
+//! #[derive(Debug, FromXml)]
+//! #[xml(rename = "infData", ns(XMLNS))]
+//! struct Balance {
+//!     balance: String,
+//! }
+//!
+//! impl CustomObject for BalanceInfo {
+//!     type Response = Balance;
+//!     const COMMAND: &'static str = "info";
+//! }
+//!
+//! let xml = instant_epp::xml::serialize_request(&CustomCommand::new(BalanceInfo), "ABC-12345").unwrap();
+//! assert!(xml.contains(r#"<info><info xmlns="http://www.verisign.com/epp/balance-1.0" /></info>"#));
+//! ```
+//!
+//! The resulting [`CustomCommand`] can be sent like any other command. It only implements
+//! [`Transaction<NoExtension>`]; to attach an extension `E`, implement
+//! `Transaction<E> for CustomCommand<T>` in your crate.
+
+use std::fmt::Debug;
+
+use instant_xml::ser::Context;
+use instant_xml::{FromXmlOwned, ToXml};
+
+use crate::common::{NoExtension, EPP_XMLNS};
+use crate::request::{Command, Transaction};
+use crate::Error;
+
+/// An object-specific command element, see the [module documentation](self)
+pub trait CustomObject: ToXml + Debug {
+    /// The type of the `<resData>` in the response, `()` if there is none
+    type Response: FromXmlOwned + Debug;
+    /// The EPP command to wrap the object in, such as `info` or `update`
+    const COMMAND: &'static str;
+
+    /// Check for mistakes that would make the server reject the command
+    ///
+    /// See [`Command::validate()`].
+    fn validate(&self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// A command for a [`CustomObject`]
+#[derive(Debug)]
+pub struct CustomCommand<T> {
+    object: T,
+}
+
+impl<T: CustomObject> CustomCommand<T> {
+    pub fn new(object: T) -> Self {
+        Self { object }
+    }
+
+    /// The object-specific command element
+    pub fn object(&self) -> &T {
+        &self.object
+    }
+}
+
+impl<T: CustomObject> Transaction<NoExtension> for CustomCommand<T> {}
+
+impl<T: CustomObject> Command for CustomCommand<T> {
+    type Response = T::Response;
+    const COMMAND: &'static str = T::COMMAND;
+
+    fn validate(&self) -> Result<(), Error> {
+        self.object.validate()
+    }
+}
+
+impl<T: CustomObject> ToXml for CustomCommand<T> {
+    fn serialize<W: std::fmt::Write + ?Sized>(
+        &self,
+        _: Option<instant_xml::Id<'_>>,
+        serializer: &mut instant_xml::Serializer<W>,
+    ) -> Result<(), instant_xml::Error> {
+        // Pass a context so that closing the command element restores the EPP default namespace,
+        // even if the object (such as an empty unit struct) leaves it changed.
+        let mut cx = Context::<0>::default();
+        cx.default_ns = EPP_XMLNS;
+        let command = serializer.write_start(T::COMMAND, EPP_XMLNS, Some(cx))?;
+        serializer.end_start()?;
+        self.object.serialize(None, serializer)?;
+        serializer.write_close(command)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use instant_xml::{FromXml, ToXml};
+
+    use super::{CustomCommand, CustomObject};
+    use crate::response::ResultCode;
+    use crate::tests::{assert_serialized, response_from_file};
+    use crate::Error;
+
+    const XMLNS: &str = "http://www.verisign.com/epp/balance-1.0";
+
+    #[derive(Debug, ToXml)]
+    #[xml(rename = "info", ns(XMLNS))]
+    struct BalanceInfo;
+
+    #[derive(Debug, FromXml)]
+    #[xml(rename = "infData", ns(XMLNS))]
+    struct Balance {
+        #[xml(rename = "creditLimit")]
+        credit_limit: String,
+        balance: String,
+    }
+
+    impl CustomObject for BalanceInfo {
+        type Response = Balance;
+        const COMMAND: &'static str = "info";
+    }
+
+    #[test]
+    fn command() {
+        assert_serialized("request/custom.xml", &CustomCommand::new(BalanceInfo));
+    }
+
+    #[test]
+    fn response() {
+        let object = response_from_file::<CustomCommand<BalanceInfo>>("response/custom.xml");
+        assert_eq!(object.result.code, ResultCode::CommandCompletedSuccessfully);
+        let balance = object.res_data().unwrap();
+        assert_eq!(balance.credit_limit, "1000.00");
+        assert_eq!(balance.balance, "200.00");
+    }
+
+    #[test]
+    fn validate() {
+        #[derive(Debug, ToXml)]
+        #[xml(rename = "update", ns(XMLNS))]
+        struct Broken;
+
+        impl CustomObject for Broken {
+            type Response = ();
+            const COMMAND: &'static str = "update";
+
+            fn validate(&self) -> Result<(), Error> {
+                Err(Error::Other("broken".into()))
+            }
+        }
+
+        let err =
+            crate::xml::serialize_request(&CustomCommand::new(Broken), "ABC-12345").unwrap_err();
+        assert_eq!(err.to_string(), "error: broken");
+    }
+}
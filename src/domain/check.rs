@@ -5,7 +5,7 @@ use std::fmt;
 use instant_xml::{FromXml, Serializer, ToXml};
 
 use super::XMLNS;
-use crate::common::{NoExtension, EPP_XMLNS};
+use crate::common::{CheckResults, Checked, NoExtension, EPP_XMLNS};
 use crate::request::{Command, Transaction};
 
 impl Transaction<NoExtension> for DomainCheck<'_> {}
@@ -13,6 +13,14 @@ impl Transaction<NoExtension> for DomainCheck<'_> {}
 impl Command for DomainCheck<'_> {
     type Response = CheckData;
     const COMMAND: &'static str = "check";
+
+    fn object_ids(&self) -> Vec<&str> {
+        self.domains.to_vec()
+    }
+
+    fn object_uris(&self) -> Vec<&str> {
+        vec![XMLNS]
+    }
 }
 
 // Request
@@ -75,9 +83,34 @@ pub struct CheckData {
     pub list: Vec<CheckedDomain>,
 }
 
+impl Checked for CheckedDomain {
+    fn id(&self) -> &str {
+        &self.name.value
+    }
+
+    fn available(&self) -> bool {
+        self.name.available
+    }
+
+    fn reason(&self) -> Option<&str> {
+        self.reason.as_ref().map(|reason| reason.value.as_str())
+    }
+}
+
+impl CheckResults for CheckData {
+    type Item = CheckedDomain;
+
+    fn results(&self) -> &[Self::Item] {
+        &self.list
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::DomainCheck;
+    use crate::common::{CheckResults, Checked};
+    use crate::contact::ContactCheck;
+    use crate::host::HostCheck;
     use crate::response::ResultCode;
     use crate::tests::{assert_serialized, response_from_file, CLTRID, SUCCESS_MSG, SVTRID};
 
@@ -105,4 +138,39 @@ mod tests {
         assert_eq!(object.tr_ids.client_tr_id.unwrap(), CLTRID);
         assert_eq!(object.tr_ids.server_tr_id, SVTRID);
     }
+
+    #[test]
+    fn generic() {
+        fn summary<T: CheckResults>(data: &T) -> Vec<(&str, bool, Option<&str>)> {
+            data.results()
+                .iter()
+                .map(|checked| (checked.id(), checked.available(), checked.reason()))
+                .collect()
+        }
+
+        let domains = response_from_file::<DomainCheck>("response/domain/check.xml");
+        let domains = domains.res_data().unwrap();
+        assert_eq!(
+            summary(domains),
+            [
+                ("eppdev.com", true, None),
+                ("eppdev.net", false, None),
+                ("eppdev.net", false, Some("In Use")),
+            ]
+        );
+        assert_eq!(domains.available().collect::<Vec<_>>(), ["eppdev.com"]);
+
+        let hosts = response_from_file::<HostCheck>("response/host/check.xml");
+        assert_eq!(
+            summary(hosts.res_data().unwrap())[1],
+            ("ns1.testing.com", false, None)
+        );
+
+        let contacts = response_from_file::<ContactCheck>("response/contact/check.xml");
+        let contacts = contacts.res_data().unwrap();
+        assert_eq!(
+            contacts.available().collect::<Vec<_>>(),
+            ["eppdev-contact-2"]
+        );
+    }
 }
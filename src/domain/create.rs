@@ -1,17 +1,62 @@
 //! Types for EPP domain create request
 
+use std::fmt;
+
 use chrono::{DateTime, Utc};
-use instant_xml::{FromXml, ToXml};
+use instant_xml::ser::Context;
+use instant_xml::{FromXml, Serializer, ToXml};
 
 use super::{DomainAuthInfo, DomainContact, HostInfo, NameServers, Period, XMLNS};
-use crate::common::{NoExtension, EPP_XMLNS};
+use crate::common::{
+    deserialize_date, deserialize_date_option, serialize_container, NoExtension, EPP_XMLNS,
+};
 use crate::request::{Command, Transaction};
+use crate::Error;
 
 impl Transaction<NoExtension> for DomainCreate<'_> {}
 
 impl Command for DomainCreate<'_> {
     type Response = CreateData;
     const COMMAND: &'static str = "create";
+
+    fn validate(&self) -> Result<(), Error> {
+        let has_ns = self.domain.ns.as_ref().is_some_and(|ns| !ns.ns.is_empty());
+        let name = self.domain.name;
+        match self.ns_policy {
+            NameServerPolicy::Required if !has_ns => Err(Error::Other(
+                format!("{name}: registry requires name servers at create").into(),
+            )),
+            NameServerPolicy::Forbidden if has_ns => Err(Error::Other(
+                format!("{name}: registry does not accept name servers at create").into(),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn object_ids(&self) -> Vec<&str> {
+        vec![self.domain.name]
+    }
+
+    fn object_uris(&self) -> Vec<&str> {
+        vec![XMLNS]
+    }
+}
+
+/// A registry's rules for name servers in a domain `<create>`
+///
+/// Some registries reject `<ns>` at create, having the delegation set by a later `<update>`,
+/// while others refuse to create domains without name servers. Set the policy on a
+/// [`DomainCreate`] to check it before the command is sent, instead of having the registry
+/// answer with `2306` (parameter value policy error).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum NameServerPolicy {
+    /// Name servers may or may not be given
+    #[default]
+    Optional,
+    /// At least one name server must be given
+    Required,
+    /// No name servers may be given
+    Forbidden,
 }
 
 // Request
@@ -26,6 +71,7 @@ pub struct DomainCreateRequestData<'a> {
     pub period: Period,
     /// The list of nameserver hosts
     /// either of type `HostObjList` or `HostAttrList`
+    #[xml(serialize_with = "serialize_container")]
     pub ns: Option<NameServers<'a>>,
     /// The domain registrant
     pub registrant: Option<&'a str>,
@@ -35,14 +81,27 @@ pub struct DomainCreateRequestData<'a> {
     pub auth_info: DomainAuthInfo<'a>,
 }
 
-#[derive(Debug, ToXml)]
 /// Type for EPP XML `<create>` command for domains
-#[xml(rename = "create", ns(EPP_XMLNS))]
+#[derive(Debug)]
 pub struct DomainCreate<'a> {
     /// The data for the domain to be created with
     /// T being the type of nameserver list (`HostObjList` or `HostAttrList`)
     /// to be supplied
     pub domain: DomainCreateRequestData<'a>,
+    ns_policy: NameServerPolicy,
+}
+
+impl ToXml for DomainCreate<'_> {
+    fn serialize<W: fmt::Write + ?Sized>(
+        &self,
+        _: Option<instant_xml::Id<'_>>,
+        serializer: &mut Serializer<W>,
+    ) -> Result<(), instant_xml::Error> {
+        let create = serializer.write_start("create", EPP_XMLNS, None::<Context<0>>)?;
+        serializer.end_start()?;
+        self.domain.serialize(None, serializer)?;
+        serializer.write_close(create)
+    }
 }
 
 impl<'a> DomainCreate<'a> {
@@ -63,8 +122,14 @@ impl<'a> DomainCreate<'a> {
                 auth_info: DomainAuthInfo::new(auth_password),
                 contacts,
             },
+            ns_policy: NameServerPolicy::Optional,
         }
     }
+
+    /// Check the name servers against the registry's `policy` before sending
+    pub fn name_server_policy(&mut self, policy: NameServerPolicy) {
+        self.ns_policy = policy;
+    }
 }
 
 // Response
@@ -76,10 +141,10 @@ pub struct CreateData {
     /// The domain name
     pub name: String,
     /// The creation date
-    #[xml(rename = "crDate")]
+    #[xml(rename = "crDate", deserialize_with = "deserialize_date")]
     pub created_at: DateTime<Utc>,
     /// The expiry date
-    #[xml(rename = "exDate")]
+    #[xml(rename = "exDate", deserialize_with = "deserialize_date_option")]
     pub expiring_at: Option<DateTime<Utc>>,
 }
 
@@ -89,10 +154,11 @@ mod tests {
 
     use chrono::{TimeZone, Utc};
 
-    use super::{DomainContact, DomainCreate, Period};
-    use crate::domain::{HostAttr, HostInfo, HostObj, PeriodLength};
+    use super::{DomainContact, DomainCreate, NameServerPolicy, Period};
+    use crate::domain::{HostAttr, HostInfo, HostObj};
     use crate::response::ResultCode;
     use crate::tests::{assert_serialized, response_from_file, CLTRID, SUCCESS_MSG, SVTRID};
+    use crate::xml;
 
     #[test]
     fn command() {
@@ -113,7 +179,7 @@ mod tests {
 
         let object = DomainCreate::new(
             "eppdev-1.com",
-            Period::Years(PeriodLength::new(1).unwrap()),
+            Period::years(1).unwrap(),
             None,
             Some("eppdev-contact-3"),
             "epP4uthd#v",
@@ -150,7 +216,7 @@ mod tests {
         ];
         let object = DomainCreate::new(
             "eppdev-1.com",
-            Period::Years(PeriodLength::new(1).unwrap()),
+            Period::years(1).unwrap(),
             Some(hosts),
             Some("eppdev-contact-3"),
             "epP4uthd#v",
@@ -193,7 +259,7 @@ mod tests {
 
         let object = DomainCreate::new(
             "eppdev-2.com",
-            Period::Years(PeriodLength::new(1).unwrap()),
+            Period::years(1).unwrap(),
             Some(hosts),
             Some("eppdev-contact-3"),
             "epP4uthd#v",
@@ -203,6 +269,48 @@ mod tests {
         assert_serialized("request/domain/create_with_host_attr.xml", &object);
     }
 
+    #[test]
+    fn name_server_policy() {
+        let hosts = &[HostInfo::Obj(HostObj {
+            name: "ns1.test.com".into(),
+        })];
+
+        let mut object = DomainCreate::new(
+            "eppdev-1.com",
+            Period::years(1).unwrap(),
+            Some(hosts),
+            None,
+            "epP4uthd#v",
+            None,
+        );
+        xml::serialize_request(&object, CLTRID).unwrap();
+        object.name_server_policy(NameServerPolicy::Forbidden);
+        let err = xml::serialize_request(&object, CLTRID).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "error: eppdev-1.com: registry does not accept name servers at create"
+        );
+        object.name_server_policy(NameServerPolicy::Required);
+        xml::serialize_request(&object, CLTRID).unwrap();
+
+        let mut object = DomainCreate::new(
+            "eppdev-1.com",
+            Period::years(1).unwrap(),
+            Some(&[]),
+            None,
+            "epP4uthd#v",
+            None,
+        );
+        object.name_server_policy(NameServerPolicy::Required);
+        let err = xml::serialize_request(&object, CLTRID).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "error: eppdev-1.com: registry requires name servers at create"
+        );
+        object.name_server_policy(NameServerPolicy::Forbidden);
+        xml::serialize_request(&object, CLTRID).unwrap();
+    }
+
     #[test]
     fn response() {
         let object = response_from_file::<DomainCreate>("response/domain/create.xml");
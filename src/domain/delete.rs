@@ -3,14 +3,22 @@
 use instant_xml::ToXml;
 
 use super::XMLNS;
-use crate::common::{NoExtension, EPP_XMLNS};
+use crate::common::{NoExtension, RawXml, EPP_XMLNS};
 use crate::request::{Command, Transaction};
 
 impl Transaction<NoExtension> for DomainDelete<'_> {}
 
 impl Command for DomainDelete<'_> {
-    type Response = ();
+    type Response = RawXml;
     const COMMAND: &'static str = "delete";
+
+    fn object_ids(&self) -> Vec<&str> {
+        vec![self.domain.name]
+    }
+
+    fn object_uris(&self) -> Vec<&str> {
+        vec![XMLNS]
+    }
 }
 
 impl<'a> DomainDelete<'a> {
@@ -40,6 +48,7 @@ pub struct DomainDelete<'a> {
 #[cfg(test)]
 mod tests {
     use super::DomainDelete;
+    use crate::common::DeleteData;
     use crate::response::ResultCode;
     use crate::tests::{assert_serialized, response_from_file, CLTRID, SUCCESS_MSG, SVTRID};
 
@@ -55,7 +64,24 @@ mod tests {
 
         assert_eq!(object.result.code, ResultCode::CommandCompletedSuccessfully);
         assert_eq!(object.result.message, SUCCESS_MSG);
-        assert_eq!(object.tr_ids.client_tr_id.unwrap(), CLTRID);
+        assert_eq!(object.tr_ids.client_tr_id.as_deref(), Some(CLTRID));
         assert_eq!(object.tr_ids.server_tr_id, SVTRID);
+
+        let data = DeleteData::from(object);
+        assert!(data.immediate && !data.pending);
+        assert_eq!(data.data, None);
+    }
+
+    #[test]
+    fn response_pending() {
+        let object = response_from_file::<DomainDelete>("response/domain/delete_pending.xml");
+        assert_eq!(
+            object.result.code,
+            ResultCode::CommandCompletedSuccessfullyActionPending
+        );
+
+        let data = DeleteData::from(object);
+        assert!(!data.immediate && data.pending);
+        assert_eq!(data.data.unwrap().name(), "delData");
     }
 }
@@ -0,0 +1,370 @@
+//! Checking internationalized domain names against a registry's IDN tables
+//!
+//! Registries only accept IDN labels in the scripts listed in their IDN tables, and reject labels
+//! mixing characters from several scripts. A domain `<check>` for such a label only returns
+//! "not available", so workloads that check many generated candidates, like search suggestions,
+//! can use an [`IdnPolicy`] to drop these before sending them.
+//!
+//! Labels may be given as U-labels or as A-labels (`xn--`). The scripts are determined from the
+//! Unicode blocks of the characters; digits and the hyphen are allowed in any script. With the
+//! `confusables` feature, the policy can also reject labels written entirely in characters that
+//! look like Latin letters, like Cyrillic `рау`.
+
+use std::fmt;
+
+use crate::Error;
+
+/// A script for IDN labels
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Script {
+    Arabic,
+    Armenian,
+    Cyrillic,
+    Devanagari,
+    Georgian,
+    Greek,
+    Han,
+    Hangul,
+    Hebrew,
+    Hiragana,
+    Katakana,
+    Latin,
+    Thai,
+}
+
+impl Script {
+    /// The script of `c`, if it is a letter in one of the supported scripts
+    pub fn of(c: char) -> Option<Self> {
+        Some(match c {
+            'a'..='z' | 'A'..='Z' => Self::Latin,
+            '\u{c0}'..='\u{24f}' if c != '\u{d7}' && c != '\u{f7}' => Self::Latin,
+            '\u{1e00}'..='\u{1eff}' => Self::Latin,
+            '\u{370}'..='\u{3ff}' | '\u{1f00}'..='\u{1fff}' => Self::Greek,
+            '\u{400}'..='\u{52f}' => Self::Cyrillic,
+            '\u{531}'..='\u{58f}' => Self::Armenian,
+            '\u{591}'..='\u{5ff}' => Self::Hebrew,
+            '\u{600}'..='\u{6ff}' | '\u{750}'..='\u{77f}' => Self::Arabic,
+            '\u{900}'..='\u{97f}' => Self::Devanagari,
+            '\u{e00}'..='\u{e7f}' => Self::Thai,
+            '\u{10a0}'..='\u{10ff}' => Self::Georgian,
+            '\u{1100}'..='\u{11ff}' | '\u{3130}'..='\u{318f}' | '\u{ac00}'..='\u{d7af}' => {
+                Self::Hangul
+            }
+            '\u{3040}'..='\u{309f}' => Self::Hiragana,
+            '\u{30a0}'..='\u{30ff}' => Self::Katakana,
+            '\u{3400}'..='\u{4dbf}' | '\u{4e00}'..='\u{9fff}' | '\u{20000}'..='\u{2a6df}' => {
+                Self::Han
+            }
+            _ => return None,
+        })
+    }
+
+    /// Whether labels may combine this script with `other`
+    ///
+    /// Japanese is written in Han, Hiragana and Katakana, and Korean in Hangul and Han.
+    fn combines_with(self, other: Self) -> bool {
+        use Script::*;
+        matches!(
+            (self, other),
+            (Han | Hiragana | Katakana, Han | Hiragana | Katakana) | (Han, Hangul) | (Hangul, Han)
+        )
+    }
+}
+
+impl fmt::Display for Script {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// The scripts a registry accepts in domain labels
+#[derive(Clone, Debug)]
+pub struct IdnPolicy {
+    scripts: Vec<Script>,
+    #[cfg(feature = "confusables")]
+    reject_confusables: bool,
+}
+
+impl IdnPolicy {
+    /// Accept labels written in one of `scripts`
+    ///
+    /// Include [`Script::Latin`] to accept ASCII labels.
+    pub fn new(scripts: &[Script]) -> Self {
+        Self {
+            scripts: scripts.to_vec(),
+            #[cfg(feature = "confusables")]
+            reject_confusables: false,
+        }
+    }
+
+    /// Whether to reject non-Latin labels that can be mistaken for a Latin one
+    #[cfg(feature = "confusables")]
+    pub fn reject_confusables(mut self, reject: bool) -> Self {
+        self.reject_confusables = reject;
+        self
+    }
+
+    /// Check the domain `name` against the policy
+    ///
+    /// All labels except the top-level one are checked, as the TLD is not subject to the
+    /// registry's IDN tables.
+    pub fn check(&self, name: &str) -> Result<(), Error> {
+        let name = name.trim_end_matches('.');
+        let labels = match name.rsplit_once('.') {
+            Some((labels, _)) => labels,
+            None => name,
+        };
+
+        for label in labels.split('.') {
+            self.check_label(label)
+                .map_err(|reason| Error::Other(format!("{name}: {reason}").into()))?;
+        }
+
+        Ok(())
+    }
+
+    /// Split `names` into the ones that pass the policy and the ones that don't
+    ///
+    /// Use this on candidate names before passing them to a [`DomainCheck`](super::DomainCheck).
+    pub fn preflight<'a>(&self, names: &[&'a str]) -> Preflight<'a> {
+        let mut preflight = Preflight::default();
+        for &name in names {
+            match self.check(name) {
+                Ok(()) => preflight.valid.push(name),
+                Err(err) => preflight.rejected.push((name, err)),
+            }
+        }
+
+        preflight
+    }
+
+    fn check_label(&self, label: &str) -> Result<(), String> {
+        if label.is_empty() {
+            return Err("empty label".to_owned());
+        } else if label.starts_with('-') || label.ends_with('-') {
+            return Err(format!("label {label:?} starts or ends with a hyphen"));
+        }
+
+        let decoded;
+        let unicode = match label.get(..4) {
+            Some(prefix) if prefix.eq_ignore_ascii_case("xn--") => {
+                decoded = decode_punycode(&label[4..])
+                    .ok_or_else(|| format!("invalid A-label {label:?}"))?;
+                &decoded
+            }
+            _ => label,
+        };
+
+        let mut scripts = Vec::<Script>::new();
+        for c in unicode.chars() {
+            if c.is_ascii_digit() || c == '-' {
+                continue;
+            }
+
+            let Some(script) = Script::of(c) else {
+                return Err(format!(
+                    "label {label:?} contains unsupported character {c:?}"
+                ));
+            };
+
+            if !self.scripts.contains(&script) {
+                return Err(format!("script {script} of label {label:?} is not allowed"));
+            } else if scripts
+                .iter()
+                .any(|&other| other != script && !other.combines_with(script))
+            {
+                return Err(format!("label {label:?} mixes scripts"));
+            } else if !scripts.contains(&script) {
+                scripts.push(script);
+            }
+        }
+
+        #[cfg(feature = "confusables")]
+        if self.reject_confusables {
+            if let Some(latin) = latin_lookalike(unicode) {
+                return Err(format!("label {label:?} can be confused with {latin:?}"));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The result of [`IdnPolicy::preflight()`]
+#[derive(Debug, Default)]
+pub struct Preflight<'a> {
+    /// The names that pass the policy
+    pub valid: Vec<&'a str>,
+    /// The names rejected by the policy, with the reason
+    pub rejected: Vec<(&'a str, Error)>,
+}
+
+/// The Latin label `label` looks like, if it is written entirely in Latin lookalikes
+#[cfg(feature = "confusables")]
+fn latin_lookalike(label: &str) -> Option<String> {
+    let mut latin = String::with_capacity(label.len());
+    let mut confusable = false;
+    for c in label.chars() {
+        let ascii = match c {
+            '0'..='9' | '-' => c,
+            // Cyrillic
+            'а' => 'a',
+            'с' => 'c',
+            'ԁ' => 'd',
+            'е' => 'e',
+            'һ' => 'h',
+            'і' => 'i',
+            'ј' => 'j',
+            'о' => 'o',
+            'р' => 'p',
+            'ԛ' => 'q',
+            'ѕ' => 's',
+            'ԝ' => 'w',
+            'х' => 'x',
+            'у' => 'y',
+            'ӏ' => 'l',
+            // Greek
+            'α' => 'a',
+            'ι' => 'i',
+            'κ' => 'k',
+            'ν' => 'v',
+            'ο' => 'o',
+            'ρ' => 'p',
+            'υ' => 'u',
+            _ => return None,
+        };
+
+        confusable |= ascii != c;
+        latin.push(ascii);
+    }
+
+    confusable.then_some(latin)
+}
+
+/// Decode the Punycode part of an A-label, as described in RFC 3492
+fn decode_punycode(input: &str) -> Option<String> {
+    const BASE: u32 = 36;
+    const T_MIN: u32 = 1;
+    const T_MAX: u32 = 26;
+
+    let (basic, extended) = match input.rfind('-') {
+        Some(i) => (&input[..i], &input[i + 1..]),
+        None => ("", input),
+    };
+
+    if !basic.is_ascii() {
+        return None;
+    }
+
+    let mut output = basic.chars().collect::<Vec<_>>();
+    let (mut n, mut i, mut bias) = (0x80_u32, 0_u32, 72_u32);
+    let mut digits = extended.bytes().peekable();
+    while digits.peek().is_some() {
+        let (old_i, mut weight, mut k) = (i, 1_u32, BASE);
+        loop {
+            let digit = match digits.next()? {
+                b @ b'a'..=b'z' => b - b'a',
+                b @ b'A'..=b'Z' => b - b'A',
+                b @ b'0'..=b'9' => b - b'0' + 26,
+                _ => return None,
+            } as u32;
+
+            i = i.checked_add(digit.checked_mul(weight)?)?;
+            let t = k.saturating_sub(bias).clamp(T_MIN, T_MAX);
+            if digit < t {
+                break;
+            }
+
+            weight = weight.checked_mul(BASE - t)?;
+            k += BASE;
+        }
+
+        let len = output.len() as u32 + 1;
+        bias = adapt(i - old_i, len, old_i == 0);
+        n = n.checked_add(i / len)?;
+        i %= len;
+        output.insert(i as usize, char::from_u32(n)?);
+        i += 1;
+    }
+
+    Some(output.into_iter().collect())
+}
+
+fn adapt(delta: u32, len: u32, first: bool) -> u32 {
+    const BASE: u32 = 36;
+    let mut delta = delta / if first { 700 } else { 2 };
+    delta += delta / len;
+
+    let mut k = 0;
+    while delta > ((BASE - 1) * 26) / 2 {
+        delta /= BASE - 1;
+        k += BASE;
+    }
+
+    k + (BASE * delta) / (delta + 38)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_punycode, IdnPolicy, Script};
+
+    #[test]
+    fn punycode() {
+        assert_eq!(decode_punycode("mller-kva").as_deref(), Some("müller"));
+        assert_eq!(decode_punycode("e1afmkfd").as_deref(), Some("пример"));
+        assert_eq!(decode_punycode("wgv71a").as_deref(), Some("日本"));
+        assert_eq!(decode_punycode("abc-").as_deref(), Some("abc"));
+        assert_eq!(decode_punycode("99999999999"), None);
+    }
+
+    #[test]
+    fn scripts() {
+        let policy = IdnPolicy::new(&[Script::Latin, Script::Cyrillic]);
+        assert!(policy.check("example.com").is_ok());
+        assert!(policy.check("müller.de").is_ok());
+        assert!(policy.check("xn--mller-kva.de").is_ok());
+        assert!(policy.check("пример-1.рф").is_ok());
+        assert!(policy.check("www.xn--e1afmkfd.xn--p1ai").is_ok());
+
+        let err = policy.check("xn--pple-43d.com").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "error: xn--pple-43d.com: label \"xn--pple-43d\" mixes scripts"
+        );
+        let err = policy.check("ελλάδα.gr").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "error: ελλάδα.gr: script Greek of label \"ελλάδα\" is not allowed"
+        );
+        assert!(policy.check("exa_mple.com").is_err());
+        assert!(policy.check("-example.com").is_err());
+        assert!(policy.check("www..com").is_err());
+        assert!(policy.check("xn--a-.com").is_err());
+
+        let policy = IdnPolicy::new(&[Script::Han, Script::Hiragana, Script::Katakana]);
+        assert!(policy.check("日本のドメイン.jp").is_ok());
+        assert!(policy.check("日本.jp").is_ok());
+        assert!(policy.check("example.jp").is_err());
+
+        let preflight = policy.preflight(&["日本.jp", "example.jp", "テスト.jp"]);
+        assert_eq!(preflight.valid, ["日本.jp", "テスト.jp"]);
+        assert_eq!(preflight.rejected.len(), 1);
+        assert_eq!(preflight.rejected[0].0, "example.jp");
+    }
+
+    #[cfg(feature = "confusables")]
+    #[test]
+    fn confusables() {
+        let policy = IdnPolicy::new(&[Script::Latin, Script::Cyrillic]);
+        assert!(policy.check("рау.com").is_ok());
+
+        let policy = policy.reject_confusables(true);
+        let err = policy.check("рау.com").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "error: рау.com: label \"рау\" can be confused with \"pay\""
+        );
+        assert!(policy.check("пример.com").is_ok());
+        assert!(policy.check("example.com").is_ok());
+    }
+}
@@ -1,10 +1,10 @@
 //! Types for EPP domain info request
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, TimeDelta, Utc};
 use instant_xml::{FromXml, ToXml};
 
-use super::{DomainAuthInfo, DomainContact, HostAttr, NameServers, Status, XMLNS};
-use crate::common::{NoExtension, EPP_XMLNS};
+use super::{DomainAuthInfo, DomainContact, NameServers, Status, XMLNS};
+use crate::common::{deserialize_date_option, NoExtension, StatusValue, EPP_XMLNS};
 use crate::request::{Command, Transaction};
 
 impl Transaction<NoExtension> for DomainInfo<'_> {}
@@ -12,19 +12,35 @@ impl Transaction<NoExtension> for DomainInfo<'_> {}
 impl Command for DomainInfo<'_> {
     type Response = InfoData;
     const COMMAND: &'static str = "info";
+
+    fn object_ids(&self) -> Vec<&str> {
+        vec![self.info.name.name]
+    }
+
+    fn object_uris(&self) -> Vec<&str> {
+        vec![XMLNS]
+    }
 }
 
 impl<'a> DomainInfo<'a> {
-    pub fn new(name: &'a str, auth_password: Option<&'a str>) -> Self {
+    pub fn new(name: &'a str) -> Self {
         Self {
             info: DomainInfoRequestData {
                 name: Domain { hosts: "all", name },
-                auth_info: auth_password.map(|password| DomainAuthInfo {
-                    password: password.into(),
-                }),
+                auth_info: None,
             },
         }
     }
+
+    /// Authorize the request with the domain's `auth_password`
+    ///
+    /// For domains sponsored by another registrar, most servers only return the full info
+    /// (contacts, dates and so on) when the correct password is given, which makes this a way
+    /// to verify a transfer auth code before requesting the transfer.
+    pub fn with_auth(mut self, auth_password: &'a str) -> Self {
+        self.info.auth_info = Some(DomainAuthInfo::new(auth_password));
+        self
+    }
 }
 
 // Request
@@ -61,17 +77,6 @@ pub struct DomainInfo<'a> {
 
 // Response
 
-/// The two types of ns lists, hostObj and hostAttr, that may be returned in the
-/// domain info response
-#[derive(Debug, FromXml)]
-pub struct DomainNsList {
-    /// List of `<hostObj>` ns elements
-    #[xml(rename = "hostObj")]
-    pub host_obj: Option<Vec<String>>,
-    /// List of `<hostAttr>` ns elements
-    pub host_attr: Option<Vec<HostAttr<'static>>>,
-}
-
 /// Type that represents the `<infData>` tag for domain info response
 #[derive(Debug, FromXml)]
 #[xml(rename = "infData", ns(XMLNS))]
@@ -82,13 +87,13 @@ pub struct InfoData {
     pub roid: String,
     /// The list of domain statuses
     #[xml(rename = "status")]
-    pub statuses: Option<Vec<Status>>,
+    pub statuses: Option<Vec<StatusValue<Status>>>,
     /// The domain registrant
     pub registrant: Option<String>,
     /// The list of domain contacts
     #[xml(rename = "contact")]
     pub contacts: Option<Vec<DomainContact<'static>>>,
-    /// The list of domain nameservers
+    /// The list of domain nameservers, in either `<hostObj>` or `<hostAttr>` form
     pub ns: Option<NameServers<'static>>,
     /// The list of domain hosts
     #[xml(rename = "host")]
@@ -100,39 +105,111 @@ pub struct InfoData {
     #[xml(rename = "crID")]
     pub creator_id: Option<String>,
     /// The domain creation date
-    #[xml(rename = "crDate")]
+    #[xml(rename = "crDate", deserialize_with = "deserialize_date_option")]
     pub created_at: Option<DateTime<Utc>>,
     /// The domain expiry date
-    #[xml(rename = "exDate")]
+    #[xml(rename = "exDate", deserialize_with = "deserialize_date_option")]
     pub expiring_at: Option<DateTime<Utc>>,
     /// The epp user who last updated the domain
     #[xml(rename = "upID")]
     pub updater_id: Option<String>,
     /// The domain last updated date
-    #[xml(rename = "upDate")]
+    #[xml(rename = "upDate", deserialize_with = "deserialize_date_option")]
     pub updated_at: Option<DateTime<Utc>>,
     /// The domain transfer date
-    #[xml(rename = "trDate")]
+    #[xml(rename = "trDate", deserialize_with = "deserialize_date_option")]
     pub transferred_at: Option<DateTime<Utc>>,
     /// The domain auth info
     #[xml(rename = "authInfo")]
     pub auth_info: Option<DomainAuthInfo<'static>>,
 }
 
+impl InfoData {
+    /// Whether the domain has `status`
+    pub fn has_status(&self, status: Status) -> bool {
+        self.statuses
+            .iter()
+            .flatten()
+            .any(|value| value.status == status)
+    }
+
+    /// Whether the domain is about to be deleted
+    ///
+    /// With the RGP extension, the `rgpStatus` tells whether it can still be restored.
+    pub fn is_pending_delete(&self) -> bool {
+        self.has_status(Status::PendingDelete)
+    }
+
+    /// Whether the domain has all statuses of a client lock
+    ///
+    /// That is, `clientDeleteProhibited`, `clientTransferProhibited` and
+    /// `clientUpdateProhibited`, as set by the `workflows::lock` helpers.
+    pub fn is_locked(&self) -> bool {
+        CLIENT_LOCK
+            .iter()
+            .all(|status| self.has_status(status.clone()))
+    }
+
+    /// Whether the domain has all statuses of a registry lock
+    ///
+    /// That is, `serverDeleteProhibited`, `serverTransferProhibited` and
+    /// `serverUpdateProhibited`, which only the registry can remove.
+    pub fn is_registry_locked(&self) -> bool {
+        REGISTRY_LOCK
+            .iter()
+            .all(|status| self.has_status(status.clone()))
+    }
+
+    /// The time left until the domain expires, if the response includes the expiry date
+    ///
+    /// Negative if the expiry date has passed, for example during the auto-renew grace period.
+    pub fn expires_in(&self) -> Option<TimeDelta> {
+        self.expires_in_at(Utc::now())
+    }
+
+    fn expires_in_at(&self, now: DateTime<Utc>) -> Option<TimeDelta> {
+        self.expiring_at.map(|expiry| expiry - now)
+    }
+}
+
+const CLIENT_LOCK: [Status; 3] = [
+    Status::ClientDeleteProhibited,
+    Status::ClientTransferProhibited,
+    Status::ClientUpdateProhibited,
+];
+
+const REGISTRY_LOCK: [Status; 3] = [
+    Status::ServerDeleteProhibited,
+    Status::ServerTransferProhibited,
+    Status::ServerUpdateProhibited,
+];
+
 #[cfg(test)]
 mod tests {
+    use std::net::IpAddr;
+
     use super::DomainInfo;
-    use crate::domain::{HostInfo, HostObj, Status};
+    use crate::common::NoExtension;
+    use crate::domain::{HostAttr, HostInfo, HostObj, Status};
     use crate::response::ResultCode;
-    use crate::tests::{assert_serialized, response_from_file, CLTRID, SUCCESS_MSG, SVTRID};
-    use chrono::{TimeZone, Utc};
+    use crate::tests::{
+        assert_serialized, get_xml, response_from_file, CLTRID, SUCCESS_MSG, SVTRID,
+    };
+    use crate::xml;
+    use chrono::{TimeDelta, TimeZone, Utc};
 
     #[test]
     fn command() {
-        let object = DomainInfo::new("eppdev.com", Some("2fooBAR"));
+        let object = DomainInfo::new("eppdev.com").with_auth("2fooBAR");
         assert_serialized("request/domain/info.xml", &object);
     }
 
+    #[test]
+    fn command_no_auth() {
+        let object = DomainInfo::new("eppdev.com");
+        assert_serialized("request/domain/info_no_auth.xml", &object);
+    }
+
     #[test]
     fn response() {
         let object = response_from_file::<DomainInfo>("response/domain/info.xml");
@@ -188,13 +265,128 @@ mod tests {
             *result.expiring_at.as_ref().unwrap(),
             Utc.with_ymd_and_hms(2023, 7, 23, 15, 31, 20).unwrap()
         );
-        assert_eq!(auth_info.password, "epP4uthd#v");
+        assert_eq!(auth_info.password.as_deref(), Some("epP4uthd#v"));
+        assert!(!auth_info.is_null());
         assert_eq!(object.tr_ids.client_tr_id.unwrap(), CLTRID);
         assert_eq!(object.tr_ids.server_tr_id, SVTRID);
     }
 
+    #[test]
+    fn response_auth_info_null() {
+        let object = response_from_file::<DomainInfo>("response/domain/info_auth_info_null.xml");
+        let auth_info = object.res_data().unwrap().auth_info.as_ref().unwrap();
+        assert!(auth_info.is_null());
+        assert_eq!(auth_info.password, None);
+    }
+
+    #[test]
+    fn response_foreign() {
+        let object = response_from_file::<DomainInfo>("response/domain/info_foreign.xml");
+        let result = object.res_data().unwrap();
+
+        assert_eq!(result.client_id, "otherreg");
+        assert_eq!(result.registrant.as_deref(), Some("other-contact-1"));
+        assert_eq!(result.contacts.as_ref().unwrap().len(), 2);
+        assert_eq!(
+            result.transferred_at,
+            Some(Utc.with_ymd_and_hms(2022, 5, 2, 13, 40, 5).unwrap())
+        );
+        assert!(result.hosts.as_deref().unwrap_or_default().is_empty());
+        assert!(result.auth_info.is_none());
+    }
+
+    #[test]
+    fn response_thin() {
+        let object = response_from_file::<DomainInfo>("response/domain/info_thin.xml");
+        let result = object.res_data().unwrap();
+
+        assert_eq!(result.name, "eppdev.com");
+        assert_eq!(result.client_id, "otherreg");
+        assert!(result.registrant.is_none());
+        assert!(result.contacts.as_deref().unwrap_or_default().is_empty());
+        assert!(result.expiring_at.is_none());
+    }
+
+    #[test]
+    fn status_helpers() {
+        let object = response_from_file::<DomainInfo>("response/domain/info_locked.xml");
+        let result = object.res_data().unwrap();
+        assert!(result.is_locked());
+        assert!(!result.is_registry_locked());
+        assert!(!result.is_pending_delete());
+        assert!(result.has_status(Status::ClientUpdateProhibited));
+
+        let now = Utc.with_ymd_and_hms(2023, 7, 22, 15, 31, 20).unwrap();
+        assert_eq!(result.expires_in_at(now), Some(TimeDelta::days(1)));
+
+        let object = response_from_file::<DomainInfo>("response/domain/info_unlocked.xml");
+        assert!(!object.res_data().unwrap().is_locked());
+
+        let object =
+            response_from_file::<DomainInfo>("response/domain/info_unhandled_namespace.xml");
+        let result = object.res_data().unwrap();
+        assert!(result.is_pending_delete());
+        assert!(result.expires_in().unwrap() < TimeDelta::zero());
+
+        let object = response_from_file::<DomainInfo>("response/domain/info_thin.xml");
+        assert_eq!(object.res_data().unwrap().expires_in(), None);
+    }
+
     #[test]
     fn response_alt() {
         response_from_file::<DomainInfo>("response/domain/info_alt.xml");
     }
+
+    #[test]
+    fn response_host_attr() {
+        let object = response_from_file::<DomainInfo>("response/domain/info_host_attr.xml");
+        let result = object.res_data().unwrap();
+        let ns = result.ns.as_ref().unwrap();
+
+        assert_eq!(ns.ns.len(), 2);
+        assert_eq!(ns.ns[0].name(), "ns1.example.net");
+        assert!(ns.ns[0].addresses().is_empty());
+        assert_eq!(
+            ns.ns[1],
+            HostInfo::Attr(HostAttr {
+                name: "ns1.eppdev-1.com".into(),
+                addresses: Some(vec![
+                    IpAddr::from([192, 0, 2, 2]),
+                    IpAddr::from([0x1080, 0, 0, 0, 0x8, 0x800, 0x200c, 0x417a]),
+                ]),
+            })
+        );
+        assert_eq!(ns.ns[1].addresses().len(), 2);
+        assert_eq!(result.hosts.as_ref().unwrap()[0], "ns1.eppdev-1.com");
+    }
+
+    #[test]
+    fn response_status_reason() {
+        let object = response_from_file::<DomainInfo>("response/domain/info_status_reason.xml");
+        let statuses = object.res_data().unwrap().statuses.as_ref().unwrap();
+
+        assert_eq!(statuses[0], Status::Ok);
+        assert_eq!(statuses[0].reason, None);
+        assert_eq!(statuses[1], Status::ClientTransferProhibited);
+        assert_eq!(statuses[1].reason.as_deref(), Some("Pending dispute"));
+        assert_eq!(statuses[1].lang.as_ref().unwrap(), "en");
+    }
+
+    #[test]
+    fn unknown_status() {
+        let xml = get_xml("response/domain/info_status_reason.xml")
+            .unwrap()
+            .replace(
+                r#"s="clientTransferProhibited""#,
+                r#"s="autoRenewGracePeriod""#,
+            );
+        let object = xml::deserialize_response::<DomainInfo, NoExtension>(&xml).unwrap();
+        let statuses = object.res_data().unwrap().statuses.as_ref().unwrap();
+
+        let status = &statuses[1].status;
+        assert_eq!(*status, Status::Other("autoRenewGracePeriod".into()));
+        assert_eq!(status.to_string(), "autoRenewGracePeriod");
+        assert!(status.is_server_managed());
+        assert_eq!(statuses[1].reason.as_deref(), Some("Pending dispute"));
+    }
 }
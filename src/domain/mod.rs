@@ -11,17 +11,20 @@ use instant_xml::ser::Context;
 use instant_xml::OptionAccumulator;
 use instant_xml::{Accumulate, Deserializer, FromXml, Serializer, ToXml};
 
+use crate::common::{Container, ObjectStatus};
 use crate::Error;
 
 pub mod check;
 pub use check::DomainCheck;
 
 pub mod create;
-pub use create::DomainCreate;
+pub use create::{DomainCreate, NameServerPolicy};
 
 pub mod delete;
 pub use delete::DomainDelete;
 
+pub mod idn;
+
 pub mod info;
 pub use info::{DomainInfo, InfoData};
 
@@ -132,12 +135,38 @@ pub enum HostInfo<'a> {
     Obj(HostObj<'a>),
 }
 
+impl HostInfo<'_> {
+    /// The host name of the nameserver
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Attr(attr) => &attr.name,
+            Self::Obj(obj) => &obj.name,
+        }
+    }
+
+    /// The glue addresses for the nameserver
+    ///
+    /// Always empty for `<hostObj>` nameservers, which reference a separate host object.
+    pub fn addresses(&self) -> &[IpAddr] {
+        match self {
+            Self::Attr(attr) => attr.addresses.as_deref().unwrap_or_default(),
+            Self::Obj(_) => &[],
+        }
+    }
+}
+
 #[derive(Debug, FromXml, ToXml)]
 #[xml(rename = "ns", ns(XMLNS))]
 pub struct NameServers<'a> {
     pub ns: Cow<'a, [HostInfo<'a>]>,
 }
 
+impl Container for NameServers<'_> {
+    fn is_empty(&self) -> bool {
+        self.ns.is_empty()
+    }
+}
+
 /// The `<contact>` type on domain creation and update requests
 #[derive(Debug, FromXml, ToXml)]
 #[xml(rename = "contact", ns(XMLNS))]
@@ -157,6 +186,48 @@ pub enum Period {
     Months(PeriodLength),
 }
 
+impl Period {
+    /// A period of `length` years, which must be between 1 and 99
+    pub fn years(length: u8) -> Result<Self, Error> {
+        Ok(Self::Years(PeriodLength::new(length)?))
+    }
+
+    /// A period of `length` months, which must be between 1 and 99
+    pub fn months(length: u8) -> Result<Self, Error> {
+        Ok(Self::Months(PeriodLength::new(length)?))
+    }
+
+    pub(crate) fn unit(&self) -> (char, u8) {
+        match self {
+            Self::Years(length) => ('y', length.0),
+            Self::Months(length) => ('m', length.0),
+        }
+    }
+}
+
+/// Converts a length and an RFC 5731 unit (`'y'` or `'m'`) into a `Period`
+impl TryFrom<(u8, char)> for Period {
+    type Error = Error;
+
+    fn try_from((length, unit): (u8, char)) -> Result<Self, Self::Error> {
+        match unit {
+            'y' => Self::years(length),
+            'm' => Self::months(length),
+            _ => Err(Error::Other(
+                format!("invalid period unit {unit:?}, expected 'y' or 'm'").into(),
+            )),
+        }
+    }
+}
+
+/// Formats the period as its length followed by its unit, e.g. `1y` or `6m`
+impl fmt::Display for Period {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (unit, length) = self.unit();
+        write!(f, "{length}{unit}")
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct PeriodLength(u8);
 
@@ -177,11 +248,7 @@ impl ToXml for Period {
         _: Option<instant_xml::Id<'_>>,
         serializer: &mut Serializer<W>,
     ) -> Result<(), instant_xml::Error> {
-        let (unit, length) = match self {
-            Self::Years(length) => ('y', length.0),
-            Self::Months(length) => ('m', length.0),
-        };
-
+        let (unit, length) = self.unit();
         let period = serializer.write_start("period", XMLNS, None::<Context<0>>)?;
         serializer.write_attr("unit", XMLNS, &unit)?;
         serializer.end_start()?;
@@ -204,22 +271,45 @@ pub const SIX_MONTHS: Period = Period::Months(PeriodLength(6));
 #[derive(Clone, Debug, FromXml, ToXml)]
 #[xml(rename = "authInfo", ns(XMLNS))]
 pub struct DomainAuthInfo<'a> {
-    /// The `<pw>` tag under `<authInfo>`
+    /// The `<pw>` tag under `<authInfo>`, absent in the `<null>` form
     #[xml(rename = "pw")]
-    pub password: Cow<'a, str>,
+    pub password: Option<Cow<'a, str>>,
+    /// The `<null>` tag under `<authInfo>`
+    null: Option<Null>,
 }
 
 impl<'a> DomainAuthInfo<'a> {
     /// Creates a DomainAuthInfo instance with the given password
     pub fn new(password: &'a str) -> Self {
         Self {
-            password: password.into(),
+            password: Some(password.into()),
+            null: None,
         }
     }
+
+    /// Creates a DomainAuthInfo instance in the `<null>` form
+    ///
+    /// Used in a domain update's `<chg>` to remove the authorization information.
+    pub fn null() -> Self {
+        Self {
+            password: None,
+            null: Some(Null),
+        }
+    }
+
+    /// Whether this is the `<null>` form, without a password
+    pub fn is_null(&self) -> bool {
+        self.null.is_some()
+    }
 }
 
+#[derive(Clone, Debug, FromXml, ToXml)]
+#[xml(rename = "null", ns(XMLNS))]
+struct Null;
+
 /// The `<status>` type on contact transactions
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
 pub enum Status {
     ClientDeleteProhibited,
     ServerDeleteProhibited,
@@ -238,10 +328,12 @@ pub enum Status {
     PendingRenew,
     PendingTransfer,
     PendingUpdate,
+    /// A status not defined in the RFC, with its value as sent by the server
+    Other(String),
 }
 
 impl Status {
-    pub fn as_str(&self) -> &'static str {
+    pub fn as_str(&self) -> &str {
         use Status::*;
         match self {
             ClientDeleteProhibited => "clientDeleteProhibited",
@@ -261,56 +353,35 @@ impl Status {
             PendingRenew => "pendingRenew",
             PendingTransfer => "pendingTransfer",
             PendingUpdate => "pendingUpdate",
+            Other(status) => status,
         }
     }
-}
 
-impl ToXml for Status {
-    fn serialize<W: fmt::Write + ?Sized>(
-        &self,
-        _: Option<instant_xml::Id<'_>>,
-        serializer: &mut Serializer<W>,
-    ) -> Result<(), instant_xml::Error> {
-        serializer.write_start("status", XMLNS, None::<Context<0>>)?;
-        serializer.write_attr("s", XMLNS, &self.as_str())?;
-        serializer.end_empty()
-    }
-}
-
-impl<'xml> FromXml<'xml> for Status {
-    fn matches(id: instant_xml::Id<'_>, _: Option<instant_xml::Id<'_>>) -> bool {
-        id == instant_xml::Id {
-            ns: XMLNS,
-            name: "status",
+    /// Whether the status is set and removed by the server only
+    ///
+    /// Clients can only add and remove the `client*` statuses.
+    pub fn is_server_managed(&self) -> bool {
+        use Status::*;
+        if let Other(status) = self {
+            return !status.starts_with("client");
         }
+
+        !matches!(
+            self,
+            ClientDeleteProhibited
+                | ClientHold
+                | ClientRenewProhibited
+                | ClientTransferProhibited
+                | ClientUpdateProhibited
+        )
     }
+}
 
-    fn deserialize<'cx>(
-        into: &mut Self::Accumulator,
-        field: &'static str,
-        deserializer: &mut Deserializer<'cx, 'xml>,
-    ) -> Result<(), instant_xml::Error> {
-        use instant_xml::de::Node;
-        use instant_xml::{Error, Id};
-
-        let node = match deserializer.next() {
-            Some(result) => result?,
-            None => return Err(Error::MissingValue(field)),
-        };
-
-        let attr = match node {
-            Node::Attribute(attr) => attr,
-            Node::Open(_) | Node::Text(_) => return Err(Error::MissingValue(field)),
-            node => return Err(Error::UnexpectedNode(format!("{node:?} in Status"))),
-        };
-
-        let id = deserializer.attribute_id(&attr)?;
-        let expected = Id { ns: "", name: "s" };
-        if id != expected {
-            return Err(Error::MissingValue(field));
-        }
+impl FromStr for Status {
+    type Err = Error;
 
-        *into = Some(match attr.value.as_ref() {
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
             "clientDeleteProhibited" => Self::ClientDeleteProhibited,
             "serverDeleteProhibited" => Self::ServerDeleteProhibited,
             "clientHold" => Self::ClientHold,
@@ -328,13 +399,36 @@ impl<'xml> FromXml<'xml> for Status {
             "pendingRenew" => Self::PendingRenew,
             "pendingTransfer" => Self::PendingTransfer,
             "pendingUpdate" => Self::PendingUpdate,
-            val => return Err(Error::UnexpectedValue(format!("invalid status {val:?}"))),
-        });
+            _ => Self::Other(s.to_owned()),
+        })
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl ObjectStatus for Status {
+    const XMLNS: &'static str = XMLNS;
 
-        deserializer.ignore()?;
-        Ok(())
+    fn is_server_managed(&self) -> bool {
+        Self::is_server_managed(self)
     }
+}
 
-    type Accumulator = Option<Self>;
-    const KIND: instant_xml::Kind = instant_xml::Kind::Element;
+#[cfg(test)]
+mod tests {
+    use super::Period;
+
+    #[test]
+    fn period() {
+        assert_eq!(Period::years(1).unwrap().to_string(), "1y");
+        assert_eq!(Period::months(6).unwrap().to_string(), "6m");
+        assert_eq!(Period::try_from((2, 'y')).unwrap().to_string(), "2y");
+        assert!(Period::years(0).is_err());
+        assert!(Period::months(100).is_err());
+        assert!(Period::try_from((1, 'd')).is_err());
+    }
 }
@@ -4,7 +4,7 @@ use chrono::{DateTime, NaiveDate, Utc};
 use instant_xml::{FromXml, ToXml};
 
 use super::{Period, XMLNS};
-use crate::common::{NoExtension, EPP_XMLNS};
+use crate::common::{deserialize_date_option, NoExtension, EPP_XMLNS};
 use crate::request::{Command, Transaction};
 
 impl Transaction<NoExtension> for DomainRenew<'_> {}
@@ -12,6 +12,14 @@ impl Transaction<NoExtension> for DomainRenew<'_> {}
 impl Command for DomainRenew<'_> {
     type Response = RenewData;
     const COMMAND: &'static str = "renew";
+
+    fn object_ids(&self) -> Vec<&str> {
+        vec![self.domain.name]
+    }
+
+    fn object_uris(&self) -> Vec<&str> {
+        vec![XMLNS]
+    }
 }
 
 impl<'a> DomainRenew<'a> {
@@ -59,27 +67,26 @@ pub struct RenewData {
     /// The name of the domain
     pub name: String,
     /// The new expiry date after renewal
-    #[xml(rename = "exDate")]
+    #[xml(rename = "exDate", deserialize_with = "deserialize_date_option")]
     pub expiring_at: Option<DateTime<Utc>>,
 }
 
 #[cfg(test)]
 mod tests {
     use super::{DomainRenew, Period};
-    use crate::domain::PeriodLength;
+    use crate::common::NoExtension;
     use crate::response::ResultCode;
-    use crate::tests::{assert_serialized, response_from_file, CLTRID, SUCCESS_MSG, SVTRID};
+    use crate::tests::{
+        assert_serialized, get_xml, response_from_file, CLTRID, SUCCESS_MSG, SVTRID,
+    };
+    use crate::xml::{self, Options};
 
     use chrono::{NaiveDate, TimeZone, Utc};
 
     #[test]
     fn command() {
         let exp_date = NaiveDate::from_ymd_opt(2022, 7, 23).unwrap();
-        let object = DomainRenew::new(
-            "eppdev.com",
-            exp_date,
-            Period::Years(PeriodLength::new(1).unwrap()),
-        );
+        let object = DomainRenew::new("eppdev.com", exp_date, Period::years(1).unwrap());
         assert_serialized("request/domain/renew.xml", &object);
     }
 
@@ -99,4 +106,24 @@ mod tests {
         assert_eq!(object.tr_ids.client_tr_id.unwrap(), CLTRID);
         assert_eq!(object.tr_ids.server_tr_id, SVTRID);
     }
+    #[test]
+    fn response_offset() {
+        let object = response_from_file::<DomainRenew>("response/domain/renew_offset.xml");
+        let result = object.res_data().unwrap();
+        assert_eq!(
+            *result.expiring_at.as_ref().unwrap(),
+            Utc.with_ymd_and_hms(2024, 7, 23, 15, 31, 0).unwrap()
+        );
+
+        // Clients with strict dates reject the offset
+        let xml = get_xml("response/domain/renew_offset.xml").unwrap();
+        let options = Options {
+            strict_dates: true,
+            ..Options::default()
+        };
+        let err = options
+            .scope(|| xml::deserialize_response::<DomainRenew, NoExtension>(&xml))
+            .unwrap_err();
+        assert!(err.to_string().contains("invalid date/time"), "{err}");
+    }
 }
@@ -1,52 +1,87 @@
 //! Types for EPP domain transfer request
 
+use std::str::FromStr;
+
 use chrono::{DateTime, Utc};
 use instant_xml::{FromXml, ToXml};
 
 use super::{DomainAuthInfo, Period, XMLNS};
-use crate::common::{NoExtension, EPP_XMLNS};
+use crate::common::{deserialize_date_option, NoExtension, EPP_XMLNS};
 use crate::request::{Command, Transaction};
+use crate::Error;
 
 impl Transaction<NoExtension> for DomainTransfer<'_> {}
 
 impl Command for DomainTransfer<'_> {
     type Response = TransferData;
     const COMMAND: &'static str = "transfer";
+
+    fn object_ids(&self) -> Vec<&str> {
+        vec![self.domain.name]
+    }
+
+    fn object_uris(&self) -> Vec<&str> {
+        vec![XMLNS]
+    }
 }
 
 impl<'a> DomainTransfer<'a> {
+    /// Same as [`request()`](Self::request)
     pub fn new(name: &'a str, period: Option<Period>, auth_password: &'a str) -> Self {
+        Self::request(name, period, auth_password)
+    }
+
+    /// Request the transfer of `name` to the logged-in registrar
+    ///
+    /// The response usually has result code 1001 with a `pending` transfer, but registries
+    /// that approve transfers immediately answer with 1000 and `serverApproved`.
+    pub fn request(name: &'a str, period: Option<Period>, auth_password: &'a str) -> Self {
         Self::build(
-            "request",
+            TransferOperation::Request,
             name,
             period,
             Some(DomainAuthInfo::new(auth_password)),
         )
     }
 
+    /// Query the state of the most recent transfer of `name`
     pub fn query(name: &'a str, auth_password: &'a str) -> Self {
         Self::build(
-            "query",
+            TransferOperation::Query,
             name,
             None,
             Some(DomainAuthInfo::new(auth_password)),
         )
     }
 
+    /// Approve a pending transfer of `name` away from the logged-in registrar
+    ///
+    /// Registries may omit the `<trnData>` from the response, or some of its fields.
     pub fn approve(name: &'a str) -> Self {
-        Self::build("approve", name, None, None)
+        Self::build(TransferOperation::Approve, name, None, None)
     }
 
+    /// Reject a pending transfer of `name` away from the logged-in registrar
+    ///
+    /// Registries may omit the `<trnData>` from the response, or some of its fields.
     pub fn reject(name: &'a str) -> Self {
-        Self::build("reject", name, None, None)
+        Self::build(TransferOperation::Reject, name, None, None)
     }
 
+    /// Cancel a pending transfer of `name` requested by the logged-in registrar
+    ///
+    /// Registries may omit the `<trnData>` from the response, or some of its fields.
     pub fn cancel(name: &'a str) -> Self {
-        Self::build("cancel", name, None, None)
+        Self::build(TransferOperation::Cancel, name, None, None)
+    }
+
+    /// The operation this command performs
+    pub fn operation(&self) -> TransferOperation {
+        self.operation
     }
 
     fn build(
-        operation: &'a str,
+        operation: TransferOperation,
         name: &'a str,
         period: Option<Period>,
         auth_info: Option<DomainAuthInfo<'a>>,
@@ -84,17 +119,30 @@ pub struct DomainTransferReqData<'a> {
 /// Type for EPP XML `<transfer>` command for domains
 pub struct DomainTransfer<'a> {
     /// The transfer operation to perform indicated by the 'op' attr
-    /// The values are one of transfer or query
     #[xml(rename = "op", attribute)]
-    operation: &'a str,
+    operation: TransferOperation,
     /// The data under the `<transfer>` tag in the transfer request
     domain: DomainTransferReqData<'a>,
 }
 
+/// The `op` attribute of a `<transfer>` command
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ToXml)]
+#[xml(scalar, rename_all = "lowercase")]
+pub enum TransferOperation {
+    Request,
+    Query,
+    Approve,
+    Reject,
+    Cancel,
+}
+
 // Response
 
 /// Type that represents the `<trnData>` tag for domain transfer response
-#[derive(Debug, FromXml)]
+///
+/// All fields are required by RFC 5731, but registries tend to leave out the requesting and
+/// acting registrar details from responses to approve, reject and cancel commands.
+#[derive(Clone, Debug, FromXml)]
 #[xml(rename = "trnData", ns(XMLNS))]
 pub struct TransferData {
     /// The domain name
@@ -104,61 +152,128 @@ pub struct TransferData {
     pub transfer_status: String,
     /// The epp user who requested the transfer
     #[xml(rename = "reID")]
-    pub requester_id: String,
-    /// The transfer rquest date
-    #[xml(rename = "reDate")]
-    pub requested_at: DateTime<Utc>,
+    pub requester_id: Option<String>,
+    /// The transfer request date
+    #[xml(rename = "reDate", deserialize_with = "deserialize_date_option")]
+    pub requested_at: Option<DateTime<Utc>>,
     /// The epp user who should acknowledge the transfer request
     #[xml(rename = "acID")]
-    pub ack_id: String,
-    /// THe date by which the acknowledgment should be made
-    #[xml(rename = "acDate")]
-    pub ack_by: DateTime<Utc>,
+    pub ack_id: Option<String>,
+    /// The date by which the acknowledgment should be made
+    #[xml(rename = "acDate", deserialize_with = "deserialize_date_option")]
+    pub ack_by: Option<DateTime<Utc>>,
     /// The domain expiry date
-    #[xml(rename = "exDate")]
+    #[xml(rename = "exDate", deserialize_with = "deserialize_date_option")]
     pub expiring_at: Option<DateTime<Utc>>,
 }
 
+impl TransferData {
+    /// Parses the `<trStatus>` value
+    pub fn status(&self) -> Result<TransferStatus, Error> {
+        TransferStatus::from_str(&self.transfer_status)
+    }
+}
+
+/// The state of a transfer, as reported in `<trStatus>`
+///
+/// See section 2.3 of [RFC 5730](https://tools.ietf.org/html/rfc5730#section-2.3).
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum TransferStatus {
+    ClientApproved,
+    ClientCancelled,
+    ClientRejected,
+    Pending,
+    ServerApproved,
+    ServerCancelled,
+    /// A status not defined in RFC 5730, with its value as sent by the server
+    Other(String),
+}
+
+impl TransferStatus {
+    pub fn as_str(&self) -> &str {
+        use TransferStatus::*;
+        match self {
+            ClientApproved => "clientApproved",
+            ClientCancelled => "clientCancelled",
+            ClientRejected => "clientRejected",
+            Pending => "pending",
+            ServerApproved => "serverApproved",
+            ServerCancelled => "serverCancelled",
+            Other(status) => status,
+        }
+    }
+
+    /// Returns true if the transfer will not change state anymore
+    ///
+    /// Unknown statuses are not considered final.
+    pub fn is_final(&self) -> bool {
+        !matches!(self, Self::Pending | Self::Other(_))
+    }
+
+    /// Returns true if the transfer completed and the domain moved to the gaining registrar
+    pub fn is_approved(&self) -> bool {
+        matches!(self, Self::ClientApproved | Self::ServerApproved)
+    }
+}
+
+impl FromStr for TransferStatus {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "clientApproved" => Self::ClientApproved,
+            "clientCancelled" => Self::ClientCancelled,
+            "clientRejected" => Self::ClientRejected,
+            "pending" => Self::Pending,
+            "serverApproved" => Self::ServerApproved,
+            "serverCancelled" => Self::ServerCancelled,
+            _ => Self::Other(s.to_owned()),
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use chrono::{TimeZone, Utc};
 
-    use super::{DomainTransfer, Period};
-    use crate::domain::PeriodLength;
+    use super::{DomainTransfer, Period, TransferOperation, TransferStatus};
     use crate::response::ResultCode;
     use crate::tests::{assert_serialized, response_from_file, CLTRID, SUCCESS_MSG, SVTRID};
 
     #[test]
     fn request_command() {
-        let object = DomainTransfer::new(
-            "testing.com",
-            Some(Period::Years(PeriodLength::new(1).unwrap())),
-            "epP4uthd#v",
-        );
+        let object =
+            DomainTransfer::request("testing.com", Some(Period::years(1).unwrap()), "epP4uthd#v");
+        assert_eq!(object.operation(), TransferOperation::Request);
         assert_serialized("request/domain/transfer_request.xml", &object);
     }
 
     #[test]
     fn approve_command() {
         let object = DomainTransfer::approve("testing.com");
+        assert_eq!(object.operation(), TransferOperation::Approve);
         assert_serialized("request/domain/transfer_approve.xml", &object);
     }
 
     #[test]
     fn reject_command() {
         let object = DomainTransfer::reject("testing.com");
+        assert_eq!(object.operation(), TransferOperation::Reject);
         assert_serialized("request/domain/transfer_reject.xml", &object);
     }
 
     #[test]
     fn cancel_command() {
         let object = DomainTransfer::cancel("testing.com");
+        assert_eq!(object.operation(), TransferOperation::Cancel);
         assert_serialized("request/domain/transfer_cancel.xml", &object);
     }
 
     #[test]
     fn query_command() {
         let object = DomainTransfer::query("testing.com", "epP4uthd#v");
+        assert_eq!(object.operation(), TransferOperation::Query);
         assert_serialized("request/domain/transfer_query.xml", &object);
     }
 
@@ -178,15 +293,17 @@ mod tests {
         );
         assert_eq!(result.name, "eppdev-transfer.com");
         assert_eq!(result.transfer_status, "pending");
-        assert_eq!(result.requester_id, "eppdev");
+        assert_eq!(result.status().unwrap(), TransferStatus::Pending);
+        assert!(!result.status().unwrap().is_final());
+        assert_eq!(result.requester_id.as_deref(), Some("eppdev"));
         assert_eq!(
             result.requested_at,
-            Utc.with_ymd_and_hms(2021, 7, 23, 15, 31, 21).unwrap(),
+            Utc.with_ymd_and_hms(2021, 7, 23, 15, 31, 21).single(),
         );
-        assert_eq!(result.ack_id, "ClientY");
+        assert_eq!(result.ack_id.as_deref(), Some("ClientY"));
         assert_eq!(
             result.ack_by,
-            Utc.with_ymd_and_hms(2021, 7, 28, 15, 31, 21).unwrap()
+            Utc.with_ymd_and_hms(2021, 7, 28, 15, 31, 21).single()
         );
         assert_eq!(
             result.expiring_at,
@@ -199,9 +316,11 @@ mod tests {
     #[test]
     fn approve_response() {
         let object = response_from_file::<DomainTransfer>("response/domain/transfer_approve.xml");
+        let result = object.res_data().unwrap();
 
         assert_eq!(object.result.code, ResultCode::CommandCompletedSuccessfully);
         assert_eq!(object.result.message, SUCCESS_MSG);
+        assert_eq!(result.status().unwrap(), TransferStatus::ClientApproved);
         assert_eq!(object.tr_ids.client_tr_id.unwrap(), CLTRID);
         assert_eq!(object.tr_ids.server_tr_id, SVTRID);
     }
@@ -209,9 +328,11 @@ mod tests {
     #[test]
     fn reject_response() {
         let object = response_from_file::<DomainTransfer>("response/domain/transfer_reject.xml");
+        let result = object.res_data().unwrap();
 
         assert_eq!(object.result.code, ResultCode::CommandCompletedSuccessfully);
         assert_eq!(object.result.message, SUCCESS_MSG);
+        assert_eq!(result.status().unwrap(), TransferStatus::ClientRejected);
         assert_eq!(object.tr_ids.client_tr_id.unwrap(), CLTRID);
         assert_eq!(object.tr_ids.server_tr_id, SVTRID);
     }
@@ -219,9 +340,11 @@ mod tests {
     #[test]
     fn cancel_response() {
         let object = response_from_file::<DomainTransfer>("response/domain/transfer_cancel.xml");
+        let result = object.res_data().unwrap();
 
         assert_eq!(object.result.code, ResultCode::CommandCompletedSuccessfully);
         assert_eq!(object.result.message, SUCCESS_MSG);
+        assert_eq!(result.status().unwrap(), TransferStatus::ClientCancelled);
         assert_eq!(object.tr_ids.client_tr_id.unwrap(), CLTRID);
         assert_eq!(object.tr_ids.server_tr_id, SVTRID);
     }
@@ -236,15 +359,15 @@ mod tests {
         assert_eq!(object.result.message, SUCCESS_MSG);
         assert_eq!(result.name, "eppdev-transfer.com");
         assert_eq!(result.transfer_status, "pending");
-        assert_eq!(result.requester_id, "eppdev");
+        assert_eq!(result.requester_id.as_deref(), Some("eppdev"));
         assert_eq!(
             result.requested_at,
-            Utc.with_ymd_and_hms(2021, 7, 23, 15, 31, 21).unwrap()
+            Utc.with_ymd_and_hms(2021, 7, 23, 15, 31, 21).single()
         );
-        assert_eq!(result.ack_id, "ClientY");
+        assert_eq!(result.ack_id.as_deref(), Some("ClientY"));
         assert_eq!(
             result.ack_by,
-            Utc.with_ymd_and_hms(2021, 7, 28, 15, 31, 21).unwrap()
+            Utc.with_ymd_and_hms(2021, 7, 28, 15, 31, 21).single()
         );
         assert_eq!(
             result.expiring_at,
@@ -253,4 +376,27 @@ mod tests {
         assert_eq!(object.tr_ids.client_tr_id.unwrap(), CLTRID);
         assert_eq!(object.tr_ids.server_tr_id, SVTRID);
     }
+
+    #[test]
+    fn bare_response() {
+        let object = response_from_file::<DomainTransfer>("response/domain/transfer_bare.xml");
+        let result = object.res_data().unwrap();
+
+        assert_eq!(result.name, "eppdev-transfer.com");
+        assert_eq!(result.status().unwrap(), TransferStatus::ClientCancelled);
+        assert_eq!(result.requester_id, None);
+        assert_eq!(result.requested_at, None);
+        assert_eq!(result.ack_id, None);
+        assert_eq!(result.ack_by, None);
+        assert_eq!(result.expiring_at, None);
+    }
+
+    #[test]
+    fn unknown_status() {
+        let status = "autoApproved".parse::<TransferStatus>().unwrap();
+        assert_eq!(status, TransferStatus::Other("autoApproved".into()));
+        assert_eq!(status.as_str(), "autoApproved");
+        assert!(!status.is_final());
+        assert!(!status.is_approved());
+    }
 }
@@ -1,11 +1,17 @@
 //! Types for EPP domain check request
 
-use instant_xml::ToXml;
+use std::fmt;
+
+use instant_xml::ser::Context;
+use instant_xml::{Serializer, ToXml};
 
 use super::{DomainAuthInfo, DomainContact, NameServers, Status, XMLNS};
 use crate::{
-    common::{NoExtension, EPP_XMLNS},
+    common::{
+        check_client_statuses, serialize_container, Container, NoExtension, StatusValue, EPP_XMLNS,
+    },
     request::{Command, Transaction},
+    Error,
 };
 
 impl Transaction<NoExtension> for DomainUpdate<'_> {}
@@ -13,6 +19,24 @@ impl Transaction<NoExtension> for DomainUpdate<'_> {}
 impl Command for DomainUpdate<'_> {
     type Response = ();
     const COMMAND: &'static str = "update";
+
+    fn validate(&self) -> Result<(), Error> {
+        if self.allow_server_statuses {
+            return Ok(());
+        }
+
+        let add = self.domain.add.as_ref().and_then(|add| add.statuses);
+        let remove = self.domain.remove.as_ref().and_then(|rem| rem.statuses);
+        check_client_statuses(self.domain.name, add, remove)
+    }
+
+    fn object_ids(&self) -> Vec<&str> {
+        vec![self.domain.name]
+    }
+
+    fn object_uris(&self) -> Vec<&str> {
+        vec![XMLNS]
+    }
 }
 
 impl<'a> DomainUpdate<'a> {
@@ -24,6 +48,7 @@ impl<'a> DomainUpdate<'a> {
                 remove: None,
                 change_info: None,
             },
+            allow_server_statuses: false,
         }
     }
 
@@ -41,6 +66,11 @@ impl<'a> DomainUpdate<'a> {
     pub fn remove(&mut self, remove: DomainRemove<'a>) {
         self.domain.remove = Some(remove);
     }
+
+    /// Allow adding and removing server-managed statuses, which are rejected by default
+    pub fn allow_server_statuses(&mut self) {
+        self.allow_server_statuses = true;
+    }
 }
 
 /// Type for elements under the `<chg>` tag for domain update
@@ -59,11 +89,12 @@ pub struct DomainChangeInfo<'a> {
 pub struct DomainAdd<'a> {
     /// The list of nameservers to add or remove
     /// Type T can be either a `HostObjList` or `HostAttrList`
+    #[xml(serialize_with = "serialize_container")]
     pub ns: Option<NameServers<'a>>,
     /// The list of contacts to add to or remove from the domain
     pub contacts: Option<&'a [DomainContact<'a>]>,
     /// The list of statuses to add to or remove from the domain
-    pub statuses: Option<&'a [Status]>,
+    pub statuses: Option<&'a [StatusValue<Status>]>,
 }
 
 /// Type for elements under the `<add>` and `<rem>` tags for domain update
@@ -72,11 +103,28 @@ pub struct DomainAdd<'a> {
 pub struct DomainRemove<'a> {
     /// The list of nameservers to add or remove
     /// Type T can be either a `HostObjList` or `HostAttrList`
+    #[xml(serialize_with = "serialize_container")]
     pub ns: Option<NameServers<'a>>,
     /// The list of contacts to add to or remove from the domain
     pub contacts: Option<&'a [DomainContact<'a>]>,
     /// The list of statuses to add to or remove from the domain
-    pub statuses: Option<&'a [Status]>,
+    pub statuses: Option<&'a [StatusValue<Status>]>,
+}
+
+impl Container for DomainAdd<'_> {
+    fn is_empty(&self) -> bool {
+        self.ns.as_ref().is_none_or(|ns| ns.is_empty())
+            && self.contacts.unwrap_or_default().is_empty()
+            && self.statuses.unwrap_or_default().is_empty()
+    }
+}
+
+impl Container for DomainRemove<'_> {
+    fn is_empty(&self) -> bool {
+        self.ns.as_ref().is_none_or(|ns| ns.is_empty())
+            && self.contacts.unwrap_or_default().is_empty()
+            && self.statuses.unwrap_or_default().is_empty()
+    }
 }
 
 /// Type for elements under the `<update>` tag for domain update
@@ -87,20 +135,37 @@ pub struct DomainUpdateRequestData<'a> {
     pub name: &'a str,
     /// `DomainAddRemove` Object containing the list of elements to be added
     /// to the domain
+    #[xml(serialize_with = "serialize_container")]
     pub add: Option<DomainAdd<'a>>,
     /// `DomainAddRemove` Object containing the list of elements to be removed
     /// from the domain
+    #[xml(serialize_with = "serialize_container")]
     pub remove: Option<DomainRemove<'a>>,
     /// The data under the `<chg>` tag for domain update
-    #[xml(rename = "domain:chg")]
+    ///
+    /// Kept even if empty: RFC 3915 restore requests and ConsoliDate sync updates consist of
+    /// an empty `<chg>` and the extension.
     pub change_info: Option<DomainChangeInfo<'a>>,
 }
 
 /// Type for EPP XML `<update>` command for domains
-#[derive(Debug, ToXml)]
-#[xml(rename = "update", ns(EPP_XMLNS))]
+#[derive(Debug)]
 pub struct DomainUpdate<'a> {
     pub domain: DomainUpdateRequestData<'a>,
+    allow_server_statuses: bool,
+}
+
+impl ToXml for DomainUpdate<'_> {
+    fn serialize<W: fmt::Write + ?Sized>(
+        &self,
+        _: Option<instant_xml::Id<'_>>,
+        serializer: &mut Serializer<W>,
+    ) -> Result<(), instant_xml::Error> {
+        let update = serializer.write_start("update", EPP_XMLNS, None::<Context<0>>)?;
+        serializer.end_start()?;
+        self.domain.serialize(None, serializer)?;
+        serializer.write_close(update)
+    }
 }
 
 #[cfg(test)]
@@ -108,6 +173,7 @@ mod tests {
     use super::{
         DomainAdd, DomainAuthInfo, DomainChangeInfo, DomainContact, DomainRemove, DomainUpdate,
     };
+    use crate::common::StatusValue;
     use crate::domain::Status;
     use crate::response::ResultCode;
     use crate::tests::{assert_serialized, response_from_file, CLTRID, SUCCESS_MSG, SVTRID};
@@ -119,7 +185,7 @@ mod tests {
         let add = DomainAdd {
             ns: None,
             contacts: None,
-            statuses: Some(&[Status::ClientDeleteProhibited]),
+            statuses: Some(&[Status::ClientDeleteProhibited.into()]),
         };
 
         let contacts = &[DomainContact {
@@ -144,6 +210,33 @@ mod tests {
         assert_serialized("request/domain/update.xml", &object);
     }
 
+    #[test]
+    fn command_status_reason() {
+        let mut object = DomainUpdate::new("eppdev.com");
+
+        let statuses = [
+            Status::ClientDeleteProhibited.into(),
+            StatusValue::new(Status::ClientHold)
+                .reason("Zahlung ausstehend", Some("de".parse().unwrap())),
+        ];
+        object.add(DomainAdd {
+            ns: None,
+            contacts: None,
+            statuses: Some(&statuses),
+        });
+        assert_serialized("request/domain/update_status_reason.xml", &object);
+    }
+
+    #[test]
+    fn command_auth_info_null() {
+        let mut object = DomainUpdate::new("eppdev.com");
+        object.info(DomainChangeInfo {
+            registrant: None,
+            auth_info: Some(DomainAuthInfo::null()),
+        });
+        assert_serialized("request/domain/update_auth_info_null.xml", &object);
+    }
+
     #[test]
     fn response() {
         let object = response_from_file::<DomainUpdate>("response/domain/update.xml");
@@ -8,14 +8,75 @@ use std::num::TryFromIntError;
 use std::str::Utf8Error;
 use std::string::FromUtf8Error;
 
-use crate::response::ResponseStatus;
+use crate::response::{EppResult, ResponseTRID};
 
 /// Error enum holding the possible error types
 #[derive(Debug)]
 pub enum Error {
-    Command(Box<ResponseStatus>),
+    /// The server returned an error result for a command
+    Command(Box<CommandError>),
     Io(std::io::Error),
     Timeout,
+    QueueTimeout,
+    /// The first frame from the server was not a `<greeting>`
+    UnexpectedGreeting {
+        raw: String,
+    },
+    /// Reconnecting failed on every attempt, see `ReconnectBackoff`
+    ReconnectExhausted {
+        attempts: u32,
+        /// The error from the last attempt
+        last: Box<Self>,
+    },
+    /// A request could not be serialized
+    Request(Box<RequestError>),
+    /// The connection can no longer be used after a framing error and needs a `reconnect()`, or
+    /// all strong handles to a shared client have been dropped, see `WeakClient`
+    Closed,
+    /// A frame did not match the length its header announced, or that length was not accepted
+    ///
+    /// The `limit` is the 4-byte minimum (the header itself) if `declared` is smaller, and the
+    /// maximum frame length otherwise. A deflated payload that inflates past the maximum is
+    /// reported with `declared` as `limit + 1`, where decompression stopped.
+    ///
+    /// If more bytes were `received` than the header announced, `declared` is within the limit.
+    /// For a response sent without a header, which is read up to its end, `declared` is the
+    /// start of the response read as a header and exceeds the limit; `received` is then the
+    /// number of bytes read without finding the end.
+    Framing {
+        /// The registry the frame came from, if it was received by an `EppClient`
+        registry: Option<String>,
+        declared: usize,
+        limit: usize,
+        received: Option<usize>,
+    },
+    /// Not sent after too many consecutive connection failures, see `CircuitBreaker`
+    CircuitOpen,
+    /// The server's greeting does not offer what the command needs, see `ServiceMenu::check()`
+    ///
+    /// Only checked before sending if enabled with `EppClient::set_check_services()`.
+    Unsupported {
+        command: &'static str,
+        reason: String,
+    },
+    /// The response echoed another client transaction ID than the request's
+    ///
+    /// Only checked if enabled with `EppClient::set_verify_transaction_ids()`.
+    TransactionMismatch {
+        expected: String,
+        received: String,
+    },
+    /// The server ended the session in response to a command that was not sent again
+    ///
+    /// The server did not execute the command. Unless `relogin` holds the error from logging in
+    /// again, the client has a new session, in which the command can be resubmitted if that is
+    /// safe. Only returned if enabled with `EppClient::set_relogin()`.
+    SessionEnded {
+        /// The error response to the command
+        error: Box<CommandError>,
+        /// Why logging in again failed, if it did
+        relogin: Option<Box<Self>>,
+    },
     Xml(Box<dyn StdError + Send + Sync>),
     Other(Box<dyn StdError + Send + Sync>),
 }
@@ -25,17 +86,126 @@ impl StdError for Error {}
 impl Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Self::Command(e) => {
-                write!(f, "command error: {}", e.result.message)
-            }
+            Self::Command(e) => write!(f, "{e}"),
             Self::Io(e) => write!(f, "I/O error: {e}"),
             Self::Timeout => write!(f, "timeout"),
+            Self::QueueTimeout => write!(f, "request expired in queue"),
+            Self::UnexpectedGreeting { raw } => write!(f, "expected greeting, received: {raw}"),
+            Self::ReconnectExhausted { attempts, last } => {
+                write!(f, "reconnect failed after {attempts} attempts: {last}")
+            }
+            Self::Request(e) => write!(f, "{e}"),
+            Self::Closed => write!(f, "client closed"),
+            Self::Framing {
+                registry,
+                declared,
+                limit,
+                received,
+            } => {
+                if let Some(registry) = registry {
+                    write!(f, "{registry}: ")?;
+                }
+
+                match (received, declared <= limit) {
+                    (Some(received), true) => write!(
+                        f,
+                        "frame header announces {declared} bytes, but {received} were received"
+                    ),
+                    (Some(received), false) => write!(
+                        f,
+                        "no end of response without header in {received} bytes (limit {limit})"
+                    ),
+                    (None, _) if declared < limit => write!(
+                        f,
+                        "frame header announces {declared} bytes, less than the minimum of {limit}"
+                    ),
+                    (None, _) => write!(
+                        f,
+                        "frame header announces {declared} bytes, more than the maximum of {limit}"
+                    ),
+                }
+            }
+            Self::CircuitOpen => write!(f, "circuit open after repeated connection failures"),
+            Self::Unsupported { command, reason } => {
+                write!(f, "{command} command not supported: {reason}")
+            }
+            Self::TransactionMismatch { expected, received } => write!(
+                f,
+                "response for transaction {received:?} received for transaction {expected:?}"
+            ),
+            Self::SessionEnded { error, relogin } => {
+                write!(f, "session ended: {error}")?;
+                match relogin {
+                    Some(err) => write!(f, " (login failed: {err})"),
+                    None => Ok(()),
+                }
+            }
             Self::Xml(e) => write!(f, "(de)serialization error: {e}"),
             Self::Other(e) => write!(f, "error: {e}"),
         }
     }
 }
 
+/// A command the server responded to with an error, see [`Error::Command`]
+#[derive(Debug)]
+pub struct CommandError {
+    /// The command, as in [`Command::COMMAND`](crate::request::Command::COMMAND)
+    pub command: &'static str,
+    /// The IDs of the objects in the request, see
+    /// [`Command::object_ids()`](crate::request::Command::object_ids)
+    pub objects: Vec<String>,
+    /// Data under the `<result>` tag
+    pub result: EppResult,
+    /// Data under the `<trID>` tag
+    pub tr_ids: ResponseTRID,
+}
+
+impl StdError for CommandError {}
+
+impl Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} command error", self.command)?;
+        if !self.objects.is_empty() {
+            write!(f, " for {}", self.objects.join(", "))?;
+        }
+
+        write!(f, ": {}", self.result.message)
+    }
+}
+
+/// Context for a request that failed to serialize, see [`Error::Request`]
+#[derive(Debug)]
+pub struct RequestError {
+    /// The command, as in [`Command::COMMAND`](crate::request::Command::COMMAND)
+    pub command: &'static str,
+    /// The registry the request was for, if it was sent through an `EppClient`
+    pub registry: Option<String>,
+    /// The client transaction ID of the request
+    pub transaction_id: String,
+    /// The underlying serialization error
+    pub source: Box<dyn StdError + Send + Sync>,
+}
+
+impl StdError for RequestError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        Some(&*self.source)
+    }
+}
+
+impl Display for RequestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(registry) = &self.registry {
+            write!(f, "{registry}: ")?;
+        }
+
+        write!(
+            f,
+            "failed to serialize {} command (clTRID {}): {}",
+            self.command, self.transaction_id, self.source
+        )
+    }
+}
+
 impl From<Box<dyn StdError + Send + Sync>> for Error {
     fn from(e: Box<dyn StdError + Send + Sync>) -> Self {
         Self::Other(e)
@@ -0,0 +1,93 @@
+//! Types for the additional email address extension
+//!
+//! As described in [Use of Internationalized Email Addresses in
+//! EPP](https://datatracker.ietf.org/doc/draft-ietf-regext-epp-eai/). The extension attaches a
+//! second email address to a contact, next to the one in the `<contact:email>` element.
+//! Besides providing an ASCII alternative to an internationalized address, registries use it
+//! to collect a separate address for, say, abuse reports or billing.
+
+use std::borrow::Cow;
+
+use instant_xml::{FromXml, ToXml};
+
+use crate::contact::{is_valid_email, ContactCreate, ContactInfo, ContactUpdate};
+use crate::request::{Extension, Transaction};
+use crate::Error;
+
+pub const XMLNS: &str = "urn:ietf:params:xml:ns:epp:addlEmail-1.0";
+
+impl Transaction<AddlEmail<'_>> for ContactCreate<'_> {}
+impl Transaction<AddlEmail<'_>> for ContactUpdate<'_> {}
+impl Transaction<AddlEmail<'_>> for ContactInfo<'_> {}
+
+impl<'a> AddlEmail<'a> {
+    /// Attach the additional `email` address
+    ///
+    /// Fails if `email` is not a valid email address, rather than leaving the registry to
+    /// reject the command.
+    pub fn new(email: &'a str) -> Result<Self, Error> {
+        match is_valid_email(email) {
+            true => Ok(Self {
+                email: email.into(),
+            }),
+            false => Err(Error::Other(
+                format!("invalid additional email address {email:?}").into(),
+            )),
+        }
+    }
+}
+
+impl Extension for AddlEmail<'_> {
+    type Response = AddlEmail<'static>;
+}
+
+/// Type for EPP XML `<addlEmail:addlEmail>` extension
+///
+/// Attached to contact create and update commands, and returned with contact info responses.
+#[derive(Debug, Eq, FromXml, PartialEq, ToXml)]
+#[xml(rename = "addlEmail", ns(XMLNS))]
+pub struct AddlEmail<'a> {
+    /// The additional email address
+    pub email: Cow<'a, str>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AddlEmail;
+    use crate::contact::{Address, ContactCreate, ContactInfo, InfoType, PostalInfo};
+    use crate::tests::{assert_serialized, response_from_file_with_ext};
+
+    #[test]
+    fn command() {
+        let address = Address::new(&[], "Paris", None, None, "FR".parse().unwrap());
+        let postal_info = PostalInfo::new(InfoType::International, "John Doe", None, address);
+        let object = ContactCreate::new(
+            "eppdev-contact-3",
+            "contact@eppdev.net",
+            postal_info,
+            None,
+            "eppdev-387323",
+        );
+        let ext = AddlEmail::new("abuse@eppdev.net").unwrap();
+
+        assert_serialized("request/extensions/addl_email.xml", (&object, &ext));
+    }
+
+    #[test]
+    fn invalid() {
+        let err = AddlEmail::new("abuse at eppdev.net").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            r#"error: invalid additional email address "abuse at eppdev.net""#
+        );
+    }
+
+    #[test]
+    fn response() {
+        let object = response_from_file_with_ext::<ContactInfo, AddlEmail>(
+            "response/extensions/addl_email.xml",
+        );
+        let ext = object.extension().unwrap();
+        assert_eq!(ext.email, "billing@eppdev.net");
+    }
+}
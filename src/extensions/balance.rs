@@ -0,0 +1,148 @@
+//! Balance Mapping for the Extensible Provisioning Protocol (EPP)
+//!
+//! <https://www.verisign.com/assets/epp-sdk/verisign_epp-extension_balance_v01.html>
+//!
+//! Registries with a proprietary command for the registrar's account balance usually follow the
+//! same pattern: an `<info>` command wrapping an empty element in the registry's namespace. For
+//! registries other than Verisign, [`AccountInfo`] sends such a command and returns the
+//! response data as [`RawXml`].
+
+use instant_xml::ser::Context;
+use instant_xml::{FromXml, Serializer, ToXml};
+
+use crate::common::{NoExtension, RawXml, EPP_XMLNS};
+use crate::request::{Command, Transaction};
+
+pub const XMLNS: &str = "http://www.verisign.com/epp/balance-1.0";
+
+// Verisign
+
+impl Transaction<NoExtension> for BalanceInfo {}
+
+impl Command for BalanceInfo {
+    type Response = InfoData;
+    const COMMAND: &'static str = "info";
+}
+
+#[derive(Debug, Default, ToXml)]
+#[xml(rename = "info", ns(XMLNS))]
+struct BalanceInfoRequest;
+
+/// Type for EPP XML `<info>` command for the account balance
+#[derive(Debug, Default, ToXml)]
+#[xml(rename = "info", ns(EPP_XMLNS))]
+pub struct BalanceInfo {
+    info: BalanceInfoRequest,
+}
+
+/// Type that represents the `<infData>` tag for the balance info response
+#[derive(Clone, Debug, Eq, FromXml, PartialEq)]
+#[xml(rename = "infData", ns(XMLNS), rename_all = "camelCase")]
+pub struct InfoData {
+    /// The credit limit of the account
+    pub credit_limit: String,
+    /// The account balance
+    pub balance: String,
+    /// The credit still available, the credit limit minus the balance
+    pub available_credit: String,
+    /// The level of available credit below which the registry sends a low balance notice
+    pub credit_threshold: Threshold,
+}
+
+/// Type that represents the `<creditThreshold>` tag
+///
+/// The threshold is either a fixed amount or a percentage of the credit limit.
+#[derive(Clone, Debug, Eq, FromXml, PartialEq)]
+#[xml(rename = "creditThreshold", ns(XMLNS))]
+pub struct Threshold {
+    /// The threshold as an amount
+    pub fixed: Option<String>,
+    /// The threshold as a percentage of the credit limit
+    pub percent: Option<String>,
+}
+
+// Other registries
+
+/// An `<info>` command for the account balance in another registry's namespace
+///
+/// Serialized as an `<info>` command containing an empty `element` in the namespace `xmlns`.
+#[derive(Clone, Copy, Debug)]
+pub struct AccountInfo<'a> {
+    xmlns: &'a str,
+    element: &'a str,
+}
+
+impl<'a> AccountInfo<'a> {
+    /// Request the account information in the `element` in namespace `xmlns`
+    pub fn new(xmlns: &'a str, element: &'a str) -> Self {
+        Self { xmlns, element }
+    }
+}
+
+impl Transaction<NoExtension> for AccountInfo<'_> {}
+
+impl Command for AccountInfo<'_> {
+    type Response = RawXml;
+    const COMMAND: &'static str = "info";
+}
+
+impl ToXml for AccountInfo<'_> {
+    fn serialize<W: std::fmt::Write + ?Sized>(
+        &self,
+        _: Option<instant_xml::Id<'_>>,
+        serializer: &mut Serializer<W>,
+    ) -> Result<(), instant_xml::Error> {
+        let mut cx = Context::<0>::default();
+        cx.default_ns = EPP_XMLNS;
+        let outer = serializer.write_start(Self::COMMAND, EPP_XMLNS, Some(cx))?;
+        serializer.end_start()?;
+
+        serializer.write_start(self.element, self.xmlns, None::<Context<0>>)?;
+        serializer.end_empty()?;
+
+        serializer.write_close(outer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AccountInfo, BalanceInfo, Threshold};
+    use crate::response::ResultCode;
+    use crate::tests::{assert_serialized, response_from_file, CLTRID, SVTRID};
+
+    #[test]
+    fn command() {
+        assert_serialized(
+            "request/extensions/balance_info.xml",
+            &BalanceInfo::default(),
+        );
+        assert_serialized(
+            "request/extensions/account_info.xml",
+            &AccountInfo::new("urn:example:params:xml:ns:account-1.0", "info"),
+        );
+    }
+
+    #[test]
+    fn response() {
+        let object = response_from_file::<BalanceInfo>("response/extensions/balance_info.xml");
+        assert_eq!(object.result.code, ResultCode::CommandCompletedSuccessfully);
+        let data = object.res_data().unwrap();
+        assert_eq!(data.credit_limit, "1000.00");
+        assert_eq!(data.balance, "200.00");
+        assert_eq!(data.available_credit, "800.00");
+        assert_eq!(
+            data.credit_threshold,
+            Threshold {
+                fixed: Some("500.00".into()),
+                percent: None,
+            }
+        );
+        assert_eq!(object.tr_ids.client_tr_id.as_deref(), Some(CLTRID));
+        assert_eq!(object.tr_ids.server_tr_id, SVTRID);
+
+        let object = response_from_file::<AccountInfo>("response/extensions/balance_info.xml");
+        let data = object.res_data().unwrap();
+        assert_eq!(data.ns(), super::XMLNS);
+        assert_eq!(data.name(), "infData");
+    }
+}
@@ -95,6 +95,7 @@ impl Operation {
 
 /// Enumerated list of operations
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum OperationKind<'a> {
     Create,
     Delete,
@@ -158,6 +159,7 @@ impl CaseIdentifier {
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum CaseIdentifierKind<'a> {
     Udrp,
     Urs,
@@ -197,6 +199,7 @@ pub struct Reason {
 /// Enumerated state of the object in the poll message
 #[derive(Debug, Default, Copy, Clone, PartialEq, Eq, FromXml)]
 #[xml(scalar, rename_all = "camelCase")]
+#[non_exhaustive]
 pub enum State {
     Before,
     #[default]
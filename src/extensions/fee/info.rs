@@ -0,0 +1,174 @@
+//! Types for fee queries through domain info
+//!
+//! Before RFC 8748, drafts of the fee extension allowed fetching the fee for a single command on
+//! a domain through a `<fee:info>` extension on the domain info command. This implements the
+//! `fee-0.5` version of those drafts, which some registries still use.
+
+use std::borrow::Cow;
+
+use instant_xml::{FromXml, ToXml};
+
+use crate::domain::{DomainInfo, Period};
+use crate::request::{Extension, Transaction};
+
+pub const XMLNS: &str = "urn:ietf:params:xml:ns:fee-0.5";
+
+impl Transaction<Info<'_>> for DomainInfo<'_> {}
+
+impl Extension for Info<'_> {
+    type Response = InfoData;
+}
+
+// Request
+
+/// Type for EPP XML `<fee:info>` extension
+#[derive(Debug, ToXml)]
+#[xml(rename = "info", ns(XMLNS))]
+pub struct Info<'a> {
+    currency: Option<Cow<'a, str>>,
+    command: Command<'a>,
+    period: Option<FeePeriod>,
+}
+
+impl<'a> Info<'a> {
+    /// Request the fee for the command `name` (like `create` or `renew`) on the domain
+    pub fn new(name: &'a str) -> Self {
+        Self {
+            currency: None,
+            command: Command {
+                phase: None,
+                subphase: None,
+                name: name.into(),
+            },
+            period: None,
+        }
+    }
+
+    /// Request the fee in the given `currency`, an ISO 4217 code
+    pub fn currency(mut self, currency: &'a str) -> Self {
+        self.currency = Some(currency.into());
+        self
+    }
+
+    /// Request the fee for the given `period`
+    pub fn period(mut self, period: Period) -> Self {
+        self.period = Some(FeePeriod::from(period));
+        self
+    }
+
+    /// Request the fee during the launch `phase`, and `subphase` if given
+    pub fn phase(mut self, phase: &'a str, subphase: Option<&'a str>) -> Self {
+        self.command.phase = Some(phase.into());
+        self.command.subphase = subphase.map(Cow::from);
+        self
+    }
+}
+
+/// The `<fee:command>` element
+#[derive(Debug, FromXml, ToXml)]
+#[xml(rename = "command", ns(XMLNS))]
+pub struct Command<'a> {
+    /// The launch phase the fee applies to
+    #[xml(attribute)]
+    pub phase: Option<Cow<'a, str>>,
+    /// The launch sub-phase the fee applies to
+    #[xml(attribute)]
+    pub subphase: Option<Cow<'a, str>>,
+    /// The command, like `create` or `renew`
+    #[xml(direct)]
+    pub name: Cow<'a, str>,
+}
+
+/// The `<fee:period>` element
+#[derive(Debug, FromXml, ToXml)]
+#[xml(rename = "period", ns(XMLNS))]
+pub struct FeePeriod {
+    /// The unit of the period, `y` for years or `m` for months
+    #[xml(attribute)]
+    pub unit: String,
+    /// The length of the period
+    #[xml(direct)]
+    pub length: u8,
+}
+
+impl From<Period> for FeePeriod {
+    fn from(period: Period) -> Self {
+        let (unit, length) = period.unit();
+        Self {
+            unit: unit.to_string(),
+            length,
+        }
+    }
+}
+
+// Response
+
+/// Type that represents the `<fee:infData>` tag in a domain info response
+#[derive(Debug, FromXml)]
+#[xml(rename = "infData", ns(XMLNS))]
+pub struct InfoData {
+    /// The currency of the fees
+    pub currency: String,
+    /// The command the fees apply to
+    pub command: Command<'static>,
+    /// The period the fees apply to
+    pub period: Option<FeePeriod>,
+    /// The fees charged for the command
+    #[xml(rename = "fee")]
+    pub fees: Vec<Fee>,
+    /// The price class, like `premium`
+    pub class: Option<String>,
+}
+
+/// Type that represents the `<fee:fee>` tag
+///
+/// Like the RFC 8748 [`Fee`](super::Fee), but in the draft namespace.
+#[derive(Debug, FromXml)]
+#[xml(rename = "fee", ns(XMLNS))]
+pub struct Fee {
+    /// A human-readable description of the fee
+    #[xml(attribute)]
+    pub description: Option<String>,
+    /// Whether the fee is refunded when the object is deleted during its grace period
+    #[xml(attribute)]
+    pub refundable: Option<bool>,
+    /// The grace period during which the fee is refundable, as an XML duration
+    #[xml(attribute, rename = "grace-period")]
+    pub grace_period: Option<String>,
+    /// The amount, as a decimal string in the response's currency
+    #[xml(direct)]
+    pub amount: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Info;
+    use crate::domain::{DomainInfo, Period};
+    use crate::tests::{assert_serialized, response_from_file_with_ext};
+
+    #[test]
+    fn command() {
+        let fee = Info::new("create")
+            .currency("EUR")
+            .period(Period::years(1).unwrap())
+            .phase("sunrise", None);
+
+        let object = DomainInfo::new("example.com");
+        assert_serialized("request/extensions/fee_info.xml", (&object, &fee));
+    }
+
+    #[test]
+    fn response() {
+        let object =
+            response_from_file_with_ext::<DomainInfo, Info>("response/extensions/fee_info.xml");
+
+        let ext = object.extension().unwrap();
+        assert_eq!(ext.currency, "EUR");
+        assert_eq!(ext.command.name, "create");
+        assert_eq!(ext.command.phase.as_deref(), Some("sunrise"));
+        assert_eq!(ext.period.as_ref().unwrap().length, 1);
+        assert_eq!(ext.fees[0].amount, "10.00");
+        assert_eq!(ext.fees[0].description.as_deref(), Some("Registration Fee"));
+        assert_eq!(ext.class.as_deref(), Some("premium-tier1"));
+    }
+}
@@ -0,0 +1,723 @@
+//! Types for the EPP fee extension
+//!
+//! As described in [RFC 8748](https://www.rfc-editor.org/rfc/rfc8748.html).
+
+use std::borrow::Cow;
+
+use instant_xml::{FromXml, ToXml};
+
+use crate::contact::{ContactCheck, ContactCreate, ContactUpdate};
+use crate::domain::check::{CheckedDomain, DomainCheck};
+use crate::domain::{DomainCreate, DomainUpdate, Period};
+use crate::request::{Extension, Transaction};
+use crate::response::Response;
+use crate::Error;
+
+pub mod info; // Fees through domain info, from the pre-RFC drafts (different namespace)
+
+pub const XMLNS: &str = "urn:ietf:params:xml:ns:epp:fee-1.0";
+
+impl Transaction<Check<'_>> for DomainCheck<'_> {}
+
+impl Transaction<Check<'_>> for ContactCheck<'_> {}
+
+impl Extension for Check<'_> {
+    type Response = CheckData;
+}
+
+impl Transaction<Create<'_>> for DomainCreate<'_> {}
+
+impl Transaction<Create<'_>> for ContactCreate<'_> {}
+
+impl Extension for Create<'_> {
+    type Response = CreateData;
+}
+
+impl Transaction<Update<'_>> for DomainUpdate<'_> {}
+
+impl Transaction<Update<'_>> for ContactUpdate<'_> {}
+
+impl Extension for Update<'_> {
+    type Response = UpdateData;
+}
+
+// Request
+
+/// Type for EPP XML `<fee:check>` extension
+#[derive(Debug, ToXml)]
+#[xml(rename = "check", ns(XMLNS))]
+pub struct Check<'a> {
+    currency: Option<Cow<'a, str>>,
+    #[xml(rename = "command")]
+    commands: Vec<CheckCommand<'a>>,
+}
+
+impl<'a> Check<'a> {
+    /// Request fees in the server's default currency
+    pub fn new() -> Self {
+        Self {
+            currency: None,
+            commands: Vec::new(),
+        }
+    }
+
+    /// Request fees in the given `currency`, an ISO 4217 code
+    pub fn currency(mut self, currency: &'a str) -> Self {
+        self.currency = Some(currency.into());
+        self
+    }
+
+    /// Request the fee for the command `name` (like `create` or `renew`), for `period` if given
+    pub fn command(mut self, name: &'a str, period: Option<Period>) -> Self {
+        self.commands.push(CheckCommand {
+            name: name.into(),
+            period: period.map(FeePeriod::from),
+        });
+        self
+    }
+}
+
+impl Default for Check<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, ToXml)]
+#[xml(rename = "command", ns(XMLNS))]
+struct CheckCommand<'a> {
+    #[xml(attribute)]
+    name: Cow<'a, str>,
+    period: Option<FeePeriod>,
+}
+
+/// Type for EPP XML `<fee:create>` extension, agreeing to the fees for a create command
+///
+/// Registries that charge for creating an object, which besides domains may include contacts,
+/// reject the command unless the client confirms the fee it expects to pay.
+#[derive(Debug, ToXml)]
+#[xml(rename = "create", ns(XMLNS))]
+pub struct Create<'a> {
+    currency: Option<Cow<'a, str>>,
+    #[xml(rename = "fee")]
+    fees: Vec<AgreedFee<'a>>,
+}
+
+impl<'a> Create<'a> {
+    /// Agree to pay `amount`, a decimal string in the server's default currency
+    pub fn new(amount: &'a str) -> Self {
+        Self {
+            currency: None,
+            fees: vec![AgreedFee {
+                amount: amount.into(),
+            }],
+        }
+    }
+
+    /// Agree to pay in the given `currency`, an ISO 4217 code
+    pub fn currency(mut self, currency: &'a str) -> Self {
+        self.currency = Some(currency.into());
+        self
+    }
+}
+
+/// Type for EPP XML `<fee:update>` extension, agreeing to the fees for an update command
+#[derive(Debug, ToXml)]
+#[xml(rename = "update", ns(XMLNS))]
+pub struct Update<'a> {
+    currency: Option<Cow<'a, str>>,
+    #[xml(rename = "fee")]
+    fees: Vec<AgreedFee<'a>>,
+}
+
+impl<'a> Update<'a> {
+    /// Agree to pay `amount`, a decimal string in the server's default currency
+    pub fn new(amount: &'a str) -> Self {
+        Self {
+            currency: None,
+            fees: vec![AgreedFee {
+                amount: amount.into(),
+            }],
+        }
+    }
+
+    /// Agree to pay in the given `currency`, an ISO 4217 code
+    pub fn currency(mut self, currency: &'a str) -> Self {
+        self.currency = Some(currency.into());
+        self
+    }
+}
+
+#[derive(Debug, ToXml)]
+#[xml(rename = "fee", ns(XMLNS))]
+struct AgreedFee<'a> {
+    #[xml(direct)]
+    amount: Cow<'a, str>,
+}
+
+/// The `<fee:period>` element
+#[derive(Debug, FromXml, ToXml)]
+#[xml(rename = "period", ns(XMLNS))]
+pub struct FeePeriod {
+    /// The unit of the period, `y` for years or `m` for months
+    #[xml(attribute)]
+    pub unit: String,
+    /// The length of the period
+    #[xml(direct)]
+    pub length: u8,
+}
+
+impl From<Period> for FeePeriod {
+    fn from(period: Period) -> Self {
+        let (unit, length) = period.unit();
+        Self {
+            unit: unit.to_string(),
+            length,
+        }
+    }
+}
+
+// Response
+
+/// Type that represents the `<fee:chkData>` tag in a check response
+#[derive(Debug, FromXml)]
+#[xml(rename = "chkData", ns(XMLNS))]
+pub struct CheckData {
+    /// The currency of all fees in the response
+    ///
+    /// Required by RFC 8748, but some servers leave it out, see [`CheckData::currency_of()`].
+    pub currency: Option<String>,
+    /// The fees for each of the checked objects
+    #[xml(rename = "cd")]
+    pub list: Vec<CheckedObject>,
+}
+
+impl CheckData {
+    /// The fee data for the object `id`, matched case-insensitively
+    pub fn get(&self, id: &str) -> Option<&CheckedObject> {
+        self.list
+            .iter()
+            .find(|object| object.id.eq_ignore_ascii_case(id))
+    }
+
+    /// The currency of the fees for `object`, one of the objects in this response
+    ///
+    /// This is the currency given for the object, if any, or else the currency for the whole
+    /// response. If the server sent neither, the fees are in the default currency of the
+    /// client's account and `None` is returned.
+    pub fn currency_of<'a>(&'a self, object: &'a CheckedObject) -> Option<&'a str> {
+        object.currency.as_deref().or(self.currency.as_deref())
+    }
+}
+
+/// Type that represents the `<fee:cd>` tag
+#[derive(Debug, FromXml)]
+#[xml(rename = "cd", ns(XMLNS))]
+pub struct CheckedObject {
+    /// Whether the server could determine the fees, `true` if absent
+    #[xml(attribute, rename = "avail")]
+    pub available: Option<bool>,
+    /// The checked object
+    #[xml(rename = "objID")]
+    pub id: String,
+    /// The currency of the fees for this object
+    ///
+    /// Not part of RFC 8748, which only has a currency for the whole response, but sent by
+    /// servers implementing earlier drafts. See [`CheckData::currency_of()`].
+    pub currency: Option<String>,
+    /// The price class, like `premium`
+    pub class: Option<String>,
+    /// The fees for each of the requested commands
+    #[xml(rename = "command")]
+    pub commands: Vec<CommandData>,
+    /// Why fees could not be determined, if they couldn't
+    pub reason: Option<String>,
+}
+
+impl CheckedObject {
+    /// Whether the server could determine the fees, which it could unless it says otherwise
+    pub fn is_available(&self) -> bool {
+        self.available.unwrap_or(true)
+    }
+
+    /// The fee data for the command `name`
+    pub fn command(&self, name: &str) -> Option<&CommandData> {
+        self.commands.iter().find(|command| command.name == name)
+    }
+}
+
+/// Type that represents the `<fee:command>` tag in a check response
+#[derive(Debug, FromXml)]
+#[xml(rename = "command", ns(XMLNS))]
+pub struct CommandData {
+    /// The command the fees apply to
+    #[xml(attribute)]
+    pub name: String,
+    /// The launch phase the fees apply to
+    #[xml(attribute)]
+    pub phase: Option<String>,
+    /// The launch sub-phase the fees apply to
+    #[xml(attribute)]
+    pub subphase: Option<String>,
+    /// Whether these are the standard fees, see [`CommandData::is_standard()`]
+    #[xml(attribute)]
+    pub standard: Option<bool>,
+    /// The period the fees apply to
+    pub period: Option<FeePeriod>,
+    /// The fees charged for the command
+    #[xml(rename = "fee")]
+    pub fees: Vec<Fee>,
+    /// The price class for this command
+    ///
+    /// Not part of RFC 8748, which only has a class per object, but some registries send it
+    /// when the class differs between commands (for example, a premium create with a standard
+    /// renewal).
+    pub class: Option<String>,
+    /// Why the fees for this command could not be determined, if they couldn't
+    pub reason: Option<String>,
+}
+
+impl CommandData {
+    /// Whether the fees are the standard fees for the command, `false` unless the server
+    /// says otherwise
+    pub fn is_standard(&self) -> bool {
+        self.standard.unwrap_or(false)
+    }
+}
+
+/// Type that represents the `<fee:fee>` tag
+#[derive(Debug, FromXml)]
+#[xml(rename = "fee", ns(XMLNS))]
+pub struct Fee {
+    /// A human-readable description of the fee
+    #[xml(attribute)]
+    pub description: Option<String>,
+    /// Whether the fee is refunded when the object is deleted during its grace period
+    #[xml(attribute)]
+    pub refundable: Option<bool>,
+    /// The grace period during which the fee is refundable, as an XML duration
+    #[xml(attribute, rename = "grace-period")]
+    pub grace_period: Option<String>,
+    /// When the fee is applied, `immediate` or `delayed`
+    #[xml(attribute)]
+    pub applied: Option<String>,
+    /// The amount, as a decimal string in the response's currency
+    #[xml(direct)]
+    pub amount: String,
+}
+
+impl Fee {
+    /// The amount in the minor unit of `currency`, see [`minor_units()`]
+    pub fn minor_units(&self, currency: &str) -> Result<i64, Error> {
+        minor_units(&self.amount, currency)
+    }
+}
+
+/// Type that represents the `<fee:creData>` tag in a create response
+#[derive(Debug, FromXml)]
+#[xml(rename = "creData", ns(XMLNS))]
+pub struct CreateData {
+    /// The currency of the fees
+    pub currency: Option<String>,
+    /// The fees charged for the command
+    #[xml(rename = "fee")]
+    pub fees: Vec<Fee>,
+    /// The account balance after the command
+    pub balance: Option<String>,
+    /// The credit limit of the account
+    #[xml(rename = "creditLimit")]
+    pub credit_limit: Option<String>,
+}
+
+impl CreateData {
+    /// The account balance in the minor unit of the currency, see [`minor_units()`]
+    ///
+    /// Fails if the response has a balance, but no currency.
+    pub fn balance_minor_units(&self) -> Result<Option<i64>, Error> {
+        account_minor_units(self.balance.as_deref(), self.currency.as_deref())
+    }
+
+    /// The credit limit in the minor unit of the currency, see [`minor_units()`]
+    ///
+    /// Fails if the response has a credit limit, but no currency.
+    pub fn credit_limit_minor_units(&self) -> Result<Option<i64>, Error> {
+        account_minor_units(self.credit_limit.as_deref(), self.currency.as_deref())
+    }
+}
+
+/// Type that represents the `<fee:updData>` tag in an update response
+#[derive(Debug, FromXml)]
+#[xml(rename = "updData", ns(XMLNS))]
+pub struct UpdateData {
+    /// The currency of the fees
+    pub currency: Option<String>,
+    /// The fees charged for the command
+    #[xml(rename = "fee")]
+    pub fees: Vec<Fee>,
+    /// The account balance after the command
+    pub balance: Option<String>,
+    /// The credit limit of the account
+    #[xml(rename = "creditLimit")]
+    pub credit_limit: Option<String>,
+}
+
+impl UpdateData {
+    /// The account balance in the minor unit of the currency, see [`minor_units()`]
+    ///
+    /// Fails if the response has a balance, but no currency.
+    pub fn balance_minor_units(&self) -> Result<Option<i64>, Error> {
+        account_minor_units(self.balance.as_deref(), self.currency.as_deref())
+    }
+
+    /// The credit limit in the minor unit of the currency, see [`minor_units()`]
+    ///
+    /// Fails if the response has a credit limit, but no currency.
+    pub fn credit_limit_minor_units(&self) -> Result<Option<i64>, Error> {
+        account_minor_units(self.credit_limit.as_deref(), self.currency.as_deref())
+    }
+}
+
+/// Type that represents the `<fee:trnData>` tag in a transfer response
+///
+/// Also sent by some registries in the poll message for a completed transfer, see
+/// [`PollExtension`](crate::poll::PollExtension).
+#[derive(Debug, FromXml)]
+#[xml(rename = "trnData", ns(XMLNS))]
+pub struct TransferData {
+    /// The currency of the fees
+    pub currency: Option<String>,
+    /// The period the domain was extended by with the transfer
+    pub period: Option<FeePeriod>,
+    /// The fees charged for the transfer
+    #[xml(rename = "fee")]
+    pub fees: Vec<Fee>,
+    /// The account balance after the transfer
+    pub balance: Option<String>,
+    /// The credit limit of the account
+    #[xml(rename = "creditLimit")]
+    pub credit_limit: Option<String>,
+}
+
+impl TransferData {
+    /// The account balance in the minor unit of the currency, see [`minor_units()`]
+    ///
+    /// Fails if the response has a balance, but no currency.
+    pub fn balance_minor_units(&self) -> Result<Option<i64>, Error> {
+        account_minor_units(self.balance.as_deref(), self.currency.as_deref())
+    }
+
+    /// The credit limit in the minor unit of the currency, see [`minor_units()`]
+    ///
+    /// Fails if the response has a credit limit, but no currency.
+    pub fn credit_limit_minor_units(&self) -> Result<Option<i64>, Error> {
+        account_minor_units(self.credit_limit.as_deref(), self.currency.as_deref())
+    }
+}
+
+// Amounts
+
+/// Convert a decimal `amount` to an integer in the minor unit of `currency`
+///
+/// Fee amounts are decimal strings such as `-5.00`; billing systems usually want them in the
+/// currency's minor unit instead (cents, for USD or EUR). The number of decimals in a minor
+/// unit is taken from ISO 4217 for the currencies that differ from the usual 2, such as JPY
+/// (0) and KWD (3). Fails if `amount` is not a decimal number, or has non-zero digits beyond
+/// the minor unit.
+pub fn minor_units(amount: &str, currency: &str) -> Result<i64, Error> {
+    let invalid = || Error::Other(format!("invalid {currency} amount {amount:?}").into());
+    let trimmed = amount.trim();
+    let (negative, digits) = match trimmed.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, trimmed.strip_prefix('+').unwrap_or(trimmed)),
+    };
+
+    let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    if (whole.is_empty() && fraction.is_empty())
+        || !whole
+            .bytes()
+            .chain(fraction.bytes())
+            .all(|b| b.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+
+    let exponent = minor_unit_exponent(currency);
+    let (fraction, rest) = fraction.split_at(fraction.len().min(exponent));
+    if rest.bytes().any(|b| b != b'0') {
+        return Err(Error::Other(
+            format!("{currency} amount {amount:?} has more than {exponent} decimals").into(),
+        ));
+    }
+
+    let mut value = 0i64;
+    let padding = exponent - fraction.len();
+    for digit in whole.bytes().chain(fraction.bytes()) {
+        value = value
+            .checked_mul(10)
+            .and_then(|value| value.checked_add(i64::from(digit - b'0')))
+            .ok_or_else(invalid)?;
+    }
+    for _ in 0..padding {
+        value = value.checked_mul(10).ok_or_else(invalid)?;
+    }
+
+    Ok(if negative { -value } else { value })
+}
+
+fn account_minor_units(amount: Option<&str>, currency: Option<&str>) -> Result<Option<i64>, Error> {
+    let Some(amount) = amount else {
+        return Ok(None);
+    };
+
+    match currency {
+        Some(currency) => minor_units(amount, currency).map(Some),
+        None => Err(Error::Other(
+            format!("no currency for amount {amount:?}").into(),
+        )),
+    }
+}
+
+/// The number of decimals in the minor unit of `currency`, per ISO 4217
+fn minor_unit_exponent(currency: &str) -> usize {
+    match currency.to_ascii_uppercase().as_str() {
+        "BIF" | "CLP" | "DJF" | "GNF" | "ISK" | "JPY" | "KMF" | "KRW" | "PYG" | "RWF" | "UGX"
+        | "UYI" | "VND" | "VUV" | "XAF" | "XOF" | "XPF" => 0,
+        "BHD" | "IQD" | "JOD" | "KWD" | "LYD" | "OMR" | "TND" => 3,
+        "CLF" | "UYW" => 4,
+        _ => 2,
+    }
+}
+
+// Combined
+
+/// Pricing hints for one domain from a combined domain and fee check response
+#[derive(Debug)]
+pub struct PriceHint<'a> {
+    /// The domain check result
+    pub domain: &'a CheckedDomain,
+    /// The fee check result for the same domain, if the server returned one
+    pub fee: Option<&'a CheckedObject>,
+}
+
+impl PriceHint<'_> {
+    /// The price class from `<fee:class>`
+    ///
+    /// If the object has no class, falls back to the class of its first command that has one.
+    pub fn class(&self) -> Option<&str> {
+        let fee = self.fee?;
+        fee.class.as_deref().or_else(|| {
+            fee.commands
+                .iter()
+                .find_map(|command| command.class.as_deref())
+        })
+    }
+
+    /// Whether the domain is likely a premium domain
+    ///
+    /// Uses the `<fee:class>` if there is one, and otherwise falls back to looking for
+    /// "premium" in the domain check's reason, which some registries use instead.
+    pub fn is_premium(&self) -> bool {
+        if let Some(class) = self.class() {
+            return contains_premium(class);
+        }
+
+        self.domain
+            .reason
+            .as_ref()
+            .is_some_and(|reason| contains_premium(&reason.value))
+    }
+}
+
+fn contains_premium(s: &str) -> bool {
+    s.to_ascii_lowercase().contains("premium")
+}
+
+impl Response<crate::domain::check::CheckData, CheckData> {
+    /// Pricing hints for each checked domain, in the order of the domain check results
+    pub fn price_hints(&self) -> Vec<PriceHint<'_>> {
+        let Some(domains) = self.res_data() else {
+            return Vec::new();
+        };
+
+        let fees = self.extension();
+        domains
+            .list
+            .iter()
+            .map(|domain| PriceHint {
+                domain,
+                fee: fees.and_then(|fees| fees.get(&domain.name.value)),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{minor_units, Check, Create};
+    use crate::contact::{Address, ContactCheck, ContactCreate, InfoType, PostalInfo};
+    use crate::domain::check::DomainCheck;
+    use crate::domain::{DomainCreate, Period};
+    use crate::extensions::secdns::{self, Algorithm, DigestAlgorithm, DsDataType};
+    use crate::request::Extensions;
+    use crate::tests::{assert_serialized, response_from_file_with_ext};
+
+    #[test]
+    fn command() {
+        let fee = Check::new()
+            .currency("USD")
+            .command("create", Some(Period::years(2).unwrap()))
+            .command("renew", None);
+
+        let object = DomainCheck {
+            domains: &["example.com", "example.net", "example.xyz"],
+        };
+
+        assert_serialized("request/extensions/fee_check.xml", (&object, &fee));
+    }
+
+    #[test]
+    fn response() {
+        let object =
+            response_from_file_with_ext::<DomainCheck, Check>("response/extensions/fee_check.xml");
+
+        let ext = object.extension().unwrap();
+        assert_eq!(ext.currency.as_deref(), Some("USD"));
+        let cd = ext.get("EXAMPLE.com").unwrap();
+        assert_eq!(ext.currency_of(cd), Some("USD"));
+        assert_eq!(cd.class.as_deref(), Some("Premium"));
+        assert_eq!(cd.commands[0].name, "create");
+        assert_eq!(cd.commands[0].period.as_ref().unwrap().length, 2);
+        assert_eq!(cd.commands[0].fees[0].amount, "200.00");
+        assert_eq!(cd.commands[0].fees[0].refundable, Some(true));
+        assert_eq!(cd.commands[0].fees[0].grace_period.as_deref(), Some("P5D"));
+        assert!(cd.is_available());
+        assert!(!cd.commands[0].is_standard());
+        let renew = cd.command("renew").unwrap();
+        assert!(renew.fees.is_empty());
+        assert_eq!(renew.class.as_deref(), Some("premium-renewal"));
+        assert_eq!(renew.reason.as_deref(), Some("Renewal price on request"));
+        assert!(ext.list[1].command("create").unwrap().is_standard());
+        assert_eq!(ext.list[2].available, Some(false));
+        assert!(!ext.list[2].is_available());
+        assert_eq!(ext.list[2].reason.as_deref(), Some("Not supported"));
+
+        let hints = object.price_hints();
+        assert_eq!(hints.len(), 3);
+        assert_eq!(hints[0].class(), Some("Premium"));
+        assert!(hints[0].is_premium());
+        assert_eq!(hints[1].class(), Some("standard"));
+        assert!(!hints[1].is_premium());
+        // No fee class, but the domain check reason says premium
+        assert_eq!(hints[2].class(), None);
+        assert!(hints[2].is_premium());
+    }
+
+    #[test]
+    fn response_currency() {
+        let object = response_from_file_with_ext::<DomainCheck, Check>(
+            "response/extensions/fee_check_currency.xml",
+        );
+
+        let ext = object.extension().unwrap();
+        assert_eq!(ext.currency, None);
+        let cd = ext.get("example.com").unwrap();
+        assert_eq!(ext.currency_of(cd), Some("EUR"));
+        let cd = ext.get("example.net").unwrap();
+        assert_eq!(ext.currency_of(cd), None);
+        assert_eq!(cd.commands[0].fees[0].amount, "10.00");
+    }
+
+    #[test]
+    fn contact_check() {
+        let fee = Check::new().command("create", None);
+        let object = ContactCheck {
+            contact_ids: &["eppdev-contact-1", "eppdev-contact-2"],
+        };
+
+        assert_serialized("request/extensions/fee_contact_check.xml", (&object, &fee));
+    }
+
+    #[test]
+    fn contact_create() {
+        let address = Address::new(&[], "Paris", None, None, "FR".parse().unwrap());
+        let postal_info = PostalInfo::new(InfoType::International, "John Doe", None, address);
+        let object = ContactCreate::new(
+            "eppdev-contact-3",
+            "contact@eppdev.net",
+            postal_info,
+            None,
+            "eppdev-387323",
+        );
+        let fee = Create::new("5.00").currency("EUR");
+
+        assert_serialized("request/extensions/fee_contact_create.xml", (&object, &fee));
+    }
+
+    #[test]
+    fn domain_create_secdns() {
+        let object = DomainCreate::new(
+            "eppdev-1.com",
+            Period::years(1).unwrap(),
+            None,
+            None,
+            "epP4uthd#v",
+            None,
+        );
+        let ds = [DsDataType::new(
+            12345,
+            Algorithm::Dsa,
+            DigestAlgorithm::Sha1,
+            "49FD46E6C4B45C55D4AC",
+            None,
+        )];
+        let extensions = Extensions(
+            Create::new("10.00").currency("USD"),
+            secdns::CreateData::from(&ds[..]),
+        );
+
+        assert_serialized(
+            "request/extensions/fee_secdns_domain_create.xml",
+            (&object, &extensions),
+        );
+    }
+
+    #[test]
+    fn contact_create_response() {
+        let object = response_from_file_with_ext::<ContactCreate, Create>(
+            "response/extensions/fee_contact_create.xml",
+        );
+
+        assert_eq!(object.res_data().unwrap().id, "eppdev-contact-4");
+        let ext = object.extension().unwrap();
+        assert_eq!(ext.currency.as_deref(), Some("EUR"));
+        assert_eq!(ext.fees[0].amount, "5.00");
+        assert_eq!(ext.fees[0].description.as_deref(), Some("Contact creation"));
+        assert_eq!(ext.balance.as_deref(), Some("-5.00"));
+        assert_eq!(ext.credit_limit.as_deref(), Some("1000.00"));
+
+        assert_eq!(ext.fees[0].minor_units("EUR").unwrap(), 500);
+        assert_eq!(ext.balance_minor_units().unwrap(), Some(-500));
+        assert_eq!(ext.credit_limit_minor_units().unwrap(), Some(100000));
+    }
+
+    #[test]
+    fn amounts() {
+        assert_eq!(minor_units("5.00", "USD").unwrap(), 500);
+        assert_eq!(minor_units("12.3", "eur").unwrap(), 1230);
+        assert_eq!(minor_units("-0.01", "EUR").unwrap(), -1);
+        assert_eq!(minor_units("+7", "EUR").unwrap(), 700);
+        assert_eq!(minor_units(".5", "EUR").unwrap(), 50);
+        assert_eq!(minor_units("1500", "JPY").unwrap(), 1500);
+        assert_eq!(minor_units("1500.000", "JPY").unwrap(), 1500);
+        assert_eq!(minor_units("1.5", "KWD").unwrap(), 1500);
+        assert_eq!(minor_units("10.0000", "USD").unwrap(), 1000);
+
+        assert_eq!(
+            minor_units("1.005", "USD").unwrap_err().to_string(),
+            "error: USD amount \"1.005\" has more than 2 decimals"
+        );
+        for invalid in ["", "-", ".", "1,00", "1.0.0", "1e3", "99999999999999999999"] {
+            assert!(minor_units(invalid, "USD").is_err(), "{invalid:?}");
+        }
+    }
+}
@@ -0,0 +1,135 @@
+//! Types for the ISPAPI key-value extension
+//!
+//! HEXONET's ISPAPI registry system passes registry-specific options, such as the acceptance
+//! of trustee terms or additional contact data, as a list of key-value pairs in its
+//! `http://schema.ispapi.net/epp/xml/keyvalue-1.0` extension. Responses use the same
+//! structure to return additional data.
+
+use std::borrow::Cow;
+
+use instant_xml::{FromXml, ToXml};
+
+use crate::{
+    contact::{
+        check::ContactCheck, create::ContactCreate, delete::ContactDelete, info::ContactInfo,
+        update::ContactUpdate,
+    },
+    domain::{
+        check::DomainCheck, create::DomainCreate, delete::DomainDelete, info::DomainInfo,
+        renew::DomainRenew, transfer::DomainTransfer, update::DomainUpdate,
+    },
+    host::{
+        check::HostCheck, create::HostCreate, delete::HostDelete, info::HostInfo,
+        update::HostUpdate,
+    },
+    request::{Extension, Transaction},
+};
+
+pub const XMLNS: &str = "http://schema.ispapi.net/epp/xml/keyvalue-1.0";
+
+// Contact
+
+impl Transaction<KeyValue<'_>> for ContactCheck<'_> {}
+impl Transaction<KeyValue<'_>> for ContactCreate<'_> {}
+impl Transaction<KeyValue<'_>> for ContactDelete<'_> {}
+impl Transaction<KeyValue<'_>> for ContactInfo<'_> {}
+impl Transaction<KeyValue<'_>> for ContactUpdate<'_> {}
+
+// Domain
+
+impl Transaction<KeyValue<'_>> for DomainCheck<'_> {}
+impl Transaction<KeyValue<'_>> for DomainCreate<'_> {}
+impl Transaction<KeyValue<'_>> for DomainDelete<'_> {}
+impl Transaction<KeyValue<'_>> for DomainInfo<'_> {}
+impl Transaction<KeyValue<'_>> for DomainRenew<'_> {}
+impl Transaction<KeyValue<'_>> for DomainTransfer<'_> {}
+impl Transaction<KeyValue<'_>> for DomainUpdate<'_> {}
+
+// Host
+
+impl Transaction<KeyValue<'_>> for HostCheck<'_> {}
+impl Transaction<KeyValue<'_>> for HostCreate<'_> {}
+impl Transaction<KeyValue<'_>> for HostDelete<'_> {}
+impl Transaction<KeyValue<'_>> for HostInfo<'_> {}
+impl Transaction<KeyValue<'_>> for HostUpdate<'_> {}
+
+impl<'a> KeyValue<'a> {
+    /// Create an empty key-value extension, add pairs with `pair()`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a pair with `key` and `value`
+    pub fn pair(mut self, key: &'a str, value: &'a str) -> Self {
+        self.pairs.push(Pair {
+            key: key.into(),
+            value: value.into(),
+        });
+        self
+    }
+
+    /// The value of the first pair with `key`
+    ///
+    /// Keys compare case-insensitively, as ISPAPI treats them that way.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.pairs
+            .iter()
+            .find(|pair| pair.key.eq_ignore_ascii_case(key))
+            .map(|pair| pair.value.as_ref())
+    }
+}
+
+impl Extension for KeyValue<'_> {
+    type Response = KeyValue<'static>;
+}
+
+/// Type for EPP XML `<keyvalue:extension>` extension
+#[derive(Debug, Default, Eq, FromXml, PartialEq, ToXml)]
+#[xml(rename = "extension", ns(XMLNS))]
+pub struct KeyValue<'a> {
+    /// The key-value pairs, in document order
+    #[xml(rename = "kv")]
+    pub pairs: Vec<Pair<'a>>,
+}
+
+/// Type for the `<keyvalue:kv>` element
+#[derive(Debug, Eq, FromXml, PartialEq, ToXml)]
+#[xml(rename = "kv", ns(XMLNS))]
+pub struct Pair<'a> {
+    #[xml(attribute)]
+    pub key: Cow<'a, str>,
+    #[xml(attribute)]
+    pub value: Cow<'a, str>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::KeyValue;
+    use crate::domain::check::DomainCheck;
+    use crate::tests::{assert_serialized, response_from_file_with_ext};
+
+    #[test]
+    fn command() {
+        let keyvalue_ext = KeyValue::new()
+            .pair("X-ACCEPT-WHOISTRUSTEE-TAC", "1")
+            .pair("X-WHOIS-BANNER0", "Example registrar");
+
+        let object = DomainCheck {
+            domains: &["example1.com", "example2.com"],
+        };
+
+        assert_serialized("request/extensions/keyvalue.xml", (&object, &keyvalue_ext));
+    }
+
+    #[test]
+    fn response() {
+        let object = response_from_file_with_ext::<DomainCheck, KeyValue>(
+            "response/extensions/keyvalue.xml",
+        );
+        let ext = object.extension().unwrap();
+        assert_eq!(ext.pairs.len(), 2);
+        assert_eq!(ext.get("price"), Some("10.99"));
+        assert_eq!(ext.get("CURRENCY"), Some("USD"));
+        assert_eq!(ext.get("X-MISSING"), None);
+    }
+}
@@ -0,0 +1,200 @@
+//! Launch Phase Mapping for the Extensible Provisioning Protocol (EPP)
+//!
+//! As described in [RFC 8334](https://www.rfc-editor.org/rfc/rfc8334.html). Of the launch
+//! extension, only the claims check form of the domain `<check>` command is supported, which
+//! finds out whether domains match a trademark during a TLD launch.
+
+use std::borrow::Cow;
+
+use instant_xml::{FromXml, ToXml};
+
+use crate::domain::check::DomainCheck;
+use crate::request::{Extension, Transaction};
+
+pub const XMLNS: &str = "urn:ietf:params:xml:ns:launch-1.0";
+
+impl Transaction<ClaimsCheck<'_>> for DomainCheck<'_> {}
+
+impl Extension for ClaimsCheck<'_> {
+    type Response = CheckData;
+}
+
+// Request
+
+/// Type for the `<check>` extension with `type="claims"`
+///
+/// Send it with a [`DomainCheck`] to get the claim keys for the domains instead of their
+/// availability.
+#[derive(Debug, ToXml)]
+#[xml(rename = "check", ns(XMLNS))]
+pub struct ClaimsCheck<'a> {
+    #[xml(attribute, rename = "type")]
+    check_type: &'static str,
+    phase: Phase<'a>,
+}
+
+impl<'a> ClaimsCheck<'a> {
+    /// Check for claims in the launch `phase`
+    pub fn new(phase: Phase<'a>) -> Self {
+        Self {
+            check_type: "claims",
+            phase,
+        }
+    }
+}
+
+/// Type for the `<phase>` tag
+#[derive(Clone, Debug, Eq, FromXml, PartialEq, ToXml)]
+#[xml(rename = "phase", ns(XMLNS))]
+pub struct Phase<'a> {
+    /// The name of a custom phase or sub-phase
+    #[xml(attribute)]
+    pub name: Option<Cow<'a, str>>,
+    /// The phase
+    #[xml(direct)]
+    pub kind: PhaseType,
+}
+
+impl<'a> Phase<'a> {
+    /// The sub-phase `name` of `kind`, or a custom phase if `kind` is [`PhaseType::Custom`]
+    pub fn named(kind: PhaseType, name: &'a str) -> Self {
+        Self {
+            name: Some(name.into()),
+            kind,
+        }
+    }
+}
+
+impl From<PhaseType> for Phase<'_> {
+    fn from(kind: PhaseType) -> Self {
+        Self { name: None, kind }
+    }
+}
+
+/// The launch phases defined in RFC 8334
+#[derive(Clone, Copy, Debug, Eq, FromXml, PartialEq, ToXml)]
+#[xml(scalar, rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum PhaseType {
+    Sunrise,
+    Landrush,
+    Claims,
+    Open,
+    Custom,
+}
+
+// Response
+
+/// Type that represents the `<chkData>` tag for a claims check response
+#[derive(Debug, FromXml)]
+#[xml(rename = "chkData", ns(XMLNS))]
+pub struct CheckData {
+    /// The launch phase of the check
+    pub phase: Phase<'static>,
+    /// Data under the `<cd>` tags
+    pub list: Vec<ClaimsData>,
+}
+
+impl CheckData {
+    /// The claims for each domain, in the order of the response
+    pub fn claims(&self) -> Vec<DomainClaims> {
+        self.list
+            .iter()
+            .map(|cd| DomainClaims {
+                name: cd.name.value.clone(),
+                exists: cd.name.exists,
+                claim_keys: cd.claim_keys.clone(),
+            })
+            .collect()
+    }
+}
+
+/// Type that represents the `<cd>` tag for a claims check response
+#[derive(Debug, FromXml)]
+#[xml(rename = "cd", ns(XMLNS))]
+pub struct ClaimsData {
+    /// Data under the `<name>` tag
+    pub name: Name,
+    /// The claim keys to look up the claims notices with, if the domain matches a trademark
+    pub claim_keys: Vec<ClaimKey>,
+}
+
+/// Type that represents the `<name>` tag for a claims check response
+#[derive(Debug, FromXml)]
+#[xml(rename = "name", ns(XMLNS))]
+pub struct Name {
+    /// Whether the domain matches a trademark
+    #[xml(attribute)]
+    pub exists: bool,
+    #[xml(direct)]
+    pub value: String,
+}
+
+/// Type that represents the `<claimKey>` tag
+#[derive(Clone, Debug, Eq, FromXml, PartialEq)]
+#[xml(rename = "claimKey", ns(XMLNS))]
+pub struct ClaimKey {
+    /// The validator of the key, the Trademark Clearinghouse if absent
+    #[xml(attribute, rename = "validatorID")]
+    pub validator_id: Option<String>,
+    /// The key to fetch the claims notice with
+    #[xml(direct)]
+    pub key: String,
+}
+
+/// The claims for a single domain, see [`CheckData::claims()`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DomainClaims {
+    /// The domain name
+    pub name: String,
+    /// Whether the domain matches a trademark, in which case the registrant must be shown the
+    /// claims notice before it is registered
+    pub exists: bool,
+    /// The claim keys to look up the claims notices with
+    pub claim_keys: Vec<ClaimKey>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ClaimsCheck, Phase, PhaseType};
+    use crate::domain::check::DomainCheck;
+    use crate::tests::{assert_serialized, response_from_file_with_ext};
+
+    #[test]
+    fn command() {
+        let object = DomainCheck {
+            domains: &["example1.tld", "example2.tld"],
+        };
+
+        assert_serialized(
+            "request/extensions/launch_claims_check.xml",
+            (&object, &ClaimsCheck::new(PhaseType::Claims.into())),
+        );
+    }
+
+    #[test]
+    fn response() {
+        let object = response_from_file_with_ext::<DomainCheck, ClaimsCheck>(
+            "response/extensions/launch_claims_check.xml",
+        );
+        assert!(object.res_data().is_none());
+        let data = object.extension().unwrap();
+        assert_eq!(data.phase, Phase::from(PhaseType::Claims));
+
+        let claims = data.claims();
+        assert_eq!(claims.len(), 2);
+        assert_eq!(claims[0].name, "example1.tld");
+        assert!(!claims[0].exists);
+        assert!(claims[0].claim_keys.is_empty());
+        assert!(claims[1].exists);
+        assert_eq!(claims[1].claim_keys.len(), 2);
+        assert_eq!(
+            claims[1].claim_keys[0].validator_id.as_deref(),
+            Some("tmch")
+        );
+        assert_eq!(
+            claims[1].claim_keys[0].key,
+            "2013041500/2/6/9/rJ1NrDO92vDsAzf7EQzgjX4R0000000001"
+        );
+    }
+}
@@ -24,12 +24,13 @@ pub struct Threshold {
 
 #[derive(Clone, Copy, Debug, FromXml, PartialEq)]
 #[xml(scalar, rename_all = "SCREAMING_SNAKE_CASE")]
+#[non_exhaustive]
 pub enum ThresholdType {
     Fixed,
     Percent,
 }
 
-const XMLNS: &str = "http://www.verisign.com/epp/lowbalance-poll-1.0";
+pub(crate) const XMLNS: &str = "http://www.verisign.com/epp/lowbalance-poll-1.0";
 
 #[cfg(test)]
 mod tests {
@@ -0,0 +1,144 @@
+//! Types for EPP extensions
+//!
+//! Use [`registry()`] to find out which extensions this crate has types for, for example to
+//! compose the `<extURI>` list of a `<login>` from what the server advertises.
+
+pub mod addl_email;
+pub mod balance;
+pub mod change_poll;
+pub mod consolidate;
+pub mod fee;
+pub mod frnic;
+pub mod keyvalue;
+pub mod launch;
+pub mod low_balance;
+pub mod namestore;
+pub mod reseller;
+pub mod rgp;
+pub mod secdns;
+
+/// The extensions this crate has typed support for
+pub fn registry() -> &'static [SupportedExtension] {
+    &SUPPORTED
+}
+
+/// Whether the extension with namespace `xmlns` has typed support in this crate
+pub fn is_supported(xmlns: &str) -> bool {
+    SUPPORTED.iter().any(|ext| ext.xmlns == xmlns)
+}
+
+/// An extension in the [`registry()`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SupportedExtension {
+    /// The name of the extension, as used in its namespace
+    pub name: &'static str,
+    /// The version of the extension
+    pub version: &'static str,
+    /// The namespace URI, as listed in the server's greeting and the login's `<extURI>`
+    pub xmlns: &'static str,
+}
+
+const SUPPORTED: [SupportedExtension; 14] = [
+    SupportedExtension {
+        name: "addlEmail",
+        version: "1.0",
+        xmlns: addl_email::XMLNS,
+    },
+    SupportedExtension {
+        name: "changePoll",
+        version: "1.0",
+        xmlns: change_poll::XMLNS,
+    },
+    SupportedExtension {
+        name: "sync",
+        version: "1.0",
+        xmlns: consolidate::XMLNS,
+    },
+    SupportedExtension {
+        name: "fee",
+        version: "1.0",
+        xmlns: fee::XMLNS,
+    },
+    SupportedExtension {
+        name: "fee",
+        version: "0.5",
+        xmlns: fee::info::XMLNS,
+    },
+    SupportedExtension {
+        name: "frnic",
+        version: "2.0",
+        xmlns: frnic::XMLNS,
+    },
+    SupportedExtension {
+        name: "keyvalue",
+        version: "1.0",
+        xmlns: keyvalue::XMLNS,
+    },
+    SupportedExtension {
+        name: "launch",
+        version: "1.0",
+        xmlns: launch::XMLNS,
+    },
+    SupportedExtension {
+        name: "lowbalance-poll",
+        version: "1.0",
+        xmlns: low_balance::XMLNS,
+    },
+    SupportedExtension {
+        name: "namestoreExt",
+        version: "1.1",
+        xmlns: namestore::XMLNS,
+    },
+    SupportedExtension {
+        name: "reseller-ext",
+        version: "1.0",
+        xmlns: reseller::XMLNS,
+    },
+    SupportedExtension {
+        name: "rgp",
+        version: "1.0",
+        xmlns: rgp::XMLNS,
+    },
+    SupportedExtension {
+        name: "rgp-poll",
+        version: "1.0",
+        xmlns: rgp::poll::XMLNS,
+    },
+    SupportedExtension {
+        name: "secDNS",
+        version: "1.1",
+        xmlns: secdns::XMLNS,
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::{is_supported, registry};
+    use crate::hello::Greeting;
+    use crate::tests::get_xml;
+    use crate::xml;
+
+    #[test]
+    fn supported() {
+        for ext in registry() {
+            assert!(ext.xmlns.contains(ext.name), "{ext:?}");
+            assert!(ext.xmlns.ends_with(ext.version), "{ext:?}");
+        }
+
+        let greeting =
+            xml::deserialize::<Greeting>(&get_xml("response/greeting.xml").unwrap()).unwrap();
+        let ext_uris = greeting.svc_menu.services.svc_ext.unwrap().ext_uris;
+        let supported = ext_uris
+            .iter()
+            .filter(|uri| is_supported(uri))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            supported,
+            [
+                "urn:ietf:params:xml:ns:secDNS-1.1",
+                "urn:ietf:params:xml:ns:rgp-1.0",
+                "http://schema.ispapi.net/epp/xml/keyvalue-1.0",
+            ]
+        );
+    }
+}
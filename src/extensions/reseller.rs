@@ -0,0 +1,167 @@
+//! Types for the EPP reseller extension
+//!
+//! As described in the [Reseller Extension](https://datatracker.ietf.org/doc/draft-ietf-regext-reseller-ext/)
+//! for EPP. It attributes domains to one of the sponsoring registrar's resellers.
+
+use std::borrow::Cow;
+
+use instant_xml::{FromXml, Id, Serializer, ToXml};
+
+use crate::common::NoExtension;
+use crate::domain::{create::DomainCreate, info::DomainInfo, update::DomainUpdate};
+use crate::request::{Extension, Transaction};
+
+pub const XMLNS: &str = "urn:ietf:params:xml:ns:epp:reseller-ext-1.0";
+
+impl<'a> Transaction<Create<'a>> for DomainCreate<'a> {}
+
+impl Extension for Create<'_> {
+    type Response = NoExtension;
+}
+
+impl<'a> Transaction<Update<'a>> for DomainUpdate<'a> {}
+
+impl Extension for Update<'_> {
+    type Response = NoExtension;
+}
+
+impl Transaction<Info> for DomainInfo<'_> {}
+
+impl Extension for Info {
+    type Response = InfoData;
+}
+
+// Request
+
+/// Type for the `<create>` tag, setting the reseller of a new domain
+#[derive(Debug, ToXml)]
+#[xml(rename = "create", ns(XMLNS))]
+pub struct Create<'a> {
+    /// The reseller's identifier
+    pub id: Cow<'a, str>,
+}
+
+impl<'a> Create<'a> {
+    pub fn new(id: impl Into<Cow<'a, str>>) -> Self {
+        Self { id: id.into() }
+    }
+}
+
+/// Type for the `<update>` tag, changing or removing the reseller of a domain
+#[derive(Debug, ToXml)]
+#[xml(rename = "update", ns(XMLNS))]
+pub struct Update<'a> {
+    remove: Option<Remove<'a>>,
+    change: Option<Change<'a>>,
+}
+
+impl<'a> Update<'a> {
+    /// Attribute the domain to the reseller with the given `id`
+    pub fn change(id: impl Into<Cow<'a, str>>) -> Self {
+        Self {
+            remove: None,
+            change: Some(Change { id: id.into() }),
+        }
+    }
+
+    /// Remove the attribution of the domain to the reseller with the given `id`
+    pub fn remove(id: impl Into<Cow<'a, str>>) -> Self {
+        Self {
+            remove: Some(Remove { id: id.into() }),
+            change: None,
+        }
+    }
+}
+
+#[derive(Debug, ToXml)]
+#[xml(rename = "rem", ns(XMLNS))]
+struct Remove<'a> {
+    id: Cow<'a, str>,
+}
+
+#[derive(Debug, ToXml)]
+#[xml(rename = "chg", ns(XMLNS))]
+struct Change<'a> {
+    id: Cow<'a, str>,
+}
+
+/// Marker for reading reseller data from a domain info response
+///
+/// The info command does not take any reseller data, so this type has no values and can't be
+/// sent to the server. Pass it as `None` through
+/// [`RequestData::new()`](crate::request::RequestData::new) to parse the response's reseller
+/// data as [`InfoData`].
+#[derive(Debug)]
+pub enum Info {}
+
+impl ToXml for Info {
+    fn serialize<W: std::fmt::Write + ?Sized>(
+        &self,
+        _: Option<Id<'_>>,
+        _: &mut Serializer<'_, W>,
+    ) -> Result<(), instant_xml::Error> {
+        match *self {}
+    }
+}
+
+// Response
+
+/// Type for the `<infData>` tag in domain info responses
+#[derive(Debug, FromXml)]
+#[xml(rename = "infData", ns(XMLNS))]
+pub struct InfoData {
+    /// The reseller's identifier
+    pub id: String,
+    /// The reseller's name
+    pub name: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Create, Info, Update};
+    use crate::domain::{DomainCreate, DomainInfo, DomainUpdate, Period};
+    use crate::tests::{assert_serialized, response_from_file_with_ext};
+
+    #[test]
+    fn create() {
+        let object = DomainCreate::new(
+            "eppdev.com",
+            Period::years(1).unwrap(),
+            None,
+            None,
+            "epP4uthd#v",
+            None,
+        );
+        let extension = Create::new("myreseller");
+        assert_serialized(
+            "request/extensions/reseller_create.xml",
+            (&object, &extension),
+        );
+    }
+
+    #[test]
+    fn update() {
+        let object = DomainUpdate::new("eppdev.com");
+        let extension = Update::change("myreseller");
+        assert_serialized(
+            "request/extensions/reseller_update.xml",
+            (&object, &extension),
+        );
+
+        let extension = Update::remove("myreseller");
+        assert_serialized(
+            "request/extensions/reseller_update_rem.xml",
+            (&object, &extension),
+        );
+    }
+
+    #[test]
+    fn info_response() {
+        let object = response_from_file_with_ext::<DomainInfo, Info>(
+            "response/extensions/reseller_info.xml",
+        );
+        let ext = object.extension().unwrap();
+        assert_eq!(ext.id, "myreseller");
+        assert_eq!(ext.name.as_deref(), Some("Example Reseller Inc."));
+    }
+}
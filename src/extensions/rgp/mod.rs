@@ -9,6 +9,7 @@ pub mod report;
 pub mod request;
 
 #[derive(Debug, PartialEq)]
+#[non_exhaustive]
 pub enum RgpStatus {
     AddPeriod,
     AutoRenewPeriod,
@@ -17,6 +18,8 @@ pub enum RgpStatus {
     RedemptionPeriod,
     PendingRestore,
     PendingDelete,
+    /// A status not defined in RFC 3915, with its value as sent by the server
+    Other(String),
 }
 
 impl<'xml> FromXml<'xml> for RgpStatus {
@@ -65,11 +68,7 @@ impl<'xml> FromXml<'xml> for RgpStatus {
             "redemptionPeriod" => Self::RedemptionPeriod,
             "pendingRestore" => Self::PendingRestore,
             "pendingDelete" => Self::PendingDelete,
-            val => {
-                return Err(Error::UnexpectedValue(format!(
-                    "invalid RgpStatus '{val:?}'"
-                )))
-            }
+            val => Self::Other(val.to_owned()),
         });
 
         deserializer.ignore()?;
@@ -4,6 +4,7 @@ use chrono::{DateTime, Utc};
 use instant_xml::FromXml;
 
 use super::RgpStatus;
+use crate::common::deserialize_date;
 
 /// RGP request status
 #[derive(Debug, FromXml)]
@@ -11,11 +12,13 @@ use super::RgpStatus;
 pub struct RgpPollData {
     pub name: String,
     pub rgp_status: RgpStatus,
+    #[xml(deserialize_with = "deserialize_date")]
     pub req_date: DateTime<Utc>,
+    #[xml(deserialize_with = "deserialize_date")]
     pub report_due_date: DateTime<Utc>,
 }
 
-pub(super) const XMLNS: &str = "http://www.verisign.com/epp/rgp-poll-1.0";
+pub(crate) const XMLNS: &str = "http://www.verisign.com/epp/rgp-poll-1.0";
 
 #[cfg(test)]
 mod tests {
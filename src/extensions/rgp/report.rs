@@ -1,14 +1,33 @@
 //! Types for EPP RGP restore report
+//!
+//! A restore report is sent after a restore request, during the pendingRestore period. Besides
+//! the registration data before the deletion and after the restore, ICANN requires gTLD
+//! registrars to include the two [`ICANN_STATEMENTS`] in the report. Reports are checked
+//! before sending: the deletion must precede the restore request, which can't be in the
+//! future. A [`ReportPolicy`] adds the registry's deadlines.
 
-use chrono::{DateTime, SecondsFormat, Utc};
-use instant_xml::ToXml;
+use chrono::{DateTime, SecondsFormat, TimeDelta, Utc};
+use instant_xml::ser::Context;
+use instant_xml::{Serializer, ToXml};
 
 use crate::common::NoExtension;
 use crate::domain::update::DomainUpdate;
 use crate::request::{Extension, Transaction};
+use crate::Error;
 
 use super::XMLNS;
 
+/// The statement that the registrar did not restore the name for its own use or resale
+pub const STATEMENT_NOT_FOR_RESALE: &str = "This registrar has not restored the Registered Name in order to assume the rights to use or sell the Registered Name for itself or for any third party.";
+
+/// The statement that the report is truthful
+pub const STATEMENT_TRUTHFUL: &str = "The information in this report is true to best of this registrar's knowledge, and this registrar acknowledges that intentionally supplying false information in this report shall constitute an incurable material breach of the Registry-Registrar Agreement.";
+
+/// The statements ICANN requires in restore reports for gTLDs
+///
+/// Pass `&ICANN_STATEMENTS` as the `statements` of [`RgpRestoreReport::new()`].
+pub const ICANN_STATEMENTS: [&str; 2] = [STATEMENT_NOT_FOR_RESALE, STATEMENT_TRUTHFUL];
+
 impl<'a> Transaction<Update<RgpRestoreReport<'a>>> for DomainUpdate<'a> {}
 
 impl<'a> RgpRestoreReport<'a> {
@@ -27,18 +46,132 @@ impl<'a> RgpRestoreReport<'a> {
             report: RgpRestoreReportSectionData {
                 pre_data,
                 post_data,
-                deleted_at: deleted_at.to_rfc3339_opts(SecondsFormat::AutoSi, true),
-                restored_at: restored_at.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+                deleted_at: Timestamp(deleted_at),
+                restored_at: Timestamp(restored_at),
                 restore_reason,
                 statements,
                 other,
             },
+            policy: None,
         }
     }
+
+    /// Check the report against the registry's `policy` before sending
+    pub fn policy(mut self, policy: ReportPolicy) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    /// Check the report as of `now`, see [`ReportPolicy`]
+    ///
+    /// This is the check run before the report is sent, with the current time.
+    pub fn check(&self, now: DateTime<Utc>) -> Result<(), Error> {
+        let report = &self.report;
+        let (deleted_at, restored_at) = (report.deleted_at.0, report.restored_at.0);
+        let problem = if report.pre_data.trim().is_empty() {
+            "no registration data from before the deletion".to_owned()
+        } else if report.post_data.trim().is_empty() {
+            "no registration data from after the restore".to_owned()
+        } else if deleted_at >= restored_at {
+            format!("deletion at {deleted_at} is not before the restore request at {restored_at}")
+        } else if restored_at > now {
+            format!("restore request at {restored_at} is in the future")
+        } else {
+            match &self.policy {
+                Some(policy) => match policy.check(report, now) {
+                    Some(problem) => problem,
+                    None => return Ok(()),
+                },
+                None => return Ok(()),
+            }
+        };
+
+        Err(Error::Other(
+            format!("invalid restore report: {problem}").into(),
+        ))
+    }
 }
 
 impl Extension for Update<RgpRestoreReport<'_>> {
     type Response = NoExtension;
+
+    fn validate(&self) -> Result<(), Error> {
+        self.data.check(Utc::now())
+    }
+}
+
+/// A registry's deadlines for restore reports, see [`RgpRestoreReport::policy()`]
+///
+/// [RFC 3915](https://tools.ietf.org/html/rfc3915#section-3.1) has a restore requested during
+/// the redemption period after the deletion, and the report sent during the pendingRestore
+/// period after the request. The defaults are the 30 and 7 days ICANN requires for gTLDs.
+#[derive(Clone, Copy, Debug)]
+pub struct ReportPolicy {
+    redemption_period: TimeDelta,
+    report_window: TimeDelta,
+    icann_statements: bool,
+}
+
+impl ReportPolicy {
+    /// A 30 day redemption period and 7 days to send the report, without required statements
+    pub fn new() -> Self {
+        Self {
+            redemption_period: TimeDelta::days(30),
+            report_window: TimeDelta::days(7),
+            icann_statements: false,
+        }
+    }
+
+    /// The longest time between the deletion and the restore request
+    pub fn redemption_period(mut self, period: TimeDelta) -> Self {
+        self.redemption_period = period;
+        self
+    }
+
+    /// The longest time between the restore request and sending the report
+    pub fn report_window(mut self, window: TimeDelta) -> Self {
+        self.report_window = window;
+        self
+    }
+
+    /// Whether the report must contain the [`ICANN_STATEMENTS`]
+    pub fn require_icann_statements(mut self, require: bool) -> Self {
+        self.icann_statements = require;
+        self
+    }
+
+    fn check(
+        &self,
+        report: &RgpRestoreReportSectionData<'_>,
+        now: DateTime<Utc>,
+    ) -> Option<String> {
+        let (deleted_at, restored_at) = (report.deleted_at.0, report.restored_at.0);
+        if restored_at - deleted_at > self.redemption_period {
+            Some(format!(
+                "restore request at {restored_at} is more than {} days after the deletion at {deleted_at}",
+                self.redemption_period.num_days()
+            ))
+        } else if now - restored_at > self.report_window {
+            Some(format!(
+                "report is due within {} days of the restore request at {restored_at}",
+                self.report_window.num_days()
+            ))
+        } else if self.icann_statements
+            && !ICANN_STATEMENTS
+                .iter()
+                .all(|required| report.statements.contains(required))
+        {
+            Some("missing statements required by ICANN".to_owned())
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for ReportPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 #[derive(Debug, ToXml)]
@@ -59,10 +192,10 @@ pub struct RgpRestoreReportSectionData<'a> {
     post_data: &'a str,
     /// The domain deletion date
     #[xml(rename = "delTime")]
-    deleted_at: String,
+    deleted_at: Timestamp,
     /// The domain restore request date
     #[xml(rename = "resTime")]
-    restored_at: String,
+    restored_at: Timestamp,
     /// The reason for domain restoration
     #[xml(rename = "resReason")]
     restore_reason: &'a str,
@@ -74,25 +207,53 @@ pub struct RgpRestoreReportSectionData<'a> {
     other: &'a str,
 }
 
-#[derive(Debug, ToXml)]
-/// Type for EPP XML `<check>` command for domains
-#[xml(rename = "restore", ns(XMLNS))]
+// Serialized in UTC with a `Z` suffix, and fractional seconds only if there are any
+#[derive(Debug)]
+struct Timestamp(DateTime<Utc>);
+
+impl ToXml for Timestamp {
+    fn serialize<W: std::fmt::Write + ?Sized>(
+        &self,
+        id: Option<instant_xml::Id<'_>>,
+        serializer: &mut Serializer<W>,
+    ) -> Result<(), instant_xml::Error> {
+        self.0
+            .to_rfc3339_opts(SecondsFormat::AutoSi, true)
+            .serialize(id, serializer)
+    }
+}
+
+/// Type for EPP XML `<restore>` tag with `op="report"`
+#[derive(Debug)]
 pub struct RgpRestoreReport<'a> {
     /// The value of the op attribute for the `<restore>` tag
-    #[xml(attribute)]
     op: &'a str,
     /// Data for the `<report>` tag
-    #[xml(rename = "rgp:report")]
     report: RgpRestoreReportSectionData<'a>,
+    policy: Option<ReportPolicy>,
+}
+
+impl ToXml for RgpRestoreReport<'_> {
+    fn serialize<W: std::fmt::Write + ?Sized>(
+        &self,
+        _: Option<instant_xml::Id<'_>>,
+        serializer: &mut Serializer<W>,
+    ) -> Result<(), instant_xml::Error> {
+        let restore = serializer.write_start("restore", XMLNS, None::<Context<0>>)?;
+        serializer.write_attr("op", XMLNS, &self.op)?;
+        serializer.end_start()?;
+        self.report.serialize(None, serializer)?;
+        serializer.write_close(restore)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
 
-    use chrono::DateTime;
+    use chrono::{DateTime, TimeDelta};
 
-    use super::{RgpRestoreReport, Update};
+    use super::{ReportPolicy, RgpRestoreReport, Update, ICANN_STATEMENTS};
     use crate::domain::update::{DomainChangeInfo, DomainUpdate};
     use crate::tests::assert_serialized;
 
@@ -105,10 +266,7 @@ mod tests {
         let deleted_at = DateTime::from_str("2021-07-10T22:00:00.0Z").unwrap();
         let restored_at = DateTime::from_str("2021-07-20T22:00:00.0Z").unwrap();
         let restore_reason = "Registrant error.";
-        let statements = &[
-            "This registrar has not restored the Registered Name in order to assume the rights to use or sell the Registered Name for itself or for any third party.",
-            "The information in this report is true to best of this registrar's knowledge, and this registrar acknowledges that intentionally supplying false information in this report shall constitute an incurable material breach of the Registry-Registrar Agreement.",
-        ];
+        let statements = &ICANN_STATEMENTS;
         let other = "Supporting information goes here.";
 
         let domain_restore_report = Update {
@@ -134,4 +292,51 @@ mod tests {
             (&object, &domain_restore_report),
         );
     }
+
+    #[test]
+    fn check() {
+        let deleted_at = DateTime::from_str("2021-07-10T22:00:00Z").unwrap();
+        let restored_at = DateTime::from_str("2021-07-20T22:00:00Z").unwrap();
+        let now = DateTime::from_str("2021-07-22T12:00:00Z").unwrap();
+        let report = |deleted_at, restored_at, statements| {
+            RgpRestoreReport::new("pre", "post", deleted_at, restored_at, "", statements, "")
+        };
+
+        assert!(report(deleted_at, restored_at, &[]).check(now).is_ok());
+        let err = report(restored_at, deleted_at, &[]).check(now).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "error: invalid restore report: deletion at 2021-07-20 22:00:00 UTC is not before the restore request at 2021-07-10 22:00:00 UTC"
+        );
+        let err = report(deleted_at, restored_at, &[])
+            .check(deleted_at)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "error: invalid restore report: restore request at 2021-07-20 22:00:00 UTC is in the future"
+        );
+        let empty = RgpRestoreReport::new("pre", " ", deleted_at, restored_at, "", &[], "");
+        assert!(empty.check(now).is_err());
+
+        // The defaults allow 30 days to request the restore and 7 more to send the report
+        let policy = ReportPolicy::default();
+        let report = report(deleted_at, restored_at, &ICANN_STATEMENTS).policy(policy);
+        assert!(report.check(now).is_ok());
+        let err = report.check(now + TimeDelta::days(7)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "error: invalid restore report: report is due within 7 days of the restore request at 2021-07-20 22:00:00 UTC"
+        );
+
+        let policy = policy.redemption_period(TimeDelta::days(5));
+        assert!(report.policy(policy).check(now).is_err());
+
+        let policy = ReportPolicy::new().require_icann_statements(true);
+        let report = RgpRestoreReport::new("pre", "post", deleted_at, restored_at, "", &[], "");
+        let err = report.policy(policy).check(now).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "error: invalid restore report: missing statements required by ICANN"
+        );
+    }
 }
@@ -3,8 +3,11 @@
 use instant_xml::{FromXml, ToXml};
 
 use crate::{
-    domain::{info::DomainInfo, update::DomainUpdate},
-    request::{Extension, Transaction},
+    domain::{
+        info::DomainInfo,
+        update::{DomainChangeInfo, DomainUpdate},
+    },
+    request::{Extension, RequestData, Transaction},
 };
 
 use super::{RgpStatus, XMLNS};
@@ -40,6 +43,42 @@ impl Default for RgpRestoreRequest<'static> {
     }
 }
 
+/// A domain `<update>` that only requests the restore of a deleted domain
+///
+/// Contains the update with an empty `<chg>` element and the `<restore op="request">`
+/// extension, as in the example in RFC 3915. Pass a reference to `transact()` to send it.
+#[derive(Debug)]
+pub struct DomainRestoreRequest<'a> {
+    update: DomainUpdate<'a>,
+    extension: Update<RgpRestoreRequest<'a>>,
+}
+
+impl<'a> DomainRestoreRequest<'a> {
+    /// Request the restore of the domain `name`
+    pub fn new(name: &'a str) -> Self {
+        let mut update = DomainUpdate::new(name);
+        update.info(DomainChangeInfo {
+            registrant: None,
+            auth_info: None,
+        });
+
+        Self {
+            update,
+            extension: Update {
+                data: RgpRestoreRequest { op: "request" },
+            },
+        }
+    }
+}
+
+impl<'r, 'a> From<&'r DomainRestoreRequest<'a>>
+    for RequestData<'r, 'r, DomainUpdate<'a>, Update<RgpRestoreRequest<'a>>>
+{
+    fn from(request: &'r DomainRestoreRequest<'a>) -> Self {
+        Self::new(&request.update, Some(&request.extension))
+    }
+}
+
 // Response
 
 #[derive(Debug, FromXml)]
@@ -61,20 +100,41 @@ pub struct RgpRequestInfoResponse {
 /// Type that represents the `<resData>` tag for domain transfer response
 #[derive(Debug, FromXml)]
 #[xml(forward)]
+#[non_exhaustive]
 pub enum RgpRequestResponse {
     Update(RgpRequestUpdateResponse),
     Info(RgpRequestInfoResponse),
 }
 
+impl RgpRequestResponse {
+    /// The grace period statuses of the domain
+    pub fn statuses(&self) -> &[RgpStatus] {
+        match self {
+            Self::Update(data) => &data.rgp_status,
+            Self::Info(data) => &data.rgp_status,
+        }
+    }
+
+    /// Whether the domain is in its redemption period, so it can be restored
+    pub fn is_redeemable(&self) -> bool {
+        self.statuses().contains(&RgpStatus::RedemptionPeriod)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{RgpRestoreRequest, Update};
+    use super::{DomainRestoreRequest, RgpRestoreRequest, Update};
     use crate::domain::info::DomainInfo;
     use crate::domain::update::{DomainChangeInfo, DomainUpdate};
     use crate::extensions::rgp::request::RgpRequestResponse;
     use crate::extensions::rgp::RgpStatus;
+    use crate::request::{Command, Extension};
+    use crate::response::Response;
     use crate::response::ResultCode;
-    use crate::tests::{assert_serialized, response_from_file_with_ext, SUCCESS_MSG, SVTRID};
+    use crate::tests::{
+        assert_serialized, get_xml, response_from_file_with_ext, SUCCESS_MSG, SVTRID,
+    };
+    use crate::xml;
 
     #[test]
     fn request_command() {
@@ -97,6 +157,14 @@ mod tests {
         );
     }
 
+    #[test]
+    fn restore_request() {
+        assert_serialized(
+            "request/extensions/rgp_restore_request.xml",
+            &DomainRestoreRequest::new("eppdev.com"),
+        );
+    }
+
     #[test]
     fn request_response() {
         let object = response_from_file_with_ext::<DomainUpdate, Update<RgpRestoreRequest>>(
@@ -107,6 +175,8 @@ mod tests {
         assert_eq!(object.result.code, ResultCode::CommandCompletedSuccessfully);
         assert_eq!(object.result.message, SUCCESS_MSG);
 
+        assert_eq!(ext.data.statuses(), [RgpStatus::PendingRestore]);
+        assert!(!ext.data.is_redeemable());
         let data = match ext.data {
             RgpRequestResponse::Update(data) => data,
             _ => panic!("Unexpected response type"),
@@ -131,4 +201,22 @@ mod tests {
         assert_eq!(data.rgp_status[0], RgpStatus::AddPeriod);
         assert_eq!(data.rgp_status[1], RgpStatus::RenewPeriod);
     }
+
+    #[test]
+    fn unknown_status() {
+        let xml = get_xml("response/extensions/domain_info_rgp.xml")
+            .unwrap()
+            .replace(r#"s="renewPeriod""#, r#"s="transferGracePeriod""#);
+        let object = xml::deserialize::<
+            Response<
+                <DomainInfo as Command>::Response,
+                <Update<RgpRestoreRequest> as Extension>::Response,
+            >,
+        >(&xml)
+        .unwrap();
+
+        let statuses = object.extension().unwrap().statuses();
+        assert_eq!(statuses[0], RgpStatus::AddPeriod);
+        assert_eq!(statuses[1], RgpStatus::Other("transferGracePeriod".into()));
+    }
 }
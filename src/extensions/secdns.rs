@@ -9,7 +9,7 @@ use std::time::Duration;
 use instant_xml::ser::Context;
 use instant_xml::{Error, Id, Serializer, ToXml};
 
-use crate::common::NoExtension;
+use crate::common::{serialize_container, Container, NoExtension};
 use crate::request::{Extension, Transaction};
 
 pub const XMLNS: &str = "urn:ietf:params:xml:ns:secDNS-1.1";
@@ -20,6 +20,12 @@ impl Extension for CreateData<'_> {
     type Response = NoExtension;
 }
 
+impl<'a> Transaction<UpdateData<'a>> for crate::domain::update::DomainUpdate<'a> {}
+
+impl Extension for UpdateData<'_> {
+    type Response = NoExtension;
+}
+
 #[derive(Debug, ToXml)]
 #[xml(rename = "create", ns(XMLNS))]
 pub struct CreateData<'a> {
@@ -70,6 +76,194 @@ impl<'a> From<(Duration, &'a [KeyDataType<'a>])> for CreateData<'a> {
     }
 }
 
+/// Type for the secDNS `<update>` extension to the domain update command
+///
+/// The server processes the `<rem>` records before the `<add>` records.
+#[derive(Debug, Default, ToXml)]
+#[xml(rename = "update", ns(XMLNS))]
+pub struct UpdateData<'a> {
+    #[xml(attribute)]
+    urgent: Option<bool>,
+    #[xml(serialize_with = "serialize_container")]
+    remove: Option<RemoveData<'a>>,
+    #[xml(serialize_with = "serialize_container")]
+    add: Option<AddData<'a>>,
+    change: Option<ChangeData>,
+}
+
+impl<'a> UpdateData<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask the server to process this update with high priority
+    pub fn urgent(&mut self) {
+        self.urgent = Some(true);
+    }
+
+    /// Sets the records for the `<add>` tag
+    pub fn add(&mut self, data: impl Into<DsOrKeyData<'a>>) {
+        self.add = Some(AddData { data: data.into() });
+    }
+
+    /// Sets the records for the `<rem>` tag
+    pub fn remove(&mut self, data: impl Into<DsOrKeyData<'a>>) {
+        self.remove = Some(RemoveData {
+            data: RemoveType::Data(data.into()),
+        });
+    }
+
+    /// Remove all existing records through the `<rem>` tag
+    pub fn remove_all(&mut self) {
+        self.remove = Some(RemoveData {
+            data: RemoveType::All(RemoveAll { all: true }),
+        });
+    }
+
+    /// Sets the maximum signature lifetime through the `<chg>` tag
+    pub fn change_maximum_signature_lifetime(&mut self, lifetime: Duration) {
+        self.change = Some(ChangeData {
+            maximum_signature_lifetime: lifetime.as_secs(),
+        });
+    }
+}
+
+#[derive(Debug, ToXml)]
+#[xml(rename = "add", ns(XMLNS))]
+struct AddData<'a> {
+    data: DsOrKeyData<'a>,
+}
+
+#[derive(Debug, ToXml)]
+#[xml(rename = "rem", ns(XMLNS))]
+struct RemoveData<'a> {
+    data: RemoveType<'a>,
+}
+
+impl Container for AddData<'_> {
+    fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+}
+
+impl Container for RemoveData<'_> {
+    fn is_empty(&self) -> bool {
+        match &self.data {
+            RemoveType::All(_) => false,
+            RemoveType::Data(data) => data.is_empty(),
+        }
+    }
+}
+
+#[derive(Debug, ToXml)]
+#[xml(forward)]
+enum RemoveType<'a> {
+    All(RemoveAll),
+    Data(DsOrKeyData<'a>),
+}
+
+#[derive(Debug, ToXml)]
+#[xml(rename = "all", ns(XMLNS))]
+struct RemoveAll {
+    #[xml(direct)]
+    all: bool,
+}
+
+#[derive(Debug, ToXml)]
+#[xml(rename = "chg", ns(XMLNS))]
+struct ChangeData {
+    #[xml(rename = "maxSigLife")]
+    maximum_signature_lifetime: u64,
+}
+
+/// Plan the updates needed to replace the `current` DS records with the `next` ones
+///
+/// Records present in both sets are left alone. Without a `wait`, the result is a single update
+/// that removes and adds records at once. With a `wait`, the new records are added first and
+/// the old ones are only removed in a second update, giving resolvers time to pick up the new
+/// DS set before the old one disappears.
+///
+/// The `next` records are checked for well-formed hex digests of the right length for their
+/// digest type and for algorithm numbers that can be used to sign a zone.
+pub fn rollover_ds<'a>(
+    current: &[DsDataType<'a>],
+    next: &[DsDataType<'a>],
+    wait: Option<Duration>,
+) -> Result<Vec<RolloverStep<'a>>, crate::Error> {
+    if next.is_empty() {
+        return Err(crate::Error::Other("no DS records to roll over to".into()));
+    }
+
+    for ds in next {
+        ds.validate()?;
+    }
+
+    let add = next
+        .iter()
+        .filter(|ds| !current.iter().any(|cur| cur.same(ds)))
+        .cloned()
+        .collect::<Vec<_>>();
+    let remove = current
+        .iter()
+        .filter(|ds| !next.iter().any(|new| new.same(ds)))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    Ok(match wait {
+        Some(wait) if !add.is_empty() && !remove.is_empty() => vec![
+            RolloverStep {
+                wait: Duration::ZERO,
+                add,
+                remove: Vec::new(),
+            },
+            RolloverStep {
+                wait,
+                add: Vec::new(),
+                remove,
+            },
+        ],
+        _ if add.is_empty() && remove.is_empty() => Vec::new(),
+        _ => vec![RolloverStep {
+            wait: Duration::ZERO,
+            add,
+            remove,
+        }],
+    })
+}
+
+/// A single domain update in a DS rollover, see [`rollover_ds()`]
+#[derive(Debug)]
+pub struct RolloverStep<'a> {
+    /// How long to wait after the previous step before sending this one
+    pub wait: Duration,
+    add: Vec<DsDataType<'a>>,
+    remove: Vec<DsDataType<'a>>,
+}
+
+impl RolloverStep<'_> {
+    /// The records added in this step
+    pub fn added(&self) -> &[DsDataType<'_>] {
+        &self.add
+    }
+
+    /// The records removed in this step
+    pub fn removed(&self) -> &[DsDataType<'_>] {
+        &self.remove
+    }
+
+    /// The extension to send along with a `DomainUpdate` for this step
+    pub fn update_data(&self) -> UpdateData<'_> {
+        let mut data = UpdateData::new();
+        if !self.remove.is_empty() {
+            data.remove(self.remove.as_slice());
+        }
+        if !self.add.is_empty() {
+            data.add(self.add.as_slice());
+        }
+        data
+    }
+}
+
 /// Struct supporting either the `dsData` or the `keyData` interface.
 #[derive(Debug)]
 pub struct DsOrKeyType<'a> {
@@ -107,7 +301,28 @@ pub enum DsOrKeyData<'a> {
     KeyData(&'a [KeyDataType<'a>]),
 }
 
-#[derive(Debug, ToXml)]
+impl DsOrKeyData<'_> {
+    fn is_empty(&self) -> bool {
+        match self {
+            Self::DsData(data) => data.is_empty(),
+            Self::KeyData(data) => data.is_empty(),
+        }
+    }
+}
+
+impl<'a> From<&'a [DsDataType<'a>]> for DsOrKeyData<'a> {
+    fn from(s: &'a [DsDataType<'a>]) -> Self {
+        Self::DsData(s)
+    }
+}
+
+impl<'a> From<&'a [KeyDataType<'a>]> for DsOrKeyData<'a> {
+    fn from(s: &'a [KeyDataType<'a>]) -> Self {
+        Self::KeyData(s)
+    }
+}
+
+#[derive(Clone, Debug, ToXml)]
 #[xml(rename = "dsData", ns(XMLNS))]
 pub struct DsDataType<'a> {
     #[xml(rename = "keyTag")]
@@ -137,6 +352,52 @@ impl<'a> DsDataType<'a> {
             key_data,
         }
     }
+
+    /// Check the digest and algorithm against the IANA registries
+    fn validate(&self) -> Result<(), crate::Error> {
+        let err = |msg: String| {
+            Err(crate::Error::Other(
+                format!("DS record with key tag {}: {msg}", self.key_tag).into(),
+            ))
+        };
+
+        // Delete, RSA/MD5, Diffie-Hellman, reserved and Indirect
+        if let alg @ (0..=2 | 4 | 9 | 11 | 123..=252 | 255) = u8::from(self.algorithm) {
+            return err(format!("algorithm {alg} cannot be used for zone signing"));
+        }
+
+        let expected = match u8::from(self.digest_type) {
+            0 => return err("digest type 0 is reserved".to_owned()),
+            1 => Some(40),
+            2 | 3 | 6 => Some(64),
+            4 => Some(96),
+            _ => None,
+        };
+
+        if self.digest.is_empty() || !self.digest.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return err(format!("digest {:?} is not a hex string", self.digest));
+        }
+
+        match expected {
+            Some(len) if self.digest.len() != len => err(format!(
+                "digest has {} hex digits, expected {len} for digest type {}",
+                self.digest.len(),
+                u8::from(self.digest_type),
+            )),
+            None if self.digest.len() % 2 != 0 => {
+                err("digest has an odd number of hex digits".to_owned())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether `self` and `other` describe the same DS record
+    fn same(&self, other: &Self) -> bool {
+        self.key_tag == other.key_tag
+            && u8::from(self.algorithm) == u8::from(other.algorithm)
+            && u8::from(self.digest_type) == u8::from(other.digest_type)
+            && self.digest.eq_ignore_ascii_case(&other.digest)
+    }
 }
 
 /// DigestAlgorithm identifies the algorithm used to construct the digest
@@ -266,7 +527,7 @@ impl ToXml for Algorithm {
     }
 }
 
-#[derive(Debug, ToXml)]
+#[derive(Clone, Debug, ToXml)]
 #[xml(rename = "keyData", ns(XMLNS))]
 pub struct KeyDataType<'a> {
     flags: Flags,
@@ -382,7 +643,7 @@ impl ToXml for Protocol {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::{self, Period, PeriodLength};
+    use crate::domain::{self, Period};
     use crate::tests::assert_serialized;
 
     #[test]
@@ -415,7 +676,7 @@ mod tests {
         ];
         let object = domain::DomainCreate::new(
             "example.com",
-            Period::Years(PeriodLength::new(2).unwrap()),
+            Period::years(2).unwrap(),
             Some(&ns),
             Some("jd1234"),
             "2fooBAR",
@@ -463,7 +724,7 @@ mod tests {
         ];
         let object = domain::DomainCreate::new(
             "example.com",
-            Period::Years(PeriodLength::new(2).unwrap()),
+            Period::years(2).unwrap(),
             Some(&ns),
             Some("jd1234"),
             "2fooBAR",
@@ -504,7 +765,7 @@ mod tests {
         ];
         let object = domain::DomainCreate::new(
             "example.com",
-            Period::Years(PeriodLength::new(2).unwrap()),
+            Period::years(2).unwrap(),
             Some(&ns),
             Some("jd1234"),
             "2fooBAR",
@@ -515,4 +776,146 @@ mod tests {
             (&object, &extension),
         );
     }
+
+    #[test]
+    fn update() {
+        let remove = [DsDataType::new(
+            12345,
+            Algorithm::Dsa,
+            DigestAlgorithm::Sha1,
+            "38EC35D5B3A34B33C99B",
+            None,
+        )];
+        let add = [DsDataType::new(
+            12346,
+            Algorithm::Dsa,
+            DigestAlgorithm::Sha1,
+            "38EC35D5B3A34B44C39B",
+            None,
+        )];
+
+        let mut extension = UpdateData::new();
+        extension.urgent();
+        extension.remove(remove.as_ref());
+        extension.add(add.as_ref());
+        extension.change_maximum_signature_lifetime(Duration::from_secs(605900));
+
+        let object = domain::DomainUpdate::new("example.com");
+        assert_serialized(
+            "request/extensions/secdns_update.xml",
+            (&object, &extension),
+        );
+    }
+
+    #[test]
+    fn update_remove_all() {
+        let mut extension = UpdateData::new();
+        extension.remove_all();
+
+        let object = domain::DomainUpdate::new("example.com");
+        assert_serialized(
+            "request/extensions/secdns_update_rem_all.xml",
+            (&object, &extension),
+        );
+    }
+
+    #[test]
+    fn rollover() {
+        const OLD: &str = "2BB183AF5F22588179A53B0A98631FAD1A292118D5F5C6D9B3BE3C8EFAD1C1D8";
+        const NEW: &str = "E2D3C916F6DEEAC73294E8268FB5885044A833FC5459588F4A9184CFC41A5766";
+        let current = [DsDataType::new(
+            12345,
+            Algorithm::EcdsaP256Sha256,
+            DigestAlgorithm::Sha256,
+            OLD,
+            None,
+        )];
+        let next = [DsDataType::new(
+            23456,
+            Algorithm::EcdsaP256Sha256,
+            DigestAlgorithm::Sha256,
+            NEW,
+            None,
+        )];
+
+        let steps = rollover_ds(&current, &next, None).unwrap();
+        assert_eq!(steps.len(), 1);
+        assert_eq!(steps[0].wait, Duration::ZERO);
+        assert_eq!(steps[0].removed()[0].key_tag, 12345);
+        assert_eq!(steps[0].added()[0].key_tag, 23456);
+
+        let wait = Duration::from_secs(86400);
+        let steps = rollover_ds(&current, &next, Some(wait)).unwrap();
+        assert_eq!(steps.len(), 2);
+        assert_eq!(steps[0].wait, Duration::ZERO);
+        assert!(steps[0].removed().is_empty());
+        assert_eq!(steps[0].added()[0].key_tag, 23456);
+        assert_eq!(steps[1].wait, wait);
+        assert_eq!(steps[1].removed()[0].key_tag, 12345);
+        assert!(steps[1].added().is_empty());
+
+        // Records in both sets are kept; digests compare case-insensitively
+        let both = [
+            DsDataType::new(
+                12345,
+                Algorithm::EcdsaP256Sha256,
+                DigestAlgorithm::Sha256,
+                OLD.to_lowercase(),
+                None,
+            ),
+            next[0].clone(),
+        ];
+        let steps = rollover_ds(&current, &both, Some(wait)).unwrap();
+        assert_eq!(steps.len(), 1);
+        assert!(steps[0].removed().is_empty());
+        assert_eq!(steps[0].added()[0].key_tag, 23456);
+
+        assert!(rollover_ds(&next, &next, Some(wait)).unwrap().is_empty());
+        assert!(rollover_ds(&current, &[], None).is_err());
+    }
+
+    #[test]
+    fn rollover_validation() {
+        let invalid = [
+            // Digest too short for SHA-1
+            (
+                Algorithm::RsaSha256,
+                DigestAlgorithm::Sha1,
+                "49FD46E6C4B45C55D4AC",
+            ),
+            // Not hex
+            (
+                Algorithm::RsaSha256,
+                DigestAlgorithm::Sha1,
+                "49FD46E6C4B45C55D4AC49FD46E6C4B45C55D4AZ",
+            ),
+            // RSA/MD5 must not be used
+            (
+                Algorithm::RsaMd5,
+                DigestAlgorithm::Sha1,
+                "49FD46E6C4B45C55D4AC49FD46E6C4B45C55D4AC",
+            ),
+            (
+                Algorithm::Other(200),
+                DigestAlgorithm::Sha1,
+                "49FD46E6C4B45C55D4AC49FD46E6C4B45C55D4AC",
+            ),
+            (Algorithm::RsaSha256, DigestAlgorithm::Other(0), "49FD"),
+            (Algorithm::RsaSha256, DigestAlgorithm::Other(9), "49F"),
+        ];
+
+        for (algorithm, digest_type, digest) in invalid {
+            let next = [DsDataType::new(1, algorithm, digest_type, digest, None)];
+            assert!(rollover_ds(&[], &next, None).is_err(), "{next:?}");
+        }
+
+        let next = [DsDataType::new(
+            1,
+            Algorithm::Ed25519,
+            DigestAlgorithm::Other(9),
+            "49FD",
+            None,
+        )];
+        assert!(rollover_ds(&[], &next, None).is_ok());
+    }
 }
@@ -3,12 +3,17 @@ use std::fmt::Debug;
 use chrono::{DateTime, Utc};
 use instant_xml::{Deserializer, FromXml, ToXml};
 
-use crate::common::{Options, ServiceExtension, Services, EPP_XMLNS};
+use crate::common::{
+    deserialize_date, LanguageTag, Options, ServiceExtension, Services, EPP_XMLNS,
+};
+use crate::request::{EPP_LANG, EPP_VERSION};
+use crate::Error;
 
 // Request
 
 #[derive(Debug, PartialEq, ToXml)]
 #[xml(rename = "hello", ns(EPP_XMLNS))]
+#[cfg_attr(not(feature = "client"), allow(dead_code))]
 pub(crate) struct Hello;
 
 // Response
@@ -16,8 +21,54 @@ pub(crate) struct Hello;
 /// Type for data within the `<svcMenu>` section of an EPP greeting
 #[derive(Debug, Eq, PartialEq)]
 pub struct ServiceMenu {
+    /// The version and the first of the advertised languages
     pub options: Options<'static>,
     pub services: Services<'static>,
+    /// All languages the server supports for responses, in the order advertised
+    ///
+    /// Entries that are not valid language tags are left out.
+    pub languages: Vec<LanguageTag>,
+}
+
+impl ServiceMenu {
+    /// Pick the first of the `preferred` languages that the server supports
+    ///
+    /// Falls back to `en`, which all servers must support.
+    pub fn select_language(&self, preferred: &[LanguageTag]) -> LanguageTag {
+        preferred
+            .iter()
+            .find(|&lang| self.languages.contains(lang))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Check that the server supports what a `command` using `object_uris` needs
+    ///
+    /// Returns [`Error::Unsupported`] if the server advertises an EPP version other than 1.0 or
+    /// does not list all of the `object_uris` as `<objURI>`, which it would otherwise reject
+    /// with an error only after the command is sent.
+    pub fn check(&self, command: &'static str, object_uris: &[&str]) -> Result<(), Error> {
+        if self.options.version != EPP_VERSION {
+            return Err(Error::Unsupported {
+                command,
+                reason: format!("server offers EPP version {}", self.options.version),
+            });
+        }
+
+        let missing = object_uris
+            .iter()
+            .filter(|&&uri| !self.services.obj_uris.iter().any(|obj| obj == uri))
+            .copied()
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            return Err(Error::Unsupported {
+                command,
+                reason: format!("server does not offer {}", missing.join(", ")),
+            });
+        }
+
+        Ok(())
+    }
 }
 
 /// Simplified service menu type for deserialization to `ServiceMenu` type from EPP greeting XML
@@ -25,7 +76,7 @@ pub struct ServiceMenu {
 #[xml(ns(EPP_XMLNS), rename = "svcMenu")]
 struct FlattenedServiceMenu {
     version: String,
-    lang: String,
+    lang: Vec<String>,
     #[xml(rename = "objURI")]
     obj_uris: Vec<String>,
     #[xml(rename = "svcExtension")]
@@ -50,15 +101,25 @@ impl<'xml> FromXml<'xml> for ServiceMenu {
             None => return Ok(()),
         };
 
+        let lang = match flattened.lang.first() {
+            Some(lang) => lang.clone(),
+            None => EPP_LANG.to_owned(),
+        };
+
         *into = Some(Self {
             options: Options {
                 version: flattened.version.into(),
-                lang: flattened.lang.into(),
+                lang: lang.into(),
             },
             services: Services {
                 obj_uris: flattened.obj_uris.into_iter().map(|s| s.into()).collect(),
                 svc_ext: flattened.svc_ext,
             },
+            languages: flattened
+                .lang
+                .iter()
+                .filter_map(|lang| lang.parse().ok())
+                .collect(),
         });
 
         Ok(())
@@ -101,6 +162,7 @@ pub struct Other;
 /// Type corresponding to possible `<retention>` type values
 #[derive(Debug, Eq, FromXml, PartialEq)]
 #[xml(forward)]
+#[non_exhaustive]
 pub enum AccessType {
     /// Data for the `<all>` tag
     All(All),
@@ -125,6 +187,7 @@ pub struct Access {
 /// Type corresponding to possible `<purpose>` type values
 #[derive(Debug, Eq, FromXml, PartialEq)]
 #[xml(forward)]
+#[non_exhaustive]
 pub enum PurposeType {
     /// Data for the `<admin>` tag
     Admin(Admin),
@@ -162,6 +225,7 @@ pub struct Purpose {
 /// Type corresponding to possible `<purpose>` type values
 #[derive(Debug, Eq, FromXml, PartialEq)]
 #[xml(forward)]
+#[non_exhaustive]
 pub enum RecipientType {
     /// Data for the `<other>` tag
     Other(Other),
@@ -226,6 +290,7 @@ pub struct Stated;
 /// Type corresponding to possible `<retention>` type values
 #[derive(Debug, Eq, FromXml, PartialEq)]
 #[xml(forward, rename = "retention", ns(EPP_XMLNS))]
+#[non_exhaustive]
 pub enum RetentionType {
     /// Data for the `<business>` tag
     Business(Business),
@@ -270,6 +335,7 @@ pub struct Relative(String);
 /// Type corresponding to possible `<expiry>` type values
 #[derive(Debug, Eq, FromXml, PartialEq)]
 #[xml(forward)]
+#[non_exhaustive]
 pub enum ExpiryType {
     /// Data for the `<absolute>` tag
     Absolute(Absolute),
@@ -304,7 +370,7 @@ pub struct Greeting {
     #[xml(rename = "svID")]
     pub service_id: String,
     /// The date from the EPP server
-    #[xml(rename = "svDate")]
+    #[xml(rename = "svDate", deserialize_with = "deserialize_date")]
     pub service_date: DateTime<Utc>,
     /// Data under the `<svcMenu>` element
     pub svc_menu: ServiceMenu,
@@ -312,13 +378,88 @@ pub struct Greeting {
     pub dcp: Dcp,
 }
 
+impl Greeting {
+    /// The differences in the service menu and data collection policy from `self` to `newer`
+    ///
+    /// The service ID and date are not compared, as the date changes with every greeting.
+    pub fn diff(&self, newer: &Self) -> GreetingDiff {
+        let (old, new) = (&self.svc_menu, &newer.svc_menu);
+        let extensions = |menu: &ServiceMenu| {
+            menu.services
+                .svc_ext
+                .as_ref()
+                .map(|ext| ext.ext_uris.iter().map(|uri| uri.to_string()).collect())
+                .unwrap_or_default()
+        };
+        let objects = |menu: &ServiceMenu| {
+            menu.services
+                .obj_uris
+                .iter()
+                .map(|uri| uri.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let (old_objects, new_objects) = (objects(old), objects(new));
+        let (old_extensions, new_extensions): (Vec<_>, Vec<_>) = (extensions(old), extensions(new));
+        GreetingDiff {
+            version: (old.options.version != new.options.version)
+                .then(|| new.options.version.to_string()),
+            added_objects: missing(&new_objects, &old_objects),
+            removed_objects: missing(&old_objects, &new_objects),
+            added_extensions: missing(&new_extensions, &old_extensions),
+            removed_extensions: missing(&old_extensions, &new_extensions),
+            added_languages: missing(&new.languages, &old.languages),
+            removed_languages: missing(&old.languages, &new.languages),
+            dcp_changed: self.dcp != newer.dcp,
+        }
+    }
+}
+
+/// Items of `items` that are not in `other`
+fn missing<T: Clone + PartialEq>(items: &[T], other: &[T]) -> Vec<T> {
+    items
+        .iter()
+        .filter(|item| !other.contains(item))
+        .cloned()
+        .collect()
+}
+
+/// Differences between two greetings, see [`Greeting::diff()`]
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct GreetingDiff {
+    /// The new protocol version, if it changed
+    pub version: Option<String>,
+    /// Object URIs (`<objURI>`) that were added
+    pub added_objects: Vec<String>,
+    /// Object URIs (`<objURI>`) that were removed
+    pub removed_objects: Vec<String>,
+    /// Extension URIs (`<extURI>`) that were added
+    pub added_extensions: Vec<String>,
+    /// Extension URIs (`<extURI>`) that were removed
+    pub removed_extensions: Vec<String>,
+    /// Languages that were added
+    pub added_languages: Vec<LanguageTag>,
+    /// Languages that were removed
+    pub removed_languages: Vec<LanguageTag>,
+    /// Whether the data collection policy (`<dcp>`) changed
+    pub dcp_changed: bool,
+}
+
+impl GreetingDiff {
+    /// Whether the greetings are the same
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use chrono::{TimeZone, Utc};
 
     use super::{ExpiryType, Greeting, Hello, Relative};
+    use crate::common::LanguageTag;
     use crate::tests::get_xml;
-    use crate::xml;
+    use crate::{domain, nsgroup, xml, Error};
 
     #[test]
     fn hello() {
@@ -348,4 +489,65 @@ mod tests {
             ExpiryType::Relative(Relative("P1M".into()))
         );
     }
+
+    #[test]
+    fn languages() {
+        let xml = get_xml("response/greeting_languages.xml").unwrap();
+        let object = xml::deserialize::<Greeting>(xml.as_str()).unwrap();
+        let menu = &object.svc_menu;
+
+        assert_eq!(menu.options.lang, "en");
+        assert_eq!(menu.languages, ["en", "fr", "de"]);
+
+        let preferred = ["it", "DE"].map(|lang| lang.parse::<LanguageTag>().unwrap());
+        assert_eq!(menu.select_language(&preferred), "de");
+        assert_eq!(menu.select_language(&preferred[..1]), "en");
+        assert_eq!(menu.select_language(&[]), "en");
+    }
+
+    #[test]
+    fn diff() {
+        let xml = get_xml("response/greeting.xml").unwrap();
+        let old = xml::deserialize::<Greeting>(xml.as_str()).unwrap();
+        assert!(old.diff(&old).is_empty());
+
+        let xml = get_xml("response/greeting_languages.xml").unwrap();
+        let new = xml::deserialize::<Greeting>(xml.as_str()).unwrap();
+        let diff = old.diff(&new);
+        assert_eq!(diff.added_languages, ["fr", "de"]);
+        assert!(diff.removed_languages.is_empty());
+        assert!(diff.added_objects.is_empty() && diff.added_extensions.is_empty());
+        assert!(!diff.dcp_changed);
+        assert_eq!(new.diff(&old).removed_languages, ["fr", "de"]);
+    }
+
+    #[test]
+    fn check() {
+        let xml = get_xml("response/greeting.xml").unwrap();
+        let mut menu = xml::deserialize::<Greeting>(xml.as_str()).unwrap().svc_menu;
+        assert!(menu.check("info", &[domain::XMLNS]).is_ok());
+        assert!(menu.check("hello", &[]).is_ok());
+
+        let err = menu
+            .check("info", &[domain::XMLNS, nsgroup::XMLNS])
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Unsupported {
+                command: "info",
+                ..
+            }
+        ));
+        assert_eq!(
+            err.to_string(),
+            "info command not supported: server does not offer http://www.eurid.eu/xml/epp/nsgroup-1.1"
+        );
+
+        menu.options.version = "2.0".into();
+        let err = menu.check("login", &[]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "login command not supported: server offers EPP version 2.0"
+        );
+    }
 }
@@ -5,7 +5,7 @@ use std::fmt::{self, Debug};
 use instant_xml::{FromXml, Serializer, ToXml};
 
 use super::XMLNS;
-use crate::common::{NoExtension, EPP_XMLNS};
+use crate::common::{CheckResults, Checked, NoExtension, EPP_XMLNS};
 use crate::request::{Command, Transaction};
 
 impl Transaction<NoExtension> for HostCheck<'_> {}
@@ -13,6 +13,14 @@ impl Transaction<NoExtension> for HostCheck<'_> {}
 impl Command for HostCheck<'_> {
     type Response = CheckData;
     const COMMAND: &'static str = "check";
+
+    fn object_ids(&self) -> Vec<&str> {
+        self.hosts.to_vec()
+    }
+
+    fn object_uris(&self) -> Vec<&str> {
+        vec![XMLNS]
+    }
 }
 
 // Request
@@ -78,6 +86,28 @@ pub struct CheckData {
     pub list: Vec<CheckedHost>,
 }
 
+impl Checked for CheckedHost {
+    fn id(&self) -> &str {
+        &self.name.value
+    }
+
+    fn available(&self) -> bool {
+        self.name.available
+    }
+
+    fn reason(&self) -> Option<&str> {
+        self.reason.as_ref().map(|reason| reason.value.as_str())
+    }
+}
+
+impl CheckResults for CheckData {
+    type Item = CheckedHost;
+
+    fn results(&self) -> &[Self::Item] {
+        &self.list
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::HostCheck;
@@ -1,27 +1,74 @@
 //! Types for EPP host create request
 
+use std::borrow::Cow;
+use std::fmt;
 use std::net::IpAddr;
 
 use chrono::{DateTime, Utc};
-use instant_xml::{FromXml, ToXml};
+use instant_xml::ser::Context;
+use instant_xml::{FromXml, Serializer, ToXml};
 
-use super::{serialize_host_addrs_option, XMLNS};
-use crate::common::{NoExtension, EPP_XMLNS};
+use super::{serialize_host_addrs_option, GluePolicy, XMLNS};
+use crate::common::{deserialize_date, NoExtension, EPP_XMLNS};
 use crate::request::{Command, Transaction};
+use crate::Error;
 
 impl Transaction<NoExtension> for HostCreate<'_> {}
 
 impl Command for HostCreate<'_> {
     type Response = CreateData;
     const COMMAND: &'static str = "create";
+
+    fn validate(&self) -> Result<(), Error> {
+        let Some(glue) = &self.glue else {
+            return Ok(());
+        };
+
+        let addresses = self.host.addresses.as_deref().unwrap_or_default();
+        glue.check(self.host.name, addresses, true)
+    }
+
+    fn object_ids(&self) -> Vec<&str> {
+        vec![self.host.name]
+    }
+
+    fn object_uris(&self) -> Vec<&str> {
+        vec![XMLNS]
+    }
 }
 
 impl<'a> HostCreate<'a> {
     pub fn new(name: &'a str, addresses: Option<&'a [IpAddr]>) -> Self {
         Self {
-            host: HostCreateRequest { name, addresses },
+            host: HostCreateRequest {
+                name,
+                addresses: addresses.map(Cow::Borrowed),
+            },
+            glue: None,
         }
     }
+
+    /// Create the host `name` with glue `addresses`
+    ///
+    /// The addresses are sorted, IPv4 before IPv6, and duplicates are removed, so the request
+    /// is the same regardless of the order they were collected in.
+    pub fn with_glue(name: &'a str, addresses: &[IpAddr]) -> Self {
+        let mut addresses = addresses.to_vec();
+        addresses.sort_unstable();
+        addresses.dedup();
+        Self {
+            host: HostCreateRequest {
+                name,
+                addresses: (!addresses.is_empty()).then_some(Cow::Owned(addresses)),
+            },
+            glue: None,
+        }
+    }
+
+    /// Check the host's addresses against the registry's `policy` before sending
+    pub fn glue_policy(&mut self, policy: GluePolicy) {
+        self.glue = Some(policy);
+    }
 }
 
 // Request
@@ -34,15 +81,28 @@ pub struct HostCreateRequest<'a> {
     pub name: &'a str,
     /// The list of IP addresses for the host
     #[xml(serialize_with = "serialize_host_addrs_option")]
-    pub addresses: Option<&'a [IpAddr]>,
+    pub addresses: Option<Cow<'a, [IpAddr]>>,
 }
 
 /// Type for EPP XML `<create>` command for hosts
-#[derive(Debug, ToXml)]
-#[xml(rename = "create", ns(EPP_XMLNS))]
+#[derive(Debug)]
 pub struct HostCreate<'a> {
     /// The instance holding the data for the host to be created
     host: HostCreateRequest<'a>,
+    glue: Option<GluePolicy>,
+}
+
+impl ToXml for HostCreate<'_> {
+    fn serialize<W: fmt::Write + ?Sized>(
+        &self,
+        _: Option<instant_xml::Id<'_>>,
+        serializer: &mut Serializer<W>,
+    ) -> Result<(), instant_xml::Error> {
+        let create = serializer.write_start("create", EPP_XMLNS, None::<Context<0>>)?;
+        serializer.end_start()?;
+        self.host.serialize(None, serializer)?;
+        serializer.write_close(create)
+    }
 }
 
 // Response
@@ -54,7 +114,7 @@ pub struct CreateData {
     /// The host name
     pub name: String,
     /// The host creation date
-    #[xml(rename = "crDate")]
+    #[xml(rename = "crDate", deserialize_with = "deserialize_date")]
     pub created_at: DateTime<Utc>,
 }
 
@@ -63,8 +123,10 @@ mod tests {
     use chrono::{TimeZone, Utc};
 
     use super::{HostCreate, IpAddr};
+    use crate::host::GluePolicy;
     use crate::response::ResultCode;
     use crate::tests::{assert_serialized, response_from_file, CLTRID, SUCCESS_MSG, SVTRID};
+    use crate::xml;
 
     #[test]
     fn command() {
@@ -77,6 +139,58 @@ mod tests {
         assert_serialized("request/host/create.xml", &object);
     }
 
+    #[test]
+    fn glue() {
+        let addresses = &[IpAddr::from([29, 245, 122, 14])];
+        let policy = GluePolicy::new(&["com"]);
+
+        let mut object = HostCreate::new("ns1.eppdev-1.COM", None);
+        object.glue_policy(policy.clone());
+        let err = xml::serialize_request(&object, CLTRID).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "error: ns1.eppdev-1.COM: in-bailiwick host requires at least one address"
+        );
+        object.glue_policy(policy.clone().require_internal_glue(false));
+        xml::serialize_request(&object, CLTRID).unwrap();
+
+        let mut object = HostCreate::new("ns1.example.net", Some(addresses));
+        object.glue_policy(policy.clone());
+        let err = xml::serialize_request(&object, CLTRID).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "error: ns1.example.net: external host must not have addresses"
+        );
+        object.glue_policy(policy.allow_external_glue(true));
+        xml::serialize_request(&object, CLTRID).unwrap();
+
+        let v6 = [IpAddr::from([
+            0x2404, 0x6800, 0x4001, 0x801, 0, 0, 0, 0x200e,
+        ])];
+        let policy = GluePolicy::new(&["com"]).require_ipv4(true);
+        let mut object = HostCreate::new("ns1.eppdev-1.com", Some(&v6));
+        object.glue_policy(policy.clone());
+        let err = xml::serialize_request(&object, CLTRID).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "error: ns1.eppdev-1.com: registry requires at least one IPv4 address"
+        );
+        policy
+            .check_glue("ns1.eppdev-1.com", &[v6[0], addresses[0]])
+            .unwrap();
+    }
+
+    #[test]
+    fn with_glue() {
+        let v4 = IpAddr::from([29, 245, 122, 14]);
+        let v6 = IpAddr::from([0x2404, 0x6800, 0x4001, 0x801, 0, 0, 0, 0x200e]);
+        let object = HostCreate::with_glue("host1.eppdev-1.com", &[v6, v4, v6]);
+        assert_serialized("request/host/create.xml", &object);
+
+        let object = HostCreate::with_glue("host1.eppdev-1.com", &[]);
+        assert_eq!(object.host.addresses, None);
+    }
+
     #[test]
     fn response() {
         let object = response_from_file::<HostCreate>("response/host/create.xml");
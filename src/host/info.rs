@@ -7,7 +7,9 @@ use chrono::{DateTime, Utc};
 use instant_xml::{FromXml, ToXml};
 
 use super::{HostAddr, Status, XMLNS};
-use crate::common::{NoExtension, EPP_XMLNS};
+use crate::common::{
+    deserialize_date, deserialize_date_option, NoExtension, StatusValue, EPP_XMLNS,
+};
 use crate::request::{Command, Transaction};
 
 impl Transaction<NoExtension> for HostInfo<'_> {}
@@ -15,6 +17,14 @@ impl Transaction<NoExtension> for HostInfo<'_> {}
 impl Command for HostInfo<'_> {
     type Response = InfoData;
     const COMMAND: &'static str = "info";
+
+    fn object_ids(&self) -> Vec<&str> {
+        vec![self.info.name]
+    }
+
+    fn object_uris(&self) -> Vec<&str> {
+        vec![XMLNS]
+    }
 }
 
 impl<'a> HostInfo<'a> {
@@ -56,7 +66,7 @@ pub struct InfoData {
     pub roid: String,
     /// The list of host statuses
     #[xml(rename = "status")]
-    pub statuses: Vec<Status>,
+    pub statuses: Vec<StatusValue<Status>>,
     /// The list of host IP addresses
     #[xml(rename = "addr", deserialize_with = "deserialize_host_addrs")]
     pub addresses: Vec<IpAddr>,
@@ -67,16 +77,16 @@ pub struct InfoData {
     #[xml(rename = "crID")]
     pub creator_id: String,
     /// The host creation date
-    #[xml(rename = "crDate")]
+    #[xml(rename = "crDate", deserialize_with = "deserialize_date")]
     pub created_at: DateTime<Utc>,
     /// The epp user that last updated the host
     #[xml(rename = "upID")]
     pub updater_id: Option<String>,
     /// The host last update date
-    #[xml(rename = "upDate")]
+    #[xml(rename = "upDate", deserialize_with = "deserialize_date_option")]
     pub updated_at: Option<DateTime<Utc>>,
     /// The host transfer date
-    #[xml(rename = "trDate")]
+    #[xml(rename = "trDate", deserialize_with = "deserialize_date_option")]
     pub transferred_at: Option<DateTime<Utc>>,
 }
 
@@ -119,9 +129,13 @@ mod tests {
     use chrono::{TimeZone, Utc};
 
     use super::{HostInfo, IpAddr};
+    use crate::common::NoExtension;
     use crate::host::Status;
     use crate::response::ResultCode;
-    use crate::tests::{assert_serialized, response_from_file, CLTRID, SUCCESS_MSG, SVTRID};
+    use crate::tests::{
+        assert_serialized, get_xml, response_from_file, CLTRID, SUCCESS_MSG, SVTRID,
+    };
+    use crate::xml;
 
     #[test]
     fn command() {
@@ -158,4 +172,18 @@ mod tests {
         assert_eq!(object.tr_ids.client_tr_id.unwrap(), CLTRID);
         assert_eq!(object.tr_ids.server_tr_id, SVTRID);
     }
+
+    #[test]
+    fn unknown_status() {
+        let xml = get_xml("response/host/info.xml").unwrap().replace(
+            r#"<host:status s="ok"/>"#,
+            r#"<host:status s="serverRenewProhibited">Registry lock</host:status>"#,
+        );
+        let object = xml::deserialize_response::<HostInfo, NoExtension>(&xml).unwrap();
+        let statuses = &object.res_data().unwrap().statuses;
+
+        assert_eq!(statuses[0], Status::Other("serverRenewProhibited".into()));
+        assert!(statuses[0].status.is_server_managed());
+        assert_eq!(statuses[0].reason.as_deref(), Some("Registry lock"));
+    }
 }
@@ -5,9 +5,12 @@
 use std::borrow::Cow;
 use std::fmt;
 use std::net::IpAddr;
+use std::str::FromStr;
 
-use instant_xml::ser::Context;
-use instant_xml::{Deserializer, FromXml, Serializer, ToXml};
+use instant_xml::{FromXml, Serializer, ToXml};
+
+use crate::common::ObjectStatus;
+use crate::Error;
 
 pub mod check;
 pub use check::HostCheck;
@@ -27,7 +30,8 @@ pub use update::HostUpdate;
 pub const XMLNS: &str = "urn:ietf:params:xml:ns:host-1.0";
 
 /// The `<status>` type on contact transactions
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
 pub enum Status {
     ClientDeleteProhibited,
     ServerDeleteProhibited,
@@ -39,10 +43,12 @@ pub enum Status {
     PendingDelete,
     PendingTransfer,
     PendingUpdate,
+    /// A status not defined in the RFC, with its value as sent by the server
+    Other(String),
 }
 
 impl Status {
-    pub fn as_str(&self) -> &'static str {
+    pub fn as_str(&self) -> &str {
         use Status::*;
         match self {
             ClientDeleteProhibited => "clientDeleteProhibited",
@@ -55,56 +61,28 @@ impl Status {
             PendingDelete => "pendingDelete",
             PendingTransfer => "pendingTransfer",
             PendingUpdate => "pendingUpdate",
+            Other(status) => status,
         }
     }
-}
-
-impl ToXml for Status {
-    fn serialize<W: fmt::Write + ?Sized>(
-        &self,
-        _: Option<instant_xml::Id<'_>>,
-        serializer: &mut Serializer<W>,
-    ) -> Result<(), instant_xml::Error> {
-        serializer.write_start("status", XMLNS, None::<Context<0>>)?;
-        serializer.write_attr("s", XMLNS, &self.as_str())?;
-        serializer.end_empty()
-    }
-}
 
-impl<'xml> FromXml<'xml> for Status {
-    fn matches(id: instant_xml::Id<'_>, _: Option<instant_xml::Id<'_>>) -> bool {
-        id == instant_xml::Id {
-            ns: XMLNS,
-            name: "status",
+    /// Whether the status is set and removed by the server only
+    ///
+    /// Clients can only add and remove the `client*` statuses.
+    pub fn is_server_managed(&self) -> bool {
+        use Status::*;
+        if let Other(status) = self {
+            return !status.starts_with("client");
         }
+
+        !matches!(self, ClientDeleteProhibited | ClientUpdateProhibited)
     }
+}
 
-    fn deserialize<'cx>(
-        into: &mut Self::Accumulator,
-        field: &'static str,
-        deserializer: &mut Deserializer<'cx, 'xml>,
-    ) -> Result<(), instant_xml::Error> {
-        use instant_xml::de::Node;
-        use instant_xml::{Error, Id};
-
-        let node = match deserializer.next() {
-            Some(result) => result?,
-            None => return Err(Error::MissingValue(field)),
-        };
-
-        let attr = match node {
-            Node::Attribute(attr) => attr,
-            Node::Open(_) | Node::Text(_) => return Err(Error::MissingValue(field)),
-            node => return Err(Error::UnexpectedNode(format!("{node:?} in Status"))),
-        };
-
-        let id = deserializer.attribute_id(&attr)?;
-        let expected = Id { ns: "", name: "s" };
-        if id != expected {
-            return Err(Error::MissingValue(field));
-        }
+impl FromStr for Status {
+    type Err = Error;
 
-        *into = Some(match attr.value.as_ref() {
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
             "clientDeleteProhibited" => Self::ClientDeleteProhibited,
             "serverDeleteProhibited" => Self::ServerDeleteProhibited,
             "clientUpdateProhibited" => Self::ClientUpdateProhibited,
@@ -115,15 +93,122 @@ impl<'xml> FromXml<'xml> for Status {
             "pendingDelete" => Self::PendingDelete,
             "pendingTransfer" => Self::PendingTransfer,
             "pendingUpdate" => Self::PendingUpdate,
-            val => return Err(Error::UnexpectedValue(format!("invalid status {val:?}"))),
-        });
+            _ => Self::Other(s.to_owned()),
+        })
+    }
+}
 
-        deserializer.ignore()?;
-        Ok(())
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl ObjectStatus for Status {
+    const XMLNS: &'static str = XMLNS;
+
+    fn is_server_managed(&self) -> bool {
+        Self::is_server_managed(self)
     }
+}
+
+/// A registry's rules for host addresses (glue records)
+///
+/// [RFC 5732](https://tools.ietf.org/html/rfc5732#section-3.2.1) has hosts subordinate to a
+/// zone managed by the registry (in-bailiwick hosts) carry addresses, while external hosts
+/// must not, as the registry isn't authoritative for them. Since registries differ in how
+/// strictly they apply this, the rules can be relaxed. Some registries also reject IPv6-only
+/// glue. Set a policy on a [`HostCreate`] or [`HostUpdate`] to check it before the command is
+/// sent.
+#[derive(Clone, Debug)]
+pub struct GluePolicy {
+    zones: Vec<String>,
+    require_internal: bool,
+    allow_external: bool,
+    require_ipv4: bool,
+}
 
-    type Accumulator = Option<Self>;
-    const KIND: instant_xml::Kind = instant_xml::Kind::Element;
+impl GluePolicy {
+    /// Hosts under any of `zones` are in-bailiwick, all others are external
+    ///
+    /// By default, in-bailiwick hosts need at least one address and external hosts may not
+    /// have any.
+    pub fn new(zones: &[&str]) -> Self {
+        Self {
+            zones: zones
+                .iter()
+                .map(|zone| zone.trim_matches('.').to_ascii_lowercase())
+                .collect(),
+            require_internal: true,
+            allow_external: false,
+            require_ipv4: false,
+        }
+    }
+
+    /// Whether in-bailiwick hosts must be created with at least one address
+    pub fn require_internal_glue(mut self, require: bool) -> Self {
+        self.require_internal = require;
+        self
+    }
+
+    /// Whether external hosts may have addresses
+    pub fn allow_external_glue(mut self, allow: bool) -> Self {
+        self.allow_external = allow;
+        self
+    }
+
+    /// Whether a host with addresses must have at least one IPv4 address
+    ///
+    /// Only checked when the host is created, as an update may add IPv6 addresses to a host
+    /// that already has IPv4 ones.
+    pub fn require_ipv4(mut self, require: bool) -> Self {
+        self.require_ipv4 = require;
+        self
+    }
+
+    /// Check the glue `addresses` for a new host `name` against the policy
+    ///
+    /// This is the check a [`HostCreate`] with this policy runs before it is sent.
+    pub fn check_glue(&self, name: &str, addresses: &[IpAddr]) -> Result<(), Error> {
+        self.check(name, addresses, true)
+    }
+
+    /// Whether `host` is subordinate to one of the policy's zones
+    pub fn is_internal(&self, host: &str) -> bool {
+        let host = host.trim_end_matches('.').to_ascii_lowercase();
+        self.zones.iter().any(|zone| {
+            host.strip_suffix(zone.as_str())
+                .is_some_and(|prefix| prefix.ends_with('.'))
+        })
+    }
+
+    /// Check the addresses a host `name` would be created with or gain
+    fn check(&self, name: &str, addresses: &[IpAddr], create: bool) -> Result<(), Error> {
+        let internal = self.is_internal(name);
+        if internal && create && self.require_internal && addresses.is_empty() {
+            return Err(Error::Other(
+                format!("{name}: in-bailiwick host requires at least one address").into(),
+            ));
+        }
+
+        if !internal && !self.allow_external && !addresses.is_empty() {
+            return Err(Error::Other(
+                format!("{name}: external host must not have addresses").into(),
+            ));
+        }
+
+        if create
+            && self.require_ipv4
+            && !addresses.is_empty()
+            && !addresses.iter().any(IpAddr::is_ipv4)
+        {
+            return Err(Error::Other(
+                format!("{name}: registry requires at least one IPv4 address").into(),
+            ));
+        }
+
+        Ok(())
+    }
 }
 
 /// The `<hostAddr>` types domain or host transactions
@@ -1,18 +1,52 @@
 //! Types for EPP host update request
 
+use std::fmt;
 use std::net::IpAddr;
 
-use instant_xml::ToXml;
+use instant_xml::ser::Context;
+use instant_xml::{Serializer, ToXml};
 
-use super::{serialize_host_addrs_option, Status, XMLNS};
-use crate::common::{NoExtension, EPP_XMLNS};
+use super::{serialize_host_addrs_option, GluePolicy, Status, XMLNS};
+use crate::common::{
+    check_client_statuses, serialize_container, Container, NoExtension, StatusValue, EPP_XMLNS,
+};
 use crate::request::{Command, Transaction};
+use crate::Error;
 
 impl Transaction<NoExtension> for HostUpdate<'_> {}
 
 impl Command for HostUpdate<'_> {
     type Response = ();
     const COMMAND: &'static str = "update";
+
+    fn validate(&self) -> Result<(), Error> {
+        if let Some(glue) = &self.glue {
+            // Check against the new name if the host is being renamed
+            let name = match &self.host.change_info {
+                Some(info) => info.name,
+                None => self.host.name,
+            };
+
+            let add = self.host.add.as_ref().and_then(|add| add.addresses);
+            glue.check(name, add.unwrap_or_default(), false)?;
+        }
+
+        if self.allow_server_statuses {
+            return Ok(());
+        }
+
+        let add = self.host.add.as_ref().and_then(|add| add.statuses);
+        let remove = self.host.remove.as_ref().and_then(|remove| remove.statuses);
+        check_client_statuses(self.host.name, add, remove)
+    }
+
+    fn object_ids(&self) -> Vec<&str> {
+        vec![self.host.name]
+    }
+
+    fn object_uris(&self) -> Vec<&str> {
+        vec![XMLNS]
+    }
 }
 
 impl<'a> HostUpdate<'a> {
@@ -24,6 +58,8 @@ impl<'a> HostUpdate<'a> {
                 remove: None,
                 change_info: None,
             },
+            allow_server_statuses: false,
+            glue: None,
         }
     }
 
@@ -41,6 +77,16 @@ impl<'a> HostUpdate<'a> {
     pub fn remove(&mut self, remove: HostRemove<'a>) {
         self.host.remove = Some(remove);
     }
+
+    /// Allow adding and removing server-managed statuses, which are rejected by default
+    pub fn allow_server_statuses(&mut self) {
+        self.allow_server_statuses = true;
+    }
+
+    /// Check addresses added to the host against the registry's `policy` before sending
+    pub fn glue_policy(&mut self, policy: GluePolicy) {
+        self.glue = Some(policy);
+    }
 }
 
 /// Type for data under the `<chg>` tag
@@ -60,7 +106,7 @@ pub struct HostAdd<'a> {
     pub addresses: Option<&'a [IpAddr]>,
     /// The statuses to be added to or removed from the host
     #[xml(rename = "host:status")]
-    pub statuses: Option<&'a [Status]>,
+    pub statuses: Option<&'a [StatusValue<Status>]>,
 }
 
 /// Type for data under the `<add>` and `<rem>` tags
@@ -72,7 +118,21 @@ pub struct HostRemove<'a> {
     pub addresses: Option<&'a [IpAddr]>,
     /// The statuses to be added to or removed from the host
     #[xml(rename = "host:status")]
-    pub statuses: Option<&'a [Status]>,
+    pub statuses: Option<&'a [StatusValue<Status>]>,
+}
+
+impl Container for HostAdd<'_> {
+    fn is_empty(&self) -> bool {
+        self.addresses.unwrap_or_default().is_empty()
+            && self.statuses.unwrap_or_default().is_empty()
+    }
+}
+
+impl Container for HostRemove<'_> {
+    fn is_empty(&self) -> bool {
+        self.addresses.unwrap_or_default().is_empty()
+            && self.statuses.unwrap_or_default().is_empty()
+    }
 }
 
 /// Type for data under the host `<update>` tag
@@ -82,10 +142,10 @@ pub struct HostUpdateRequest<'a> {
     /// The name of the host
     name: &'a str,
     /// The IP addresses and statuses to be added to the host
-    #[xml(rename = "host:add")]
+    #[xml(serialize_with = "serialize_container")]
     add: Option<HostAdd<'a>>,
     /// The IP addresses and statuses to be removed from the host
-    #[xml(rename = "host:rem")]
+    #[xml(serialize_with = "serialize_container")]
     remove: Option<HostRemove<'a>>,
     /// The host details that need to be updated
     #[xml(rename = "host:chg")]
@@ -93,19 +153,35 @@ pub struct HostUpdateRequest<'a> {
 }
 
 /// Type for EPP XML `<update>` command for hosts
-#[derive(Debug, ToXml)]
-#[xml(rename = "update", ns(EPP_XMLNS))]
+#[derive(Debug)]
 pub struct HostUpdate<'a> {
     /// The instance holding the data for the host to be updated
     host: HostUpdateRequest<'a>,
+    allow_server_statuses: bool,
+    glue: Option<GluePolicy>,
+}
+
+impl ToXml for HostUpdate<'_> {
+    fn serialize<W: fmt::Write + ?Sized>(
+        &self,
+        _: Option<instant_xml::Id<'_>>,
+        serializer: &mut Serializer<W>,
+    ) -> Result<(), instant_xml::Error> {
+        let update = serializer.write_start("update", EPP_XMLNS, None::<Context<0>>)?;
+        serializer.end_start()?;
+        self.host.serialize(None, serializer)?;
+        serializer.write_close(update)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::IpAddr;
     use super::{HostAdd, HostChangeInfo, HostRemove, HostUpdate, Status};
+    use crate::host::GluePolicy;
     use crate::response::ResultCode;
     use crate::tests::{assert_serialized, response_from_file, CLTRID, SUCCESS_MSG, SVTRID};
+    use crate::xml;
 
     #[test]
     fn command() {
@@ -120,7 +196,7 @@ mod tests {
 
         let remove = HostRemove {
             addresses: None,
-            statuses: Some(&[Status::ClientDeleteProhibited]),
+            statuses: Some(&[Status::ClientDeleteProhibited.into()]),
         };
 
         let mut object = HostUpdate::new("host1.eppdev-1.com");
@@ -134,6 +210,48 @@ mod tests {
         assert_serialized("request/host/update.xml", &object);
     }
 
+    #[test]
+    fn server_statuses() {
+        let statuses = [Status::ClientDeleteProhibited.into(), Status::Linked.into()];
+        let mut object = HostUpdate::new("host1.eppdev-1.com");
+        object.remove(HostRemove {
+            addresses: None,
+            statuses: Some(&statuses),
+        });
+
+        let err = xml::serialize_request(&object, CLTRID).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "error: host1.eppdev-1.com: server-managed status linked cannot be added or removed"
+        );
+
+        object.allow_server_statuses();
+        xml::serialize_request(&object, CLTRID).unwrap();
+    }
+
+    #[test]
+    fn glue() {
+        let addresses = &[IpAddr::from([29, 245, 122, 14])];
+        let mut object = HostUpdate::new("ns1.example.com");
+        object.add(HostAdd {
+            addresses: Some(addresses),
+            statuses: None,
+        });
+        object.info(HostChangeInfo {
+            name: "ns1.example.net",
+        });
+
+        object.glue_policy(GluePolicy::new(&["com"]));
+        let err = xml::serialize_request(&object, CLTRID).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "error: ns1.example.net: external host must not have addresses"
+        );
+
+        object.glue_policy(GluePolicy::new(&["com", "net"]));
+        xml::serialize_request(&object, CLTRID).unwrap();
+    }
+
     #[test]
     fn response() {
         let object = response_from_file::<HostUpdate>("response/host/update.xml");
@@ -0,0 +1,199 @@
+//! Loading TLS client certificates and keys from files
+//!
+//! [`EppClient::connect()`] takes the client certificate chain and private key as DER, while
+//! registries usually hand them out as PEM files or as a PKCS#12 bundle. An [`Identity`] can be
+//! loaded from either and converted into the pair `connect()` expects:
+//!
+//! ```no_run
+//! # use std::time::Duration;
+//! # use instant_epp::EppClient;
+//! # use instant_epp::identity::Identity;
+//! # async fn example() -> Result<(), instant_epp::Error> {
+//! let identity = Identity::from_pem_files("client.crt", "client.key")?;
+//! let server = ("epp.example.com".to_owned(), 700);
+//! let client = EppClient::connect(
+//!     "example".to_owned(),
+//!     server,
+//!     Some(identity.into()),
+//!     Duration::from_secs(5),
+//! )
+//! .await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Loading PKCS#12 bundles requires the `pkcs12` feature. Bundles must be protected by a MAC,
+//! as the ones produced by OpenSSL are.
+//!
+//! [`EppClient::connect()`]: crate::EppClient::connect
+
+use std::fmt;
+use std::path::Path;
+
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+
+use crate::Error;
+
+/// A TLS client certificate chain and its private key
+#[derive(Debug)]
+pub struct Identity {
+    /// The certificate chain, starting with the client certificate
+    pub certs: Vec<CertificateDer<'static>>,
+    /// The private key of the client certificate
+    pub key: PrivateKeyDer<'static>,
+}
+
+impl Identity {
+    /// Load the certificate chain from the PEM file `cert` and the private key from `key`
+    ///
+    /// `cert` should contain the client certificate first, followed by any intermediate
+    /// certificates. The key may be in PKCS#1, SEC1 or PKCS#8 format.
+    pub fn from_pem_files(cert: impl AsRef<Path>, key: impl AsRef<Path>) -> Result<Self, Error> {
+        let (cert, key) = (cert.as_ref(), key.as_ref());
+        let certs = CertificateDer::pem_file_iter(cert)
+            .and_then(|iter| iter.collect::<Result<Vec<_>, _>>())
+            .map_err(|err| file_error(cert, err))?;
+        if certs.is_empty() {
+            return Err(file_error(cert, "no certificates found"));
+        }
+
+        Ok(Self {
+            certs,
+            key: PrivateKeyDer::from_pem_file(key).map_err(|err| file_error(key, err))?,
+        })
+    }
+
+    /// Load the certificate chain and private key from the PKCS#12 bundle at `path`
+    ///
+    /// The bundle must contain exactly one private key. The certificate sharing its local key
+    /// ID with the key is put first in the chain.
+    #[cfg(feature = "pkcs12")]
+    pub fn from_pkcs12(path: impl AsRef<Path>, passphrase: &str) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let der = std::fs::read(path).map_err(|err| file_error(path, err))?;
+        pkcs12::parse(&der, passphrase).map_err(|err| file_error(path, err))
+    }
+}
+
+impl From<Identity> for (Vec<CertificateDer<'static>>, PrivateKeyDer<'static>) {
+    fn from(identity: Identity) -> Self {
+        (identity.certs, identity.key)
+    }
+}
+
+fn file_error(path: &Path, err: impl fmt::Display) -> Error {
+    Error::Other(format!("{}: {err}", path.display()).into())
+}
+
+/// Reading PKCS#12 (RFC 7292) bundles through the `p12-keystore` crate
+#[cfg(feature = "pkcs12")]
+mod pkcs12 {
+    use der::Decode;
+    use p12_keystore::{KeyStore, KeyStoreEntry, Pkcs12ImportPolicy};
+    use pkcs12::pfx::Pfx;
+    use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
+
+    use super::Identity;
+
+    pub(super) fn parse(der: &[u8], passphrase: &str) -> Result<Identity, String> {
+        // The MAC is optional in the format, but without it a tampered bundle goes unnoticed
+        let pfx = Pfx::from_der(der).map_err(|err| err.to_string())?;
+        if pfx.mac_data.is_none() {
+            return Err("bundle has no MAC to verify its integrity".into());
+        }
+
+        let policy = Pkcs12ImportPolicy::Strict;
+        let store = match KeyStore::from_pkcs12(der, passphrase, policy) {
+            Ok(store) => store,
+            Err(p12_keystore::error::Error::MacError(_)) => {
+                return Err("MAC verification failed, wrong passphrase?".into())
+            }
+            Err(err) => return Err(err.to_string()),
+        };
+
+        let mut chains = store.entries().filter_map(|(_, entry)| match entry {
+            KeyStoreEntry::PrivateKeyChain(chain) => Some(chain),
+            _ => None,
+        });
+
+        let chain = match (chains.next(), chains.next()) {
+            (Some(chain), None) => chain,
+            (None, _) => return Err("no private key found".into()),
+            (Some(_), Some(_)) => return Err("more than one private key found".into()),
+        };
+
+        if chain.certs().is_empty() {
+            return Err("no certificates found".into());
+        }
+
+        // The chain starts with the certificate sharing its local key ID with the key
+        Ok(Identity {
+            certs: chain
+                .certs()
+                .iter()
+                .map(|cert| CertificateDer::from(cert.as_der().to_vec()))
+                .collect(),
+            key: PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(chain.key().as_der().to_vec())),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Identity;
+    use crate::tests::RESOURCES_DIR;
+
+    #[test]
+    fn pem() {
+        let identity = Identity::from_pem_files(
+            format!("{RESOURCES_DIR}/identity/client.crt"),
+            format!("{RESOURCES_DIR}/identity/client.key"),
+        )
+        .unwrap();
+        assert_eq!(identity.certs.len(), 2);
+
+        let err = Identity::from_pem_files(
+            format!("{RESOURCES_DIR}/identity/client.key"),
+            format!("{RESOURCES_DIR}/identity/client.key"),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "error: ./tests/resources/identity/client.key: no certificates found"
+        );
+    }
+
+    #[cfg(feature = "pkcs12")]
+    #[test]
+    fn pkcs12() {
+        let pem = Identity::from_pem_files(
+            format!("{RESOURCES_DIR}/identity/client.crt"),
+            format!("{RESOURCES_DIR}/identity/client.key"),
+        )
+        .unwrap();
+
+        let path = format!("{RESOURCES_DIR}/identity/client.p12");
+        let identity = Identity::from_pkcs12(&path, "secret").unwrap();
+        assert_eq!(identity.certs, pem.certs);
+        assert_eq!(identity.key.secret_der(), pem.key.secret_der());
+
+        let err = Identity::from_pkcs12(&path, "wrong").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "error: ./tests/resources/identity/client.p12: MAC verification failed, wrong passphrase?"
+        );
+
+        // Bundles from before OpenSSL 3.0 use RC2 and 3DES
+        let path = format!("{RESOURCES_DIR}/identity/legacy.p12");
+        let identity = Identity::from_pkcs12(&path, "secret").unwrap();
+        assert_eq!(identity.certs, pem.certs);
+
+        let path = format!("{RESOURCES_DIR}/identity/nomac.p12");
+        let err = Identity::from_pkcs12(&path, "secret").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "error: ./tests/resources/identity/nomac.p12: bundle has no MAC to verify its integrity"
+        );
+    }
+}
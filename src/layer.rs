@@ -0,0 +1,192 @@
+//! Middleware for the commands sent by an [`EppClient`](crate::EppClient)
+//!
+//! A [`Layer`] sees every command sent through `transact()` before it is serialized, and every
+//! response once it has been parsed. This allows cross-cutting features, such as attaching an
+//! extension to all commands for a TLD or counting failures per object, without changes to the
+//! client. Layers are added with `EppClient::layer()` and called in the order they were added.
+//!
+//! Since the same layers see commands of all types, they don't get the caller's typed command:
+//! a layer can read the command's name, transaction ID, objects and namespaces, and add
+//! extensions to it, but not change the command itself. Responses can be parsed into the types
+//! for a command the layer knows about with [`Incoming::parse()`], including the data for the
+//! extensions it attached.
+//!
+//! ```no_run
+//! # use async_trait::async_trait;
+//! use instant_epp::extensions::namestore::NameStore;
+//! use instant_epp::layer::{Layer, Outgoing};
+//! use instant_epp::{domain, host, Error};
+//!
+//! /// Attach the namestore extension to all domain and host commands
+//! struct SubProduct(NameStore<'static>);
+//!
+//! #[async_trait]
+//! impl Layer for SubProduct {
+//!     async fn request(&self, request: &mut Outgoing<'_>) -> Result<(), Error> {
+//!         let uris = request.object_uris();
+//!         let object = uris.contains(&domain::XMLNS) || uris.contains(&host::XMLNS);
+//!         if object && !request.has_extension() {
+//!             request.attach(&self.0)?;
+//!         }
+//!         Ok(())
+//!     }
+//! }
+//! ```
+
+use async_trait::async_trait;
+
+use crate::request::{Command, Extension};
+use crate::response::{EppResult, MessageQueue, Response, ResponseTRID, ResultCode};
+use crate::xml::{self, Options};
+use crate::Error;
+
+/// Hooks called around each command sent through `EppClient::transact()`
+///
+/// If a hook fails, the `transact()` call fails with its error. A failing `request()` hook
+/// keeps the command from being sent; once `response()` is called, the server has processed
+/// the command. Raw requests sent through `transact_xml()` bypass the layers.
+#[async_trait]
+pub trait Layer: Send + Sync {
+    /// Inspect or change `request` before it is serialized and sent
+    ///
+    /// The hook can await, for example to hold back requests while some limit is reached.
+    async fn request(&self, _request: &mut Outgoing<'_>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Inspect the server's `response` to a request
+    ///
+    /// Called for all responses that could be parsed, including those with a failure code.
+    async fn response(&self, _response: &Incoming<'_>) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// A request about to be sent, as seen by [`Layer::request()`]
+#[derive(Debug)]
+pub struct Outgoing<'a> {
+    command: &'static str,
+    transaction_id: &'a str,
+    object_ids: Vec<&'a str>,
+    object_uris: Vec<&'a str>,
+    extension: bool,
+    pub(crate) attached: Vec<String>,
+}
+
+impl<'a> Outgoing<'a> {
+    pub(crate) fn new(
+        command: &'static str,
+        transaction_id: &'a str,
+        object_ids: Vec<&'a str>,
+        object_uris: Vec<&'a str>,
+        extension: bool,
+    ) -> Self {
+        Self {
+            command,
+            transaction_id,
+            object_ids,
+            object_uris,
+            extension,
+            attached: Vec::new(),
+        }
+    }
+
+    /// The command, as in [`Command::COMMAND`]
+    pub fn command(&self) -> &'static str {
+        self.command
+    }
+
+    /// The client transaction ID of the request
+    pub fn transaction_id(&self) -> &str {
+        self.transaction_id
+    }
+
+    /// The IDs of the objects in the request, as in
+    /// [`Command::object_ids()`](crate::request::Command::object_ids)
+    pub fn object_ids(&self) -> &[&'a str] {
+        &self.object_ids
+    }
+
+    /// The namespaces of the object mappings the command uses, as in
+    /// [`Command::object_uris()`](crate::request::Command::object_uris)
+    pub fn object_uris(&self) -> &[&'a str] {
+        &self.object_uris
+    }
+
+    /// Whether the caller sent the command with an extension
+    pub fn has_extension(&self) -> bool {
+        self.extension
+    }
+
+    /// Add `extension` to the `<extension>` element of the request
+    ///
+    /// The extension is validated and serialized right away. Its data in the response is not
+    /// part of the caller's response type, but can be read with [`Incoming::parse()`].
+    pub fn attach<E: Extension>(&mut self, extension: &E) -> Result<(), Error> {
+        extension.validate()?;
+        let xml = instant_xml::to_string(extension).map_err(|e| Error::Xml(e.into()))?;
+        self.attached.push(xml);
+        Ok(())
+    }
+}
+
+/// A parsed response, as seen by [`Layer::response()`]
+#[derive(Debug)]
+pub struct Incoming<'a> {
+    pub(crate) command: &'static str,
+    pub(crate) transaction_id: &'a str,
+    pub(crate) result: &'a EppResult,
+    pub(crate) message_queue: Option<&'a MessageQueue>,
+    pub(crate) tr_ids: &'a ResponseTRID,
+    pub(crate) xml: &'a str,
+    pub(crate) options: Options,
+}
+
+impl<'a> Incoming<'a> {
+    /// The command the response is for, as in
+    /// [`Command::COMMAND`]
+    pub fn command(&self) -> &'static str {
+        self.command
+    }
+
+    /// The client transaction ID of the request
+    pub fn transaction_id(&self) -> &'a str {
+        self.transaction_id
+    }
+
+    /// The result code of the response
+    pub fn code(&self) -> ResultCode {
+        self.result.code
+    }
+
+    /// The `<result>` of the response, with its message and any `<extValue>` details
+    pub fn result(&self) -> &'a EppResult {
+        self.result
+    }
+
+    /// The `<msgQ>` of the response, if any
+    pub fn message_queue(&self) -> Option<&'a MessageQueue> {
+        self.message_queue
+    }
+
+    /// The transaction IDs of the response
+    pub fn tr_ids(&self) -> &'a ResponseTRID {
+        self.tr_ids
+    }
+
+    /// The raw XML of the response
+    pub fn xml(&self) -> &'a str {
+        self.xml
+    }
+
+    /// Parse the response as the response to `Cmd` with the extension `Ext`
+    ///
+    /// `Cmd` should be the type of the [`command()`](Self::command). The response is parsed
+    /// with the client's settings, such as `EppClient::set_strict_dates()`.
+    pub fn parse<Cmd: Command, Ext: Extension>(
+        &self,
+    ) -> Result<Response<Cmd::Response, Ext::Response>, Error> {
+        self.options
+            .scope(|| xml::deserialize_response::<Cmd, Ext>(self.xml))
+    }
+}
@@ -11,9 +11,16 @@
 //! - [RFC 5733](https://tools.ietf.org/html/rfc5733) - Extensible Provisioning Protocol (EPP) Contact Mapping
 //! - [RFC 5734](https://tools.ietf.org/html/rfc5734) - Extensible Provisioning Protocol (EPP) Transport over TCP
 //! - [RFC 3915](https://tools.ietf.org/html/rfc3915) - Domain Registry Grace Period Mapping
+//! - [RFC 8748](https://www.rfc-editor.org/rfc/rfc8748.html) - Registry Fee Extension for the Extensible Provisioning Protocol (EPP)
+//! - [RFC 8334](https://www.rfc-editor.org/rfc/rfc8334.html) - Launch Phase Mapping for the Extensible Provisioning Protocol (EPP), claims checks only
 //! - [ConsoliDate mapping](https://www.verisign.com/assets/consolidate-mapping.txt)
 //! - [Namestore Extension Mapping](https://www.verisign.com/assets/epp-sdk/verisign_epp-extension_namestoreext_v01.html)
 //! - [Low Balance Mapping](https://www.verisign.com/assets/epp-sdk/verisign_epp-extension_low-balance_v01.html)
+//! - [Balance Mapping](https://www.verisign.com/assets/epp-sdk/verisign_epp-extension_balance_v01.html)
+//! - [Reseller Extension](https://datatracker.ietf.org/doc/draft-ietf-regext-reseller-ext/)
+//! - ISPAPI (HEXONET) key-value extension
+//! - [Additional Email Address Extension](https://datatracker.ietf.org/doc/draft-ietf-regext-epp-eai/)
+//! - EURid name server group mapping (nsgroup-1.1)
 //!
 //! This library is used in production with at [Instant Domains](https://instantdomains.com/).
 //!
@@ -29,39 +36,89 @@
 //!
 //! ## Getting started
 //!
-//! You will usually want to start by initializing an [`EppClient`]. Refer to the example code
-//! on that type for more information.
+//! You will usually want to start by initializing an `EppClient`. Refer to the example code
+//! on that type for more information. The [`prelude`] module exports the most commonly used
+//! types.
+//!
+//! ## Features
+//!
+//! The `client` feature (enabled by default) provides the `EppClient` and its networking stack
+//! based on tokio. Without it, the request and response types can still be (de)serialized
+//! through the functions in the [`xml`] module, for example in WASM or server-side tooling.
+//! The `rustls-aws-lc-rs` (default) and `rustls-ring` features provide a TLS connector, and the
+//! `pkcs12` feature allows loading its client certificate from a PKCS#12 bundle.
+//! The `blocking` feature adds a synchronous client in the `blocking` module, and the `cache`
+//! feature a cache for domain availability checks in `workflows::availability`. The `deflate`
+//! feature allows compressing EPP frames for registries that support it, and the `prometheus`
+//! feature keeps per-client metrics in the `metrics` module. The `confusables` feature adds
+//! detection of Latin lookalikes to the IDN checks in `domain::idn`.
+//!
+//! The client only needs a tokio runtime for its timers and the rustls connector's sockets.
+//! With the `async-io` feature, both come from async-io instead, so the client can run on smol
+//! or async-std. Other `Connector`s can wrap their futures-io streams in `client::Compat` to
+//! provide the tokio I/O traits the client reads and writes through.
 
 #![warn(unreachable_pub)]
 #![warn(clippy::use_self)]
 
+#[cfg(feature = "blocking")]
+pub mod blocking;
+#[cfg(feature = "client")]
+pub mod circuit_breaker;
+#[cfg(feature = "client")]
 pub mod client;
+pub mod codec;
 pub mod common;
+#[cfg(feature = "client")]
 pub mod connection;
 pub mod contact;
+pub mod custom;
 pub mod domain;
 mod error;
 pub mod hello;
 pub mod host;
+#[cfg(feature = "__rustls")]
+pub mod identity;
+#[cfg(feature = "client")]
+pub mod layer;
 pub mod login;
 pub mod logout;
+#[cfg(feature = "prometheus")]
+pub mod metrics;
+pub mod nsgroup;
+pub mod object;
 pub mod poll;
+pub mod prelude;
+#[cfg(feature = "client")]
+pub mod rate_limit;
 pub mod request;
 pub mod response;
+#[cfg(feature = "client")]
+mod runtime;
 pub mod xml;
 
-pub mod extensions {
-    pub mod change_poll;
-    pub mod consolidate;
-    pub mod frnic;
-    pub mod low_balance;
-    pub mod namestore;
-    pub mod rgp;
-    pub mod secdns;
+/// Higher-level operations built on top of [`EppClient`]
+#[cfg(feature = "client")]
+pub mod workflows {
+    #[cfg(feature = "cache")]
+    pub mod availability;
+    pub mod claims;
+    pub mod dependencies;
+    mod ids;
+    pub mod lock;
+    pub mod pending;
+    pub mod poll;
+    pub mod register;
+    pub mod registrant;
+    pub mod rename;
+    pub mod transfer;
 }
 
+pub mod extensions;
+
+#[cfg(feature = "client")]
 pub use client::EppClient;
-pub use error::Error;
+pub use error::{CommandError, Error, RequestError};
 
 #[cfg(test)]
 pub mod tests;
@@ -1,19 +1,28 @@
-use std::fmt::Debug;
+use std::fmt::{self, Debug};
+use std::ops::RangeInclusive;
 
-use instant_xml::ToXml;
+use instant_xml::{Serializer, ToXml};
 
 use crate::{
-    common::{NoExtension, Options, ServiceExtension, Services, EPP_XMLNS},
+    common::{LanguageTag, NoExtension, Options, ServiceExtension, Services, EPP_XMLNS},
     contact, domain, host,
     request::{Command, Transaction, EPP_LANG, EPP_VERSION},
+    Error,
 };
 
 impl Transaction<NoExtension> for Login<'_> {}
 
 /// Type corresponding to the `<login>` tag in an EPP XML login request
+#[derive(Debug, Eq, PartialEq)]
+pub struct Login<'a> {
+    request: LoginRequest<'a>,
+    /// The allowed number of characters in passwords
+    password_length: RangeInclusive<usize>,
+}
+
 #[derive(Debug, Eq, PartialEq, ToXml)]
 #[xml(rename = "login", ns(EPP_XMLNS))]
-pub struct Login<'a> {
+struct LoginRequest<'a> {
     /// The username to use for the login
     #[xml(rename = "clID")]
     username: &'a str,
@@ -38,49 +47,149 @@ impl<'a> Login<'a> {
         ext_uris: Option<&'_ [&'a str]>,
     ) -> Self {
         Self {
-            username,
-            password,
-            new_password,
-            options: Options {
-                version: EPP_VERSION.into(),
-                lang: EPP_LANG.into(),
-            },
-            services: Services {
-                obj_uris: vec![
-                    host::XMLNS.into(),
-                    contact::XMLNS.into(),
-                    domain::XMLNS.into(),
-                ],
-                svc_ext: ext_uris.and_then(|uris| {
-                    (!uris.is_empty()).then(|| ServiceExtension {
-                        ext_uris: uris.iter().map(|&u| u.into()).collect(),
-                    })
-                }),
+            request: LoginRequest {
+                username,
+                password,
+                new_password,
+                options: Options {
+                    version: EPP_VERSION.into(),
+                    lang: EPP_LANG.into(),
+                },
+                services: Services {
+                    obj_uris: vec![
+                        host::XMLNS.into(),
+                        contact::XMLNS.into(),
+                        domain::XMLNS.into(),
+                    ],
+                    svc_ext: ext_uris.and_then(|uris| {
+                        (!uris.is_empty()).then(|| ServiceExtension {
+                            ext_uris: uris.iter().map(|&u| u.into()).collect(),
+                        })
+                    }),
+                },
             },
+            password_length: PASSWORD_LENGTH,
         }
     }
 
     /// Sets the `<options>` tag data
+    ///
+    /// The version must be `1.0` and the language a valid language tag, or sending the login
+    /// fails without contacting the server.
     pub fn options(&mut self, options: Options<'a>) {
-        self.options = options;
+        self.request.options = options;
+    }
+
+    /// Request responses in the language `lang`
+    ///
+    /// The server must support the language, as advertised in its greeting; it will reject the
+    /// login otherwise.
+    pub fn language(&mut self, lang: &LanguageTag) {
+        self.request.options.lang = lang.to_string().into();
     }
 
     /// Sets the `<svcs>` tag data
     pub fn services(&mut self, services: Services<'a>) {
-        self.services = services;
+        self.request.services = services;
+    }
+
+    /// Allow passwords with a number of characters in `length`
+    ///
+    /// The EPP schema limits passwords to 6 to 16 characters, which is checked before the login
+    /// is sent. Use this for registries that document different limits.
+    pub fn password_length(&mut self, length: RangeInclusive<usize>) {
+        self.password_length = length;
+    }
+}
+
+impl ToXml for Login<'_> {
+    fn serialize<W: fmt::Write + ?Sized>(
+        &self,
+        field: Option<instant_xml::Id<'_>>,
+        serializer: &mut Serializer<W>,
+    ) -> Result<(), instant_xml::Error> {
+        self.request.serialize(field, serializer)
     }
 }
 
 impl Command for Login<'_> {
     type Response = ();
     const COMMAND: &'static str = "login";
+
+    fn validate(&self) -> Result<(), Error> {
+        let LoginRequest {
+            username,
+            password,
+            new_password,
+            options,
+            ..
+        } = &self.request;
+
+        if options.version != EPP_VERSION {
+            return Err(Error::Other(
+                format!(
+                    "{username}: unsupported EPP version {:?} in login options",
+                    options.version
+                )
+                .into(),
+            ));
+        }
+
+        if options.lang.parse::<LanguageTag>().is_err() {
+            return Err(Error::Other(
+                format!(
+                    "{username}: invalid language tag {:?} in login options",
+                    options.lang
+                )
+                .into(),
+            ));
+        }
+
+        let (min, max) = (self.password_length.start(), self.password_length.end());
+        for (name, pw) in [
+            ("password", Some(password)),
+            ("new password", new_password.as_ref()),
+        ] {
+            let Some(pw) = pw else {
+                continue;
+            };
+
+            if !self.password_length.contains(&pw.chars().count()) {
+                return Err(Error::Other(
+                    format!("{username}: {name} must be {min} to {max} characters long").into(),
+                ));
+            }
+        }
+
+        if new_password.is_some_and(|new| new == *password) {
+            return Err(Error::Other(
+                format!("{username}: new password must differ from the current password").into(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn object_uris(&self) -> Vec<&str> {
+        self.request
+            .services
+            .obj_uris
+            .iter()
+            .map(|uri| &**uri)
+            .collect()
+    }
 }
 
+/// Password lengths allowed by the `pwType` of the EPP schema
+const PASSWORD_LENGTH: RangeInclusive<usize> = 6..=16;
+
 #[cfg(test)]
 mod tests {
     use super::Login;
+    use crate::common::Options;
     use crate::response::ResultCode;
     use crate::tests::{assert_serialized, response_from_file, CLTRID, SUCCESS_MSG, SVTRID};
+    use crate::xml;
 
     #[test]
     fn command() {
@@ -97,6 +206,59 @@ mod tests {
         assert_serialized("request/login_no_extension.xml", &object);
     }
 
+    #[test]
+    fn command_language() {
+        let mut object = Login::new("username", "password", None, None);
+        object.language(&"de".parse().unwrap());
+        assert_serialized("request/login_lang.xml", &object);
+    }
+
+    #[test]
+    fn validation() {
+        let err = |object: &Login| {
+            xml::serialize_request(object, CLTRID)
+                .unwrap_err()
+                .to_string()
+        };
+
+        let object = Login::new("username", "short", None, None);
+        assert_eq!(
+            err(&object),
+            "error: username: password must be 6 to 16 characters long"
+        );
+
+        let mut object = Login::new(
+            "username",
+            "password",
+            Some("a-much-too-long-password"),
+            None,
+        );
+        assert_eq!(
+            err(&object),
+            "error: username: new password must be 6 to 16 characters long"
+        );
+        object.password_length(8..=32);
+        xml::serialize_request(&object, CLTRID).unwrap();
+
+        let object = Login::new("username", "password", Some("password"), None);
+        assert_eq!(
+            err(&object),
+            "error: username: new password must differ from the current password"
+        );
+
+        let mut object = Login::new("username", "password", None, None);
+        object.options(Options::build("2.0", "en"));
+        assert_eq!(
+            err(&object),
+            r#"error: username: unsupported EPP version "2.0" in login options"#
+        );
+        object.options(Options::build("1.0", "not a tag"));
+        assert_eq!(
+            err(&object),
+            r#"error: username: invalid language tag "not a tag" in login options"#
+        );
+    }
+
     #[test]
     fn response() {
         let object = response_from_file::<Login>("response/login.xml");
@@ -0,0 +1,137 @@
+//! Prometheus metrics for an [`EppClient`](crate::EppClient)
+//!
+//! Every client keeps a set of metrics, available through
+//! [`EppClient::metrics()`](crate::EppClient::metrics). All of them carry a `registry` label
+//! with the name passed to the client's constructor:
+//!
+//! - `epp_connection_state`: 1 for the current state of the connection (`connecting`, `ready`
+//!   or `closed`, in the `state` label), 0 for the others
+//! - `epp_requests_in_flight`: requests sent or queued that have not been answered yet,
+//!   including those whose future was dropped
+//! - `epp_requests_queued`: requests waiting for an earlier request to finish
+//! - `epp_poll_queue_depth`: the number of messages in the poll queue, as of the last response
+//!   that reported it
+//! - `epp_responses_total`: responses by `command` and result `code`
+//!
+//! Encode the [`Metrics::registry()`] with a `prometheus::TextEncoder` to serve them directly, or
+//! register the `Metrics`, which is a `Collector`, with an application-wide registry.
+
+use prometheus::core::{Collector, Desc};
+use prometheus::proto::MetricFamily;
+use prometheus::{IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry};
+
+use crate::connection::ConnectionEvent;
+use crate::response::ResultCode;
+
+/// The metrics of a single client, see the [module documentation](self)
+///
+/// Clones share the underlying metrics.
+#[derive(Clone, Debug)]
+pub struct Metrics {
+    state: IntGaugeVec,
+    in_flight: IntGauge,
+    queued: IntGauge,
+    poll_queue: IntGauge,
+    responses: IntCounterVec,
+}
+
+impl Metrics {
+    pub(crate) fn new(registry_name: &str) -> Self {
+        let opts =
+            |name: &str, help: &str| Opts::new(name, help).const_label("registry", registry_name);
+
+        // Creating metrics only fails for invalid names, which these aren't
+        Self {
+            state: IntGaugeVec::new(
+                opts("epp_connection_state", "The state of the connection"),
+                &["state"],
+            )
+            .unwrap(),
+            in_flight: IntGauge::with_opts(opts(
+                "epp_requests_in_flight",
+                "Requests that have not been answered yet",
+            ))
+            .unwrap(),
+            queued: IntGauge::with_opts(opts(
+                "epp_requests_queued",
+                "Requests waiting for an earlier request to finish",
+            ))
+            .unwrap(),
+            poll_queue: IntGauge::with_opts(opts(
+                "epp_poll_queue_depth",
+                "Messages in the poll queue",
+            ))
+            .unwrap(),
+            responses: IntCounterVec::new(
+                opts(
+                    "epp_responses_total",
+                    "Responses by command and result code",
+                ),
+                &["command", "code"],
+            )
+            .unwrap(),
+        }
+    }
+
+    /// A new registry with only this client's metrics
+    pub fn registry(&self) -> Registry {
+        let registry = Registry::new();
+        // Only fails for duplicate metrics, which a fresh registry doesn't have
+        registry.register(Box::new(self.clone())).unwrap();
+        registry
+    }
+
+    pub(crate) fn event(&self, event: &ConnectionEvent) {
+        let current = match event {
+            ConnectionEvent::Connected | ConnectionEvent::Reconnecting => "connecting",
+            ConnectionEvent::GreetingReceived => "ready",
+            ConnectionEvent::Closed { .. } => "closed",
+            ConnectionEvent::KeepaliveSent => return,
+        };
+
+        for state in STATES {
+            self.state
+                .with_label_values(&[state])
+                .set((state == current).into());
+        }
+    }
+
+    pub(crate) fn requests(&self, in_flight: usize, queued: usize) {
+        self.in_flight.set(in_flight as i64);
+        self.queued.set(queued as i64);
+    }
+
+    pub(crate) fn response(&self, command: &str, code: ResultCode, queue_depth: Option<u32>) {
+        self.responses
+            .with_label_values(&[command, &code.code().to_string()])
+            .inc();
+
+        match (code, queue_depth) {
+            (ResultCode::CommandCompletedSuccessfullyNoMessages, _) => self.poll_queue.set(0),
+            (_, Some(depth)) => self.poll_queue.set(depth.into()),
+            (_, None) => {}
+        }
+    }
+}
+
+impl Collector for Metrics {
+    fn desc(&self) -> Vec<&Desc> {
+        let mut desc = self.state.desc();
+        desc.extend(self.in_flight.desc());
+        desc.extend(self.queued.desc());
+        desc.extend(self.poll_queue.desc());
+        desc.extend(self.responses.desc());
+        desc
+    }
+
+    fn collect(&self) -> Vec<MetricFamily> {
+        let mut families = self.state.collect();
+        families.extend(self.in_flight.collect());
+        families.extend(self.queued.collect());
+        families.extend(self.poll_queue.collect());
+        families.extend(self.responses.collect());
+        families
+    }
+}
+
+const STATES: [&str; 3] = ["connecting", "ready", "closed"];
@@ -0,0 +1,341 @@
+//! Mapping for EURid name server group objects
+//!
+//! A name server group holds up to nine name servers, and can be used in place of the name
+//! servers of a .eu domain. Updating the group changes the delegation of all domains that use
+//! it. See the EURid registration guidelines for the `nsgroup-1.1` mapping.
+
+use instant_xml::{FromXml, ToXml};
+
+use crate::common::{CheckResults, Checked, NoExtension, EPP_XMLNS};
+use crate::request::{Command, Transaction};
+use crate::Error;
+
+pub const XMLNS: &str = "http://www.eurid.eu/xml/epp/nsgroup-1.1";
+
+/// The maximum number of name servers in a group
+pub const MAX_NS: usize = 9;
+
+fn check_ns(name: &str, ns: &[&str]) -> Result<(), Error> {
+    match ns.len() {
+        len if len > MAX_NS => Err(Error::Other(
+            format!("{name}: {len} name servers, but a group holds at most {MAX_NS}").into(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+// Check
+
+impl Transaction<NoExtension> for NsGroupCheck<'_> {}
+
+impl Command for NsGroupCheck<'_> {
+    type Response = CheckData;
+    const COMMAND: &'static str = "check";
+
+    fn object_ids(&self) -> Vec<&str> {
+        self.list.names.to_vec()
+    }
+
+    fn object_uris(&self) -> Vec<&str> {
+        vec![XMLNS]
+    }
+}
+
+impl<'a> NsGroupCheck<'a> {
+    pub fn new(names: &'a [&'a str]) -> Self {
+        Self {
+            list: NsGroupList { names },
+        }
+    }
+}
+
+#[derive(Debug, ToXml)]
+#[xml(rename = "check", ns(XMLNS))]
+struct NsGroupList<'a> {
+    #[xml(rename = "name")]
+    names: &'a [&'a str],
+}
+
+/// Type for EPP XML `<check>` command for name server groups
+#[derive(Debug, ToXml)]
+#[xml(rename = "check", ns(EPP_XMLNS))]
+pub struct NsGroupCheck<'a> {
+    list: NsGroupList<'a>,
+}
+
+#[derive(Debug, FromXml)]
+#[xml(rename = "name", ns(XMLNS))]
+pub struct Name {
+    #[xml(attribute, rename = "avail")]
+    pub available: bool,
+    #[xml(direct)]
+    pub value: String,
+}
+
+/// A single result from a name server group `<check>` response
+#[derive(Debug, FromXml)]
+#[xml(rename = "cd", ns(XMLNS))]
+pub struct CheckedNsGroup {
+    pub name: Name,
+    pub reason: Option<String>,
+}
+
+impl Checked for CheckedNsGroup {
+    fn id(&self) -> &str {
+        &self.name.value
+    }
+
+    fn available(&self) -> bool {
+        self.name.available
+    }
+
+    fn reason(&self) -> Option<&str> {
+        self.reason.as_deref()
+    }
+}
+
+/// Type that represents the `<chkData>` tag for name server group check response
+#[derive(Debug, FromXml)]
+#[xml(rename = "chkData", ns(XMLNS))]
+pub struct CheckData {
+    pub list: Vec<CheckedNsGroup>,
+}
+
+impl CheckResults for CheckData {
+    type Item = CheckedNsGroup;
+
+    fn results(&self) -> &[CheckedNsGroup] {
+        &self.list
+    }
+}
+
+// Create
+
+impl Transaction<NoExtension> for NsGroupCreate<'_> {}
+
+impl Command for NsGroupCreate<'_> {
+    type Response = ();
+    const COMMAND: &'static str = "create";
+
+    fn validate(&self) -> Result<(), Error> {
+        check_ns(self.group.name, self.group.ns)
+    }
+
+    fn object_ids(&self) -> Vec<&str> {
+        vec![self.group.name]
+    }
+
+    fn object_uris(&self) -> Vec<&str> {
+        vec![XMLNS]
+    }
+}
+
+impl<'a> NsGroupCreate<'a> {
+    pub fn new(name: &'a str, ns: &'a [&'a str]) -> Self {
+        Self {
+            group: NsGroupCreateRequest { name, ns },
+        }
+    }
+}
+
+#[derive(Debug, ToXml)]
+#[xml(rename = "create", ns(XMLNS))]
+struct NsGroupCreateRequest<'a> {
+    name: &'a str,
+    ns: &'a [&'a str],
+}
+
+/// Type for EPP XML `<create>` command for name server groups
+#[derive(Debug, ToXml)]
+#[xml(rename = "create", ns(EPP_XMLNS))]
+pub struct NsGroupCreate<'a> {
+    group: NsGroupCreateRequest<'a>,
+}
+
+// Update
+
+impl Transaction<NoExtension> for NsGroupUpdate<'_> {}
+
+impl Command for NsGroupUpdate<'_> {
+    type Response = ();
+    const COMMAND: &'static str = "update";
+
+    fn validate(&self) -> Result<(), Error> {
+        check_ns(self.group.name, self.group.ns)
+    }
+
+    fn object_ids(&self) -> Vec<&str> {
+        vec![self.group.name]
+    }
+
+    fn object_uris(&self) -> Vec<&str> {
+        vec![XMLNS]
+    }
+}
+
+impl<'a> NsGroupUpdate<'a> {
+    /// Replace the name servers of the group `name` with `ns`
+    pub fn new(name: &'a str, ns: &'a [&'a str]) -> Self {
+        Self {
+            group: NsGroupUpdateRequest { name, ns },
+        }
+    }
+}
+
+#[derive(Debug, ToXml)]
+#[xml(rename = "update", ns(XMLNS))]
+struct NsGroupUpdateRequest<'a> {
+    name: &'a str,
+    ns: &'a [&'a str],
+}
+
+/// Type for EPP XML `<update>` command for name server groups
+#[derive(Debug, ToXml)]
+#[xml(rename = "update", ns(EPP_XMLNS))]
+pub struct NsGroupUpdate<'a> {
+    group: NsGroupUpdateRequest<'a>,
+}
+
+// Delete
+
+impl Transaction<NoExtension> for NsGroupDelete<'_> {}
+
+impl Command for NsGroupDelete<'_> {
+    type Response = ();
+    const COMMAND: &'static str = "delete";
+
+    fn object_ids(&self) -> Vec<&str> {
+        vec![self.group.name]
+    }
+
+    fn object_uris(&self) -> Vec<&str> {
+        vec![XMLNS]
+    }
+}
+
+impl<'a> NsGroupDelete<'a> {
+    pub fn new(name: &'a str) -> Self {
+        Self {
+            group: NsGroupDeleteRequest { name },
+        }
+    }
+}
+
+#[derive(Debug, ToXml)]
+#[xml(rename = "delete", ns(XMLNS))]
+struct NsGroupDeleteRequest<'a> {
+    name: &'a str,
+}
+
+/// Type for EPP XML `<delete>` command for name server groups
+#[derive(Debug, ToXml)]
+#[xml(rename = "delete", ns(EPP_XMLNS))]
+pub struct NsGroupDelete<'a> {
+    group: NsGroupDeleteRequest<'a>,
+}
+
+// Info
+
+impl Transaction<NoExtension> for NsGroupInfo<'_> {}
+
+impl Command for NsGroupInfo<'_> {
+    type Response = InfoData;
+    const COMMAND: &'static str = "info";
+
+    fn object_ids(&self) -> Vec<&str> {
+        vec![self.group.name]
+    }
+
+    fn object_uris(&self) -> Vec<&str> {
+        vec![XMLNS]
+    }
+}
+
+impl<'a> NsGroupInfo<'a> {
+    pub fn new(name: &'a str) -> Self {
+        Self {
+            group: NsGroupInfoRequest { name },
+        }
+    }
+}
+
+#[derive(Debug, ToXml)]
+#[xml(rename = "info", ns(XMLNS))]
+struct NsGroupInfoRequest<'a> {
+    name: &'a str,
+}
+
+/// Type for EPP XML `<info>` command for name server groups
+#[derive(Debug, ToXml)]
+#[xml(rename = "info", ns(EPP_XMLNS))]
+pub struct NsGroupInfo<'a> {
+    group: NsGroupInfoRequest<'a>,
+}
+
+/// Type that represents the `<infData>` tag for name server group info response
+#[derive(Debug, FromXml)]
+#[xml(rename = "infData", ns(XMLNS))]
+pub struct InfoData {
+    /// The name of the group
+    pub name: String,
+    /// The host names of the name servers in the group
+    pub ns: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NsGroupCheck, NsGroupCreate, NsGroupDelete, NsGroupInfo, NsGroupUpdate};
+    use crate::common::CheckResults;
+    use crate::response::ResultCode;
+    use crate::tests::{assert_serialized, response_from_file, CLTRID, SVTRID};
+
+    #[test]
+    fn check() {
+        assert_serialized(
+            "request/nsgroup/check.xml",
+            &NsGroupCheck::new(&["nsg-1", "nsg-2"]),
+        );
+
+        let object = response_from_file::<NsGroupCheck>("response/nsgroup/check.xml");
+        let data = object.res_data().unwrap();
+        assert_eq!(data.available().collect::<Vec<_>>(), ["nsg-2"]);
+        assert_eq!(data.list[0].reason.as_deref(), Some("In use"));
+    }
+
+    #[test]
+    fn create() {
+        let ns = ["ns1.example.eu", "ns2.example.eu"];
+        assert_serialized(
+            "request/nsgroup/create.xml",
+            &NsGroupCreate::new("nsg-1", &ns),
+        );
+        assert_serialized(
+            "request/nsgroup/update.xml",
+            &NsGroupUpdate::new("nsg-1", &ns),
+        );
+
+        let ns = ["ns.example.eu"; 10];
+        let err = crate::xml::serialize_request(&NsGroupCreate::new("nsg-1", &ns), CLTRID);
+        assert_eq!(
+            err.unwrap_err().to_string(),
+            "error: nsg-1: 10 name servers, but a group holds at most 9"
+        );
+    }
+
+    #[test]
+    fn delete() {
+        assert_serialized("request/nsgroup/delete.xml", &NsGroupDelete::new("nsg-1"));
+    }
+
+    #[test]
+    fn info() {
+        assert_serialized("request/nsgroup/info.xml", &NsGroupInfo::new("nsg-1"));
+
+        let object = response_from_file::<NsGroupInfo>("response/nsgroup/info.xml");
+        assert_eq!(object.result.code, ResultCode::CommandCompletedSuccessfully);
+        let data = object.res_data().unwrap();
+        assert_eq!(data.name, "nsg-1");
+        assert_eq!(data.ns, ["ns1.example.eu", "ns2.example.eu"]);
+        assert_eq!(object.tr_ids.server_tr_id, SVTRID);
+    }
+}
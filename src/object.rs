@@ -0,0 +1,372 @@
+//! Generic commands for registry-defined object mappings
+//!
+//! Some registries expose object types beyond domains, hosts and contacts, such as the name
+//! server groups and key groups at EURid. Their commands follow the same pattern as the standard
+//! object mappings: the EPP command element wraps an element of the same name in the object's
+//! namespace, which contains the object ID and other object-specific elements. A [`Custom`]
+//! object describes the namespace and the name of the ID element, and creates commands that
+//! can be sent without a typed module for the object:
+//!
+//! ```
+//! use instant_epp::object::Custom;
+//!
+//! let nsgroup = Custom::new("http://www.eurid.eu/xml/epp/nsgroup-1.1", "name");
+//! let check = nsgroup.check(&["group-1", "group-2"]);
+//! let create = nsgroup.create("group-1", &[("ns", "ns1.example.eu"), ("ns", "ns2.example.eu")]);
+//! ```
+//!
+//! Responses are not typed either: `<check>` responses are parsed into [`CheckData`], others are
+//! captured as [`RawXml`]. For objects that need more than this, see the [`custom`](crate::custom)
+//! module.
+
+use instant_xml::ser::Context;
+use instant_xml::{AnyElement, FromXml, Serializer, ToXml};
+
+use crate::common::{CheckResults, Checked, NoExtension, RawXml, EPP_XMLNS};
+use crate::request::{Command, Transaction};
+
+/// A registry-defined object type, see the [module documentation](self)
+#[derive(Clone, Copy, Debug)]
+pub struct Custom<'a> {
+    xmlns: &'static str,
+    id: &'a str,
+}
+
+impl<'a> Custom<'a> {
+    /// An object type in namespace `xmlns`, identified by the contents of its `id` element
+    pub fn new(xmlns: &'static str, id: &'a str) -> Self {
+        Self { xmlns, id }
+    }
+
+    /// Check whether objects with these `ids` can be created
+    pub fn check(&self, ids: &'a [&'a str]) -> ObjectCheck<'a> {
+        ObjectCheck { object: *self, ids }
+    }
+
+    /// Retrieve information about the object with ID `id`
+    pub fn info(&self, id: &'a str) -> ObjectInfo<'a> {
+        ObjectInfo { object: *self, id }
+    }
+
+    /// Create an object with ID `id`
+    ///
+    /// Each of the `fields` is serialized as an element with text content in the object's
+    /// namespace, after the ID and in the given order.
+    pub fn create(&self, id: &'a str, fields: &'a [(&'a str, &'a str)]) -> ObjectCreate<'a> {
+        ObjectCreate {
+            object: *self,
+            id,
+            fields,
+        }
+    }
+
+    /// Delete the object with ID `id`
+    pub fn delete(&self, id: &'a str) -> ObjectDelete<'a> {
+        ObjectDelete { object: *self, id }
+    }
+
+    fn serialize<W: std::fmt::Write + ?Sized>(
+        &self,
+        command: &'static str,
+        ids: &[&str],
+        fields: &[(&str, &str)],
+        serializer: &mut Serializer<W>,
+    ) -> Result<(), instant_xml::Error> {
+        let mut cx = Context::<0>::default();
+        cx.default_ns = EPP_XMLNS;
+        let outer = serializer.write_start(command, EPP_XMLNS, Some(cx))?;
+        serializer.end_start()?;
+
+        let mut cx = Context::<0>::default();
+        cx.default_ns = self.xmlns;
+        let inner = serializer.write_start(command, self.xmlns, Some(cx))?;
+        serializer.end_start()?;
+
+        let ids = ids.iter().map(|id| (self.id, *id));
+        for (name, value) in ids.chain(fields.iter().copied()) {
+            let element = serializer.write_start(name, self.xmlns, None::<Context<0>>)?;
+            serializer.end_start()?;
+            value.serialize(None, serializer)?;
+            serializer.write_close(element)?;
+        }
+
+        serializer.write_close(inner)?;
+        serializer.write_close(outer)
+    }
+}
+
+/// A `<check>` command for a [`Custom`] object
+#[derive(Debug)]
+pub struct ObjectCheck<'a> {
+    object: Custom<'a>,
+    ids: &'a [&'a str],
+}
+
+impl Transaction<NoExtension> for ObjectCheck<'_> {}
+
+impl Command for ObjectCheck<'_> {
+    type Response = CheckData;
+    const COMMAND: &'static str = "check";
+
+    fn object_ids(&self) -> Vec<&str> {
+        self.ids.to_vec()
+    }
+
+    fn object_uris(&self) -> Vec<&str> {
+        vec![self.object.xmlns]
+    }
+}
+
+impl ToXml for ObjectCheck<'_> {
+    fn serialize<W: std::fmt::Write + ?Sized>(
+        &self,
+        _: Option<instant_xml::Id<'_>>,
+        serializer: &mut Serializer<W>,
+    ) -> Result<(), instant_xml::Error> {
+        self.object
+            .serialize(Self::COMMAND, self.ids, &[], serializer)
+    }
+}
+
+/// An `<info>` command for a [`Custom`] object
+#[derive(Debug)]
+pub struct ObjectInfo<'a> {
+    object: Custom<'a>,
+    id: &'a str,
+}
+
+impl Transaction<NoExtension> for ObjectInfo<'_> {}
+
+impl Command for ObjectInfo<'_> {
+    type Response = RawXml;
+    const COMMAND: &'static str = "info";
+
+    fn object_ids(&self) -> Vec<&str> {
+        vec![self.id]
+    }
+
+    fn object_uris(&self) -> Vec<&str> {
+        vec![self.object.xmlns]
+    }
+}
+
+impl ToXml for ObjectInfo<'_> {
+    fn serialize<W: std::fmt::Write + ?Sized>(
+        &self,
+        _: Option<instant_xml::Id<'_>>,
+        serializer: &mut Serializer<W>,
+    ) -> Result<(), instant_xml::Error> {
+        self.object
+            .serialize(Self::COMMAND, &[self.id], &[], serializer)
+    }
+}
+
+/// A `<create>` command for a [`Custom`] object
+#[derive(Debug)]
+pub struct ObjectCreate<'a> {
+    object: Custom<'a>,
+    id: &'a str,
+    fields: &'a [(&'a str, &'a str)],
+}
+
+impl Transaction<NoExtension> for ObjectCreate<'_> {}
+
+impl Command for ObjectCreate<'_> {
+    type Response = RawXml;
+    const COMMAND: &'static str = "create";
+
+    fn object_ids(&self) -> Vec<&str> {
+        vec![self.id]
+    }
+
+    fn object_uris(&self) -> Vec<&str> {
+        vec![self.object.xmlns]
+    }
+}
+
+impl ToXml for ObjectCreate<'_> {
+    fn serialize<W: std::fmt::Write + ?Sized>(
+        &self,
+        _: Option<instant_xml::Id<'_>>,
+        serializer: &mut Serializer<W>,
+    ) -> Result<(), instant_xml::Error> {
+        self.object
+            .serialize(Self::COMMAND, &[self.id], self.fields, serializer)
+    }
+}
+
+/// A `<delete>` command for a [`Custom`] object
+#[derive(Debug)]
+pub struct ObjectDelete<'a> {
+    object: Custom<'a>,
+    id: &'a str,
+}
+
+impl Transaction<NoExtension> for ObjectDelete<'_> {}
+
+impl Command for ObjectDelete<'_> {
+    type Response = ();
+    const COMMAND: &'static str = "delete";
+
+    fn object_ids(&self) -> Vec<&str> {
+        vec![self.id]
+    }
+
+    fn object_uris(&self) -> Vec<&str> {
+        vec![self.object.xmlns]
+    }
+}
+
+impl ToXml for ObjectDelete<'_> {
+    fn serialize<W: std::fmt::Write + ?Sized>(
+        &self,
+        _: Option<instant_xml::Id<'_>>,
+        serializer: &mut Serializer<W>,
+    ) -> Result<(), instant_xml::Error> {
+        self.object
+            .serialize(Self::COMMAND, &[self.id], &[], serializer)
+    }
+}
+
+/// A single result from the `<chkData>` of a [`Custom`] object
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CheckedObject {
+    pub id: String,
+    pub available: bool,
+    pub reason: Option<String>,
+}
+
+impl Checked for CheckedObject {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn available(&self) -> bool {
+        self.available
+    }
+
+    fn reason(&self) -> Option<&str> {
+        self.reason.as_deref()
+    }
+}
+
+/// The `<chkData>` from a `<check>` response for a [`Custom`] object
+///
+/// Each `<cd>` element is expected to contain the object ID with an `avail` attribute, and an
+/// optional `<reason>`, as in the standard object mappings.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CheckData {
+    pub list: Vec<CheckedObject>,
+}
+
+impl CheckResults for CheckData {
+    type Item = CheckedObject;
+
+    fn results(&self) -> &[CheckedObject] {
+        &self.list
+    }
+}
+
+impl<'xml> FromXml<'xml> for CheckData {
+    fn matches(id: instant_xml::Id<'_>, _: Option<instant_xml::Id<'_>>) -> bool {
+        id.name == "chkData"
+    }
+
+    fn deserialize<'cx>(
+        into: &mut Self::Accumulator,
+        field: &'static str,
+        deserializer: &mut instant_xml::Deserializer<'cx, 'xml>,
+    ) -> Result<(), instant_xml::Error> {
+        let mut elem = None;
+        <AnyElement as FromXml>::deserialize(&mut elem, field, deserializer)?;
+        let Some(elem) = elem else {
+            return Err(instant_xml::Error::MissingValue(field));
+        };
+
+        let mut list = Vec::new();
+        for cd in elem.children.iter().filter(|child| child.name == "cd") {
+            let mut children = cd.children.iter();
+            let Some(id) = children.next() else {
+                return Err(instant_xml::Error::MissingValue("CheckedObject::id"));
+            };
+
+            let available = match id.attributes.iter().find(|attr| attr.name == "avail") {
+                Some(attr) => matches!(&*attr.value, "1" | "true"),
+                None => return Err(instant_xml::Error::MissingValue("CheckedObject::available")),
+            };
+
+            list.push(CheckedObject {
+                id: id.text.as_deref().unwrap_or_default().trim().to_owned(),
+                available,
+                reason: children
+                    .find(|child| child.name == "reason")
+                    .and_then(|reason| reason.text.as_deref())
+                    .map(|reason| reason.trim().to_owned()),
+            });
+        }
+
+        *into = Some(Self { list });
+        Ok(())
+    }
+
+    type Accumulator = Option<Self>;
+    const KIND: instant_xml::Kind = instant_xml::Kind::Element;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Custom;
+    use crate::common::CheckResults;
+    use crate::response::ResultCode;
+    use crate::tests::{assert_serialized, response_from_file};
+
+    const XMLNS: &str = "http://www.eurid.eu/xml/epp/nsgroup-1.1";
+
+    #[test]
+    fn command() {
+        let nsgroup = Custom::new(XMLNS, "name");
+        assert_serialized(
+            "request/object/check.xml",
+            &nsgroup.check(&["nsg-1", "nsg-2"]),
+        );
+        assert_serialized(
+            "request/object/create.xml",
+            &nsgroup.create(
+                "nsg-1",
+                &[("ns", "ns1.example.eu"), ("ns", "ns2.example.eu")],
+            ),
+        );
+        assert_serialized(
+            "request/object/create_escaped.xml",
+            &nsgroup.create("nsg&1", &[("ns", "<ns>ns1.example.eu</ns>")]),
+        );
+        assert_serialized("request/object/info.xml", &nsgroup.info("nsg-1"));
+        assert_serialized("request/object/delete.xml", &nsgroup.delete("nsg-1"));
+    }
+
+    #[test]
+    fn check_response() {
+        let object = response_from_file::<super::ObjectCheck>("response/object/check.xml");
+        assert_eq!(object.result.code, ResultCode::CommandCompletedSuccessfully);
+        let data = object.res_data().unwrap();
+        assert_eq!(data.available().collect::<Vec<_>>(), ["nsg-2"]);
+        assert_eq!(data.list[0].id, "nsg-1");
+        assert_eq!(data.list[0].reason.as_deref(), Some("In use"));
+    }
+
+    #[test]
+    fn info_response() {
+        let object = response_from_file::<super::ObjectInfo>("response/object/info.xml");
+        let data = object.res_data().unwrap();
+        assert_eq!(data.ns(), XMLNS);
+        assert_eq!(data.name(), "infData");
+        let ns = data
+            .0
+            .children
+            .iter()
+            .filter(|child| child.name == "ns")
+            .filter_map(|child| child.text.as_deref())
+            .collect::<Vec<_>>();
+        assert_eq!(ns, ["ns1.example.eu", "ns2.example.eu"]);
+    }
+}
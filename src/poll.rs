@@ -1,13 +1,19 @@
+use std::fmt::{self, Debug};
+use std::marker::PhantomData;
+
+use chrono::{DateTime, Utc};
 use instant_xml::ser::Context;
-use instant_xml::{FromXml, ToXml};
+use instant_xml::{Deserializer, FromXml, FromXmlOwned, ToXml};
 
-use crate::common::{NoExtension, EPP_XMLNS};
+use crate::common::{deserialize_date, NoExtension, RawXml, EPP_XMLNS};
+use crate::contact;
 use crate::domain;
 use crate::domain::transfer::TransferData;
 use crate::extensions::low_balance::LowBalance;
 use crate::extensions::rgp::poll::RgpPollData;
 use crate::host;
-use crate::request::{Command, Transaction};
+use crate::request::{Command, Extension, Transaction};
+use crate::response::ResponseTRID;
 
 impl Transaction<NoExtension> for Poll {}
 
@@ -16,6 +22,24 @@ impl Command for Poll {
     const COMMAND: &'static str = "poll";
 }
 
+impl<T: FromXmlOwned + Debug> Transaction<NoExtension> for PollWith<T> {}
+
+impl<T: FromXmlOwned + Debug> Command for PollWith<T> {
+    type Response = PollDataWith<T>;
+    const COMMAND: &'static str = "poll";
+}
+
+impl<T: FromXmlOwned + Debug> Transaction<PollExtension<T>> for Poll {}
+
+impl<T: FromXmlOwned + Debug, U: FromXmlOwned + Debug> Transaction<PollExtension<U>>
+    for PollWith<T>
+{
+}
+
+impl<T: FromXmlOwned + Debug> Extension for PollExtension<T> {
+    type Response = T;
+}
+
 impl Transaction<NoExtension> for Ack<'_> {}
 
 impl Command for Ack<'_> {
@@ -41,6 +65,82 @@ impl ToXml for Poll {
     }
 }
 
+/// Type for EPP XML `<poll>` command with `op="req"`, for registry-specific message data
+///
+/// Sends the same request as [`Poll`], but the response data is first matched against `T`,
+/// falling back to the types in [`PollData`] if it doesn't match. This allows types for
+/// messages that this crate doesn't know about to be defined elsewhere. Use an enum with
+/// `#[xml(forward)]` for `T` to support several of them.
+pub struct PollWith<T> {
+    data: PhantomData<fn() -> T>,
+}
+
+impl<T> PollWith<T> {
+    pub fn new() -> Self {
+        Self { data: PhantomData }
+    }
+}
+
+impl<T> Default for PollWith<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Debug for PollWith<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PollWith").finish()
+    }
+}
+
+impl<T> ToXml for PollWith<T> {
+    fn serialize<W: std::fmt::Write + ?Sized>(
+        &self,
+        field: Option<instant_xml::Id<'_>>,
+        serializer: &mut instant_xml::Serializer<W>,
+    ) -> Result<(), instant_xml::Error> {
+        Poll.serialize(field, serializer)
+    }
+}
+
+/// Extension data in a poll response, parsed as `T`
+///
+/// Poll messages can carry extension data along with the `<resData>`, such as the fees for a
+/// completed transfer. Used as the extension for a [`Poll`] or [`PollWith`], nothing is added
+/// to the request, while the `<extension>` of the response is parsed as `T`. Use an enum with
+/// `#[xml(forward)]` for `T` if the queue holds messages with different extensions.
+pub struct PollExtension<T> {
+    data: PhantomData<fn() -> T>,
+}
+
+impl<T> PollExtension<T> {
+    pub fn new() -> Self {
+        Self { data: PhantomData }
+    }
+}
+
+impl<T> Default for PollExtension<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Debug for PollExtension<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PollExtension").finish()
+    }
+}
+
+impl<T> ToXml for PollExtension<T> {
+    fn serialize<W: std::fmt::Write + ?Sized>(
+        &self,
+        _: Option<instant_xml::Id<'_>>,
+        _: &mut instant_xml::Serializer<W>,
+    ) -> Result<(), instant_xml::Error> {
+        Ok(())
+    }
+}
+
 /// Type for EPP XML `<poll>` command with `op="ack"`
 #[derive(Debug)]
 pub struct Ack<'a> {
@@ -66,6 +166,7 @@ impl ToXml for Ack<'_> {
 /// Type that represents the `<resData>` tag for message poll response
 #[derive(Debug, FromXml)]
 #[xml(forward)]
+#[non_exhaustive]
 pub enum PollData {
     /// Data under the `<domain:trnData>` tag
     DomainTransfer(TransferData),
@@ -77,14 +178,228 @@ pub enum PollData {
     LowBalance(LowBalance),
     /// Data under the `<rgp-poll:pollData>` tag
     RgpPoll(RgpPollData),
+    /// Data under the `<domain:panData>`, `<host:panData>` or `<contact:panData>` tag
+    PendingAction(PendingActionData),
+    /// Data this crate has no type for
+    ///
+    /// This lets such messages be logged and acknowledged like any other.
+    Unknown(RawXml),
+}
+
+/// The outcome of a command that was answered with `1001` (action pending)
+///
+/// Registries report the outcome through the poll queue, identifying the command by the
+/// transaction IDs of its response. See `workflows::pending` for a way to match these back to
+/// the original commands.
+#[derive(Debug, Eq, PartialEq)]
+pub struct PendingActionData {
+    /// The type of object the action was requested for
+    pub object: PendingObject,
+    /// The domain or host name, or contact ID
+    pub id: String,
+    /// Whether the action was completed (`true`) or rejected (`false`)
+    pub result: bool,
+    /// The transaction IDs of the command that requested the action
+    pub tr_ids: ResponseTRID,
+    /// When the action was completed or rejected
+    pub date: DateTime<Utc>,
+}
+
+/// The object type of [`PendingActionData`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum PendingObject {
+    Domain,
+    Host,
+    Contact,
+}
+
+impl<'xml> FromXml<'xml> for PendingActionData {
+    fn matches(id: instant_xml::Id<'_>, field: Option<instant_xml::Id<'_>>) -> bool {
+        PanData::matches(id, field)
+    }
+
+    fn deserialize<'cx>(
+        into: &mut Self::Accumulator,
+        field: &'static str,
+        deserializer: &mut Deserializer<'cx, 'xml>,
+    ) -> Result<(), instant_xml::Error> {
+        let mut value = None;
+        PanData::deserialize(&mut value, field, deserializer)?;
+        *into = value.map(|data| match data {
+            PanData::Domain(data) => Self {
+                object: PendingObject::Domain,
+                id: data.name.value,
+                result: data.name.result,
+                tr_ids: data.tr_ids.into(),
+                date: data.date,
+            },
+            PanData::Host(data) => Self {
+                object: PendingObject::Host,
+                id: data.name.value,
+                result: data.name.result,
+                tr_ids: data.tr_ids.into(),
+                date: data.date,
+            },
+            PanData::Contact(data) => Self {
+                object: PendingObject::Contact,
+                id: data.id.value,
+                result: data.id.result,
+                tr_ids: data.tr_ids.into(),
+                date: data.date,
+            },
+        });
+
+        Ok(())
+    }
+
+    type Accumulator = Option<Self>;
+    const KIND: instant_xml::Kind = PanData::KIND;
+}
+
+#[derive(Debug, FromXml)]
+#[xml(forward)]
+enum PanData {
+    Domain(DomainPanData),
+    Host(HostPanData),
+    Contact(ContactPanData),
+}
+
+#[derive(Debug, FromXml)]
+#[xml(rename = "panData", ns(domain::XMLNS))]
+struct DomainPanData {
+    name: DomainPaName,
+    #[xml(rename = "paTRID")]
+    tr_ids: DomainPaTrId,
+    #[xml(rename = "paDate", deserialize_with = "deserialize_date")]
+    date: DateTime<Utc>,
+}
+
+#[derive(Debug, FromXml)]
+#[xml(rename = "name", ns(domain::XMLNS))]
+struct DomainPaName {
+    #[xml(attribute, rename = "paResult")]
+    result: bool,
+    #[xml(direct)]
+    value: String,
+}
+
+#[derive(Debug, FromXml)]
+#[xml(rename = "paTRID", ns(domain::XMLNS))]
+struct DomainPaTrId {
+    #[xml(rename = "clTRID", ns(EPP_XMLNS))]
+    client_tr_id: Option<String>,
+    #[xml(rename = "svTRID", ns(EPP_XMLNS))]
+    server_tr_id: String,
+}
+
+impl From<DomainPaTrId> for ResponseTRID {
+    fn from(tr_ids: DomainPaTrId) -> Self {
+        Self {
+            client_tr_id: tr_ids.client_tr_id,
+            server_tr_id: tr_ids.server_tr_id,
+        }
+    }
+}
+
+#[derive(Debug, FromXml)]
+#[xml(rename = "panData", ns(host::XMLNS))]
+struct HostPanData {
+    name: HostPaName,
+    #[xml(rename = "paTRID")]
+    tr_ids: HostPaTrId,
+    #[xml(rename = "paDate", deserialize_with = "deserialize_date")]
+    date: DateTime<Utc>,
+}
+
+#[derive(Debug, FromXml)]
+#[xml(rename = "name", ns(host::XMLNS))]
+struct HostPaName {
+    #[xml(attribute, rename = "paResult")]
+    result: bool,
+    #[xml(direct)]
+    value: String,
+}
+
+#[derive(Debug, FromXml)]
+#[xml(rename = "paTRID", ns(host::XMLNS))]
+struct HostPaTrId {
+    #[xml(rename = "clTRID", ns(EPP_XMLNS))]
+    client_tr_id: Option<String>,
+    #[xml(rename = "svTRID", ns(EPP_XMLNS))]
+    server_tr_id: String,
+}
+
+impl From<HostPaTrId> for ResponseTRID {
+    fn from(tr_ids: HostPaTrId) -> Self {
+        Self {
+            client_tr_id: tr_ids.client_tr_id,
+            server_tr_id: tr_ids.server_tr_id,
+        }
+    }
+}
+
+#[derive(Debug, FromXml)]
+#[xml(rename = "panData", ns(contact::XMLNS))]
+struct ContactPanData {
+    id: ContactPaId,
+    #[xml(rename = "paTRID")]
+    tr_ids: ContactPaTrId,
+    #[xml(rename = "paDate", deserialize_with = "deserialize_date")]
+    date: DateTime<Utc>,
+}
+
+#[derive(Debug, FromXml)]
+#[xml(rename = "id", ns(contact::XMLNS))]
+struct ContactPaId {
+    #[xml(attribute, rename = "paResult")]
+    result: bool,
+    #[xml(direct)]
+    value: String,
+}
+
+#[derive(Debug, FromXml)]
+#[xml(rename = "paTRID", ns(contact::XMLNS))]
+struct ContactPaTrId {
+    #[xml(rename = "clTRID", ns(EPP_XMLNS))]
+    client_tr_id: Option<String>,
+    #[xml(rename = "svTRID", ns(EPP_XMLNS))]
+    server_tr_id: String,
+}
+
+impl From<ContactPaTrId> for ResponseTRID {
+    fn from(tr_ids: ContactPaTrId) -> Self {
+        Self {
+            client_tr_id: tr_ids.client_tr_id,
+            server_tr_id: tr_ids.server_tr_id,
+        }
+    }
+}
+
+/// Type that represents the `<resData>` tag for a [`PollWith`] response
+#[derive(Debug, FromXml)]
+#[xml(forward)]
+#[non_exhaustive]
+pub enum PollDataWith<T> {
+    /// Data matching the caller's type
+    Custom(T),
+    /// Any other data
+    Standard(Box<PollData>),
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{Ack, Poll, PollData};
+    use instant_xml::FromXml;
+
+    use super::{Ack, PendingObject, Poll, PollData, PollDataWith, PollExtension, PollWith};
+    use crate::domain::transfer::TransferStatus;
+    use crate::extensions::fee;
     use crate::host::Status;
     use crate::response::ResultCode;
-    use crate::tests::{assert_serialized, response_from_file, CLTRID, SUCCESS_MSG, SVTRID};
+    use crate::tests::{
+        assert_serialized, response_from_file, response_from_file_with_ext, CLTRID, SUCCESS_MSG,
+        SVTRID,
+    };
 
     use chrono::{TimeZone, Utc};
     use std::net::IpAddr;
@@ -104,8 +419,8 @@ mod tests {
 
         assert_eq!(object.result.code, ResultCode::CommandCompletedSuccessfully);
         assert_eq!(object.result.message, SUCCESS_MSG);
-        assert_eq!(msg.count, 4);
-        assert_eq!(msg.id, "12345".to_string());
+        assert_eq!(msg.count(), Some(4));
+        assert_eq!(msg.id(), Some("12345"));
         assert_eq!(object.tr_ids.server_tr_id, SVTRID);
     }
 
@@ -129,26 +444,26 @@ mod tests {
             object.result.message,
             "Command completed successfully; ack to dequeue"
         );
-        assert_eq!(msg.count, 5);
-        assert_eq!(msg.id, "12345".to_string());
+        assert_eq!(msg.count(), Some(5));
+        assert_eq!(msg.id(), Some("12345"));
         assert_eq!(
             msg.date,
             Utc.with_ymd_and_hms(2021, 7, 23, 19, 12, 43).single()
         );
-        assert_eq!(msg.message.as_ref().unwrap().text, "Transfer requested.");
+        assert_eq!(msg.text(), Some("Transfer requested."));
 
         if let PollData::DomainTransfer(tr) = &result {
             assert_eq!(tr.name, "eppdev-transfer.com");
             assert_eq!(tr.transfer_status, "pending");
-            assert_eq!(tr.requester_id, "eppdev");
+            assert_eq!(tr.requester_id.as_deref(), Some("eppdev"));
             assert_eq!(
                 tr.requested_at,
-                Utc.with_ymd_and_hms(2021, 7, 23, 15, 31, 21).unwrap()
+                Utc.with_ymd_and_hms(2021, 7, 23, 15, 31, 21).single()
             );
-            assert_eq!(tr.ack_id, "ClientY");
+            assert_eq!(tr.ack_id.as_deref(), Some("ClientY"));
             assert_eq!(
                 tr.ack_by,
-                Utc.with_ymd_and_hms(2021, 7, 28, 15, 31, 21).unwrap()
+                Utc.with_ymd_and_hms(2021, 7, 28, 15, 31, 21).single()
             );
             assert_eq!(
                 tr.expiring_at,
@@ -176,19 +491,19 @@ mod tests {
             object.result.message,
             "Command completed successfully; ack to dequeue"
         );
-        assert_eq!(msg.count, 4);
-        assert_eq!(msg.id, "12345".to_string());
+        assert_eq!(msg.count(), Some(4));
+        assert_eq!(msg.id(), Some("12345"));
         assert_eq!(
             msg.date,
             Utc.with_ymd_and_hms(2022, 1, 2, 11, 30, 45).single()
         );
-        assert_eq!(msg.message.as_ref().unwrap().text, "Unused objects policy");
+        assert_eq!(msg.text(), Some("Unused objects policy"));
 
         if let PollData::HostInfo(host) = &result {
             assert_eq!(host.name, "ns.test.com");
 
             assert_eq!(host.roid, "1234");
-            assert!(host.statuses.contains(&Status::Ok));
+            assert!(host.statuses.iter().any(|s| *s == Status::Ok));
             assert!(host
                 .addresses
                 .iter()
@@ -212,6 +527,107 @@ mod tests {
         assert_eq!(object.tr_ids.server_tr_id, SVTRID);
     }
 
+    #[test]
+    fn pending_action_response() {
+        let object = response_from_file::<Poll>("response/poll/poll_domain_pending_action.xml");
+        let Some(PollData::PendingAction(data)) = object.res_data() else {
+            panic!("expected pending action data, got {:?}", object.res_data());
+        };
+
+        assert_eq!(data.object, PendingObject::Domain);
+        assert_eq!(data.id, "eppdev-pending.com");
+        assert!(data.result);
+        assert_eq!(
+            data.tr_ids.client_tr_id.as_deref(),
+            Some("cltrid:1626454800")
+        );
+        assert_eq!(data.tr_ids.server_tr_id, "RO-6879-1627224600000000");
+        assert_eq!(
+            data.date,
+            Utc.with_ymd_and_hms(2021, 7, 23, 19, 12, 43).unwrap()
+        );
+
+        let object = response_from_file::<Poll>("response/poll/poll_contact_pending_action.xml");
+        let Some(PollData::PendingAction(data)) = object.res_data() else {
+            panic!("expected pending action data, got {:?}", object.res_data());
+        };
+
+        assert_eq!(data.object, PendingObject::Contact);
+        assert_eq!(data.id, "eppdev-contact-3");
+        assert!(!data.result);
+        assert_eq!(data.tr_ids.client_tr_id, None);
+        assert_eq!(data.tr_ids.server_tr_id, "RO-6879-1627224600000001");
+    }
+
+    #[test]
+    fn unknown_response() {
+        let object = response_from_file::<Poll>("response/poll/poll_unknown.xml");
+        let msg = object.message_queue().unwrap();
+        assert_eq!(msg.id(), Some("12347"));
+
+        let Some(PollData::Unknown(raw)) = object.res_data() else {
+            panic!("expected unknown poll data, got {:?}", object.res_data());
+        };
+
+        assert_eq!(raw.ns(), "http://www.example.com/epp/notify-1.0");
+        assert_eq!(raw.name(), "notification");
+        assert_eq!(
+            raw.to_string(),
+            "<notification xmlns=\"http://www.example.com/epp/notify-1.0\" kind=\"abuse\">\
+             <object>eppdev.com</object><text>Phishing &amp; malware</text></notification>"
+        );
+    }
+
+    #[test]
+    fn custom_response() {
+        #[derive(Debug, FromXml)]
+        #[xml(rename = "notification", ns("http://www.example.com/epp/notify-1.0"))]
+        struct Notification {
+            #[xml(attribute)]
+            kind: String,
+            object: String,
+            text: String,
+        }
+
+        assert_serialized("request/poll/poll.xml", &PollWith::<Notification>::new());
+
+        let object = response_from_file::<PollWith<Notification>>("response/poll/poll_unknown.xml");
+        let Some(PollDataWith::Custom(notification)) = object.res_data() else {
+            panic!("expected custom poll data, got {:?}", object.res_data());
+        };
+
+        assert_eq!(notification.kind, "abuse");
+        assert_eq!(notification.object, "eppdev.com");
+        assert_eq!(notification.text, "Phishing & malware");
+
+        // Data of other types is parsed as usual
+        let object =
+            response_from_file::<PollWith<Notification>>("response/poll/poll_domain_transfer.xml");
+        let Some(PollDataWith::Standard(data)) = object.res_data() else {
+            panic!("expected standard poll data, got {:?}", object.res_data());
+        };
+        assert!(matches!(**data, PollData::DomainTransfer(_)));
+    }
+
+    #[test]
+    fn extension_response() {
+        let object = response_from_file_with_ext::<Poll, PollExtension<fee::TransferData>>(
+            "response/poll/poll_domain_transfer_fee.xml",
+        );
+
+        let Some(PollData::DomainTransfer(tr)) = object.res_data() else {
+            panic!("unexpected message data: {:?}", object.res_data());
+        };
+        assert_eq!(tr.name, "eppdev-transfer.com");
+        assert_eq!(tr.status().unwrap(), TransferStatus::ServerApproved);
+
+        let fee = object.extension().unwrap();
+        assert_eq!(fee.currency.as_deref(), Some("USD"));
+        assert_eq!(fee.period.as_ref().unwrap().length, 1);
+        assert_eq!(fee.fees[0].amount, "5.00");
+        assert_eq!(fee.fees[0].minor_units("USD").unwrap(), 500);
+    }
+
     #[test]
     fn message_only_response() {
         let object = response_from_file::<Poll>("response/poll/poll_message_only.xml");
@@ -227,18 +643,50 @@ mod tests {
             "Command completed successfully; ack to dequeue"
         );
 
-        assert_eq!(msg.count, 4);
-        assert_eq!(msg.id, "12346".to_string());
+        assert_eq!(msg.count(), Some(4));
+        assert_eq!(msg.id(), Some("12346"));
         assert_eq!(
             msg.date,
             Utc.with_ymd_and_hms(2000, 6, 8, 22, 10, 0).single()
         );
-        assert_eq!(msg.message.as_ref().unwrap().text, "Credit balance low.");
+        assert_eq!(msg.text(), Some("Credit balance low."));
 
         assert_eq!(object.tr_ids.client_tr_id.unwrap(), CLTRID);
         assert_eq!(object.tr_ids.server_tr_id, SVTRID);
     }
 
+    #[test]
+    fn html_message_response() {
+        let object = response_from_file::<Poll>("response/poll/poll_html_message.xml");
+        let msg = object.message_queue().unwrap();
+
+        assert_eq!(msg.count(), Some(2));
+        assert_eq!(msg.id(), Some("12348"));
+        assert_eq!(msg.date(), None);
+        assert_eq!(
+            msg.text(),
+            Some(
+                "Domain &quot;eppdev.com&quot; transferred away.<br>\
+                 Contact&nbsp;support <b>now</b> &amp; quote #42."
+            )
+        );
+        assert_eq!(
+            msg.message.as_ref().unwrap().sanitized(),
+            r#"Domain "eppdev.com" transferred away. Contact support now & quote #42."#
+        );
+    }
+
+    #[test]
+    fn bare_queue_response() {
+        let object = response_from_file::<Poll>("response/poll/poll_bare_queue.xml");
+        let msg = object.message_queue().unwrap();
+
+        assert_eq!(msg.count(), None);
+        assert_eq!(msg.id(), Some("12349"));
+        assert_eq!(msg.date(), None);
+        assert_eq!(msg.text(), None);
+    }
+
     #[test]
     fn empty_queue_response() {
         let object = response_from_file::<Poll>("response/poll/poll_empty_queue.xml");
@@ -0,0 +1,30 @@
+//! The most commonly used types, for a glob import
+//!
+//! ```
+//! use instant_epp::prelude::*;
+//! ```
+//!
+//! This covers the client, the commands for the standard object mappings and the extensions
+//! most registries use. The `<info>` commands for hosts and the name server type for domains
+//! are both called `HostInfo`, so neither is included here; import them from [`host`] or
+//! [`domain`] as needed.
+//!
+//! [`host`]: crate::host
+//! [`domain`]: crate::domain
+
+#[cfg(feature = "client")]
+pub use crate::client::{Connector, EppClient, SharedClient};
+pub use crate::common::{CheckResults, NoExtension};
+pub use crate::contact::{ContactCheck, ContactCreate, ContactDelete, ContactInfo, ContactUpdate};
+pub use crate::domain::{
+    DomainCheck, DomainContact, DomainCreate, DomainDelete, DomainInfo, DomainRenew,
+    DomainTransfer, DomainUpdate, HostObj, Period,
+};
+pub use crate::extensions::namestore::NameStore;
+pub use crate::extensions::{fee, rgp, secdns};
+pub use crate::host::{HostCheck, HostCreate, HostDelete, HostUpdate};
+pub use crate::login::Login;
+pub use crate::logout::Logout;
+pub use crate::poll::{Ack, Poll, PollData};
+pub use crate::response::{Response, ResultCode};
+pub use crate::Error;
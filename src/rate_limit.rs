@@ -0,0 +1,202 @@
+//! Rate limiting of commands sent through an [`EppClient`](crate::EppClient)
+//!
+//! Registries commonly limit how many commands a registrar may send per second, often with
+//! separate limits for queries (like check and info) and transform commands (like create and
+//! update). A [`RateLimiter`] holds a token bucket for each of these classes; commands that
+//! would exceed the rate wait until they fit, rather than being sent and rejected.
+
+use std::time::{Duration, Instant};
+
+use tracing::debug;
+
+/// The classes of commands for rate limiting
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CommandClass {
+    /// Commands that only read data: check, info, poll and transfer queries
+    Query,
+    /// Commands that change data: create, delete, renew, transfer and update
+    Transform,
+    /// Session management (hello, login and logout), which is never limited
+    Session,
+}
+
+impl CommandClass {
+    /// The class of the command named `command`, as in `Command::COMMAND`
+    ///
+    /// Transfers count as transform commands, even for `op="query"`.
+    pub fn of(command: &str) -> Self {
+        match command {
+            "check" | "info" | "poll" => Self::Query,
+            "hello" | "login" | "logout" => Self::Session,
+            _ => Self::Transform,
+        }
+    }
+}
+
+/// A rate limit for one class of commands
+#[derive(Clone, Copy, Debug)]
+pub struct RateLimit {
+    rate: f64,
+    burst: f64,
+}
+
+impl RateLimit {
+    /// Allow `rate` commands per second on average, and up to `burst` at once
+    ///
+    /// Panics if `rate` is not positive.
+    pub fn per_second(rate: f64, burst: u32) -> Self {
+        assert!(rate > 0.0, "rate must be positive");
+        Self {
+            rate,
+            burst: f64::from(burst.max(1)),
+        }
+    }
+}
+
+/// Token bucket rate limiter for queries and transform commands
+///
+/// Set one on a client with `EppClient::set_rate_limiter()`. Classes without a limit are not
+/// limited.
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    queries: Option<Bucket>,
+    transforms: Option<Bucket>,
+}
+
+impl RateLimiter {
+    /// A rate limiter without any limits
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limit queries to `limit`
+    pub fn queries(mut self, limit: RateLimit) -> Self {
+        self.queries = Some(Bucket::new(limit, Instant::now()));
+        self
+    }
+
+    /// Limit transform commands to `limit`
+    pub fn transforms(mut self, limit: RateLimit) -> Self {
+        self.transforms = Some(Bucket::new(limit, Instant::now()));
+        self
+    }
+
+    /// Statistics on throttled commands of the given `class`
+    pub fn stats(&self, class: CommandClass) -> ThrottleStats {
+        self.bucket(class)
+            .map(|bucket| bucket.stats)
+            .unwrap_or_default()
+    }
+
+    /// Wait until a command of the given `class` may be sent
+    pub(crate) async fn acquire(&mut self, class: CommandClass) {
+        let Some(bucket) = self.bucket_mut(class) else {
+            return;
+        };
+
+        let Some(wait) = bucket.reserve(Instant::now()) else {
+            return;
+        };
+
+        debug!("throttling {class:?} command for {wait:?}");
+        crate::runtime::sleep(wait).await;
+    }
+
+    fn bucket(&self, class: CommandClass) -> Option<&Bucket> {
+        match class {
+            CommandClass::Query => self.queries.as_ref(),
+            CommandClass::Transform => self.transforms.as_ref(),
+            CommandClass::Session => None,
+        }
+    }
+
+    fn bucket_mut(&mut self, class: CommandClass) -> Option<&mut Bucket> {
+        match class {
+            CommandClass::Query => self.queries.as_mut(),
+            CommandClass::Transform => self.transforms.as_mut(),
+            CommandClass::Session => None,
+        }
+    }
+}
+
+/// How often and for how long commands were delayed by a [`RateLimiter`]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ThrottleStats {
+    /// The number of commands that had to wait
+    pub throttled: u64,
+    /// The total time those commands waited
+    pub waited: Duration,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    limit: RateLimit,
+    // Available tokens as of `updated`; negative if commands are waiting for future tokens
+    tokens: f64,
+    updated: Instant,
+    stats: ThrottleStats,
+}
+
+impl Bucket {
+    fn new(limit: RateLimit, now: Instant) -> Self {
+        Self {
+            limit,
+            tokens: limit.burst,
+            updated: now,
+            stats: ThrottleStats::default(),
+        }
+    }
+
+    /// Take a token, returning how long to wait for it if none is available yet
+    fn reserve(&mut self, now: Instant) -> Option<Duration> {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.limit.rate).min(self.limit.burst);
+        self.updated = now;
+
+        self.tokens -= 1.0;
+        if self.tokens >= 0.0 {
+            return None;
+        }
+
+        let wait = Duration::from_secs_f64(-self.tokens / self.limit.rate);
+        self.stats.throttled += 1;
+        self.stats.waited += wait;
+        Some(wait)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{Bucket, CommandClass, RateLimit};
+
+    #[test]
+    fn bucket() {
+        let start = Instant::now();
+        let mut bucket = Bucket::new(RateLimit::per_second(2.0, 2), start);
+
+        // The burst is available immediately
+        assert_eq!(bucket.reserve(start), None);
+        assert_eq!(bucket.reserve(start), None);
+
+        // After that, commands are spaced out at the rate
+        assert_eq!(bucket.reserve(start), Some(Duration::from_millis(500)));
+        assert_eq!(bucket.reserve(start), Some(Duration::from_secs(1)));
+        assert_eq!(bucket.stats.throttled, 2);
+        assert_eq!(bucket.stats.waited, Duration::from_millis(1500));
+
+        // Tokens refill over time, up to the burst
+        let later = start + Duration::from_secs(10);
+        assert_eq!(bucket.reserve(later), None);
+        assert_eq!(bucket.reserve(later), None);
+        assert!(bucket.reserve(later).is_some());
+    }
+
+    #[test]
+    fn classes() {
+        assert_eq!(CommandClass::of("check"), CommandClass::Query);
+        assert_eq!(CommandClass::of("update"), CommandClass::Transform);
+        assert_eq!(CommandClass::of("login"), CommandClass::Session);
+    }
+}
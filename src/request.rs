@@ -5,7 +5,9 @@ use std::fmt::Debug;
 use instant_xml::ser::Context;
 use instant_xml::{FromXmlOwned, ToXml};
 
-use crate::common::EPP_XMLNS;
+use crate::common::{NoExtension, EPP_XMLNS};
+use crate::response::ExtensionPair;
+use crate::Error;
 
 pub const EPP_VERSION: &str = "1.0";
 pub const EPP_LANG: &str = "en";
@@ -16,10 +18,39 @@ pub trait Transaction<Ext: Extension>: Command + Sized {}
 pub trait Command: ToXml + Debug {
     type Response: FromXmlOwned + Debug;
     const COMMAND: &'static str;
+
+    /// Check for mistakes that would make the server reject the command
+    ///
+    /// Called before the command is serialized for sending.
+    fn validate(&self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// The IDs of the objects the command is about, such as domain names
+    ///
+    /// Included in [`Error::Command`] if the server rejects the command.
+    fn object_ids(&self) -> Vec<&str> {
+        Vec::new()
+    }
+
+    /// The namespaces of the object mappings the command uses, such as the domain mapping
+    ///
+    /// The server must advertise these as `<objURI>` in its greeting, see
+    /// [`ServiceMenu::check()`](crate::hello::ServiceMenu::check).
+    fn object_uris(&self) -> Vec<&str> {
+        Vec::new()
+    }
 }
 
 pub trait Extension: ToXml + Debug {
     type Response: FromXmlOwned + Debug;
+
+    /// Check for mistakes that would make the server reject the extension
+    ///
+    /// Called before the request is serialized for sending, after [`Command::validate()`].
+    fn validate(&self) -> Result<(), Error> {
+        Ok(())
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -31,15 +62,23 @@ pub(crate) struct CommandWrapper<'a, D, E> {
     data: &'a D,
     /// The client TRID
     extension: Option<&'a E>,
+    /// Serialized extensions to add after `extension`
+    extra: &'a [String],
     client_tr_id: String,
 }
 
 impl<'a, E: Extension, D: Transaction<E>> CommandWrapper<'a, D, E> {
-    pub(crate) fn new(data: &'a D, extension: Option<&'a E>, client_tr_id: &'a str) -> Self {
+    pub(crate) fn new(
+        data: &'a D,
+        extension: Option<&'a E>,
+        extra: &'a [String],
+        client_tr_id: &'a str,
+    ) -> Self {
         Self {
             command: D::COMMAND,
             data,
             extension,
+            extra,
             client_tr_id: client_tr_id.into(),
         }
     }
@@ -54,8 +93,16 @@ impl<D: ToXml, E: ToXml> ToXml for CommandWrapper<'_, D, E> {
         let command = serializer.write_start("command", EPP_XMLNS, None::<Context<0>>)?;
         serializer.end_start()?;
         self.data.serialize(None, serializer)?;
-        if let Some(extension) = self.extension {
-            Ext { inner: extension }.serialize(None, serializer)?;
+        if self.extension.is_some() || !self.extra.is_empty() {
+            let ext = serializer.write_start("extension", EPP_XMLNS, None::<Context<0>>)?;
+            serializer.end_start()?;
+            if let Some(extension) = self.extension {
+                extension.serialize(None, serializer)?;
+            }
+            for xml in self.extra {
+                serializer.write_str(xml)?;
+            }
+            serializer.write_close(ext)?;
         }
 
         let cl_tr_id = serializer.write_start("clTRID", EPP_XMLNS, None::<Context<0>>)?;
@@ -68,8 +115,81 @@ impl<D: ToXml, E: ToXml> ToXml for CommandWrapper<'_, D, E> {
     }
 }
 
-#[derive(Debug, ToXml)]
-#[xml(rename = "extension", ns(EPP_XMLNS))]
-struct Ext<E> {
-    inner: E,
+/// Two extensions to send with the same command
+///
+/// A command supports the pair if it supports both extensions. The extension data in the
+/// response is parsed as an [`ExtensionPair`] of both extensions' responses.
+#[derive(Debug)]
+pub struct Extensions<A, B>(pub A, pub B);
+
+impl<A: Extension, B: Extension> Extension for Extensions<A, B> {
+    type Response = ExtensionPair<A::Response, B::Response>;
+
+    fn validate(&self) -> Result<(), Error> {
+        self.0.validate()?;
+        self.1.validate()
+    }
+}
+
+impl<C, A, B> Transaction<Extensions<A, B>> for C
+where
+    C: Transaction<A> + Transaction<B>,
+    A: Extension,
+    B: Extension,
+{
+}
+
+impl<A: ToXml, B: ToXml> ToXml for Extensions<A, B> {
+    fn serialize<W: std::fmt::Write + ?Sized>(
+        &self,
+        _: Option<instant_xml::Id<'_>>,
+        serializer: &mut instant_xml::Serializer<W>,
+    ) -> Result<(), instant_xml::Error> {
+        self.0.serialize(None, serializer)?;
+        self.1.serialize(None, serializer)
+    }
+}
+
+#[derive(Debug)]
+pub struct RequestData<'c, 'e, C, E> {
+    pub(crate) command: &'c C,
+    pub(crate) extension: Option<&'e E>,
 }
+
+impl<'c, 'e, C: Command, E: Extension> RequestData<'c, 'e, C, E> {
+    /// Create request data with an optional extension
+    ///
+    /// Passing `None` for the `extension` sends the command without an `<extension>` element,
+    /// while still parsing the response's extension data as `E::Response`.
+    pub fn new(command: &'c C, extension: Option<&'e E>) -> Self {
+        Self { command, extension }
+    }
+}
+
+impl<'c, C: Command> From<&'c C> for RequestData<'c, 'static, C, NoExtension> {
+    fn from(command: &'c C) -> Self {
+        Self {
+            command,
+            extension: None,
+        }
+    }
+}
+
+impl<'c, 'e, C: Command, E: Extension> From<(&'c C, &'e E)> for RequestData<'c, 'e, C, E> {
+    fn from((command, extension): (&'c C, &'e E)) -> Self {
+        Self {
+            command,
+            extension: Some(extension),
+        }
+    }
+}
+
+// Manual impl because this does not depend on whether `C` and `E` are `Clone`
+impl<C, E> Clone for RequestData<'_, '_, C, E> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+// Manual impl because this does not depend on whether `C` and `E` are `Copy`
+impl<C, E> Copy for RequestData<'_, '_, C, E> {}
@@ -1,11 +1,13 @@
 //! Types for EPP responses
 
-use std::fmt::Debug;
+use std::fmt::{self, Debug};
+use std::marker::PhantomData;
 
 use chrono::{DateTime, Utc};
+use instant_xml::de::Node;
 use instant_xml::{Accumulate, AnyAttribute, AnyElement, FromXml, Kind};
 
-use crate::common::EPP_XMLNS;
+use crate::common::{deserialize_date_option, LanguageTag, EPP_XMLNS};
 
 /// Type corresponding to the `<value>` tag (errValueType) in an EPP response XML.
 ///
@@ -47,8 +49,6 @@ impl<'xml> FromXml<'xml> for ResultValue {
         _field: &'static str,
         deserializer: &mut instant_xml::Deserializer<'cx, 'xml>,
     ) -> Result<(), instant_xml::Error> {
-        use instant_xml::de::Node;
-
         let mut attributes = Vec::new();
         let mut inner = None;
         loop {
@@ -110,12 +110,19 @@ pub struct ExtValue {
 pub struct Reason {
     /// Language of the reason message (defaults to "en" if absent)
     #[xml(attribute)]
-    pub lang: Option<String>,
+    pub lang: Option<LanguageTag>,
     /// The human-readable reason text
     #[xml(direct)]
     pub text: String,
 }
 
+impl Reason {
+    /// The language of the reason message, defaulting to `en`
+    pub fn language(&self) -> LanguageTag {
+        self.lang.clone().unwrap_or_default()
+    }
+}
+
 /// Type corresponding to the `<result>` tag in an EPP response XML
 ///
 /// Per RFC 5730, a result can contain zero or more `<value>` and `<extValue>`
@@ -127,8 +134,7 @@ pub struct EppResult {
     #[xml(attribute)]
     pub code: ResultCode,
     /// The result message
-    #[xml(rename = "msg")]
-    pub message: String,
+    pub message: Message,
     /// Data under `<value>` tags
     #[xml(rename = "value")]
     pub values: Vec<ResultValue>,
@@ -139,81 +145,127 @@ pub struct EppResult {
 
 /// Response codes as enumerated in section 3 of RFC 5730
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
 pub enum ResultCode {
-    CommandCompletedSuccessfully = 1000,
-    CommandCompletedSuccessfullyActionPending = 1001,
-    CommandCompletedSuccessfullyNoMessages = 1300,
-    CommandCompletedSuccessfullyAckToDequeue = 1301,
-    CommandCompletedSuccessfullyEndingSession = 1500,
-    UnknownCommand = 2000,
-    CommandSyntaxError = 2001,
-    CommandUseError = 2002,
-    RequiredParameterMissing = 2003,
-    ParameterValueRangeError = 2004,
-    ParameterValueSyntaxError = 2005,
-    UnimplementedProtocolVersion = 2100,
-    UnimplementedCommand = 2101,
-    UnimplementedOption = 2102,
-    UnimplementedExtension = 2103,
-    BillingFailure = 2104,
-    ObjectIsNotEligibleForRenewal = 2105,
-    ObjectIsNotEligibleForTransfer = 2106,
-    AuthenticationError = 2200,
-    AuthorizationError = 2201,
-    InvalidAuthorizationInformation = 2202,
-    ObjectPendingTransfer = 2300,
-    ObjectNotPendingTransfer = 2301,
-    ObjectExists = 2302,
-    ObjectDoesNotExist = 2303,
-    ObjectStatusProhibitsOperation = 2304,
-    ObjectAssociationProhibitsOperation = 2305,
-    ParameterValuePolicyError = 2306,
-    UnimplementedObjectService = 2307,
-    DataManagementPolicyViolation = 2308,
-    CommandFailed = 2400,
-    CommandFailedServerClosingConnection = 2500,
-    AuthenticationErrorServerClosingConnection = 2501,
-    SessionLimitExceededServerClosingConnection = 2502,
+    CommandCompletedSuccessfully,
+    CommandCompletedSuccessfullyActionPending,
+    CommandCompletedSuccessfullyNoMessages,
+    CommandCompletedSuccessfullyAckToDequeue,
+    CommandCompletedSuccessfullyEndingSession,
+    UnknownCommand,
+    CommandSyntaxError,
+    CommandUseError,
+    RequiredParameterMissing,
+    ParameterValueRangeError,
+    ParameterValueSyntaxError,
+    UnimplementedProtocolVersion,
+    UnimplementedCommand,
+    UnimplementedOption,
+    UnimplementedExtension,
+    BillingFailure,
+    ObjectIsNotEligibleForRenewal,
+    ObjectIsNotEligibleForTransfer,
+    AuthenticationError,
+    AuthorizationError,
+    InvalidAuthorizationInformation,
+    ObjectPendingTransfer,
+    ObjectNotPendingTransfer,
+    ObjectExists,
+    ObjectDoesNotExist,
+    ObjectStatusProhibitsOperation,
+    ObjectAssociationProhibitsOperation,
+    ParameterValuePolicyError,
+    UnimplementedObjectService,
+    DataManagementPolicyViolation,
+    CommandFailed,
+    CommandFailedServerClosingConnection,
+    AuthenticationErrorServerClosingConnection,
+    SessionLimitExceededServerClosingConnection,
+    /// A code not defined in RFC 5730, as used by some registries
+    Other(u16),
 }
 
 impl ResultCode {
-    pub fn from_u16(code: u16) -> Option<Self> {
+    /// The result code for `code`, [`Other`](Self::Other) if RFC 5730 doesn't define it
+    pub fn from_u16(code: u16) -> Self {
         match code {
-            1000 => Some(Self::CommandCompletedSuccessfully),
-            1001 => Some(Self::CommandCompletedSuccessfullyActionPending),
-            1300 => Some(Self::CommandCompletedSuccessfullyNoMessages),
-            1301 => Some(Self::CommandCompletedSuccessfullyAckToDequeue),
-            1500 => Some(Self::CommandCompletedSuccessfullyEndingSession),
-            2000 => Some(Self::UnknownCommand),
-            2001 => Some(Self::CommandSyntaxError),
-            2002 => Some(Self::CommandUseError),
-            2003 => Some(Self::RequiredParameterMissing),
-            2004 => Some(Self::ParameterValueRangeError),
-            2005 => Some(Self::ParameterValueSyntaxError),
-            2100 => Some(Self::UnimplementedProtocolVersion),
-            2101 => Some(Self::UnimplementedCommand),
-            2102 => Some(Self::UnimplementedOption),
-            2103 => Some(Self::UnimplementedExtension),
-            2104 => Some(Self::BillingFailure),
-            2105 => Some(Self::ObjectIsNotEligibleForRenewal),
-            2106 => Some(Self::ObjectIsNotEligibleForTransfer),
-            2200 => Some(Self::AuthenticationError),
-            2201 => Some(Self::AuthorizationError),
-            2202 => Some(Self::InvalidAuthorizationInformation),
-            2300 => Some(Self::ObjectPendingTransfer),
-            2301 => Some(Self::ObjectNotPendingTransfer),
-            2302 => Some(Self::ObjectExists),
-            2303 => Some(Self::ObjectDoesNotExist),
-            2304 => Some(Self::ObjectStatusProhibitsOperation),
-            2305 => Some(Self::ObjectAssociationProhibitsOperation),
-            2306 => Some(Self::ParameterValuePolicyError),
-            2307 => Some(Self::UnimplementedObjectService),
-            2308 => Some(Self::DataManagementPolicyViolation),
-            2400 => Some(Self::CommandFailed),
-            2500 => Some(Self::CommandFailedServerClosingConnection),
-            2501 => Some(Self::AuthenticationErrorServerClosingConnection),
-            2502 => Some(Self::SessionLimitExceededServerClosingConnection),
-            _ => None,
+            1000 => Self::CommandCompletedSuccessfully,
+            1001 => Self::CommandCompletedSuccessfullyActionPending,
+            1300 => Self::CommandCompletedSuccessfullyNoMessages,
+            1301 => Self::CommandCompletedSuccessfullyAckToDequeue,
+            1500 => Self::CommandCompletedSuccessfullyEndingSession,
+            2000 => Self::UnknownCommand,
+            2001 => Self::CommandSyntaxError,
+            2002 => Self::CommandUseError,
+            2003 => Self::RequiredParameterMissing,
+            2004 => Self::ParameterValueRangeError,
+            2005 => Self::ParameterValueSyntaxError,
+            2100 => Self::UnimplementedProtocolVersion,
+            2101 => Self::UnimplementedCommand,
+            2102 => Self::UnimplementedOption,
+            2103 => Self::UnimplementedExtension,
+            2104 => Self::BillingFailure,
+            2105 => Self::ObjectIsNotEligibleForRenewal,
+            2106 => Self::ObjectIsNotEligibleForTransfer,
+            2200 => Self::AuthenticationError,
+            2201 => Self::AuthorizationError,
+            2202 => Self::InvalidAuthorizationInformation,
+            2300 => Self::ObjectPendingTransfer,
+            2301 => Self::ObjectNotPendingTransfer,
+            2302 => Self::ObjectExists,
+            2303 => Self::ObjectDoesNotExist,
+            2304 => Self::ObjectStatusProhibitsOperation,
+            2305 => Self::ObjectAssociationProhibitsOperation,
+            2306 => Self::ParameterValuePolicyError,
+            2307 => Self::UnimplementedObjectService,
+            2308 => Self::DataManagementPolicyViolation,
+            2400 => Self::CommandFailed,
+            2500 => Self::CommandFailedServerClosingConnection,
+            2501 => Self::AuthenticationErrorServerClosingConnection,
+            2502 => Self::SessionLimitExceededServerClosingConnection,
+            _ => Self::Other(code),
+        }
+    }
+
+    /// The numeric code, as sent by the server
+    pub fn code(&self) -> u16 {
+        use ResultCode::*;
+        match self {
+            CommandCompletedSuccessfully => 1000,
+            CommandCompletedSuccessfullyActionPending => 1001,
+            CommandCompletedSuccessfullyNoMessages => 1300,
+            CommandCompletedSuccessfullyAckToDequeue => 1301,
+            CommandCompletedSuccessfullyEndingSession => 1500,
+            UnknownCommand => 2000,
+            CommandSyntaxError => 2001,
+            CommandUseError => 2002,
+            RequiredParameterMissing => 2003,
+            ParameterValueRangeError => 2004,
+            ParameterValueSyntaxError => 2005,
+            UnimplementedProtocolVersion => 2100,
+            UnimplementedCommand => 2101,
+            UnimplementedOption => 2102,
+            UnimplementedExtension => 2103,
+            BillingFailure => 2104,
+            ObjectIsNotEligibleForRenewal => 2105,
+            ObjectIsNotEligibleForTransfer => 2106,
+            AuthenticationError => 2200,
+            AuthorizationError => 2201,
+            InvalidAuthorizationInformation => 2202,
+            ObjectPendingTransfer => 2300,
+            ObjectNotPendingTransfer => 2301,
+            ObjectExists => 2302,
+            ObjectDoesNotExist => 2303,
+            ObjectStatusProhibitsOperation => 2304,
+            ObjectAssociationProhibitsOperation => 2305,
+            ParameterValuePolicyError => 2306,
+            UnimplementedObjectService => 2307,
+            DataManagementPolicyViolation => 2308,
+            CommandFailed => 2400,
+            CommandFailedServerClosingConnection => 2500,
+            AuthenticationErrorServerClosingConnection => 2501,
+            SessionLimitExceededServerClosingConnection => 2502,
+            Other(code) => *code,
         }
     }
 
@@ -226,7 +278,7 @@ impl ResultCode {
                 | CommandCompletedSuccessfullyNoMessages
                 | CommandCompletedSuccessfullyAckToDequeue
                 | CommandCompletedSuccessfullyEndingSession
-        )
+        ) || matches!(self, Other(code) if *code < 2000)
     }
 
     /// Returns true if this error is likely to persist across similar requests inside the same
@@ -253,6 +305,71 @@ impl ResultCode {
             _ => false,
         }
     }
+
+    /// Classify this code by what it means for resubmitting the command
+    ///
+    /// All failure codes indicate the command was not executed, but not all of them make it
+    /// safe to try again: a create that failed with `ObjectExists` after an earlier timeout may
+    /// well have succeeded the first time around.
+    pub fn classify(&self) -> ResultClass {
+        use ResultCode::*;
+        match self {
+            CommandCompletedSuccessfully
+            | CommandCompletedSuccessfullyActionPending
+            | CommandCompletedSuccessfullyNoMessages
+            | CommandCompletedSuccessfullyAckToDequeue
+            | CommandCompletedSuccessfullyEndingSession => ResultClass::Success,
+            CommandFailed => ResultClass::RetrySafe,
+            BillingFailure => ResultClass::BillingRelated,
+            AuthenticationError
+            | CommandFailedServerClosingConnection
+            | AuthenticationErrorServerClosingConnection
+            | SessionLimitExceededServerClosingConnection => ResultClass::SessionFatal,
+            UnknownCommand
+            | CommandSyntaxError
+            | CommandUseError
+            | RequiredParameterMissing
+            | ParameterValueRangeError
+            | ParameterValueSyntaxError
+            | UnimplementedProtocolVersion
+            | UnimplementedCommand
+            | UnimplementedOption
+            | UnimplementedExtension
+            | ObjectIsNotEligibleForRenewal
+            | ObjectIsNotEligibleForTransfer
+            | AuthorizationError
+            | InvalidAuthorizationInformation
+            | ObjectPendingTransfer
+            | ObjectNotPendingTransfer
+            | ObjectExists
+            | ObjectDoesNotExist
+            | ObjectStatusProhibitsOperation
+            | ObjectAssociationProhibitsOperation
+            | ParameterValuePolicyError
+            | UnimplementedObjectService
+            | DataManagementPolicyViolation => ResultClass::RetryUnsafe,
+            // RFC 5730 reserves 1xxx for success and 2xxx for errors
+            Other(code) if *code < 2000 => ResultClass::Success,
+            Other(_) => ResultClass::RetryUnsafe,
+        }
+    }
+}
+
+/// What a [`ResultCode`] means for resubmitting a command, see [`ResultCode::classify()`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ResultClass {
+    /// The command was completed
+    Success,
+    /// The server failed to execute the command for reasons unrelated to the request;
+    /// submitting it again in the same session is safe
+    RetrySafe,
+    /// The request itself or the state of the object caused the failure; submitting it again
+    /// unchanged will not help, and may hide that an earlier attempt already took effect
+    RetryUnsafe,
+    /// The session can no longer be used; a new connection and login are needed
+    SessionFatal,
+    /// The registry could not charge the account; retry only after resolving the billing issue
+    BillingRelated,
 }
 
 impl<'xml> FromXml<'xml> for ResultCode {
@@ -271,9 +388,9 @@ impl<'xml> FromXml<'xml> for ResultCode {
         let mut value = None;
         u16::deserialize(&mut value, field, deserializer)?;
         if let Some(value) = value {
-            *into = match Self::from_u16(value) {
-                Some(value) => Some(value),
-                None => {
+            *into = match value {
+                1000..=2999 => Some(Self::from_u16(value)),
+                _ => {
                     return Err(instant_xml::Error::UnexpectedValue(format!(
                         "unexpected result code '{value}'"
                     )))
@@ -289,7 +406,7 @@ impl<'xml> FromXml<'xml> for ResultCode {
 }
 
 /// Type corresponding to the `<trID>` tag in an EPP response XML
-#[derive(Debug, Eq, FromXml, PartialEq)]
+#[derive(Clone, Debug, Eq, FromXml, PartialEq)]
 #[xml(rename = "trID", ns(EPP_XMLNS))]
 pub struct ResponseTRID {
     /// The client TRID
@@ -301,32 +418,257 @@ pub struct ResponseTRID {
 }
 
 /// Type corresponding to the `<msgQ>` tag in an EPP response XML
+///
+/// RFC 5730 requires the `count` and `id` attributes, and the `<qDate>` and `<msg>` elements
+/// on poll responses, but some servers leave out any of them; all are optional here.
 #[derive(Debug, Eq, FromXml, PartialEq)]
 #[xml(rename = "msgQ", ns(EPP_XMLNS))]
 pub struct MessageQueue {
     /// The message count
     #[xml(attribute)]
-    pub count: u32,
+    pub count: Option<u32>,
     /// The message ID
     #[xml(attribute)]
-    pub id: String,
+    pub id: Option<String>,
     /// The message date
-    #[xml(rename = "qDate")]
+    #[xml(rename = "qDate", deserialize_with = "deserialize_date_option")]
     pub date: Option<DateTime<Utc>>,
     /// The message text
     #[xml(rename = "msg")]
-    pub message: Option<Message>,
+    pub message: Option<QueueMessage>,
+}
+
+impl MessageQueue {
+    /// The number of messages in the queue, including this one, if the server reported it
+    pub fn count(&self) -> Option<u32> {
+        self.count
+    }
+
+    /// The message ID, as needed to acknowledge the message
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    /// When the message was enqueued
+    pub fn date(&self) -> Option<DateTime<Utc>> {
+        self.date
+    }
+
+    /// The message text, as sent by the server
+    pub fn text(&self) -> Option<&str> {
+        self.message.as_ref().map(|msg| msg.text.as_str())
+    }
+}
+
+/// Type corresponding to the `<msg>` tag in a message queue entry
+///
+/// The message may be followed by child elements with structured data, which are skipped.
+/// Use [`sanitized()`](Self::sanitized) for text that is fit
+/// for logs and display.
+#[derive(Debug, Eq, PartialEq)]
+pub struct QueueMessage {
+    /// Language of the message (defaults to "en" if absent)
+    pub lang: Option<LanguageTag>,
+    /// The message text, after XML entity decoding
+    pub text: String,
+}
+
+impl QueueMessage {
+    /// The language of the message, defaulting to `en`
+    pub fn language(&self) -> LanguageTag {
+        self.lang.clone().unwrap_or_default()
+    }
+
+    /// The message text without HTML tags and entities, with whitespace collapsed
+    ///
+    /// Some servers escape HTML-formatted text into the message, so that `text` contains
+    /// tags like `<br>` and entities like `&nbsp;`. Those are removed or decoded here.
+    pub fn sanitized(&self) -> String {
+        sanitize(&self.text)
+    }
+}
+
+impl fmt::Display for QueueMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+impl PartialEq<&str> for QueueMessage {
+    fn eq(&self, other: &&str) -> bool {
+        self.text == *other
+    }
+}
+
+impl<'xml> FromXml<'xml> for QueueMessage {
+    fn matches(id: instant_xml::Id<'_>, field: Option<instant_xml::Id<'_>>) -> bool {
+        match field {
+            Some(field) => id == field,
+            None => {
+                id == instant_xml::Id {
+                    ns: EPP_XMLNS,
+                    name: "msg",
+                }
+            }
+        }
+    }
+
+    fn deserialize<'cx>(
+        into: &mut Self::Accumulator,
+        _field: &'static str,
+        deserializer: &mut instant_xml::Deserializer<'cx, 'xml>,
+    ) -> Result<(), instant_xml::Error> {
+        let mut lang = None;
+        let mut text = String::new();
+        loop {
+            match deserializer.next() {
+                Some(Ok(Node::Attribute(attr))) => {
+                    if deserializer.attribute_id(&attr)?.name != "lang" {
+                        continue;
+                    }
+
+                    lang = Some(LanguageTag::received(attr.value.into_owned()));
+                }
+                Some(Ok(Node::Text(chunk))) => text.push_str(&chunk),
+                Some(Ok(Node::Open(element))) => deserializer.nested(element).ignore()?,
+                Some(Ok(Node::Close { .. })) | None => break,
+                Some(Ok(_)) => continue,
+                Some(Err(e)) => return Err(e),
+            }
+        }
+
+        *into = Some(Self { lang, text });
+        Ok(())
+    }
+
+    type Accumulator = Option<Self>;
+    const KIND: Kind = Kind::Element;
+}
+
+/// Strip HTML tags, decode HTML entities and collapse whitespace in `text`
+fn sanitize(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        stripped.push_str(&rest[..start]);
+        let tag = &rest[start + 1..];
+        let is_tag = tag
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '/' || c == '!');
+        match (is_tag, tag.find('>')) {
+            (true, Some(end)) => {
+                // Keep words separated by tags like `<br>` apart
+                stripped.push(' ');
+                rest = &tag[end + 1..];
+            }
+            _ => {
+                stripped.push('<');
+                rest = tag;
+            }
+        }
+    }
+    stripped.push_str(rest);
+
+    decode_entities(&stripped)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Decode HTML entities in `text`, leaving anything that isn't a known entity as is
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        let entity = &rest[start + 1..];
+        let end = entity.find(';').filter(|&end| end <= 8);
+        match end.and_then(|end| decode_entity(&entity[..end]).map(|c| (end, c))) {
+            Some((end, c)) => {
+                decoded.push(c);
+                rest = &entity[end + 1..];
+            }
+            None => {
+                decoded.push('&');
+                rest = entity;
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
 }
 
+fn decode_entity(name: &str) -> Option<char> {
+    Some(match name {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => ' ',
+        _ => {
+            let code = match name.strip_prefix('#')? {
+                hex if hex.starts_with(['x', 'X']) => u32::from_str_radix(&hex[1..], 16),
+                decimal => decimal.parse(),
+            };
+            return char::from_u32(code.ok()?);
+        }
+    })
+}
+
+/// Type corresponding to the `<msg>` tag in results and message queue entries
 #[derive(Debug, Eq, FromXml, PartialEq)]
 #[xml(rename = "msg", ns(EPP_XMLNS))]
 pub struct Message {
+    /// Language of the message (defaults to "en" if absent)
     #[xml(attribute)]
-    pub lang: Option<String>,
+    pub lang: Option<LanguageTag>,
+    /// The human-readable message text
     #[xml(direct)]
     pub text: String,
 }
 
+impl Message {
+    /// The language of the message, defaulting to `en`
+    pub fn language(&self) -> LanguageTag {
+        self.lang.clone().unwrap_or_default()
+    }
+
+    /// The message text with HTML entities decoded
+    ///
+    /// Some servers escape the message twice, so that `text` contains entities like `&lt;`
+    /// after XML entity decoding. Any tags in the text are kept.
+    pub fn decoded(&self) -> String {
+        decode_entities(&self.text)
+    }
+
+    /// The message text without HTML tags and entities, with whitespace collapsed
+    ///
+    /// Like [`QueueMessage::sanitized()`], for text that is fit for logs and display.
+    pub fn sanitized(&self) -> String {
+        sanitize(&self.text)
+    }
+}
+
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+impl PartialEq<str> for Message {
+    fn eq(&self, other: &str) -> bool {
+        self.text == other
+    }
+}
+
+impl PartialEq<&str> for Message {
+    fn eq(&self, other: &&str) -> bool {
+        self.text == *other
+    }
+}
+
 #[derive(Debug, FromXml, PartialEq)]
 /// Type corresponding to the `<response>` tag in an EPP response XML
 /// containing an `<extension>` tag
@@ -400,12 +742,123 @@ pub struct Extension<E> {
     pub data: E,
 }
 
+/// The data for two extensions from the same `<extension>` element
+///
+/// Each of the children is parsed as `A` or `B`, whichever matches it, so the order does not
+/// matter; children that match neither are ignored. This is the response type for
+/// [`Extensions`](crate::request::Extensions), but can also be used to parse responses with
+/// extension data for several extensions, such as poll messages with both `changePoll` and fee
+/// data. Nest pairs to combine more than two extensions.
+#[derive(Debug, Eq, PartialEq)]
+pub struct ExtensionPair<A, B>(pub Option<A>, pub Option<B>);
+
+impl<'xml, A: FromXml<'xml>, B: FromXml<'xml>> FromXml<'xml> for ExtensionPair<A, B> {
+    fn matches(id: instant_xml::Id<'_>, _: Option<instant_xml::Id<'_>>) -> bool {
+        A::matches(id, None) || B::matches(id, None)
+    }
+
+    fn deserialize<'cx>(
+        into: &mut Self::Accumulator,
+        field: &'static str,
+        deserializer: &mut instant_xml::Deserializer<'cx, 'xml>,
+    ) -> Result<(), instant_xml::Error> {
+        match A::matches(deserializer.parent(), None) {
+            true => A::deserialize(
+                into.0.get_or_insert_with(Default::default),
+                field,
+                deserializer,
+            ),
+            false => B::deserialize(
+                into.1.get_or_insert_with(Default::default),
+                field,
+                deserializer,
+            ),
+        }
+    }
+
+    type Accumulator = PairAccumulator<A, B, A::Accumulator, B::Accumulator>;
+    const KIND: Kind = Kind::Element;
+}
+
+/// Accumulator for deserializing an [`ExtensionPair`]
+pub struct PairAccumulator<A, B, X, Y>(Option<X>, Option<Y>, PhantomData<(A, B)>);
+
+impl<A, B, X: Accumulate<A>, Y: Accumulate<B>> Accumulate<ExtensionPair<A, B>>
+    for PairAccumulator<A, B, X, Y>
+{
+    fn try_done(self, field: &'static str) -> Result<ExtensionPair<A, B>, instant_xml::Error> {
+        Ok(ExtensionPair(
+            self.0.map(|acc| acc.try_done(field)).transpose()?,
+            self.1.map(|acc| acc.try_done(field)).transpose()?,
+        ))
+    }
+}
+
+// Manual impl because this does not depend on whether the type parameters are `Default`
+impl<A, B, X, Y> Default for PairAccumulator<A, B, X, Y> {
+    fn default() -> Self {
+        Self(None, None, PhantomData)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{ResponseStatus, ResultCode};
+    use super::{ExtensionPair, LanguageTag, Response, ResponseStatus, ResultClass, ResultCode};
+    use crate::extensions::change_poll::{ChangePoll, OperationKind};
+    use crate::extensions::fee;
+    use crate::poll::PollData;
     use crate::tests::{get_xml, CLTRID, SVTRID};
     use crate::xml;
 
+    #[test]
+    fn classify() {
+        let class = |code| ResultCode::from_u16(code).classify();
+        assert_eq!(class(1000), ResultClass::Success);
+        assert_eq!(class(1001), ResultClass::Success);
+        assert_eq!(class(2104), ResultClass::BillingRelated);
+        assert_eq!(class(2302), ResultClass::RetryUnsafe);
+        assert_eq!(class(2400), ResultClass::RetrySafe);
+        assert_eq!(class(2502), ResultClass::SessionFatal);
+        assert_eq!(class(1002), ResultClass::Success);
+        assert_eq!(class(2309), ResultClass::RetryUnsafe);
+    }
+
+    #[test]
+    fn unknown_code() {
+        let xml = get_xml("response/error.xml")
+            .unwrap()
+            .replace(r#"code="2303""#, r#"code="2309""#);
+        let object = xml::deserialize::<ResponseStatus>(&xml).unwrap();
+        assert_eq!(object.result.code, ResultCode::Other(2309));
+        assert_eq!(object.result.code.code(), 2309);
+        assert!(!object.result.code.is_success());
+        assert_eq!(ResultCode::from_u16(2303).code(), 2303);
+
+        let xml = xml.replace(r#"code="2309""#, r#"code="42""#);
+        assert!(xml::deserialize::<ResponseStatus>(&xml).is_err());
+    }
+
+    #[test]
+    fn extension_pair() {
+        let xml = get_xml("response/poll/poll_change_fee.xml").unwrap();
+        let object = xml::deserialize::<
+            Response<PollData, ExtensionPair<ChangePoll, fee::CreateData>>,
+        >(&xml)
+        .unwrap();
+
+        let ExtensionPair(change, fee) = object.extension().unwrap();
+        let change = change.as_ref().unwrap();
+        assert_eq!(change.operation.kind().unwrap(), OperationKind::Renew);
+        assert_eq!(change.who, "Registry");
+        assert_eq!(fee.as_ref().unwrap().fees[0].amount, "10.00");
+
+        let object = xml::deserialize::<
+            Response<PollData, ExtensionPair<fee::CreateData, fee::UpdateData>>,
+        >(&xml)
+        .unwrap();
+        assert!(object.extension().unwrap().1.is_none());
+    }
+
     #[test]
     fn error() {
         let xml = get_xml("response/error.xml").unwrap();
@@ -446,6 +899,64 @@ mod tests {
         assert_eq!(object.tr_ids.server_tr_id, SVTRID);
     }
 
+    #[test]
+    fn error_lang() {
+        let xml = get_xml("response/error_lang.xml").unwrap();
+        let object = xml::deserialize::<ResponseStatus>(xml.as_str()).unwrap();
+
+        assert_eq!(object.result.message, "Objekt existiert nicht");
+        assert_eq!(object.result.message.language(), "de-ch");
+        assert_eq!(object.result.message.language().primary(), "de");
+        let reason = &object.result.ext_values[0].reason;
+        assert_eq!(reason.language(), "de-CH");
+        assert_eq!(reason.text, "545 Objekt nicht gefunden");
+
+        let xml = get_xml("response/error.xml").unwrap();
+        let object = xml::deserialize::<ResponseStatus>(xml.as_str()).unwrap();
+        assert_eq!(object.result.message.lang, None);
+        assert_eq!(object.result.message.language(), "en");
+
+        assert!("en-GB".parse::<LanguageTag>().is_ok());
+        assert!("i-klingon".parse::<LanguageTag>().is_ok());
+        assert!("".parse::<LanguageTag>().is_err());
+        assert!("en_GB".parse::<LanguageTag>().is_err());
+        assert!("1en".parse::<LanguageTag>().is_err());
+    }
+
+    #[test]
+    fn malformed_lang() {
+        let xml = get_xml("response/error_lang.xml")
+            .unwrap()
+            .replace("de-CH", "de_CH");
+        let object = xml::deserialize::<ResponseStatus>(xml.as_str()).unwrap();
+
+        let lang = object.result.message.language();
+        assert_eq!(lang, "de_CH");
+        assert!(!lang.is_well_formed());
+        assert_eq!(object.result.ext_values[0].reason.language(), "de_CH");
+        assert_eq!(object.result.message, "Objekt existiert nicht");
+    }
+
+    #[test]
+    fn error_escaped() {
+        let xml = get_xml("response/error_escaped.xml").unwrap();
+        let object = xml::deserialize::<ResponseStatus>(xml.as_str()).unwrap();
+
+        let message = &object.result.message;
+        assert_eq!(
+            message.text,
+            "Object &lt;domain:name&gt; example.com does not exist&nbsp;<br/>"
+        );
+        assert_eq!(
+            message.decoded(),
+            "Object <domain:name> example.com does not exist <br/>"
+        );
+        assert_eq!(
+            message.sanitized(),
+            "Object <domain:name> example.com does not exist"
+        );
+    }
+
     #[test]
     fn error_value_attrs() {
         let xml = get_xml("response/error_value_attrs.xml").unwrap();
@@ -473,7 +984,7 @@ mod tests {
         assert_eq!(ext_value.value.inner.text.as_deref(), Some("example.com"));
 
         // lang attribute on <reason>
-        assert_eq!(ext_value.reason.lang.as_deref(), Some("de"));
+        assert_eq!(ext_value.reason.language(), "de");
         assert_eq!(ext_value.reason.text, "Domainname ist nicht verfügbar.");
 
         assert_eq!(object.tr_ids.client_tr_id.unwrap(), CLTRID);
@@ -546,8 +1057,8 @@ mod tests {
 
         // msgQ
         let mq = object.message_queue.unwrap();
-        assert_eq!(mq.count, 201);
-        assert_eq!(mq.id, "1");
+        assert_eq!(mq.count, Some(201));
+        assert_eq!(mq.id(), Some("1"));
 
         assert_eq!(object.tr_ids.client_tr_id.unwrap(), "ABC-12345");
         assert_eq!(object.tr_ids.server_tr_id, "54322-XYZ");
@@ -0,0 +1,142 @@
+//! Timers and I/O for the async runtime the client is driven by
+//!
+//! By default, the client uses tokio's timers, which must run inside a tokio runtime. With the
+//! `async-io` feature, timers from async-io are used instead, so that the client can be driven
+//! by smol, async-std or any other executor. The client reads and writes connections through
+//! tokio's I/O traits, which don't depend on the tokio runtime; [`Compat`] implements them for
+//! streams implementing the futures-io traits, like async-io's `Async<TcpStream>`. The tokio
+//! synchronization primitives the client uses work on any executor.
+
+use std::future::Future;
+#[cfg(feature = "async-io")]
+use std::io;
+#[cfg(feature = "async-io")]
+use std::pin::Pin;
+#[cfg(feature = "async-io")]
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+/// A timeout elapsed before the future completed
+#[derive(Debug)]
+pub(crate) struct Elapsed;
+
+/// Wait until `duration` has elapsed
+pub(crate) async fn sleep(duration: Duration) {
+    #[cfg(not(feature = "async-io"))]
+    tokio::time::sleep(duration).await;
+    #[cfg(feature = "async-io")]
+    async_io::Timer::after(duration).await;
+}
+
+/// Run `fut` to completion, unless `duration` elapses first
+pub(crate) async fn timeout<F: Future>(duration: Duration, fut: F) -> Result<F::Output, Elapsed> {
+    timeout_at(Instant::now() + duration, fut).await
+}
+
+/// Run `fut` to completion, unless `deadline` passes first
+#[cfg(not(feature = "async-io"))]
+pub(crate) async fn timeout_at<F: Future>(deadline: Instant, fut: F) -> Result<F::Output, Elapsed> {
+    tokio::time::timeout_at(deadline.into(), fut)
+        .await
+        .map_err(|_| Elapsed)
+}
+
+/// Run `fut` to completion, unless `deadline` passes first
+#[cfg(feature = "async-io")]
+pub(crate) async fn timeout_at<F: Future>(deadline: Instant, fut: F) -> Result<F::Output, Elapsed> {
+    // `or()` polls the first future first, so a completed `fut` wins over an expired timer
+    futures_lite::future::or(async { Ok(fut.await) }, async {
+        async_io::Timer::at(deadline).await;
+        Err(Elapsed)
+    })
+    .await
+}
+
+/// Adapts a futures-io stream to tokio's I/O traits
+///
+/// Use this in a [`Connector`](crate::client::Connector) to return connections from runtimes
+/// other than tokio, for example `Compat::new(Async::<TcpStream>::connect(addr).await?)`.
+#[cfg(feature = "async-io")]
+#[derive(Debug)]
+pub struct Compat<T>(T);
+
+#[cfg(feature = "async-io")]
+impl<T> Compat<T> {
+    /// Wrap the futures-io stream `inner`
+    pub fn new(inner: T) -> Self {
+        Self(inner)
+    }
+
+    /// The wrapped stream
+    pub fn get_ref(&self) -> &T {
+        &self.0
+    }
+
+    /// Unwrap the stream
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+#[cfg(feature = "async-io")]
+impl<T: futures_lite::AsyncRead + Unpin> tokio::io::AsyncRead for Compat<T> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let read =
+            futures_lite::ready!(Pin::new(&mut self.0).poll_read(cx, buf.initialize_unfilled()))?;
+        buf.advance(read);
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(feature = "async-io")]
+impl<T: futures_lite::AsyncWrite + Unpin> tokio::io::AsyncWrite for Compat<T> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.0).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_close(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::pending;
+    use std::time::{Duration, Instant};
+
+    use super::{sleep, timeout, timeout_at};
+
+    #[tokio::test]
+    async fn timeout_elapses() {
+        let result = timeout(Duration::from_millis(10), pending::<()>()).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn timeout_completes() {
+        let result = timeout(Duration::from_secs(10), async { 42 }).await;
+        assert_eq!(result.unwrap(), 42);
+    }
+
+    #[tokio::test]
+    async fn deadline_passed() {
+        let start = Instant::now();
+        sleep(Duration::from_millis(10)).await;
+        assert!(start.elapsed() >= Duration::from_millis(10));
+
+        // A future that is ready immediately still completes after the deadline
+        assert_eq!(timeout_at(start, async { 1 }).await.unwrap(), 1);
+    }
+}
@@ -0,0 +1,55 @@
+//! Parse the registry responses in tests/resources/corpus, see the README there
+
+use std::fs;
+use std::path::Path;
+
+use super::RESOURCES_DIR;
+use crate::common::RawXml;
+use crate::contact::{ContactCheck, ContactInfo};
+use crate::domain::{DomainCheck, DomainInfo, DomainTransfer};
+use crate::host::{HostCheck, HostInfo};
+use crate::poll::Poll;
+use crate::request::Command;
+use crate::response::Response;
+use crate::xml;
+
+/// Extension data is captured as is, as samples may use any extensions
+fn parse<Cmd: Command>(xml: &str) -> Result<(), crate::Error> {
+    xml::deserialize::<Response<Cmd::Response, Vec<RawXml>>>(xml).map(|_| ())
+}
+
+#[test]
+fn corpus() {
+    let root = Path::new(RESOURCES_DIR).join("corpus");
+    let mut count = 0;
+    for dir in fs::read_dir(&root).unwrap() {
+        let dir = dir.unwrap().path();
+        if !dir.is_dir() {
+            continue;
+        }
+
+        let command = dir.file_name().unwrap().to_str().unwrap().to_owned();
+        for file in fs::read_dir(&dir).unwrap() {
+            let path = file.unwrap().path();
+            let xml = xml::normalize(&fs::read_to_string(&path).unwrap());
+            let result = match command.as_str() {
+                "contact_check" => parse::<ContactCheck>(&xml),
+                "contact_info" => parse::<ContactInfo>(&xml),
+                "domain_check" => parse::<DomainCheck>(&xml),
+                "domain_info" => parse::<DomainInfo>(&xml),
+                "domain_transfer" => parse::<DomainTransfer>(&xml),
+                "host_check" => parse::<HostCheck>(&xml),
+                "host_info" => parse::<HostInfo>(&xml),
+                "poll" => parse::<Poll>(&xml),
+                _ => panic!("unknown command directory {}", dir.display()),
+            };
+
+            if let Err(err) = result {
+                panic!("failed to parse {}: {err}", path.display());
+            }
+            count += 1;
+        }
+    }
+
+    assert!(count > 0, "no responses found in {}", root.display());
+}
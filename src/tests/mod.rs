@@ -2,17 +2,17 @@
 
 use std::{error::Error, fs::File, io::Read};
 
-use regex::Regex;
 use similar_asserts::assert_eq;
 
 use crate::{
-    client::RequestData,
     common::NoExtension,
-    request::{Command, CommandWrapper, Extension, Transaction},
+    request::{Command, CommandWrapper, Extension, RequestData, Transaction},
     response::Response,
     xml,
 };
 
+mod corpus;
+
 pub(crate) const RESOURCES_DIR: &str = "./tests/resources";
 pub(crate) const CLTRID: &str = "cltrid:1626454866";
 pub(crate) const SVTRID: &str = "RO-6879-1627224678242975";
@@ -20,22 +20,10 @@ pub(crate) const SUCCESS_MSG: &str = "Command completed successfully";
 
 /// Reads EPP XML requests and responses from the test/resources directory to run tests on
 pub(crate) fn get_xml(path: &str) -> Result<String, Box<dyn Error>> {
-    let ws_regex = Regex::new(r"[\s]{2,}")?;
-
     let mut f = File::open(format!("{RESOURCES_DIR}/{path}"))?;
     let mut buf = String::new();
-
     f.read_to_string(&mut buf)?;
-    if !buf.is_empty() {
-        let mat = Regex::new(r"\?>").unwrap().find(buf.as_str()).unwrap();
-        let start = mat.end();
-        buf = format!(
-            "{}\r\n{}",
-            &buf[..start],
-            ws_regex.replace_all(&buf[start..], "")
-        );
-    }
-    Ok(buf)
+    Ok(xml::normalize(&buf))
 }
 
 #[track_caller]
@@ -48,7 +36,7 @@ pub(crate) fn assert_serialized<'c, 'e, Cmd, Ext>(
 {
     let expected = get_xml(path).unwrap();
     let req = req.into();
-    let document = CommandWrapper::new(req.command, req.extension, CLTRID);
+    let document = CommandWrapper::new(req.command, req.extension, &[], CLTRID);
     assert_eq!(expected, xml::serialize(document).unwrap());
 }
 
@@ -0,0 +1,136 @@
+//! Caching of domain availability checks
+//!
+//! Availability search frontends easily run into registry query limits. [`CheckCache`]
+//! remembers the result of each domain check for a while, so that repeated checks for the
+//! same name can be answered without asking the registry again. Available names usually get
+//! a shorter TTL than taken ones, since they are the ones about to change.
+
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use crate::client::{Connector, EppClient};
+use crate::common::{CheckResults, Checked};
+use crate::domain::DomainCheck;
+use crate::Error;
+
+/// A TTL cache for domain check results
+#[derive(Debug)]
+pub struct CheckCache {
+    available_ttl: Duration,
+    taken_ttl: Duration,
+    entries: HashMap<String, Entry>,
+}
+
+impl CheckCache {
+    /// Cache available names for `available_ttl` and taken names for `taken_ttl`
+    pub fn new(available_ttl: Duration, taken_ttl: Duration) -> Self {
+        Self {
+            available_ttl,
+            taken_ttl,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Check the availability of `names`, only asking the registry about uncached names
+    ///
+    /// Results are returned in the order of `names`. Names compare case-insensitively. If all
+    /// names are cached, no command is sent.
+    pub async fn check_domains<C: Connector>(
+        &mut self,
+        client: &mut EppClient<C>,
+        names: &[&str],
+        id: &str,
+    ) -> Result<Vec<Availability>, Error> {
+        let now = Instant::now();
+        self.entries.retain(|_, entry| entry.expires > now);
+
+        let mut fresh = HashSet::new();
+        let missing = names
+            .iter()
+            .copied()
+            .filter(|name| {
+                let key = key(name);
+                !self.entries.contains_key(&key) && fresh.insert(key)
+            })
+            .collect::<Vec<_>>();
+
+        if !missing.is_empty() {
+            let rsp = client
+                .transact(&DomainCheck { domains: &missing }, id)
+                .await?;
+            for checked in rsp
+                .res_data()
+                .map(|data| data.results())
+                .unwrap_or_default()
+            {
+                let ttl = match checked.available() {
+                    true => self.available_ttl,
+                    false => self.taken_ttl,
+                };
+
+                self.entries.insert(
+                    key(checked.id()),
+                    Entry {
+                        available: checked.available(),
+                        reason: checked.reason().map(str::to_owned),
+                        expires: now + ttl,
+                    },
+                );
+            }
+        }
+
+        names
+            .iter()
+            .map(|&name| {
+                let key = key(name);
+                let Some(entry) = self.entries.get(&key) else {
+                    return Err(Error::Other(
+                        format!("{name}: missing from domain check response").into(),
+                    ));
+                };
+
+                Ok(Availability {
+                    name: name.to_owned(),
+                    available: entry.available,
+                    reason: entry.reason.clone(),
+                    cached: !fresh.contains(&key),
+                })
+            })
+            .collect()
+    }
+
+    /// Forget the cached result for `name`
+    ///
+    /// Use this after creating or deleting the domain, or when it is known to have changed.
+    pub fn invalidate(&mut self, name: &str) {
+        self.entries.remove(&key(name));
+    }
+
+    /// Forget all cached results
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// The availability of a domain, from the registry or the cache
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Availability {
+    /// The domain name, as passed to `check_domains()`
+    pub name: String,
+    pub available: bool,
+    /// The reason the registry gave for the name being unavailable, if any
+    pub reason: Option<String>,
+    /// Whether this result was answered from the cache
+    pub cached: bool,
+}
+
+#[derive(Debug)]
+struct Entry {
+    available: bool,
+    reason: Option<String>,
+    expires: Instant,
+}
+
+fn key(name: &str) -> String {
+    name.to_ascii_lowercase()
+}
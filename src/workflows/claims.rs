@@ -0,0 +1,39 @@
+//! Checking domains for trademark claims during a TLD launch
+//!
+//! While a new TLD is in its claims period, a domain matching a trademark in the Trademark
+//! Clearinghouse can only be registered after the registrant has seen the claims notice for
+//! it. [`EppClient::claims_check()`] finds out which domains need a notice, and the claim keys
+//! to fetch the notices with.
+
+use crate::client::{Connector, EppClient};
+use crate::domain::DomainCheck;
+use crate::extensions::launch::{ClaimsCheck, DomainClaims, Phase};
+use crate::request::RequestData;
+use crate::Error;
+
+impl<C: Connector> EppClient<C> {
+    /// Check the `domains` for trademark claims in the launch `phase`
+    ///
+    /// Sends a domain `<check>` with the claims check form of the launch extension, using the
+    /// client transaction `id`, and returns the claims for each domain in the order of the
+    /// response.
+    pub async fn claims_check(
+        &mut self,
+        domains: &[&str],
+        phase: Phase<'_>,
+        id: &str,
+    ) -> Result<Vec<DomainClaims>, Error> {
+        let check = DomainCheck { domains };
+        let extension = ClaimsCheck::new(phase);
+        let rsp = self
+            .transact(RequestData::new(&check, Some(&extension)), id)
+            .await?;
+
+        match rsp.extension() {
+            Some(data) => Ok(data.claims()),
+            None => Err(Error::Other(
+                "claims check response has no launch check data".into(),
+            )),
+        }
+    }
+}
@@ -0,0 +1,101 @@
+//! Finding the domains that keep a host from being deleted
+//!
+//! Registries refuse to delete a host while domains are delegated to it, which shows as the
+//! `linked` status on the host. EPP has no command to list those domains, so
+//! [`EppClient::host_dependencies()`] checks the name servers of a list of candidate domains,
+//! usually the domains the registrar has on record for the host.
+
+use tracing::debug;
+
+use super::ids::TransactionIds;
+use crate::client::{Connector, EppClient};
+use crate::domain::{self, DomainInfo};
+use crate::host::{HostInfo, Status};
+use crate::Error;
+
+/// The result of [`EppClient::host_dependencies()`]
+#[derive(Debug)]
+pub struct HostDependencies {
+    /// The host name
+    pub host: String,
+    /// Whether the registry reports the host as `linked` to any domain
+    pub linked: bool,
+    /// The candidate domains that are delegated to the host
+    pub domains: Vec<String>,
+    /// The candidate domains whose information the registry did not return, with its error
+    ///
+    /// This happens, for example, for domains sponsored by another registrar.
+    pub unknown: Vec<(String, Error)>,
+}
+
+impl HostDependencies {
+    /// Whether the registry would currently allow deleting the host
+    pub fn deletable(&self) -> bool {
+        !self.linked
+    }
+}
+
+impl<C: Connector> EppClient<C> {
+    /// Find out which of the `candidates` are delegated to `host`
+    ///
+    /// The host's status is checked first; if it is not `linked`, no domain refers to it and the
+    /// candidates are not queried. Otherwise a domain `<info>` is sent for each of the candidates
+    /// to compare its name servers against the host name. Errors from the registry for a single
+    /// domain are collected in [`HostDependencies::unknown`], other errors are returned. The
+    /// commands are sent with client transaction IDs derived from `id`, like `id-1`.
+    pub async fn host_dependencies(
+        &mut self,
+        host: &str,
+        candidates: &[&str],
+        id: &str,
+    ) -> Result<HostDependencies, Error> {
+        let mut ids = TransactionIds::new(id);
+        let rsp = self.transact(&HostInfo::new(host), &ids.next_id()).await?;
+        let linked = rsp
+            .res_data()
+            .is_some_and(|data| data.statuses.iter().any(|s| *s == Status::Linked));
+
+        let mut dependencies = HostDependencies {
+            host: host.to_owned(),
+            linked,
+            domains: Vec::new(),
+            unknown: Vec::new(),
+        };
+
+        if !linked {
+            debug!("host {host} is not linked");
+            return Ok(dependencies);
+        }
+
+        for &domain in candidates {
+            let rsp = match self
+                .transact(&DomainInfo::new(domain), &ids.next_id())
+                .await
+            {
+                Ok(rsp) => rsp,
+                Err(err @ Error::Command(_)) => {
+                    debug!("no info for candidate {domain}: {err}");
+                    dependencies.unknown.push((domain.to_owned(), err));
+                    continue;
+                }
+                Err(err) => return Err(err),
+            };
+
+            let Some(ns) = rsp.res_data().and_then(|data| data.ns.as_ref()) else {
+                continue;
+            };
+
+            // Only `<hostObj>` delegations refer to host objects
+            let delegated = ns.ns.iter().any(|ns| match ns {
+                domain::HostInfo::Obj(obj) => obj.name.eq_ignore_ascii_case(host),
+                domain::HostInfo::Attr(_) => false,
+            });
+
+            if delegated {
+                dependencies.domains.push(domain.to_owned());
+            }
+        }
+
+        Ok(dependencies)
+    }
+}
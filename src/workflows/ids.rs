@@ -0,0 +1,84 @@
+//! Client transaction IDs for the commands of a workflow
+
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+/// The length limit of client transaction IDs, as checked by
+/// [`check_transaction_id()`](crate::xml::check_transaction_id)
+const MAX_LEN: usize = 64;
+
+/// Derives a client transaction ID for each command a workflow sends
+///
+/// The IDs are the caller's ID with a sequence number appended, like `ABC-12345-1` and
+/// `ABC-12345-2`. This keeps them unique per command, so that responses and pending action
+/// notifications can be matched to the command they are for, while still leading back to the
+/// workflow run that sent them.
+///
+/// IDs that would exceed the 64 character limit are shortened: the end of the caller's ID is
+/// replaced by a hash of all of it, so that IDs from different runs stay distinct.
+pub(crate) struct TransactionIds<'a> {
+    base: &'a str,
+    count: usize,
+}
+
+impl<'a> TransactionIds<'a> {
+    pub(crate) fn new(base: &'a str) -> Self {
+        Self { base, count: 0 }
+    }
+
+    /// The ID for the next command
+    pub(crate) fn next_id(&mut self) -> String {
+        self.count += 1;
+        let suffix = format!("-{}", self.count);
+        if self.base.chars().count() + suffix.len() <= MAX_LEN {
+            return format!("{}{suffix}", self.base);
+        }
+
+        let mut hasher = DefaultHasher::new();
+        self.base.hash(&mut hasher);
+        let hash = hasher.finish() as u32;
+
+        // Keep room for the separator and 8 hex digits of the hash
+        let prefix = self
+            .base
+            .chars()
+            .take(MAX_LEN - suffix.len() - 9)
+            .collect::<String>();
+        format!("{prefix}~{hash:08x}{suffix}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TransactionIds;
+    use crate::xml::check_transaction_id;
+
+    #[test]
+    fn sequence() {
+        let mut ids = TransactionIds::new("ABC-12345");
+        assert_eq!(ids.next_id(), "ABC-12345-1");
+        assert_eq!(ids.next_id(), "ABC-12345-2");
+    }
+
+    #[test]
+    fn long_base() {
+        let base = "A".repeat(63);
+        let mut ids = TransactionIds::new(&base);
+        let first = ids.next_id();
+        assert_eq!(first.len(), 64);
+        assert!(first.starts_with(&"A".repeat(52)));
+        assert!(first.ends_with("-1"));
+        check_transaction_id(&first).unwrap();
+
+        let second = ids.next_id();
+        assert_eq!(second[..second.len() - 2], first[..first.len() - 2]);
+        assert!(second.ends_with("-2"));
+
+        // Different IDs with a common prefix stay distinct
+        let other = format!("{}B", "A".repeat(62));
+        assert_ne!(TransactionIds::new(&other).next_id(), first);
+
+        // IDs that fit are kept as they are
+        let base = "A".repeat(62);
+        assert_eq!(TransactionIds::new(&base).next_id(), format!("{base}-1"));
+    }
+}
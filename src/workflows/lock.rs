@@ -0,0 +1,206 @@
+//! Helpers for locking domains against unwanted changes
+//!
+//! Locking a domain adds the `clientDeleteProhibited`, `clientTransferProhibited` and
+//! `clientUpdateProhibited` statuses to it; unlocking removes them again. Both operations
+//! read the resulting status set back from the registry to verify that it took effect.
+//!
+//! Registries that offer a separate registry lock service usually require an extension on the
+//! update command. Use [`lock_domain_with()`] and [`unlock_domain_with()`] to pass one along.
+
+use super::ids::TransactionIds;
+use crate::client::{Connector, EppClient, RequestData};
+use crate::common::{NoExtension, StatusValue};
+use crate::domain::update::{DomainAdd, DomainRemove, DomainUpdate};
+use crate::domain::{DomainInfo, Status};
+use crate::request::{Extension, Transaction};
+use crate::Error;
+
+/// The statuses that make up a client lock
+pub const LOCK_STATUSES: [Status; 3] = [
+    Status::ClientDeleteProhibited,
+    Status::ClientTransferProhibited,
+    Status::ClientUpdateProhibited,
+];
+
+/// Lock the domain `name`, returning the resulting set of statuses
+///
+/// The commands are sent with client transaction IDs derived from `id`, like `id-1`.
+pub async fn lock_domain<C: Connector>(
+    client: &mut EppClient<C>,
+    name: &str,
+    id: &str,
+) -> Result<Vec<Status>, Error> {
+    lock_domain_with(client, name, None::<&NoExtension>, id).await
+}
+
+/// Lock the domain `name`, sending `extension` along with the update command
+///
+/// Only the lock statuses that are not yet present on the domain are added.
+pub async fn lock_domain_with<C, E>(
+    client: &mut EppClient<C>,
+    name: &str,
+    extension: Option<&E>,
+    id: &str,
+) -> Result<Vec<Status>, Error>
+where
+    C: Connector,
+    E: Extension,
+    for<'a> DomainUpdate<'a>: Transaction<E>,
+{
+    let mut ids = TransactionIds::new(id);
+    let current = statuses(client, name, &mut ids).await?;
+    let add = missing(&current)
+        .into_iter()
+        .map(StatusValue::from)
+        .collect::<Vec<_>>();
+    if !add.is_empty() {
+        let mut update = DomainUpdate::new(name);
+        update.add(DomainAdd {
+            ns: None,
+            contacts: None,
+            statuses: Some(&add),
+        });
+
+        let data = RequestData {
+            command: &update,
+            extension,
+        };
+        client.transact(data, &ids.next_id()).await?;
+    }
+
+    let statuses = statuses(client, name, &mut ids).await?;
+    if !missing(&statuses).is_empty() {
+        return Err(Error::Other(
+            format!("{name}: lock statuses missing after update: {statuses:?}").into(),
+        ));
+    }
+
+    Ok(statuses)
+}
+
+/// Unlock the domain `name`, returning the resulting set of statuses
+///
+/// The commands are sent with client transaction IDs derived from `id`, like `id-1`.
+pub async fn unlock_domain<C: Connector>(
+    client: &mut EppClient<C>,
+    name: &str,
+    id: &str,
+) -> Result<Vec<Status>, Error> {
+    unlock_domain_with(client, name, None::<&NoExtension>, id).await
+}
+
+/// Unlock the domain `name`, sending `extension` along with the update commands
+///
+/// While `clientUpdateProhibited` is set, registries reject any update other than the one
+/// removing that status. It is therefore removed in a separate update, before the others.
+pub async fn unlock_domain_with<C, E>(
+    client: &mut EppClient<C>,
+    name: &str,
+    extension: Option<&E>,
+    id: &str,
+) -> Result<Vec<Status>, Error>
+where
+    C: Connector,
+    E: Extension,
+    for<'a> DomainUpdate<'a>: Transaction<E>,
+{
+    let mut ids = TransactionIds::new(id);
+    let current = statuses(client, name, &mut ids).await?;
+    let (update_lock, other) = present(&current);
+    for remove in [update_lock, other] {
+        if remove.is_empty() {
+            continue;
+        }
+
+        let remove = remove
+            .into_iter()
+            .map(StatusValue::from)
+            .collect::<Vec<_>>();
+        let mut update = DomainUpdate::new(name);
+        update.remove(DomainRemove {
+            ns: None,
+            contacts: None,
+            statuses: Some(&remove),
+        });
+
+        let data = RequestData {
+            command: &update,
+            extension,
+        };
+        client.transact(data, &ids.next_id()).await?;
+    }
+
+    let statuses = statuses(client, name, &mut ids).await?;
+    if statuses.iter().any(|status| LOCK_STATUSES.contains(status)) {
+        return Err(Error::Other(
+            format!("{name}: lock statuses present after update: {statuses:?}").into(),
+        ));
+    }
+
+    Ok(statuses)
+}
+
+async fn statuses<C: Connector>(
+    client: &mut EppClient<C>,
+    name: &str,
+    ids: &mut TransactionIds<'_>,
+) -> Result<Vec<Status>, Error> {
+    let rsp = client
+        .transact(&DomainInfo::new(name), &ids.next_id())
+        .await?;
+    Ok(rsp
+        .res_data
+        .and_then(|data| data.into_inner().statuses)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|value| value.status)
+        .collect())
+}
+
+/// Lock statuses that are not in `current`
+fn missing(current: &[Status]) -> Vec<Status> {
+    LOCK_STATUSES
+        .iter()
+        .filter(|status| !current.contains(status))
+        .cloned()
+        .collect()
+}
+
+/// Lock statuses in `current`, split into `clientUpdateProhibited` and the rest
+fn present(current: &[Status]) -> (Vec<Status>, Vec<Status>) {
+    LOCK_STATUSES
+        .iter()
+        .filter(|status| current.contains(status))
+        .cloned()
+        .partition(|status| *status == Status::ClientUpdateProhibited)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{missing, present, Status};
+
+    #[test]
+    fn status_sets() {
+        let current = [Status::Ok, Status::ClientTransferProhibited];
+        assert_eq!(
+            missing(&current),
+            [
+                Status::ClientDeleteProhibited,
+                Status::ClientUpdateProhibited
+            ]
+        );
+
+        let current = [
+            Status::ClientUpdateProhibited,
+            Status::ServerHold,
+            Status::ClientDeleteProhibited,
+        ];
+        assert_eq!(
+            present(&current),
+            (
+                vec![Status::ClientUpdateProhibited],
+                vec![Status::ClientDeleteProhibited]
+            )
+        );
+    }
+}
@@ -0,0 +1,115 @@
+//! Matching pending action notifications to the commands that requested them
+//!
+//! Registries may answer a transform command with `1001` (action pending) and report the
+//! outcome later through the poll queue, as [`PendingActionData`]. That notification identifies
+//! the original command by the transaction IDs of its response. [`PendingActions`] keeps track
+//! of the pending commands, along with a value of the caller's choosing, and hands that value
+//! back once the outcome arrives.
+
+use crate::poll::PendingActionData;
+use crate::response::{Response, ResponseTRID, ResultCode};
+
+/// Commands awaiting a pending action notification, each with a value of type `T`
+#[derive(Debug)]
+pub struct PendingActions<T> {
+    pending: Vec<(ResponseTRID, T)>,
+}
+
+impl<T> PendingActions<T> {
+    pub fn new() -> Self {
+        Self {
+            pending: Vec::new(),
+        }
+    }
+
+    /// Start tracking the command that produced `response`, if its action is pending
+    ///
+    /// Returns `value` back if the response has any other result code, as nothing will be
+    /// reported for it through the poll queue.
+    pub fn track<D, E>(&mut self, response: &Response<D, E>, value: T) -> Option<T> {
+        match response.result.code {
+            ResultCode::CommandCompletedSuccessfullyActionPending => {
+                self.insert(response.tr_ids.clone(), value);
+                None
+            }
+            _ => Some(value),
+        }
+    }
+
+    /// Start tracking the command with these transaction IDs
+    ///
+    /// This can be used to resume tracking after a restart.
+    pub fn insert(&mut self, tr_ids: ResponseTRID, value: T) {
+        self.pending.push((tr_ids, value));
+    }
+
+    /// Stop tracking the command that `data` reports on, returning its value
+    ///
+    /// Commands are matched by server transaction ID, or by client transaction ID if the
+    /// server transaction IDs differ. Returns `None` if the command is not tracked here.
+    pub fn resolve(&mut self, data: &PendingActionData) -> Option<T> {
+        let idx = self
+            .pending
+            .iter()
+            .position(|(tr_ids, _)| tr_ids.server_tr_id == data.tr_ids.server_tr_id)
+            .or_else(|| {
+                let client_tr_id = data.tr_ids.client_tr_id.as_deref()?;
+                self.pending
+                    .iter()
+                    .position(|(tr_ids, _)| tr_ids.client_tr_id.as_deref() == Some(client_tr_id))
+            })?;
+
+        Some(self.pending.remove(idx).1)
+    }
+
+    /// The transaction IDs of the commands that are still pending
+    pub fn pending(&self) -> impl Iterator<Item = &ResponseTRID> {
+        self.pending.iter().map(|(tr_ids, _)| tr_ids)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
+impl<T> Default for PendingActions<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PendingActions;
+    use crate::domain::DomainTransfer;
+    use crate::poll::{Poll, PollData};
+    use crate::response::ResponseTRID;
+    use crate::tests::response_from_file;
+
+    #[test]
+    fn resolve() {
+        let mut pending = PendingActions::new();
+        let rsp = response_from_file::<DomainTransfer>("response/domain/transfer_request.xml");
+        assert_eq!(pending.track(&rsp, "transfer"), None);
+
+        let rsp = response_from_file::<Poll>("response/poll/poll_domain_transfer.xml");
+        assert_eq!(pending.track(&rsp, "poll"), Some("poll"));
+
+        pending.insert(
+            ResponseTRID {
+                client_tr_id: Some("cltrid:1626454800".into()),
+                server_tr_id: "RO-6879-1627224600000000".into(),
+            },
+            "create",
+        );
+        assert_eq!(pending.pending().count(), 2);
+
+        let rsp = response_from_file::<Poll>("response/poll/poll_domain_pending_action.xml");
+        let Some(PollData::PendingAction(data)) = rsp.res_data() else {
+            panic!("expected pending action data, got {:?}", rsp.res_data());
+        };
+        assert_eq!(pending.resolve(data), Some("create"));
+        assert_eq!(pending.resolve(data), None);
+        assert_eq!(pending.pending().count(), 1);
+    }
+}
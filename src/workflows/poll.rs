@@ -0,0 +1,167 @@
+//! Bounded processing of the poll message queue
+//!
+//! During busy windows the poll queue may hold more messages than can be handled in one go.
+//! [`Drain`] processes messages until the queue is empty, a number of messages has been
+//! handled or a deadline has passed, and records the queue depth reported along the way.
+
+use std::time::Instant;
+
+use tracing::debug;
+
+use super::ids::TransactionIds;
+use crate::client::{Connector, EppClient};
+use crate::common::NoExtension;
+use crate::poll::{Ack, Poll, PollData};
+use crate::response::{Response, ResultCode};
+use crate::Error;
+
+/// Bounds for draining the poll queue
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Drain {
+    limit: Option<usize>,
+    deadline: Option<Instant>,
+}
+
+impl Drain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop after `limit` messages have been handled
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Stop once `deadline` has passed
+    ///
+    /// The deadline is checked before each poll request, so a message that is being handled
+    /// when the deadline passes is still acknowledged.
+    pub fn deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Poll, handle and acknowledge messages until the queue is empty or a bound is reached
+    ///
+    /// Each message is passed to `handle` before it is acknowledged. If `handle` returns an
+    /// error, the message is left in the queue and the error is returned. The commands are sent
+    /// with client transaction IDs derived from `id`, like `id-1`.
+    pub async fn run<C: Connector>(
+        &self,
+        client: &mut EppClient<C>,
+        id: &str,
+        mut handle: impl FnMut(Response<PollData, NoExtension>) -> Result<(), Error>,
+    ) -> Result<DrainReport, Error> {
+        let mut ids = TransactionIds::new(id);
+        let mut report = DrainReport {
+            handled: 0,
+            depth: Vec::new(),
+            stop: DrainStop::Empty,
+        };
+
+        loop {
+            if self.limit.is_some_and(|limit| report.handled >= limit) {
+                report.stop = DrainStop::Limit;
+                break;
+            }
+
+            if self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+            {
+                report.stop = DrainStop::Deadline;
+                break;
+            }
+
+            let rsp = client.transact(&Poll, &ids.next_id()).await?;
+            if rsp.result.code == ResultCode::CommandCompletedSuccessfullyNoMessages {
+                report.depth.push(QueueDepth {
+                    at: Instant::now(),
+                    count: 0,
+                });
+                break;
+            }
+
+            let (message_id, count) = match rsp.message_queue() {
+                Some(queue) => match queue.id() {
+                    Some(id) => (id.to_owned(), queue.count()),
+                    None => return Err(Error::Other("poll response without message ID".into())),
+                },
+                None => {
+                    return Err(Error::Other(
+                        "poll response without message queue data".into(),
+                    ))
+                }
+            };
+
+            if let Some(count) = count {
+                report.depth.push(QueueDepth {
+                    at: Instant::now(),
+                    count,
+                });
+            }
+
+            handle(rsp)?;
+            client
+                .transact(
+                    &Ack {
+                        message_id: &message_id,
+                    },
+                    &ids.next_id(),
+                )
+                .await?;
+
+            match count {
+                Some(count) => debug!("acknowledged poll message {message_id}, {count} in queue"),
+                None => debug!("acknowledged poll message {message_id}"),
+            }
+            report.handled += 1;
+        }
+
+        Ok(report)
+    }
+}
+
+/// The result of draining the poll queue with [`Drain::run()`]
+#[derive(Debug)]
+pub struct DrainReport {
+    /// The number of messages handled and acknowledged
+    pub handled: usize,
+    /// The queue depth reported by each poll response, in order
+    ///
+    /// Responses in which the server left out the message count are not included.
+    pub depth: Vec<QueueDepth>,
+    /// Why draining stopped
+    pub stop: DrainStop,
+}
+
+impl DrainReport {
+    /// The number of messages left in the queue, as of the last poll response
+    ///
+    /// Returns `None` if no poll request was sent.
+    pub fn remaining(&self) -> Option<u32> {
+        // The count in a poll response includes the message being returned, which was acked
+        self.depth.last().map(|depth| depth.count.saturating_sub(1))
+    }
+}
+
+/// The reason [`Drain::run()`] stopped
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DrainStop {
+    /// The queue is empty
+    Empty,
+    /// The maximum number of messages was handled
+    Limit,
+    /// The deadline passed
+    Deadline,
+}
+
+/// The queue depth as reported by the `<msgQ>` count at a point in time
+#[derive(Clone, Copy, Debug)]
+pub struct QueueDepth {
+    /// When the poll response was received
+    pub at: Instant,
+    /// The number of messages in the queue, including the one returned in the response
+    pub count: u32,
+}
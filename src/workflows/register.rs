@@ -0,0 +1,211 @@
+//! Registering a domain along with its contacts in a single call
+//!
+//! Registering a domain usually takes more than the domain `<create>` command: the registrant
+//! and other contacts must exist before the domain can refer to them. A [`RegisterSpec`]
+//! describes the domain, its contacts, name servers and DNSSEC records, and
+//! [`EppClient::register_domain()`] creates the contacts that do not exist yet before creating
+//! the domain. Use the individual commands for anything this does not cover.
+
+use std::borrow::Cow;
+
+use tracing::debug;
+
+use super::ids::TransactionIds;
+use crate::client::{Connector, EppClient, RequestData};
+use crate::common::CheckResults;
+use crate::contact::{ContactCheck, ContactCreate};
+use crate::domain::create::CreateData;
+use crate::domain::{DomainContact, DomainCreate, HostInfo, Period};
+use crate::extensions::secdns::{self, DsDataType};
+use crate::Error;
+
+/// A contact to refer to from a [`RegisterSpec`]
+#[derive(Debug)]
+pub enum RegisterContact<'a> {
+    /// A contact that already exists with this ID
+    Existing(&'a str),
+    /// A contact to create, unless a contact with the same ID already exists
+    New(Box<ContactCreate<'a>>),
+}
+
+impl RegisterContact<'_> {
+    fn id(&self) -> &str {
+        match self {
+            Self::Existing(id) => id,
+            Self::New(create) => create.id(),
+        }
+    }
+}
+
+impl<'a> From<&'a str> for RegisterContact<'a> {
+    fn from(id: &'a str) -> Self {
+        Self::Existing(id)
+    }
+}
+
+impl<'a> From<ContactCreate<'a>> for RegisterContact<'a> {
+    fn from(create: ContactCreate<'a>) -> Self {
+        Self::New(Box::new(create))
+    }
+}
+
+/// Describes a domain to register with [`EppClient::register_domain()`]
+#[derive(Debug)]
+pub struct RegisterSpec<'a> {
+    name: &'a str,
+    period: Period,
+    auth_password: &'a str,
+    registrant: RegisterContact<'a>,
+    contacts: Vec<(&'a str, RegisterContact<'a>)>,
+    ns: Option<&'a [HostInfo<'a>]>,
+    ds: Option<&'a [DsDataType<'a>]>,
+}
+
+impl<'a> RegisterSpec<'a> {
+    /// Register the domain `name` for `period` to `registrant`
+    pub fn new(
+        name: &'a str,
+        period: Period,
+        auth_password: &'a str,
+        registrant: impl Into<RegisterContact<'a>>,
+    ) -> Self {
+        Self {
+            name,
+            period,
+            auth_password,
+            registrant: registrant.into(),
+            contacts: Vec::new(),
+            ns: None,
+            ds: None,
+        }
+    }
+
+    /// Add a contact of `contact_type` (usually `admin`, `billing` or `tech`)
+    pub fn contact(
+        mut self,
+        contact_type: &'a str,
+        contact: impl Into<RegisterContact<'a>>,
+    ) -> Self {
+        self.contacts.push((contact_type, contact.into()));
+        self
+    }
+
+    /// Delegate the domain to these name servers
+    pub fn ns(mut self, ns: &'a [HostInfo<'a>]) -> Self {
+        self.ns = Some(ns);
+        self
+    }
+
+    /// Publish these DS records for the domain, through the secDNS extension
+    pub fn ds(mut self, ds: &'a [DsDataType<'a>]) -> Self {
+        self.ds = Some(ds);
+        self
+    }
+
+    /// The contacts to create, without duplicate IDs
+    fn new_contacts(&self) -> Vec<&ContactCreate<'a>> {
+        let mut new = Vec::<&ContactCreate<'a>>::new();
+        let contacts = self.contacts.iter().map(|(_, contact)| contact);
+        for contact in [&self.registrant].into_iter().chain(contacts) {
+            let RegisterContact::New(create) = contact else {
+                continue;
+            };
+
+            if !new.iter().any(|c| c.id() == create.id()) {
+                new.push(&**create);
+            }
+        }
+
+        new
+    }
+}
+
+/// The result of [`EppClient::register_domain()`]
+#[derive(Debug)]
+pub struct Registration {
+    /// The `<creData>` from the domain create response
+    pub domain: CreateData,
+    /// IDs of the contacts that were created for the domain
+    pub created_contacts: Vec<String>,
+}
+
+impl<C: Connector> EppClient<C> {
+    /// Register a domain as described by `spec`, creating its contacts as needed
+    ///
+    /// New contacts are checked first, and those whose ID is not in use yet are created. A new
+    /// contact whose ID is already in use is assumed to be an earlier copy of the same contact,
+    /// so that a failed registration can be retried with the same `spec`. Contacts created
+    /// before a failing command are not deleted. The commands are sent with client transaction
+    /// IDs derived from `id`, like `id-1`.
+    pub async fn register_domain(
+        &mut self,
+        spec: &RegisterSpec<'_>,
+        id: &str,
+    ) -> Result<Registration, Error> {
+        let mut ids = TransactionIds::new(id);
+        let mut created_contacts = Vec::new();
+        let new = spec.new_contacts();
+        if !new.is_empty() {
+            let contacts = new.iter().map(|c| c.id()).collect::<Vec<_>>();
+            let rsp = self
+                .transact(
+                    &ContactCheck {
+                        contact_ids: &contacts,
+                    },
+                    &ids.next_id(),
+                )
+                .await?;
+            let available = match rsp.res_data() {
+                Some(data) => data.available().map(str::to_owned).collect::<Vec<_>>(),
+                None => Vec::new(),
+            };
+
+            for create in new {
+                if !available.iter().any(|id| id == create.id()) {
+                    debug!("contact {} already exists", create.id());
+                    continue;
+                }
+
+                self.transact(create, &ids.next_id()).await?;
+                created_contacts.push(create.id().to_owned());
+            }
+        }
+
+        let contacts = spec
+            .contacts
+            .iter()
+            .map(|(contact_type, contact)| DomainContact {
+                contact_type: Cow::Borrowed(contact_type),
+                id: Cow::Borrowed(contact.id()),
+            })
+            .collect::<Vec<_>>();
+
+        let create = DomainCreate::new(
+            spec.name,
+            spec.period,
+            spec.ns,
+            Some(spec.registrant.id()),
+            spec.auth_password,
+            (!contacts.is_empty()).then_some(&contacts[..]),
+        );
+
+        let rsp = match spec.ds {
+            Some(ds) => {
+                let extension = secdns::CreateData::from(ds);
+                let data = RequestData::new(&create, Some(&extension));
+                self.transact(data, &ids.next_id()).await?
+            }
+            None => self.transact(&create, &ids.next_id()).await?,
+        };
+
+        let domain = rsp
+            .res_data
+            .map(|data| data.into_inner())
+            .ok_or_else(|| Error::Other(format!("{}: no create data", spec.name).into()))?;
+
+        Ok(Registration {
+            domain,
+            created_contacts,
+        })
+    }
+}
@@ -0,0 +1,215 @@
+//! Changing the registrant of a domain under the ICANN Transfer Policy
+//!
+//! Part C of the ICANN Transfer Policy (formerly IRTP-C) covers changes of registrant for
+//! gTLD domains. After confirming the change with both the prior and the new registrant, the
+//! registrar updates the registrant and, unless the prior registrant opted out, locks the domain
+//! against transfers to another registrar for 60 days. [`RegistrantChange`] checks that the
+//! domain's statuses allow the update, sends it along with the transfer lock, and recognizes
+//! the poll message for registries that process the change asynchronously.
+//!
+//! Obtaining the confirmations and keeping track of when the 60 days have passed remain up to
+//! the registrar.
+
+use tracing::debug;
+
+use super::ids::TransactionIds;
+use crate::client::{Connector, EppClient, RequestData};
+use crate::common::{NoExtension, StatusValue};
+use crate::domain::update::{DomainAdd, DomainChangeInfo, DomainUpdate};
+use crate::domain::{DomainAuthInfo, DomainInfo, Status};
+use crate::poll::{PendingObject, PollData};
+use crate::request::{Extension, Transaction};
+use crate::response::{ResponseTRID, ResultCode};
+use crate::Error;
+
+/// Statuses that keep the registrant of a domain from being changed
+pub const BLOCKING_STATUSES: [Status; 5] = [
+    Status::ClientUpdateProhibited,
+    Status::ServerUpdateProhibited,
+    Status::PendingDelete,
+    Status::PendingTransfer,
+    Status::PendingUpdate,
+];
+
+/// A change of registrant for a domain
+#[derive(Debug)]
+pub struct RegistrantChange {
+    name: String,
+    registrant: String,
+    auth_password: Option<String>,
+    transfer_lock: bool,
+}
+
+impl RegistrantChange {
+    /// Make the contact `registrant` the registrant of the domain `name`
+    ///
+    /// By default, `clientTransferProhibited` is added to the domain along with the change.
+    pub fn new(name: &str, registrant: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            registrant: registrant.to_owned(),
+            auth_password: None,
+            transfer_lock: true,
+        }
+    }
+
+    /// Set a new auth info password along with the change
+    pub fn auth_password(mut self, password: &str) -> Self {
+        self.auth_password = Some(password.to_owned());
+        self
+    }
+
+    /// Whether to lock the domain against transfers
+    ///
+    /// Disable this if the prior registrant opted out of the 60-day transfer lock.
+    pub fn transfer_lock(mut self, lock: bool) -> Self {
+        self.transfer_lock = lock;
+        self
+    }
+
+    /// Check the domain's statuses, then update its registrant
+    ///
+    /// Returns an error without sending the update if the domain has any of the
+    /// [`BLOCKING_STATUSES`]. The commands are sent with client transaction IDs derived from
+    /// `id`, like `id-1`.
+    pub async fn run<C: Connector>(
+        &self,
+        client: &mut EppClient<C>,
+        id: &str,
+    ) -> Result<RegistrantChangeOutcome, Error> {
+        self.run_with(client, None::<&NoExtension>, id).await
+    }
+
+    /// Check the domain's statuses, then update its registrant with `extension`
+    ///
+    /// Some registries require an extension to designate the update as a change of registrant
+    /// under the Transfer Policy, for example to record the opt-out from the transfer lock.
+    pub async fn run_with<C, E>(
+        &self,
+        client: &mut EppClient<C>,
+        extension: Option<&E>,
+        id: &str,
+    ) -> Result<RegistrantChangeOutcome, Error>
+    where
+        C: Connector,
+        E: Extension,
+        for<'a> DomainUpdate<'a>: Transaction<E>,
+    {
+        let mut ids = TransactionIds::new(id);
+        let name = self.name.as_str();
+        let rsp = client
+            .transact(&DomainInfo::new(name), &ids.next_id())
+            .await?;
+        let Some(data) = rsp.res_data() else {
+            return Err(Error::Other(
+                format!("{name}: no info data in response").into(),
+            ));
+        };
+
+        if data
+            .registrant
+            .as_deref()
+            .is_some_and(|current| current.eq_ignore_ascii_case(&self.registrant))
+        {
+            debug!("{name} already has registrant {}", self.registrant);
+            return Ok(RegistrantChangeOutcome::Unchanged);
+        }
+
+        let statuses = data.statuses.as_deref().unwrap_or_default();
+        if let Some(status) = statuses
+            .iter()
+            .find(|value| BLOCKING_STATUSES.contains(&value.status))
+        {
+            return Err(Error::Other(
+                format!(
+                    "{name}: registrant cannot be changed while the domain has status {}",
+                    status.status
+                )
+                .into(),
+            ));
+        }
+
+        let lock = [StatusValue::from(Status::ClientTransferProhibited)];
+        let locked = statuses
+            .iter()
+            .any(|value| value.status == Status::ClientTransferProhibited);
+
+        let mut update = DomainUpdate::new(name);
+        if self.transfer_lock && !locked {
+            update.add(DomainAdd {
+                ns: None,
+                contacts: None,
+                statuses: Some(&lock),
+            });
+        }
+        update.info(DomainChangeInfo {
+            registrant: Some(&self.registrant),
+            auth_info: self.auth_password.as_deref().map(DomainAuthInfo::new),
+        });
+
+        let data = RequestData {
+            command: &update,
+            extension,
+        };
+        let rsp = client.transact(data, &ids.next_id()).await?;
+        match rsp.result.code {
+            ResultCode::CommandCompletedSuccessfullyActionPending => {
+                debug!("registrant change for {name} is pending");
+                Ok(RegistrantChangeOutcome::Pending(rsp.tr_ids))
+            }
+            _ => Ok(RegistrantChangeOutcome::Completed),
+        }
+    }
+
+    /// The outcome reported by a poll message for this change, if `data` is about it
+    ///
+    /// Returns `Some(true)` once the registry has completed the change, `Some(false)` if it was
+    /// rejected and `None` for poll messages about anything else.
+    pub fn confirmation(&self, data: &PollData) -> Option<bool> {
+        match data {
+            PollData::PendingAction(data)
+                if data.object == PendingObject::Domain
+                    && data.id.eq_ignore_ascii_case(&self.name) =>
+            {
+                Some(data.result)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// The result of [`RegistrantChange::run()`]
+#[derive(Debug, Eq, PartialEq)]
+pub enum RegistrantChangeOutcome {
+    /// The registry has changed the registrant
+    Completed,
+    /// The registry has accepted the change, but reports the outcome through the poll queue
+    ///
+    /// Contains the transaction IDs of the update, for use with
+    /// [`PendingActions`](super::pending::PendingActions) or
+    /// [`RegistrantChange::confirmation()`].
+    Pending(ResponseTRID),
+    /// The domain already has the new registrant, no update was sent
+    Unchanged,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RegistrantChange;
+    use crate::poll::Poll;
+    use crate::tests::response_from_file;
+
+    #[test]
+    fn confirmation() {
+        let rsp = response_from_file::<Poll>("response/poll/poll_domain_pending_action.xml");
+        let data = rsp.res_data().unwrap();
+
+        let change = RegistrantChange::new("EPPDEV-PENDING.com", "eppdev-contact-3");
+        assert_eq!(change.confirmation(data), Some(true));
+        let change = RegistrantChange::new("eppdev-1.com", "eppdev-contact-3");
+        assert_eq!(change.confirmation(data), None);
+
+        let rsp = response_from_file::<Poll>("response/poll/poll_domain_transfer.xml");
+        assert_eq!(change.confirmation(rsp.res_data().unwrap()), None);
+    }
+}
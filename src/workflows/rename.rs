@@ -0,0 +1,190 @@
+//! Renaming a host that serves as a name server for other domains
+//!
+//! Hosts can usually only be renamed in place within the bailiwick of their superordinate
+//! domain, and registries refuse to delete a host that is still delegated to. Moving the
+//! delegations of a number of domains to a new host name therefore takes a sequence of
+//! commands: create the new host, point each domain at it instead of the old host, then
+//! delete the old host. [`HostRename`] runs this sequence and keeps track of the steps that
+//! have completed, so that an interrupted rename can be resumed later.
+
+use std::net::IpAddr;
+
+use tracing::debug;
+
+use super::ids::TransactionIds;
+use crate::client::{Connector, EppClient};
+use crate::domain::update::{DomainAdd, DomainRemove, DomainUpdate};
+use crate::domain::{HostInfo, HostObj, NameServers};
+use crate::host::{GluePolicy, HostCreate, HostDelete};
+use crate::Error;
+
+/// Moves the delegations of a list of domains from one host to another
+///
+/// Only delegations in `<hostObj>` form are updated; domains that use `<hostAttr>` don't refer
+/// to host objects and are not affected by them.
+#[derive(Debug)]
+pub struct HostRename {
+    old: String,
+    new: String,
+    addresses: Vec<IpAddr>,
+    glue: Option<GluePolicy>,
+    domains: Vec<String>,
+    progress: RenameProgress,
+}
+
+impl HostRename {
+    /// Rename the host `old` to `new`, updating the name servers of each of `domains`
+    ///
+    /// The caller provides the domains, as the registry has no command to list the domains
+    /// delegated to a host.
+    pub fn new(old: &str, new: &str, domains: Vec<String>) -> Self {
+        Self {
+            old: old.to_owned(),
+            new: new.to_owned(),
+            addresses: Vec::new(),
+            glue: None,
+            domains,
+            progress: RenameProgress::default(),
+        }
+    }
+
+    /// Create the new host with these glue `addresses`
+    ///
+    /// Required if the new host is subordinate to a domain in the same registry.
+    pub fn addresses(mut self, addresses: Vec<IpAddr>) -> Self {
+        self.addresses = addresses;
+        self
+    }
+
+    /// Check the new host's addresses against `policy` before creating it
+    pub fn glue_policy(mut self, policy: GluePolicy) -> Self {
+        self.glue = Some(policy);
+        self
+    }
+
+    /// Skip the steps recorded in `progress`, as obtained from an earlier, interrupted run
+    pub fn resume(mut self, progress: RenameProgress) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// The steps completed so far
+    ///
+    /// After `run()` fails, persist this to resume the rename later.
+    pub fn progress(&self) -> &RenameProgress {
+        &self.progress
+    }
+
+    /// Run the remaining steps of the rename, calling `report` after each completes
+    ///
+    /// Stops at the first failing command and returns its error. The steps completed before
+    /// that are kept in [`progress()`](Self::progress), and are skipped when `run()` is called
+    /// again. The commands are sent with client transaction IDs derived from `id`, like `id-1`.
+    pub async fn run<C: Connector>(
+        &mut self,
+        client: &mut EppClient<C>,
+        id: &str,
+        mut report: impl FnMut(RenameStep<'_>),
+    ) -> Result<(), Error> {
+        let mut ids = TransactionIds::new(id);
+        if !self.progress.created {
+            let addresses = (!self.addresses.is_empty()).then_some(&self.addresses[..]);
+            let mut create = HostCreate::new(&self.new, addresses);
+            if let Some(policy) = &self.glue {
+                create.glue_policy(policy.clone());
+            }
+
+            client.transact(&create, &ids.next_id()).await?;
+            debug!("created host {}", self.new);
+            self.progress.created = true;
+            report(RenameStep::Created);
+        }
+
+        let add = [HostInfo::Obj(HostObj {
+            name: self.new.as_str().into(),
+        })];
+        let remove = [HostInfo::Obj(HostObj {
+            name: self.old.as_str().into(),
+        })];
+
+        for domain in &self.domains {
+            if self.progress.is_updated(domain) {
+                continue;
+            }
+
+            let mut update = DomainUpdate::new(domain);
+            update.add(DomainAdd {
+                ns: Some(NameServers {
+                    ns: (&add[..]).into(),
+                }),
+                contacts: None,
+                statuses: None,
+            });
+            update.remove(DomainRemove {
+                ns: Some(NameServers {
+                    ns: (&remove[..]).into(),
+                }),
+                contacts: None,
+                statuses: None,
+            });
+
+            client.transact(&update, &ids.next_id()).await?;
+            debug!("moved {domain} from {} to {}", self.old, self.new);
+            self.progress.updated.push(domain.clone());
+            report(RenameStep::Updated {
+                domain,
+                done: self.progress.updated.len(),
+                total: self.domains.len(),
+            });
+        }
+
+        if !self.progress.deleted {
+            let delete = HostDelete::new(&self.old);
+            client.transact(&delete, &ids.next_id()).await?;
+            debug!("deleted host {}", self.old);
+            self.progress.deleted = true;
+            report(RenameStep::Deleted);
+        }
+
+        Ok(())
+    }
+}
+
+/// The steps of a [`HostRename`] that have completed
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RenameProgress {
+    /// Whether the new host has been created
+    pub created: bool,
+    /// The domains that have been moved to the new host
+    pub updated: Vec<String>,
+    /// Whether the old host has been deleted
+    pub deleted: bool,
+}
+
+impl RenameProgress {
+    /// Whether `domain` has been moved to the new host
+    ///
+    /// Domain names compare case-insensitively.
+    pub fn is_updated(&self, domain: &str) -> bool {
+        self.updated
+            .iter()
+            .any(|updated| updated.eq_ignore_ascii_case(domain))
+    }
+}
+
+/// A step of a [`HostRename`], passed to the `report` callback once it completes
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RenameStep<'a> {
+    /// The new host was created
+    Created,
+    /// The name servers of `domain` were updated
+    Updated {
+        domain: &'a str,
+        /// The number of domains updated so far, including this one
+        done: usize,
+        /// The number of domains to update
+        total: usize,
+    },
+    /// The old host was deleted
+    Deleted,
+}
@@ -0,0 +1,224 @@
+//! Orchestration for domain transfers
+//!
+//! A transfer request is usually answered with a `1001` (action pending) response, after which
+//! the outcome is reported out of band through the poll queue. The [`TransferManager`] keeps
+//! track of transfers requested through it and resolves the [`PendingTransfer`] future for a
+//! domain once a poll message reports a final
+//! [`TransferStatus`](crate::domain::transfer::TransferStatus).
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll as TaskPoll};
+
+use tokio::sync::oneshot;
+use tracing::debug;
+
+use super::ids::TransactionIds;
+use crate::client::{Connector, EppClient};
+use crate::common::NoExtension;
+use crate::domain::transfer::{DomainTransfer, TransferData};
+use crate::domain::Period;
+use crate::poll::{Ack, Poll, PollData};
+use crate::response::{Response, ResponseData, ResultCode};
+use crate::Error;
+
+/// Tracks pending domain transfers until they reach a final state
+#[derive(Debug, Default)]
+pub struct TransferManager {
+    /// Senders for transfers that have not been resolved yet, keyed by lowercase domain name
+    pending: HashMap<String, oneshot::Sender<TransferData>>,
+}
+
+impl TransferManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request a transfer for the domain `name` and start tracking it
+    ///
+    /// If the registry completes the transfer immediately, the returned future resolves
+    /// without any poll messages being processed.
+    pub async fn request<C: Connector>(
+        &mut self,
+        client: &mut EppClient<C>,
+        name: &str,
+        period: Option<Period>,
+        auth_password: &str,
+        id: &str,
+    ) -> Result<PendingTransfer, Error> {
+        let transfer = DomainTransfer::request(name, period, auth_password);
+        let rsp = client.transact(&transfer, id).await?;
+
+        let (tx, pending) = PendingTransfer::new(name);
+        match rsp.res_data.map(ResponseData::into_inner) {
+            Some(data) if is_final(&data) => {
+                let _ = tx.send(data);
+            }
+            _ => {
+                self.pending.insert(key(name), tx);
+            }
+        }
+
+        Ok(pending)
+    }
+
+    /// Start tracking a transfer that was requested elsewhere
+    ///
+    /// This can be used to resume tracking after a restart. If `name` was already tracked,
+    /// the previously returned future will resolve with an error.
+    pub fn track(&mut self, name: &str) -> PendingTransfer {
+        let (tx, pending) = PendingTransfer::new(name);
+        self.pending.insert(key(name), tx);
+        pending
+    }
+
+    /// Feed transfer data obtained elsewhere (for example, from a transfer query)
+    ///
+    /// Returns `true` if this resolved one of the tracked transfers.
+    pub fn update(&mut self, data: TransferData) -> bool {
+        if !is_final(&data) {
+            return false;
+        }
+
+        match self.pending.remove(&key(&data.name)) {
+            Some(tx) => {
+                debug!(
+                    "transfer for {} resolved: {}",
+                    data.name, data.transfer_status
+                );
+                let _ = tx.send(data);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Retrieve the next message from the poll queue
+    ///
+    /// Messages reporting a final state for a tracked transfer are acknowledged and used to
+    /// resolve the corresponding `PendingTransfer`. Any other message is returned to the caller
+    /// as-is and must be acknowledged by the caller to make progress through the queue. The
+    /// poll and the acknowledgement are sent with the client transaction IDs `id-1` and `id-2`.
+    pub async fn poll<C: Connector>(
+        &mut self,
+        client: &mut EppClient<C>,
+        id: &str,
+    ) -> Result<PollOutcome, Error> {
+        let mut ids = TransactionIds::new(id);
+        let rsp = client.transact(&Poll, &ids.next_id()).await?;
+        if rsp.result.code == ResultCode::CommandCompletedSuccessfullyNoMessages {
+            return Ok(PollOutcome::Empty);
+        }
+
+        let queue_id = rsp.message_queue().and_then(|queue| queue.id());
+        let (data, message_id) = match (rsp.res_data(), queue_id) {
+            (Some(PollData::DomainTransfer(data)), Some(id))
+                if is_final(data) && self.pending.contains_key(&key(&data.name)) =>
+            {
+                (data.clone(), id.to_owned())
+            }
+            _ => return Ok(PollOutcome::Other(Box::new(rsp))),
+        };
+
+        client
+            .transact(
+                &Ack {
+                    message_id: &message_id,
+                },
+                &ids.next_id(),
+            )
+            .await?;
+
+        let name = data.name.clone();
+        self.update(data);
+        Ok(PollOutcome::Resolved(name))
+    }
+
+    /// Names of the domains for which transfers are still pending
+    pub fn pending(&self) -> impl Iterator<Item = &str> {
+        self.pending.keys().map(|name| name.as_str())
+    }
+}
+
+/// The result of a call to [`TransferManager::poll()`]
+#[derive(Debug)]
+pub enum PollOutcome {
+    /// The poll queue is empty
+    Empty,
+    /// The transfer for the named domain was resolved, and the message was acknowledged
+    Resolved(String),
+    /// A message that does not belong to a tracked transfer, still to be acknowledged
+    Other(Box<Response<PollData, NoExtension>>),
+}
+
+/// Future resolving to the final `TransferData` for a domain transfer
+#[derive(Debug)]
+pub struct PendingTransfer {
+    name: String,
+    rx: oneshot::Receiver<TransferData>,
+}
+
+impl PendingTransfer {
+    fn new(name: &str) -> (oneshot::Sender<TransferData>, Self) {
+        let (tx, rx) = oneshot::channel();
+        let name = name.to_owned();
+        (tx, Self { name, rx })
+    }
+
+    /// The name of the domain being transferred
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Future for PendingTransfer {
+    type Output = Result<TransferData, Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> TaskPoll<Self::Output> {
+        Pin::new(&mut self.rx).poll(cx).map(|result| {
+            result.map_err(|_| {
+                Error::Other(format!("transfer for {} is no longer tracked", self.name).into())
+            })
+        })
+    }
+}
+
+fn is_final(data: &TransferData) -> bool {
+    data.status().is_ok_and(|status| status.is_final())
+}
+
+fn key(name: &str) -> String {
+    name.to_ascii_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_final, TransferManager};
+    use crate::domain::transfer::{DomainTransfer, TransferStatus};
+    use crate::tests::response_from_file;
+
+    #[test]
+    fn update() {
+        let mut manager = TransferManager::new();
+        let mut pending = manager.track("EPPDEV-TRANSFER.com");
+        assert_eq!(
+            manager.pending().collect::<Vec<_>>(),
+            ["eppdev-transfer.com"]
+        );
+
+        let rsp = response_from_file::<DomainTransfer>("response/domain/transfer_query.xml");
+        let mut data = rsp.res_data.unwrap().into_inner();
+        assert!(!is_final(&data));
+        assert!(!manager.update(data));
+
+        let rsp = response_from_file::<DomainTransfer>("response/domain/transfer_query.xml");
+        data = rsp.res_data.unwrap().into_inner();
+        data.transfer_status = TransferStatus::ClientApproved.as_str().to_owned();
+        assert!(manager.update(data));
+        assert_eq!(manager.pending().count(), 0);
+
+        let data = pending.rx.try_recv().unwrap();
+        assert!(data.status().unwrap().is_approved());
+    }
+}
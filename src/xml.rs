@@ -1,12 +1,490 @@
 //! Types to use in serialization to and deserialization from EPP XML
 
+use std::cell::Cell;
+use std::ops::Range;
+
 use instant_xml::{FromXml, FromXmlOwned, ToXml};
 
 use crate::common::EPP_XMLNS;
-use crate::error::Error;
+use crate::error::{Error, RequestError};
+use crate::request::{Command, CommandWrapper, Extension, RequestData, Transaction};
+use crate::response::Response;
 
 pub const EPP_XML_HEADER: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="no"?>"#;
 
+/// Serialize a command with an optional extension to an EPP XML request document
+///
+/// The `id` is used as the client transaction ID, and must be valid as such, see
+/// [`check_transaction_id()`]. This is what `EppClient::transact()` sends; it is useful on its
+/// own for tooling that handles the transport itself.
+pub fn serialize_request<'c, 'e, Cmd, Ext>(
+    data: impl Into<RequestData<'c, 'e, Cmd, Ext>>,
+    id: &str,
+) -> Result<String, Error>
+where
+    Cmd: Transaction<Ext> + Command + 'c,
+    Ext: Extension + 'e,
+{
+    serialize_request_with(data.into(), id, &[], Options::default())
+}
+
+/// Serialize a request with `extra` serialized extensions added to its `<extension>` element
+pub(crate) fn serialize_request_with<Cmd, Ext>(
+    data: RequestData<'_, '_, Cmd, Ext>,
+    id: &str,
+    extra: &[String],
+    options: Options,
+) -> Result<String, Error>
+where
+    Cmd: Transaction<Ext> + Command,
+    Ext: Extension,
+{
+    data.command.validate()?;
+    if let Some(extension) = data.extension {
+        extension.validate()?;
+    }
+
+    check_transaction_id(id)?;
+    let wrapper = CommandWrapper::new(data.command, data.extension, extra, id);
+    options
+        .scope(|| serialize(wrapper))
+        .map_err(|err| match err {
+            Error::Xml(source) => Error::Request(Box::new(RequestError {
+                command: Cmd::COMMAND,
+                registry: None,
+                transaction_id: id.to_owned(),
+                source,
+            })),
+            err => err,
+        })
+}
+
+/// Check that `id` can be used as a client transaction ID
+///
+/// RFC 5730 defines transaction IDs as tokens of 3 to 64 characters. A token can not contain
+/// line breaks or tabs, nor leading, trailing or consecutive spaces.
+pub fn check_transaction_id(id: &str) -> Result<(), Error> {
+    let len = id.chars().count();
+    let problem = if !(3..=64).contains(&len) {
+        "must be 3 to 64 characters long"
+    } else if id.chars().any(|c| c.is_control()) {
+        "contains control characters"
+    } else if id.starts_with(' ') || id.ends_with(' ') || id.contains("  ") {
+        "contains leading, trailing or consecutive spaces"
+    } else {
+        return Ok(());
+    };
+
+    Err(Error::Other(
+        format!("invalid client transaction ID {id:?}: {problem}").into(),
+    ))
+}
+
+/// Serialize a request like [`serialize_request()`], using the given namespace `prefixes`
+pub fn serialize_request_prefixed<'c, 'e, Cmd, Ext>(
+    data: impl Into<RequestData<'c, 'e, Cmd, Ext>>,
+    id: &str,
+    prefixes: &NamespacePrefixes,
+) -> Result<String, Error>
+where
+    Cmd: Transaction<Ext> + Command + 'c,
+    Ext: Extension + 'e,
+{
+    Ok(prefixes.apply(serialize_request(data, id)?))
+}
+
+/// Namespace prefixes to write elements with, for registries that require them
+///
+/// Requests are serialized with default namespace declarations (`<create
+/// xmlns="urn:ietf:params:xml:ns:domain-1.0">`), which any conforming server accepts. Some
+/// registries insist on prefixed elements (`<domain:create xmlns:domain="...">`) instead.
+/// Every element in a namespace that has a prefix here is written with that prefix, and the
+/// prefix is declared on the outermost such element.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct NamespacePrefixes {
+    // Pairs of prefix and namespace URI
+    prefixes: Vec<(String, String)>,
+}
+
+impl NamespacePrefixes {
+    /// No prefixes, add some with `prefix()`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The conventional `domain`, `host` and `contact` prefixes for the object mappings
+    pub fn objects() -> Self {
+        Self::new()
+            .prefix("domain", crate::domain::XMLNS)
+            .prefix("host", crate::host::XMLNS)
+            .prefix("contact", crate::contact::XMLNS)
+    }
+
+    /// Write elements in the namespace `ns` with `prefix`
+    ///
+    /// Replaces an earlier prefix for the same namespace.
+    pub fn prefix(mut self, prefix: &str, ns: &str) -> Self {
+        self.prefixes.retain(|(_, existing)| existing != ns);
+        self.prefixes.push((prefix.to_owned(), ns.to_owned()));
+        self
+    }
+
+    fn get(&self, ns: &str) -> Option<&str> {
+        self.prefixes
+            .iter()
+            .find(|(_, existing)| existing == ns)
+            .map(|(prefix, _)| prefix.as_str())
+    }
+
+    /// Rewrite a serialized document to use the prefixes
+    ///
+    /// This relies on the serializer's output: no comments or CDATA, and elements are either
+    /// unprefixed or use prefixes this doesn't know about.
+    pub(crate) fn apply(&self, xml: String) -> String {
+        if self.prefixes.is_empty() {
+            return xml;
+        }
+
+        struct Open<'a> {
+            // The default namespace in scope within the element, as serialized
+            ns: &'a str,
+            // The prefix the element was written with
+            prefix: Option<&'a str>,
+            // Whether the element declares its prefix
+            declares: bool,
+        }
+
+        let mut out = String::with_capacity(xml.len() + xml.len() / 4);
+        let mut stack = Vec::<Open<'_>>::new();
+        let mut rest = xml.as_str();
+        while let Some(start) = rest.find('<') {
+            out.push_str(&rest[..start]);
+            rest = &rest[start..];
+            let end = tag_end(rest);
+            let tag = &rest[..end];
+            rest = &rest[end..];
+
+            if tag.starts_with("<?") {
+                out.push_str(tag);
+                continue;
+            }
+
+            if let Some(name) = tag.strip_prefix("</") {
+                match stack.pop() {
+                    Some(Open {
+                        prefix: Some(prefix),
+                        ..
+                    }) => {
+                        out.push_str("</");
+                        out.push_str(prefix);
+                        out.push(':');
+                        out.push_str(name);
+                    }
+                    _ => out.push_str(tag),
+                }
+                continue;
+            }
+
+            let empty = tag.ends_with("/>");
+            let inner = &tag[1..tag.len() - if empty { 2 } else { 1 }];
+            let (name, attrs) =
+                inner.split_at(inner.find(char::is_whitespace).unwrap_or(inner.len()));
+            let attrs = attributes(attrs);
+
+            let parent = stack.last().map(|open| open.ns).unwrap_or_default();
+            let ns = attrs
+                .iter()
+                .find(|(key, _)| *key == "xmlns")
+                .map(|(_, value)| *value)
+                .unwrap_or(parent);
+
+            let prefix = match name.contains(':') {
+                true => None,
+                false => self.get(ns),
+            };
+
+            let Some(prefix) = prefix else {
+                out.push_str(tag);
+                if !empty {
+                    stack.push(Open {
+                        ns,
+                        prefix: None,
+                        declares: false,
+                    });
+                }
+                continue;
+            };
+
+            let declared = stack
+                .iter()
+                .any(|open| open.declares && open.prefix == Some(prefix));
+            out.push('<');
+            out.push_str(prefix);
+            out.push(':');
+            out.push_str(name);
+            if !declared {
+                out.push_str(&format!(" xmlns:{prefix}=\"{ns}\""));
+            }
+            for (key, value) in &attrs {
+                if *key != "xmlns" {
+                    out.push_str(&format!(" {key}=\"{value}\""));
+                }
+            }
+
+            match empty {
+                true => out.push_str("/>"),
+                false => {
+                    out.push('>');
+                    stack.push(Open {
+                        ns,
+                        prefix: Some(prefix),
+                        declares: !declared,
+                    });
+                }
+            }
+        }
+
+        out.push_str(rest);
+        out
+    }
+}
+
+/// Split the attributes of a start tag into keys and (still escaped) values
+fn attributes(mut s: &str) -> Vec<(&str, &str)> {
+    let mut attrs = Vec::new();
+    loop {
+        s = s.trim_start();
+        let Some((key, value)) = s.split_once('=') else {
+            return attrs;
+        };
+
+        let value = value.trim_start();
+        let Some(quote) = value.chars().next() else {
+            return attrs;
+        };
+
+        let value = &value[1..];
+        let Some(end) = value.find(quote) else {
+            return attrs;
+        };
+
+        attrs.push((key.trim(), &value[..end]));
+        s = &value[end + 1..];
+    }
+}
+
+/// Deserialize an EPP XML response document to the command `Cmd` with extension `Ext`
+pub fn deserialize_response<Cmd: Command, Ext: Extension>(
+    xml: &str,
+) -> Result<Response<Cmd::Response, Ext::Response>, Error> {
+    deserialize(xml)
+}
+
+/// Deserialize a response like [`deserialize_response()`], reporting what the types don't cover
+///
+/// Meant for onboarding a registry whose responses deviate from the RFCs. The regular
+/// deserialization silently skips elements it doesn't know and fails on the first one it can't
+/// parse. This reports all elements that were skipped or kept the response from parsing, with
+/// their path and raw XML, along with the response parsed without the latter.
+///
+/// The document is deserialized again for every element in it, so this is much slower than
+/// [`deserialize_response()`] and not meant for regular use.
+pub fn diagnose_response<Cmd: Command, Ext: Extension>(
+    xml: &str,
+) -> Diagnosis<Response<Cmd::Response, Ext::Response>> {
+    diagnose(xml)
+}
+
+/// The result of [`diagnose_response()`]
+#[derive(Debug)]
+pub struct Diagnosis<T> {
+    /// The response, parsed without the `invalid` elements
+    pub response: Result<T, Error>,
+    /// Elements that are not part of the parsed response
+    pub ignored: Vec<Unexpected>,
+    /// Elements left out to parse the response, with the error they caused
+    ///
+    /// For an element that can't be left out, such as a required field, this is the innermost
+    /// optional element containing it.
+    pub invalid: Vec<(Unexpected, Error)>,
+}
+
+/// An element found by [`diagnose_response()`]
+#[derive(Debug, Eq, PartialEq)]
+pub struct Unexpected {
+    /// The names of the element and its ancestors as written, like `epp/response/resData`
+    pub path: String,
+    /// The element as it appears in the document
+    pub raw: String,
+}
+
+fn diagnose<T: FromXmlOwned + std::fmt::Debug>(xml: &str) -> Diagnosis<T> {
+    let elements = elements(xml);
+    let mut removed = Vec::<Range<usize>>::new();
+    let parse = |removed: &[Range<usize>], extra: &Range<usize>| {
+        let mut ranges = removed.to_vec();
+        ranges.retain(|range| !contains(extra, range));
+        ranges.push(extra.clone());
+        deserialize::<T>(&without(xml, ranges))
+    };
+    let unexpected = |path: &str, range: &Range<usize>| Unexpected {
+        path: path.to_owned(),
+        raw: xml[range.clone()].to_owned(),
+    };
+
+    // Leave out the innermost element that keeps the response from parsing, until it parses
+    let mut invalid = Vec::new();
+    let mut response = deserialize::<T>(xml);
+    while let Err(err) = response {
+        let mut inner_first = elements.iter().collect::<Vec<_>>();
+        inner_first.sort_by_key(|(_, range)| range.end);
+        let fixed = inner_first.into_iter().find_map(|(path, range)| {
+            match removed.iter().any(|r| contains(r, range)) {
+                true => None,
+                false => parse(&removed, range).ok().map(|rsp| (path, range, rsp)),
+            }
+        });
+
+        let Some((path, range, rsp)) = fixed else {
+            response = Err(err);
+            break;
+        };
+
+        invalid.push((unexpected(path, range), err));
+        removed.retain(|r| !contains(range, r));
+        removed.push(range.clone());
+        response = Ok(rsp);
+    }
+
+    // Elements that can be left out without changing the response were ignored
+    let mut ignored = Vec::new();
+    if let Ok(rsp) = &response {
+        let expected = format!("{rsp:?}");
+        for (path, range) in &elements {
+            if removed.iter().any(|r| contains(r, range)) {
+                continue;
+            }
+
+            if let Ok(rsp) = parse(&removed, range) {
+                if format!("{rsp:?}") == expected {
+                    ignored.push(unexpected(path, range));
+                    removed.push(range.clone());
+                }
+            }
+        }
+    }
+
+    Diagnosis {
+        response,
+        ignored,
+        invalid,
+    }
+}
+
+/// The paths and byte ranges of the elements in `xml`, in document order
+fn elements(xml: &str) -> Vec<(String, Range<usize>)> {
+    let mut elements = Vec::<(String, Range<usize>)>::new();
+    // Indices into `elements` of the open elements
+    let mut open = Vec::<usize>::new();
+    let mut pos = 0;
+    while let Some(start) = xml[pos..].find('<') {
+        let start = pos + start;
+        let tag = &xml[start..start + tag_end(&xml[start..])];
+        pos = start + tag.len();
+
+        if tag.starts_with("<?") || tag.starts_with("<!") {
+            continue;
+        } else if tag.starts_with("</") {
+            if let Some(i) = open.pop() {
+                elements[i].1.end = pos;
+            }
+            continue;
+        }
+
+        let name = tag[1..]
+            .split(|c: char| c.is_whitespace() || c == '/' || c == '>')
+            .next()
+            .unwrap_or_default();
+        let path = match open.last() {
+            Some(&i) => format!("{}/{name}", elements[i].0),
+            None => name.to_owned(),
+        };
+
+        match tag.ends_with("/>") {
+            true => elements.push((path, start..pos)),
+            false => {
+                open.push(elements.len());
+                elements.push((path, start..usize::MAX));
+            }
+        }
+    }
+
+    // Elements that are never closed
+    elements.retain(|(_, range)| range.end != usize::MAX);
+    elements
+}
+
+fn contains(outer: &Range<usize>, inner: &Range<usize>) -> bool {
+    outer.start <= inner.start && inner.end <= outer.end
+}
+
+/// `xml` without the (non-overlapping) `ranges`
+fn without(xml: &str, mut ranges: Vec<Range<usize>>) -> String {
+    ranges.sort_by_key(|range| range.start);
+    let mut out = String::with_capacity(xml.len());
+    let mut pos = 0;
+    for range in ranges {
+        out.push_str(&xml[pos..range.start]);
+        pos = range.end;
+    }
+
+    out.push_str(&xml[pos..]);
+    out
+}
+
+/// Settings of a client that change how its requests and responses are (de)serialized
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct Options {
+    /// Serialize empty containers, see `EppClient::set_keep_empty_elements()`
+    pub(crate) keep_empty_elements: bool,
+    /// Only accept RFC 3339 dates in UTC, see `EppClient::set_strict_dates()`
+    pub(crate) strict_dates: bool,
+}
+
+impl Options {
+    /// The options of the document being (de)serialized on this thread
+    ///
+    /// instant-xml has no way to pass state to `ToXml` and `FromXml` implementations, so the
+    /// options are only set for the duration of a `scope()` call.
+    pub(crate) fn current() -> Self {
+        CURRENT.get()
+    }
+
+    /// Call `f` with these options in effect on this thread
+    pub(crate) fn scope<T>(self, f: impl FnOnce() -> T) -> T {
+        // Restores the previous options, even if `f` panics
+        struct Restore(Options);
+
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                CURRENT.set(self.0);
+            }
+        }
+
+        let _restore = Restore(CURRENT.replace(self));
+        f()
+    }
+}
+
+thread_local! {
+    static CURRENT: Cell<Options> = const {
+        Cell::new(Options {
+            keep_empty_elements: false,
+            strict_dates: false,
+        })
+    };
+}
+
 pub(crate) fn serialize(data: impl ToXml) -> Result<String, Error> {
     Ok(format!(
         "{}\r\n{}",
@@ -27,3 +505,431 @@ pub(crate) fn deserialize<T: FromXmlOwned>(xml: &str) -> Result<T, Error> {
 pub(crate) struct Epp<T> {
     pub(crate) data: T,
 }
+
+/// Indent an XML document for use in diagnostics
+///
+/// Each element starts on its own line, indented by its depth; elements containing only text
+/// keep it on the same line. Whitespace between elements is dropped. This does not validate
+/// its input: malformed XML is formatted as well as possible.
+#[cfg_attr(not(feature = "client"), allow(dead_code))]
+pub(crate) fn pretty(xml: &str) -> String {
+    let mut out = String::with_capacity(xml.len() * 2);
+    let mut depth = 0usize;
+    let mut last = Last::Other;
+
+    let mut rest = xml;
+    while !rest.is_empty() {
+        if !rest.starts_with('<') {
+            let end = rest.find('<').unwrap_or(rest.len());
+            let text = rest[..end].trim();
+            rest = &rest[end..];
+            if text.is_empty() {
+                continue;
+            }
+
+            if last != Last::Open {
+                newline(&mut out, depth);
+            }
+            out.push_str(text);
+            last = Last::Text;
+            continue;
+        }
+
+        let end = tag_end(rest);
+        let tag = &rest[..end];
+        rest = &rest[end..];
+
+        if tag.starts_with("</") {
+            depth = depth.saturating_sub(1);
+            if last == Last::Other {
+                newline(&mut out, depth);
+            }
+            out.push_str(tag);
+            last = Last::Other;
+        } else if tag.starts_with("<?") || tag.starts_with("<!") || tag.ends_with("/>") {
+            newline(&mut out, depth);
+            out.push_str(tag);
+            last = Last::Other;
+        } else {
+            newline(&mut out, depth);
+            out.push_str(tag);
+            depth += 1;
+            last = Last::Open;
+        }
+    }
+
+    out
+}
+
+/// Normalize the whitespace in an XML document for comparison
+///
+/// Whitespace between tags, such as the indentation between elements, is dropped, and so is
+/// leading and trailing whitespace in text content; whitespace within text is kept. An XML
+/// declaration at the start is followed by `\r\n`, like in the requests this crate serializes.
+/// This makes an indented document, like the fixtures in this crate's test suite, comparable to
+/// serialized requests and to responses as registries send them. This does not validate its
+/// input.
+pub fn normalize(xml: &str) -> String {
+    let mut out = String::with_capacity(xml.len());
+    let mut rest = xml.trim_start();
+    if rest.starts_with("<?xml") {
+        let end = tag_end(rest);
+        out.push_str(&rest[..end]);
+        out.push_str("\r\n");
+        rest = &rest[end..];
+    }
+
+    while !rest.is_empty() {
+        let end = match rest.starts_with('<') {
+            true => tag_end(rest),
+            false => rest.find('<').unwrap_or(rest.len()),
+        };
+
+        let token = &rest[..end];
+        rest = &rest[end..];
+        match token.starts_with('<') {
+            true => out.push_str(token),
+            false => out.push_str(token.trim()),
+        }
+    }
+
+    out
+}
+
+/// The kind of token most recently written by [`pretty()`]
+#[derive(Clone, Copy, Eq, PartialEq)]
+enum Last {
+    Open,
+    Text,
+    Other,
+}
+
+fn newline(out: &mut String, depth: usize) {
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    for _ in 0..depth {
+        out.push_str("  ");
+    }
+}
+
+/// Find the end of the tag at the start of `s`, skipping over quoted attribute values
+fn tag_end(s: &str) -> usize {
+    let terminator = if s.starts_with("<!--") {
+        Some("-->")
+    } else if s.starts_with("<![CDATA[") {
+        Some("]]>")
+    } else {
+        None
+    };
+
+    if let Some(terminator) = terminator {
+        return match s.find(terminator) {
+            Some(pos) => pos + terminator.len(),
+            None => s.len(),
+        };
+    }
+
+    let mut quote = None;
+    for (i, c) in s.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if c == q => quote = None,
+            (None, '>') => return i + 1,
+            _ => {}
+        }
+    }
+
+    s.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt;
+
+    use instant_xml::{Serializer, ToXml};
+
+    use std::time::Duration;
+
+    use super::{
+        check_transaction_id, diagnose_response, normalize, pretty, serialize_request,
+        serialize_request_prefixed, serialize_request_with, NamespacePrefixes, Options,
+    };
+    use crate::common::{NoExtension, StatusValue};
+    use crate::contact::{self, ContactCreate, ContactUpdate};
+    use crate::domain::update::{DomainAdd, DomainRemove};
+    use crate::domain::{
+        DomainCheck, DomainContact, DomainCreate, DomainUpdate, HostInfo, HostObj, NameServers,
+        Period, Status,
+    };
+    use crate::extensions::secdns::{self, Algorithm, CreateData, DigestAlgorithm, DsDataType};
+    use crate::host::update::{HostAdd, HostChangeInfo};
+    use crate::host::HostUpdate;
+    use crate::request::{Command, Transaction};
+    use crate::tests::{assert_serialized, get_xml, CLTRID};
+    use crate::Error;
+
+    #[test]
+    fn transaction_id() {
+        assert!(check_transaction_id(CLTRID).is_ok());
+        assert!(check_transaction_id("ABC 12345").is_ok());
+        assert!(check_transaction_id(&"x".repeat(64)).is_ok());
+
+        for id in [
+            "id",
+            &"x".repeat(65),
+            "ABC\n12345",
+            " ABC-12345",
+            "ABC  12345",
+        ] {
+            assert!(check_transaction_id(id).is_err(), "{id:?}");
+        }
+
+        let err = serialize_request(&crate::logout::Logout, "id").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            r#"error: invalid client transaction ID "id": must be 3 to 64 characters long"#
+        );
+    }
+
+    #[test]
+    fn request_error() {
+        #[derive(Debug)]
+        struct Broken;
+
+        impl ToXml for Broken {
+            fn serialize<W: fmt::Write + ?Sized>(
+                &self,
+                _: Option<instant_xml::Id<'_>>,
+                _: &mut Serializer<W>,
+            ) -> Result<(), instant_xml::Error> {
+                Err(instant_xml::Error::UnexpectedValue("broken".to_owned()))
+            }
+        }
+
+        impl Transaction<NoExtension> for Broken {}
+
+        impl Command for Broken {
+            type Response = ();
+            const COMMAND: &'static str = "broken";
+        }
+
+        let Err(Error::Request(err)) = serialize_request(&Broken, "cltrid") else {
+            panic!("expected a request error");
+        };
+
+        assert_eq!(err.command, "broken");
+        assert_eq!(err.transaction_id, "cltrid");
+        assert_eq!(
+            err.to_string(),
+            "failed to serialize broken command (clTRID cltrid): unexpected value: 'broken'"
+        );
+    }
+
+    #[test]
+    fn pretty_print() {
+        let xml = concat!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="no"?>"#,
+            "\r\n",
+            r#"<epp xmlns="urn:ietf:params:xml:ns:epp-1.0"><response>"#,
+            r#"<result code="1000"><msg>Command completed successfully</msg></result>"#,
+            r#"<resData><domain:chkData xmlns:domain="urn:ietf:params:xml:ns:domain-1.0">"#,
+            r#"<domain:cd><domain:name avail="1">eppdev.com</domain:name></domain:cd>"#,
+            r#"<domain:cd><domain:name avail="0"/><!-- a > b --></domain:cd>"#,
+            r#"</domain:chkData></resData><trID><clTRID a=">">cltrid</clTRID><svTRID></svTRID>"#,
+            r#"</trID></response></epp>"#,
+        );
+
+        let expected = r#"<?xml version="1.0" encoding="UTF-8" standalone="no"?>
+<epp xmlns="urn:ietf:params:xml:ns:epp-1.0">
+  <response>
+    <result code="1000">
+      <msg>Command completed successfully</msg>
+    </result>
+    <resData>
+      <domain:chkData xmlns:domain="urn:ietf:params:xml:ns:domain-1.0">
+        <domain:cd>
+          <domain:name avail="1">eppdev.com</domain:name>
+        </domain:cd>
+        <domain:cd>
+          <domain:name avail="0"/>
+          <!-- a > b -->
+        </domain:cd>
+      </domain:chkData>
+    </resData>
+    <trID>
+      <clTRID a=">">cltrid</clTRID>
+      <svTRID></svTRID>
+    </trID>
+  </response>
+</epp>"#;
+        assert_eq!(pretty(xml), expected);
+
+        // Malformed input is formatted without panicking
+        assert_eq!(pretty("</a>text<b attr=\"x"), "</a>\ntext\n<b attr=\"x");
+    }
+
+    #[test]
+    fn diagnose() {
+        let xml = get_xml("response/contact/create_full.xml").unwrap();
+        let diagnosis = diagnose_response::<ContactCreate, NoExtension>(&xml);
+        let rsp = diagnosis.response.unwrap();
+        assert_eq!(rsp.res_data().unwrap().id, "eppdev-contact-4");
+        assert!(diagnosis.invalid.is_empty());
+
+        let paths = diagnosis
+            .ignored
+            .iter()
+            .map(|unexpected| unexpected.path.rsplit('/').next().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            [
+                "contact:roid",
+                "contact:status",
+                "contact:postalInfo",
+                "contact:voice",
+                "contact:email",
+                "contact:clID",
+                "contact:crID",
+            ]
+        );
+        let ignored = &diagnosis.ignored[0];
+        assert_eq!(
+            ignored.path,
+            "epp/response/resData/contact:creData/contact:roid"
+        );
+        assert_eq!(
+            ignored.raw,
+            "<contact:roid>eppdev-contact-4-REP</contact:roid>"
+        );
+
+        let xml = get_xml("response/domain/check.xml")
+            .unwrap()
+            .replace(r#"avail="1""#, r#"avail="maybe""#);
+        let diagnosis = diagnose_response::<DomainCheck, NoExtension>(&xml);
+        let rsp = diagnosis.response.unwrap();
+        assert_eq!(rsp.res_data().unwrap().list.len(), 2);
+        assert!(diagnosis.ignored.is_empty());
+        let (invalid, _) = &diagnosis.invalid[0];
+        assert_eq!(
+            invalid.path,
+            "epp/response/resData/domain:chkData/domain:cd"
+        );
+        assert!(invalid.raw.contains("maybe"));
+
+        let diagnosis = diagnose_response::<DomainCheck, NoExtension>("<epp>");
+        assert!(diagnosis.response.is_err());
+    }
+
+    #[test]
+    fn normalize_whitespace() {
+        let xml = concat!(
+            "\r\n<?xml version=\"1.0\" encoding=\"UTF-8\"?>\r\n",
+            "<epp xmlns=\"urn:ietf:params:xml:ns:epp-1.0\">\r\n",
+            "  <response>\r\n",
+            "    <msg lang=\"en\">\r\n      Credit  balance low.\r\n    </msg>\r\n",
+            "    <svTRID attr=\"a > b\"> </svTRID>\r\n",
+            "  </response>\r\n",
+            "</epp>\r\n",
+        );
+
+        assert_eq!(
+            normalize(xml),
+            concat!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\r\n",
+                "<epp xmlns=\"urn:ietf:params:xml:ns:epp-1.0\"><response>",
+                "<msg lang=\"en\">Credit  balance low.</msg>",
+                "<svTRID attr=\"a > b\"></svTRID></response></epp>",
+            )
+        );
+        assert_eq!(normalize(&normalize(xml)), normalize(xml));
+    }
+
+    #[test]
+    fn prefixed() {
+        let ns = [HostInfo::Obj(HostObj {
+            name: "ns1.eppdev-1.com".into(),
+        })];
+        let object = DomainCreate::new(
+            "eppdev-1.com",
+            Period::years(1).unwrap(),
+            Some(&ns),
+            Some("eppdev-contact-3"),
+            "epP4uthd#v",
+            None,
+        );
+        let ds_data = [DsDataType::new(
+            12345,
+            Algorithm::Dsa,
+            DigestAlgorithm::Sha1,
+            "49FD46E6C4B45C55D4AC",
+            None,
+        )];
+        let extension = CreateData::from((Duration::from_secs(604800), ds_data.as_ref()));
+
+        let prefixes = NamespacePrefixes::objects().prefix("secDNS", secdns::XMLNS);
+        let xml = serialize_request_prefixed((&object, &extension), CLTRID, &prefixes).unwrap();
+        assert_eq!(xml, get_xml("request/domain/create_prefixed.xml").unwrap());
+    }
+
+    #[test]
+    fn empty_elements() {
+        // Empty containers are left out by default
+        let contacts = ["admin", "tech", "billing"].map(|contact_type| DomainContact {
+            contact_type: contact_type.into(),
+            id: "eppdev-contact-3".into(),
+        });
+        let object = DomainCreate::new(
+            "eppdev-1.com",
+            Period::years(1).unwrap(),
+            Some(&[]),
+            Some("eppdev-contact-3"),
+            "epP4uthd#v",
+            Some(&contacts),
+        );
+        assert_serialized("request/domain/create.xml", &object);
+
+        let statuses = [StatusValue::new(Status::ClientHold)];
+        let mut domain = DomainUpdate::new("eppdev-1.com");
+        domain.add(DomainAdd {
+            ns: Some(NameServers { ns: [][..].into() }),
+            contacts: Some(&[]),
+            statuses: None,
+        });
+        domain.remove(DomainRemove {
+            ns: None,
+            contacts: None,
+            statuses: Some(&statuses),
+        });
+        assert_serialized("request/domain/update_empty.xml", &domain);
+
+        let mut host = HostUpdate::new("host1.eppdev-1.com");
+        host.add(HostAdd {
+            addresses: Some(&[]),
+            statuses: None,
+        });
+        host.info(HostChangeInfo {
+            name: "host2.eppdev-1.com",
+        });
+        assert_serialized("request/host/update_empty.xml", &host);
+
+        let statuses = [StatusValue::new(contact::Status::ClientDeleteProhibited)];
+        let mut object = ContactUpdate::new("eppdev-contact-3");
+        object.add(&[]);
+        object.remove(&statuses);
+        assert_serialized("request/contact/update_empty.xml", &object);
+
+        // Registries that require them get them back
+        let options = Options {
+            keep_empty_elements: true,
+            ..Options::default()
+        };
+        let xml = serialize_request_with((&domain).into(), CLTRID, &[], options).unwrap();
+        assert_eq!(
+            xml,
+            get_xml("request/domain/update_empty_kept.xml").unwrap()
+        );
+        assert_serialized("request/domain/update_empty.xml", &domain);
+    }
+}
@@ -1,17 +1,41 @@
 use std::fs::File;
 use std::io::{self, Read, Write};
 use std::str;
-use std::time::Duration;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
-use regex::Regex;
 use tokio::time::timeout;
 use tokio_test::io::Builder;
 
-use instant_epp::client::{Connector, EppClient};
-use instant_epp::domain::{DomainCheck, DomainContact, DomainCreate, Period, PeriodLength};
+use instant_epp::circuit_breaker::CircuitBreaker;
+use instant_epp::client::{
+    Archiver, CloseReason, ConnectionEvent, Connector, Credentials, EppClient, GreetingRetry,
+    Keepalive, PendingStore, PendingTransaction, ReconnectBackoff,
+};
+use instant_epp::contact::{Address, ContactCreate, InfoType, PostalInfo};
+use instant_epp::domain;
+use instant_epp::domain::transfer::TransferStatus;
+use instant_epp::domain::{
+    DomainCheck, DomainContact, DomainCreate, DomainDelete, DomainInfo, Period, Status,
+};
+use instant_epp::extensions::fee;
+use instant_epp::extensions::launch::PhaseType;
+use instant_epp::extensions::namestore::NameStore;
+use instant_epp::extensions::reseller;
+use instant_epp::layer::{Incoming, Layer, Outgoing};
 use instant_epp::login::Login;
+use instant_epp::nsgroup::NsGroupInfo;
+use instant_epp::poll::PollData;
+use instant_epp::request::RequestData;
 use instant_epp::response::ResultCode;
+use instant_epp::workflows::lock;
+use instant_epp::workflows::poll::{Drain, DrainStop};
+use instant_epp::workflows::register::RegisterSpec;
+use instant_epp::workflows::registrant::{RegistrantChange, RegistrantChangeOutcome};
+use instant_epp::workflows::rename::{HostRename, RenameProgress};
+use instant_epp::workflows::transfer::{PollOutcome, TransferManager};
 use instant_epp::Error;
 
 const CLTRID: &str = "cltrid:1626454866";
@@ -44,30 +68,22 @@ fn len_bytes(bytes: &str) -> [u8; 4] {
 }
 
 fn xml(path: &str) -> String {
-    let ws_regex = Regex::new(r"[\s]{2,}").unwrap();
-    let end_regex = Regex::new(r"\?>").unwrap();
-
     let mut f = File::open(format!("tests/resources/{path}")).unwrap();
     let mut buf = String::new();
     f.read_to_string(&mut buf).unwrap();
-
-    if !buf.is_empty() {
-        let mat = end_regex.find(buf.as_str()).unwrap();
-        let start = mat.end();
-        buf = format!(
-            "{}\r\n{}",
-            &buf[..start],
-            ws_regex.replace_all(&buf[start..], "")
-        );
-    }
-
-    buf
+    instant_epp::xml::normalize(&buf)
 }
 
 fn build_stream(units: &[&str]) -> Builder {
     let mut builder = Builder::new();
-    for (i, path) in units.iter().enumerate() {
-        let buf = xml(path);
+    for (i, unit) in units.iter().enumerate() {
+        // Requests sent by workflows carry the command's number in the workflow, as in `path#2`
+        let buf = match unit.split_once('#') {
+            Some((path, n)) => {
+                xml(path).replace(&format!(">{CLTRID}<"), &format!(">{CLTRID}-{n}<"))
+            }
+            None => xml(unit),
+        };
         match i % 2 {
             0 => builder.read(&len_bytes(&buf)).read(buf.as_bytes()),
             1 => builder.write(&len_bytes(&buf)).write(buf.as_bytes()),
@@ -135,6 +151,147 @@ async fn client() {
     assert_eq!(result.list[0].name.value, "eppdev.com");
 }
 
+#[cfg(feature = "blocking")]
+#[test]
+fn blocking() {
+    let _guard = log_to_stdout();
+
+    struct FakeConnector;
+
+    #[async_trait]
+    impl Connector for FakeConnector {
+        type Connection = tokio_test::io::Mock;
+
+        async fn connect(&self, _: Duration) -> Result<Self::Connection, Error> {
+            Ok(build_stream(&[
+                "response/greeting.xml",
+                "request/domain/check.xml",
+                "response/domain/check.xml",
+            ])
+            .build())
+        }
+    }
+
+    let mut client =
+        instant_epp::blocking::EppClient::new(FakeConnector, "test".into(), Duration::from_secs(5))
+            .unwrap();
+
+    assert_eq!(client.xml_greeting(), xml("response/greeting.xml"));
+    let rsp = client
+        .transact(
+            &DomainCheck {
+                domains: &["eppdev.com", "eppdev.net"],
+            },
+            CLTRID,
+        )
+        .unwrap();
+
+    let result = rsp.res_data().unwrap();
+    assert_eq!(result.list[0].name.value, "eppdev.com");
+}
+
+#[cfg(feature = "async-io")]
+#[test]
+fn async_io() {
+    use std::net::{SocketAddr, TcpListener, TcpStream};
+
+    use async_io::Async;
+    use instant_epp::client::Compat;
+
+    let _guard = log_to_stdout();
+
+    struct AsyncIoConnector(SocketAddr);
+
+    #[async_trait]
+    impl Connector for AsyncIoConnector {
+        type Connection = Compat<Async<TcpStream>>;
+
+        async fn connect(&self, _: Duration) -> Result<Self::Connection, Error> {
+            Ok(Compat::new(Async::<TcpStream>::connect(self.0).await?))
+        }
+    }
+
+    // The server runs on its own thread, so the client is driven without any tokio runtime
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let greeting = xml("response/greeting.xml");
+        stream.write_all(&len_bytes(&greeting)).unwrap();
+        stream.write_all(greeting.as_bytes()).unwrap();
+
+        let mut len = [0; 4];
+        stream.read_exact(&mut len).unwrap();
+        let mut request = vec![0; u32::from_be_bytes(len) as usize - 4];
+        stream.read_exact(&mut request).unwrap();
+        assert_eq!(
+            str::from_utf8(&request).unwrap(),
+            xml("request/domain/check.xml")
+        );
+
+        let response = xml("response/domain/check.xml");
+        stream.write_all(&len_bytes(&response)).unwrap();
+        stream.write_all(response.as_bytes()).unwrap();
+    });
+
+    futures_lite::future::block_on(async {
+        let connector = AsyncIoConnector(addr);
+        let mut client = EppClient::new(connector, "test".into(), Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert_eq!(client.xml_greeting(), xml("response/greeting.xml"));
+
+        let rsp = client
+            .transact(
+                &DomainCheck {
+                    domains: &["eppdev.com", "eppdev.net"],
+                },
+                CLTRID,
+            )
+            .await
+            .unwrap();
+        let result = rsp.res_data().unwrap();
+        assert_eq!(result.list[0].name.value, "eppdev.com");
+    });
+
+    server.join().unwrap();
+}
+
+#[tokio::test]
+async fn reseller_info() {
+    let _guard = log_to_stdout();
+
+    struct FakeConnector;
+
+    #[async_trait]
+    impl Connector for FakeConnector {
+        type Connection = tokio_test::io::Mock;
+
+        async fn connect(&self, _: Duration) -> Result<Self::Connection, Error> {
+            Ok(build_stream(&[
+                "response/greeting.xml",
+                "request/domain/info.xml",
+                "response/extensions/reseller_info.xml",
+            ])
+            .build())
+        }
+    }
+
+    let mut client = EppClient::new(FakeConnector, "test".into(), Duration::from_secs(5))
+        .await
+        .unwrap();
+
+    // The info command is sent without an extension, but the reseller data is parsed
+    let info = DomainInfo::new("eppdev.com").with_auth("2fooBAR");
+    let data = RequestData::new(&info, None::<&reseller::Info>);
+    let rsp = client.transact(data, CLTRID).await.unwrap();
+
+    assert_eq!(rsp.res_data().unwrap().name, "eppdev-1.com");
+    let reseller = rsp.extension().unwrap();
+    assert_eq!(reseller.id, "myreseller");
+    assert_eq!(reseller.name.as_deref(), Some("Example Reseller Inc."));
+}
+
 #[tokio::test]
 async fn dropped() {
     let _guard = log_to_stdout();
@@ -232,7 +389,7 @@ async fn dropped() {
     // remainder of the in-flight request before starting the new one, and succeed.
     let create = DomainCreate::new(
         "eppdev-1.com",
-        Period::Years(PeriodLength::new(1).unwrap()),
+        Period::years(1).unwrap(),
         None,
         Some("eppdev-contact-3"),
         "epP4uthd#v",
@@ -242,3 +399,1865 @@ async fn dropped() {
     let rsp = client.transact(&create, CLTRID).await.unwrap();
     assert_eq!(rsp.result.code, ResultCode::CommandCompletedSuccessfully);
 }
+
+#[tokio::test]
+async fn close_gracefully() {
+    let _guard = log_to_stdout();
+
+    struct FakeConnector;
+
+    #[async_trait]
+    impl Connector for FakeConnector {
+        type Connection = tokio_test::io::Mock;
+
+        async fn connect(&self, _: Duration) -> Result<Self::Connection, Error> {
+            let mut builder = Builder::new();
+
+            let buf = xml("response/greeting.xml");
+            builder.read(&len_bytes(&buf)).read(buf.as_bytes());
+
+            let buf = xml("request/domain/check.xml");
+            builder.write(&len_bytes(&buf)).write(buf.as_bytes());
+
+            // The check future is dropped during this wait, leaving the request in flight.
+            builder.wait(Duration::from_millis(100));
+
+            let buf = xml("response/domain/check.xml");
+            builder.read(&len_bytes(&buf)).read(buf.as_bytes());
+
+            let buf = xml("request/logout.xml");
+            builder.write(&len_bytes(&buf)).write(buf.as_bytes());
+
+            let buf = xml("response/logout.xml");
+            builder.read(&len_bytes(&buf)).read(buf.as_bytes());
+
+            Ok(builder.build())
+        }
+    }
+
+    let mut client = EppClient::new(FakeConnector, "test".into(), Duration::from_secs(5))
+        .await
+        .unwrap();
+
+    timeout(
+        Duration::from_millis(10),
+        client.transact(
+            &DomainCheck {
+                domains: &["eppdev.com", "eppdev.net"],
+            },
+            CLTRID,
+        ),
+    )
+    .await
+    .unwrap_err();
+
+    // Closing should finish the dropped check before sending the logout command.
+    let deadline = Instant::now() + Duration::from_secs(5);
+    client.close_gracefully(CLTRID, deadline).await.unwrap();
+}
+
+#[tokio::test]
+async fn transfer_manager() {
+    let _guard = log_to_stdout();
+
+    struct FakeConnector;
+
+    #[async_trait]
+    impl Connector for FakeConnector {
+        type Connection = tokio_test::io::Mock;
+
+        async fn connect(&self, _: Duration) -> Result<Self::Connection, Error> {
+            Ok(build_stream(&[
+                "response/greeting.xml",
+                "request/domain/transfer_request.xml",
+                "response/domain/transfer_request.xml",
+                "request/poll/poll.xml#1",
+                "response/poll/poll_domain_transfer_approved.xml",
+                "request/poll/ack.xml#2",
+                "response/poll/ack.xml",
+            ])
+            .build())
+        }
+    }
+
+    let mut client = EppClient::new(FakeConnector, "test".into(), Duration::from_secs(5))
+        .await
+        .unwrap();
+
+    let mut manager = TransferManager::new();
+    let period = Period::years(1).unwrap();
+    let pending = manager
+        .request(
+            &mut client,
+            "testing.com",
+            Some(period),
+            "epP4uthd#v",
+            CLTRID,
+        )
+        .await
+        .unwrap();
+    assert_eq!(manager.pending().collect::<Vec<_>>(), ["testing.com"]);
+
+    match manager.poll(&mut client, CLTRID).await.unwrap() {
+        PollOutcome::Resolved(name) => assert_eq!(name, "testing.com"),
+        outcome => panic!("unexpected poll outcome: {outcome:?}"),
+    }
+
+    let data = pending.await.unwrap();
+    assert_eq!(data.status().unwrap(), TransferStatus::ClientApproved);
+    assert_eq!(manager.pending().count(), 0);
+}
+
+#[tokio::test]
+async fn lock_unlock() {
+    let _guard = log_to_stdout();
+
+    struct FakeConnector;
+
+    #[async_trait]
+    impl Connector for FakeConnector {
+        type Connection = tokio_test::io::Mock;
+
+        async fn connect(&self, _: Duration) -> Result<Self::Connection, Error> {
+            Ok(build_stream(&[
+                "response/greeting.xml",
+                // Lock: only the statuses not yet present are added
+                "request/domain/info_no_auth.xml#1",
+                "response/domain/info_unlocked.xml",
+                "request/domain/update_lock.xml#2",
+                "response/domain/update.xml",
+                "request/domain/info_no_auth.xml#3",
+                "response/domain/info_locked.xml",
+                // Unlock: `clientUpdateProhibited` goes first, on its own
+                "request/domain/info_no_auth.xml#1",
+                "response/domain/info_locked.xml",
+                "request/domain/update_unlock_update.xml#2",
+                "response/domain/update.xml",
+                "request/domain/update_unlock.xml#3",
+                "response/domain/update.xml",
+                "request/domain/info_no_auth.xml#4",
+                "response/domain/info_ok.xml",
+            ])
+            .build())
+        }
+    }
+
+    let mut client = EppClient::new(FakeConnector, "test".into(), Duration::from_secs(5))
+        .await
+        .unwrap();
+
+    let statuses = lock::lock_domain(&mut client, "eppdev.com", CLTRID)
+        .await
+        .unwrap();
+    assert_eq!(statuses, lock::LOCK_STATUSES);
+
+    let statuses = lock::unlock_domain(&mut client, "eppdev.com", CLTRID)
+        .await
+        .unwrap();
+    assert_eq!(statuses, [Status::Ok]);
+}
+
+#[tokio::test]
+async fn rename_host() {
+    let _guard = log_to_stdout();
+
+    struct FakeConnector;
+
+    #[async_trait]
+    impl Connector for FakeConnector {
+        type Connection = tokio_test::io::Mock;
+
+        async fn connect(&self, _: Duration) -> Result<Self::Connection, Error> {
+            Ok(build_stream(&[
+                "response/greeting.xml",
+                "request/host/create.xml#1",
+                "response/host/create.xml",
+                "request/domain/update_rename_host.xml#2",
+                "response/domain/update.xml",
+                "request/host/delete.xml#3",
+                "response/host/delete.xml",
+            ])
+            .build())
+        }
+    }
+
+    let mut client = EppClient::new(FakeConnector, "test".into(), Duration::from_secs(5))
+        .await
+        .unwrap();
+
+    // `eppdev-2.com` was moved in an earlier run
+    let progress = RenameProgress {
+        created: false,
+        updated: vec!["eppdev-2.com".into()],
+        deleted: false,
+    };
+    let domains = vec!["eppdev-1.com".into(), "EPPDEV-2.com".into()];
+    let mut rename = HostRename::new("ns1.eppdev-1.com", "host1.eppdev-1.com", domains)
+        .addresses(vec![
+            "29.245.122.14".parse().unwrap(),
+            "2404:6800:4001:801::200e".parse().unwrap(),
+        ])
+        .resume(progress);
+
+    let mut steps = Vec::new();
+    rename
+        .run(&mut client, CLTRID, |step| steps.push(format!("{step:?}")))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        steps,
+        [
+            "Created",
+            r#"Updated { domain: "eppdev-1.com", done: 2, total: 2 }"#,
+            "Deleted",
+        ]
+    );
+    assert!(rename.progress().deleted);
+
+    // Nothing is left to do
+    rename.run(&mut client, CLTRID, |_| {}).await.unwrap();
+}
+
+#[tokio::test]
+async fn host_dependencies() {
+    let _guard = log_to_stdout();
+
+    struct FakeConnector;
+
+    #[async_trait]
+    impl Connector for FakeConnector {
+        type Connection = tokio_test::io::Mock;
+
+        async fn connect(&self, _: Duration) -> Result<Self::Connection, Error> {
+            Ok(build_stream(&[
+                "response/greeting.xml",
+                "request/host/info.xml#1",
+                "response/host/info_linked.xml",
+                "request/domain/info_no_auth.xml#2",
+                "response/error.xml",
+                "request/domain/info_other.xml#3",
+                "response/domain/info.xml",
+            ])
+            .build())
+        }
+    }
+
+    let mut client = EppClient::new(FakeConnector, "test".into(), Duration::from_secs(5))
+        .await
+        .unwrap();
+
+    let dependencies = client
+        .host_dependencies("ns1.eppdev-1.com", &["eppdev.com", "eppdev-1.com"], CLTRID)
+        .await
+        .unwrap();
+
+    assert!(!dependencies.deletable());
+    assert_eq!(dependencies.domains, ["eppdev-1.com"]);
+    assert_eq!(dependencies.unknown.len(), 1);
+    assert_eq!(dependencies.unknown[0].0, "eppdev.com");
+
+    let Error::Command(err) = &dependencies.unknown[0].1 else {
+        panic!("unexpected error: {:?}", dependencies.unknown[0].1);
+    };
+    assert_eq!(err.command, "info");
+    assert_eq!(err.objects, ["eppdev.com"]);
+    assert_eq!(err.result.code, ResultCode::ObjectDoesNotExist);
+    assert_eq!(
+        err.to_string(),
+        "info command error for eppdev.com: Object does not exist"
+    );
+}
+
+#[tokio::test]
+async fn claims_check() {
+    let _guard = log_to_stdout();
+
+    struct FakeConnector;
+
+    #[async_trait]
+    impl Connector for FakeConnector {
+        type Connection = tokio_test::io::Mock;
+
+        async fn connect(&self, _: Duration) -> Result<Self::Connection, Error> {
+            Ok(build_stream(&[
+                "response/greeting.xml",
+                "request/extensions/launch_claims_check.xml",
+                "response/extensions/launch_claims_check.xml",
+            ])
+            .build())
+        }
+    }
+
+    let mut client = EppClient::new(FakeConnector, "test".into(), Duration::from_secs(5))
+        .await
+        .unwrap();
+
+    let claims = client
+        .claims_check(
+            &["example1.tld", "example2.tld"],
+            PhaseType::Claims.into(),
+            CLTRID,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(claims.len(), 2);
+    assert!(!claims[0].exists);
+    assert_eq!(claims[1].name, "example2.tld");
+    assert!(claims[1].exists);
+    assert_eq!(
+        claims[1].claim_keys[1].validator_id.as_deref(),
+        Some("custom-tmch")
+    );
+}
+
+#[tokio::test]
+async fn registrant_change() {
+    let _guard = log_to_stdout();
+
+    struct FakeConnector;
+
+    #[async_trait]
+    impl Connector for FakeConnector {
+        type Connection = tokio_test::io::Mock;
+
+        async fn connect(&self, _: Duration) -> Result<Self::Connection, Error> {
+            Ok(build_stream(&[
+                "response/greeting.xml",
+                "request/domain/info_other.xml#1",
+                "response/domain/info_ok.xml",
+                "request/domain/update_registrant.xml#2",
+                "response/domain/update.xml",
+                "request/domain/info_other.xml#1",
+                "response/domain/info_locked.xml",
+                "request/domain/info_other.xml#1",
+                "response/domain/info_ok.xml",
+            ])
+            .build())
+        }
+    }
+
+    let mut client = EppClient::new(FakeConnector, "test".into(), Duration::from_secs(5))
+        .await
+        .unwrap();
+
+    let change = RegistrantChange::new("eppdev-1.com", "eppdev-contact-3");
+    let outcome = change.run(&mut client, CLTRID).await.unwrap();
+    assert_eq!(outcome, RegistrantChangeOutcome::Completed);
+
+    let err = change.run(&mut client, CLTRID).await.unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "error: eppdev-1.com: registrant cannot be changed while the domain has status clientUpdateProhibited"
+    );
+
+    let change = RegistrantChange::new("eppdev-1.com", "EPPDEV-CONTACT-2");
+    let outcome = change.run(&mut client, CLTRID).await.unwrap();
+    assert_eq!(outcome, RegistrantChangeOutcome::Unchanged);
+}
+
+#[tokio::test]
+async fn register_domain() {
+    let _guard = log_to_stdout();
+
+    struct FakeConnector;
+
+    #[async_trait]
+    impl Connector for FakeConnector {
+        type Connection = tokio_test::io::Mock;
+
+        async fn connect(&self, _: Duration) -> Result<Self::Connection, Error> {
+            Ok(build_stream(&[
+                "response/greeting.xml",
+                "request/contact/check_single.xml#1",
+                "response/contact/check_single.xml",
+                "request/contact/create_minimal.xml#2",
+                "response/contact/create.xml",
+                "request/domain/create.xml#3",
+                "response/domain/create.xml",
+            ])
+            .build())
+        }
+    }
+
+    let mut client = EppClient::new(FakeConnector, "test".into(), Duration::from_secs(5))
+        .await
+        .unwrap();
+
+    let address = Address::new(&[], "Paris", None, None, "FR".parse().unwrap());
+    let postal_info = PostalInfo::new(InfoType::International, "John Doe", None, address);
+    let registrant = ContactCreate::new(
+        "eppdev-contact-3",
+        "contact@eppdev.net",
+        postal_info,
+        None,
+        "eppdev-387323",
+    );
+
+    let spec = RegisterSpec::new(
+        "eppdev-1.com",
+        Period::years(1).unwrap(),
+        "epP4uthd#v",
+        registrant,
+    )
+    .contact("admin", "eppdev-contact-3")
+    .contact("tech", "eppdev-contact-3")
+    .contact("billing", "eppdev-contact-3");
+
+    let registration = client.register_domain(&spec, CLTRID).await.unwrap();
+    assert_eq!(registration.created_contacts, ["eppdev-contact-3"]);
+    assert_eq!(registration.domain.name, "eppdev-2.com");
+}
+
+#[tokio::test]
+async fn drain_poll_queue() {
+    let _guard = log_to_stdout();
+
+    struct FakeConnector;
+
+    #[async_trait]
+    impl Connector for FakeConnector {
+        type Connection = tokio_test::io::Mock;
+
+        async fn connect(&self, _: Duration) -> Result<Self::Connection, Error> {
+            Ok(build_stream(&[
+                "response/greeting.xml",
+                // First run, limited to two messages
+                "request/poll/poll.xml#1",
+                "response/poll/poll_domain_transfer.xml",
+                "request/poll/ack.xml#2",
+                "response/poll/ack.xml",
+                "request/poll/poll.xml#3",
+                "response/poll/poll_host_info.xml",
+                "request/poll/ack.xml#4",
+                "response/poll/ack.xml",
+                // Second run, until the queue is empty
+                "request/poll/poll.xml#1",
+                "response/poll/poll_host_info.xml",
+                "request/poll/ack.xml#2",
+                "response/poll/ack.xml",
+                "request/poll/poll.xml#3",
+                "response/poll/poll_empty_queue.xml",
+            ])
+            .build())
+        }
+    }
+
+    let mut client = EppClient::new(FakeConnector, "test".into(), Duration::from_secs(5))
+        .await
+        .unwrap();
+
+    let mut messages = Vec::new();
+    let report = Drain::new()
+        .limit(2)
+        .run(&mut client, CLTRID, |rsp| {
+            messages.push(rsp);
+            Ok(())
+        })
+        .await
+        .unwrap();
+    assert_eq!(report.handled, 2);
+    assert_eq!(report.stop, DrainStop::Limit);
+    let depth = report.depth.iter().map(|d| d.count).collect::<Vec<_>>();
+    assert_eq!(depth, [5, 4]);
+    assert_eq!(report.remaining(), Some(3));
+    assert_eq!(messages.len(), 2);
+
+    let report = Drain::new()
+        .run(&mut client, CLTRID, |_| Ok(()))
+        .await
+        .unwrap();
+    assert_eq!(report.handled, 1);
+    assert_eq!(report.stop, DrainStop::Empty);
+    assert_eq!(report.remaining(), Some(0));
+
+    let report = Drain::new()
+        .deadline(Instant::now())
+        .run(&mut client, CLTRID, |_| Ok(()))
+        .await
+        .unwrap();
+    assert_eq!(report.handled, 0);
+    assert_eq!(report.stop, DrainStop::Deadline);
+    assert_eq!(report.remaining(), None);
+}
+
+#[tokio::test]
+async fn transaction_mismatch() {
+    let _guard = log_to_stdout();
+
+    struct FakeConnector;
+
+    #[async_trait]
+    impl Connector for FakeConnector {
+        type Connection = tokio_test::io::Mock;
+
+        async fn connect(&self, _: Duration) -> Result<Self::Connection, Error> {
+            Ok(build_stream(&[
+                "response/greeting.xml",
+                "request/domain/check.xml",
+                "response/domain/check_mismatch.xml",
+                "request/domain/check.xml",
+                "response/domain/check.xml",
+                "request/domain/check.xml",
+                "response/domain/check_mismatch.xml",
+            ])
+            .build())
+        }
+    }
+
+    let mut client = EppClient::new(FakeConnector, "test".into(), Duration::from_secs(5))
+        .await
+        .unwrap();
+
+    let check = DomainCheck {
+        domains: &["eppdev.com", "eppdev.net"],
+    };
+    client.transact(&check, CLTRID).await.unwrap();
+
+    client.set_verify_transaction_ids(true);
+    client.transact(&check, CLTRID).await.unwrap();
+    let err = client.transact(&check, CLTRID).await.unwrap_err();
+    match err {
+        Error::TransactionMismatch { expected, received } => {
+            assert_eq!(expected, CLTRID);
+            assert_eq!(received, "cltrid:1626454867");
+        }
+        err => panic!("expected transaction mismatch, got {err}"),
+    }
+}
+
+#[tokio::test]
+async fn split() {
+    let _guard = log_to_stdout();
+
+    struct FakeConnector;
+
+    #[async_trait]
+    impl Connector for FakeConnector {
+        type Connection = tokio_test::io::Mock;
+
+        async fn connect(&self, _: Duration) -> Result<Self::Connection, Error> {
+            Ok(build_stream(&[
+                "response/greeting.xml",
+                "request/domain/check.xml",
+                "response/domain/check.xml",
+            ])
+            .build())
+        }
+    }
+
+    let client = EppClient::new(FakeConnector, "test".into(), Duration::from_secs(5))
+        .await
+        .unwrap();
+
+    let (mut reader, mut writer) = client.into_split().await.unwrap();
+    writer
+        .write_frame(&xml("request/domain/check.xml"))
+        .await
+        .unwrap();
+    let frame = reader.read_frame().await.unwrap();
+    assert_eq!(frame, xml("response/domain/check.xml"));
+    assert!(matches!(reader.read_frame().await, Err(Error::Io(_))));
+}
+
+#[tokio::test]
+async fn check_services() {
+    let _guard = log_to_stdout();
+
+    struct FakeConnector;
+
+    #[async_trait]
+    impl Connector for FakeConnector {
+        type Connection = tokio_test::io::Mock;
+
+        async fn connect(&self, _: Duration) -> Result<Self::Connection, Error> {
+            Ok(build_stream(&[
+                "response/greeting.xml",
+                "request/domain/check.xml",
+                "response/domain/check.xml",
+            ])
+            .build())
+        }
+    }
+
+    let mut client = EppClient::new(FakeConnector, "test".into(), Duration::from_secs(5))
+        .await
+        .unwrap();
+    client.set_check_services(true);
+
+    // The greeting does not offer the nsgroup mapping, so nothing is sent
+    let err = client
+        .transact(&NsGroupInfo::new("nsgroup-1"), CLTRID)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        Error::Unsupported {
+            command: "info",
+            ..
+        }
+    ));
+
+    let check = DomainCheck {
+        domains: &["eppdev.com", "eppdev.net"],
+    };
+    client.transact(&check, CLTRID).await.unwrap();
+}
+
+#[tokio::test]
+async fn queue_ttl() {
+    let _guard = log_to_stdout();
+
+    struct FakeConnector;
+
+    #[async_trait]
+    impl Connector for FakeConnector {
+        type Connection = tokio_test::io::Mock;
+
+        async fn connect(&self, _: Duration) -> Result<Self::Connection, Error> {
+            let mut builder = Builder::new();
+
+            let buf = xml("response/greeting.xml");
+            builder.read(&len_bytes(&buf)).read(buf.as_bytes());
+
+            let buf = xml("request/domain/check.xml");
+            builder.write(&len_bytes(&buf)).write(buf.as_bytes());
+
+            // The check future is dropped during this wait, the next request is queued
+            builder.wait(Duration::from_millis(100));
+
+            let buf = xml("response/domain/check.xml");
+            builder.read(&len_bytes(&buf)).read(buf.as_bytes());
+
+            // The queued request expired, so only the retry is written
+            let buf = xml("request/domain/check.xml");
+            builder.write(&len_bytes(&buf)).write(buf.as_bytes());
+
+            let buf = xml("response/domain/check.xml");
+            builder.read(&len_bytes(&buf)).read(buf.as_bytes());
+
+            Ok(builder.build())
+        }
+    }
+
+    let mut client = EppClient::new(FakeConnector, "test".into(), Duration::from_secs(5))
+        .await
+        .unwrap();
+    client.set_queue_ttl(Some(Duration::from_millis(20)));
+
+    let check = DomainCheck {
+        domains: &["eppdev.com", "eppdev.net"],
+    };
+
+    timeout(Duration::from_millis(10), client.transact(&check, CLTRID))
+        .await
+        .unwrap_err();
+
+    match client.transact(&check, CLTRID).await {
+        Err(Error::QueueTimeout) => {}
+        rsp => panic!("expected queue timeout, got {rsp:?}"),
+    }
+
+    let rsp = client.transact(&check, CLTRID).await.unwrap();
+    assert_eq!(rsp.result.code, ResultCode::CommandCompletedSuccessfully);
+}
+
+#[tokio::test]
+async fn reconnect_login() {
+    let _guard = log_to_stdout();
+
+    struct FakeConnector {
+        connects: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Connector for FakeConnector {
+        type Connection = tokio_test::io::Mock;
+
+        async fn connect(&self, _: Duration) -> Result<Self::Connection, Error> {
+            Ok(match self.connects.fetch_add(1, Ordering::SeqCst) {
+                0 => build_stream(&["response/greeting.xml"]),
+                _ => build_stream(&[
+                    "response/greeting.xml",
+                    "request/login_no_extension.xml",
+                    "response/login.xml",
+                ]),
+            }
+            .build())
+        }
+    }
+
+    let connector = FakeConnector {
+        connects: AtomicUsize::new(0),
+    };
+    let mut client = EppClient::new(connector, "test".into(), Duration::from_secs(5))
+        .await
+        .unwrap();
+
+    client.set_credentials(Some(Credentials {
+        username: "old-username".into(),
+        password: "old-password".into(),
+        ext_uris: Vec::new(),
+        transaction_id: CLTRID.into(),
+    }));
+
+    // Only the most recently set credentials are used
+    let credentials = Credentials {
+        username: "username".into(),
+        password: "password".into(),
+        ext_uris: Vec::new(),
+        transaction_id: CLTRID.into(),
+    };
+    assert!(format!("{credentials:?}").contains(r#"password: "<redacted>""#));
+    client.set_credentials(Some(credentials));
+
+    client.reconnect().await.unwrap();
+
+    client.set_transaction_prefix("acme-test-").unwrap();
+    let id = client.next_transaction_id();
+    assert!(id.starts_with("acme-test-") && id.ends_with("-1"), "{id}");
+    assert!(client.next_transaction_id().ends_with("-2"));
+
+    client.set_transaction_span_tag(true);
+    let id = tracing::info_span!("request").in_scope(|| client.next_transaction_id());
+    let (rest, tag) = id.rsplit_once('-').unwrap();
+    assert!(rest.ends_with("-3"), "{id}");
+    assert!(
+        tag.len() == 8 && tag.bytes().all(|b| b.is_ascii_hexdigit()),
+        "{id}"
+    );
+    assert!(client.next_transaction_id().ends_with("-4"));
+    assert!(client.set_transaction_prefix("acme\ttest-").is_err());
+    assert!(client.set_transaction_prefix(&"x".repeat(33)).is_err());
+
+    let greetings = client.last_greetings().collect::<Vec<_>>();
+    assert_eq!(greetings.len(), 2);
+    assert!(greetings[0].received <= greetings[1].received);
+    assert_eq!(greetings[1].service_id, "ISPAPI EPP Server");
+    assert_eq!(
+        greetings[1].service_date.to_rfc3339(),
+        "2021-07-25T14:51:17+00:00"
+    );
+}
+
+#[tokio::test]
+async fn relogin() {
+    let _guard = log_to_stdout();
+
+    struct FakeConnector {
+        connects: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Connector for FakeConnector {
+        type Connection = tokio_test::io::Mock;
+
+        async fn connect(&self, _: Duration) -> Result<Self::Connection, Error> {
+            Ok(match self.connects.fetch_add(1, Ordering::SeqCst) {
+                0 => build_stream(&[
+                    "response/greeting.xml",
+                    "request/domain/check.xml",
+                    "response/session_limit.xml",
+                ]),
+                // The check is sent again after logging in, the delete is not
+                1 => build_stream(&[
+                    "response/greeting.xml",
+                    "request/login_no_extension.xml",
+                    "response/login.xml",
+                    "request/domain/check.xml",
+                    "response/domain/check.xml",
+                    "request/domain/delete.xml",
+                    "response/session_ended.xml",
+                ]),
+                _ => build_stream(&[
+                    "response/greeting.xml",
+                    "request/login_no_extension.xml",
+                    "response/login.xml",
+                ]),
+            }
+            .build())
+        }
+    }
+
+    let connector = FakeConnector {
+        connects: AtomicUsize::new(0),
+    };
+    let mut client = EppClient::new(connector, "test".into(), Duration::from_secs(5))
+        .await
+        .unwrap();
+
+    client.set_relogin(true);
+    client.set_credentials(Some(Credentials {
+        username: "username".into(),
+        password: "password".into(),
+        ext_uris: Vec::new(),
+        transaction_id: CLTRID.into(),
+    }));
+
+    let check = DomainCheck {
+        domains: &["eppdev.com", "eppdev.net"],
+    };
+    let rsp = client.transact(&check, CLTRID).await.unwrap();
+    assert_eq!(rsp.res_data().unwrap().list[0].name.value, "eppdev.com");
+
+    let err = client
+        .transact(&DomainDelete::new("eppdev.com"), CLTRID)
+        .await
+        .unwrap_err();
+    let Error::SessionEnded { error, relogin } = &err else {
+        panic!("unexpected error: {err:?}");
+    };
+    assert_eq!(
+        error.result.code,
+        ResultCode::CommandFailedServerClosingConnection
+    );
+    assert!(relogin.is_none());
+    assert_eq!(
+        err.to_string(),
+        "session ended: delete command error for eppdev.com: Command failed; server closing connection"
+    );
+}
+
+#[tokio::test]
+async fn reconnect_language() {
+    let _guard = log_to_stdout();
+
+    struct FakeConnector {
+        connects: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Connector for FakeConnector {
+        type Connection = tokio_test::io::Mock;
+
+        async fn connect(&self, _: Duration) -> Result<Self::Connection, Error> {
+            Ok(match self.connects.fetch_add(1, Ordering::SeqCst) {
+                0 => build_stream(&["response/greeting.xml"]),
+                _ => build_stream(&[
+                    "response/greeting_languages.xml",
+                    "request/login_language.xml",
+                    "response/login.xml",
+                ]),
+            }
+            .build())
+        }
+    }
+
+    let connector = FakeConnector {
+        connects: AtomicUsize::new(0),
+    };
+    let mut client = EppClient::new(connector, "test".into(), Duration::from_secs(5))
+        .await
+        .unwrap();
+
+    client.set_credentials(Some(Credentials {
+        username: "username".into(),
+        password: "password".into(),
+        ext_uris: Vec::new(),
+        transaction_id: CLTRID.into(),
+    }));
+
+    // The first greeting only advertises `en`, the one after reconnecting also `de`
+    let languages = ["it", "de"].map(|lang| lang.parse().unwrap());
+    client.set_languages(languages.to_vec());
+    client.reconnect().await.unwrap();
+}
+
+#[tokio::test]
+async fn poll_with_ext() {
+    let _guard = log_to_stdout();
+
+    struct FakeConnector;
+
+    #[async_trait]
+    impl Connector for FakeConnector {
+        type Connection = tokio_test::io::Mock;
+
+        async fn connect(&self, _: Duration) -> Result<Self::Connection, Error> {
+            Ok(build_stream(&[
+                "response/greeting.xml",
+                "request/poll/poll.xml",
+                "response/poll/poll_domain_transfer_fee.xml",
+            ])
+            .build())
+        }
+    }
+
+    let mut client = EppClient::new(FakeConnector, "test".into(), Duration::from_secs(5))
+        .await
+        .unwrap();
+
+    let rsp = client
+        .poll_with_ext::<fee::TransferData>(CLTRID)
+        .await
+        .unwrap();
+    assert!(matches!(rsp.res_data(), Some(PollData::DomainTransfer(_))));
+    assert_eq!(rsp.extension().unwrap().fees[0].amount, "5.00");
+}
+
+#[tokio::test]
+async fn pending_store() {
+    let _guard = log_to_stdout();
+
+    struct FakeConnector;
+
+    #[async_trait]
+    impl Connector for FakeConnector {
+        type Connection = tokio_test::io::Mock;
+
+        async fn connect(&self, _: Duration) -> Result<Self::Connection, Error> {
+            Ok(build_stream(&[
+                "response/greeting.xml",
+                "request/domain/check.xml",
+                "response/domain/check.xml",
+                "request/domain/delete.xml",
+                "response/domain/delete.xml",
+                "request/domain/delete.xml",
+                "response/error.xml",
+            ])
+            .build())
+        }
+    }
+
+    type Entry = (PendingTransaction, Option<ResultCode>);
+
+    #[derive(Clone, Default)]
+    struct Store {
+        log: Arc<Mutex<Vec<Entry>>>,
+    }
+
+    #[async_trait]
+    impl PendingStore for Store {
+        async fn begin(&self, transaction: &PendingTransaction) -> Result<(), Error> {
+            self.log.lock().unwrap().push((transaction.clone(), None));
+            Ok(())
+        }
+
+        async fn complete(
+            &self,
+            transaction: &PendingTransaction,
+            code: ResultCode,
+        ) -> Result<(), Error> {
+            self.log
+                .lock()
+                .unwrap()
+                .push((transaction.clone(), Some(code)));
+            Ok(())
+        }
+    }
+
+    let mut client = EppClient::new(FakeConnector, "test".into(), Duration::from_secs(5))
+        .await
+        .unwrap();
+
+    let store = Store::default();
+    client.set_pending_store(Some(Box::new(store.clone())));
+    let check = DomainCheck {
+        domains: &["eppdev.com", "eppdev.net"],
+    };
+    client.transact(&check, CLTRID).await.unwrap();
+    let delete = DomainDelete::new("eppdev.com");
+    client.transact(&delete, CLTRID).await.unwrap();
+    client.transact(&delete, CLTRID).await.unwrap_err();
+
+    let transaction = PendingTransaction {
+        transaction_id: CLTRID.into(),
+        command: "delete",
+        objects: vec!["eppdev.com".into()],
+    };
+    let log = store.log.lock().unwrap().clone();
+    assert_eq!(
+        log,
+        [
+            (transaction.clone(), None),
+            (
+                transaction.clone(),
+                Some(ResultCode::CommandCompletedSuccessfully)
+            ),
+            (transaction.clone(), None),
+            (transaction, Some(ResultCode::ObjectDoesNotExist)),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn archiver() {
+    let _guard = log_to_stdout();
+
+    struct FakeConnector;
+
+    #[async_trait]
+    impl Connector for FakeConnector {
+        type Connection = tokio_test::io::Mock;
+
+        async fn connect(&self, _: Duration) -> Result<Self::Connection, Error> {
+            Ok(build_stream(&[
+                "response/greeting.xml",
+                "request/domain/check.xml",
+                "response/domain/check.xml",
+                "request/domain/check.xml",
+                "response/domain/check.xml",
+            ])
+            .build())
+        }
+    }
+
+    #[derive(Clone, Default)]
+    struct Archive {
+        entries: Arc<Mutex<Vec<(String, String)>>>,
+        fail: bool,
+    }
+
+    #[async_trait]
+    impl Archiver for Archive {
+        async fn archive(&self, request: &str, response: &str) -> Result<(), Error> {
+            if self.fail {
+                return Err(Error::Other("archive unavailable".into()));
+            }
+
+            let entry = (request.to_owned(), response.to_owned());
+            self.entries.lock().unwrap().push(entry);
+            Ok(())
+        }
+    }
+
+    let mut client = EppClient::new(FakeConnector, "test".into(), Duration::from_secs(5))
+        .await
+        .unwrap();
+
+    let archive = Archive::default();
+    client.set_archiver(Some(Box::new(archive.clone())));
+    let check = DomainCheck {
+        domains: &["eppdev.com", "eppdev.net"],
+    };
+    client.transact(&check, CLTRID).await.unwrap();
+
+    let entries = archive.entries.lock().unwrap().clone();
+    assert_eq!(
+        entries,
+        [(
+            xml("request/domain/check.xml"),
+            xml("response/domain/check.xml")
+        )]
+    );
+
+    // A failing archiver fails the request
+    client.set_archiver(Some(Box::new(Archive {
+        fail: true,
+        ..Archive::default()
+    })));
+    let err = client.transact(&check, CLTRID).await.unwrap_err();
+    assert_eq!(err.to_string(), "error: archive unavailable");
+}
+
+#[tokio::test]
+async fn events() {
+    let _guard = log_to_stdout();
+
+    struct FakeConnector {
+        connects: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Connector for FakeConnector {
+        type Connection = tokio_test::io::Mock;
+
+        async fn connect(&self, _: Duration) -> Result<Self::Connection, Error> {
+            Ok(match self.connects.fetch_add(1, Ordering::SeqCst) {
+                0 => build_stream(&[
+                    "response/greeting.xml",
+                    "request/hello.xml",
+                    "response/greeting.xml",
+                ]),
+                _ => build_stream(&["response/greeting.xml"]),
+            }
+            .build())
+        }
+    }
+
+    let connector = FakeConnector {
+        connects: AtomicUsize::new(0),
+    };
+    let mut client = EppClient::new(connector, "test".into(), Duration::from_secs(5))
+        .await
+        .unwrap();
+
+    let mut events = client.events();
+    client.hello().await.unwrap();
+    client.reconnect().await.unwrap();
+    client.shutdown().await.unwrap();
+
+    let mut received = Vec::new();
+    while let Ok(event) = events.try_recv() {
+        received.push(event);
+    }
+
+    assert_eq!(
+        received,
+        [
+            ConnectionEvent::Reconnecting,
+            ConnectionEvent::Connected,
+            ConnectionEvent::GreetingReceived,
+            ConnectionEvent::Closed {
+                reason: CloseReason::Shutdown
+            },
+        ]
+    );
+}
+
+#[tokio::test]
+async fn keepalive() {
+    let _guard = log_to_stdout();
+
+    struct FakeConnector;
+
+    #[async_trait]
+    impl Connector for FakeConnector {
+        type Connection = tokio_test::io::Mock;
+
+        async fn connect(&self, _: Duration) -> Result<Self::Connection, Error> {
+            Ok(build_stream(&[
+                "response/greeting.xml",
+                "request/hello.xml",
+                "response/greeting.xml",
+                "request/poll/poll.xml",
+                "response/poll/poll_empty_queue.xml",
+            ])
+            .build())
+        }
+    }
+
+    let mut client = EppClient::new(FakeConnector, "test".into(), Duration::from_secs(5))
+        .await
+        .unwrap();
+
+    let mut events = client.events();
+    client.keepalive(CLTRID).await.unwrap();
+    client.set_keepalive(Keepalive::Poll);
+    client.keepalive(CLTRID).await.unwrap();
+    assert!(client.idle() < Duration::from_secs(5));
+
+    assert_eq!(events.try_recv().unwrap(), ConnectionEvent::KeepaliveSent);
+    assert_eq!(events.try_recv().unwrap(), ConnectionEvent::KeepaliveSent);
+    assert!(events.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn greeting_changed() {
+    let _guard = log_to_stdout();
+
+    struct FakeConnector;
+
+    #[async_trait]
+    impl Connector for FakeConnector {
+        type Connection = tokio_test::io::Mock;
+
+        async fn connect(&self, _: Duration) -> Result<Self::Connection, Error> {
+            Ok(build_stream(&[
+                "response/greeting.xml",
+                "request/hello.xml",
+                "response/greeting.xml",
+                "request/hello.xml",
+                "response/greeting_languages.xml",
+            ])
+            .build())
+        }
+    }
+
+    let mut client = EppClient::new(FakeConnector, "test".into(), Duration::from_secs(5))
+        .await
+        .unwrap();
+
+    assert_eq!(client.greeting_changed().await.unwrap(), None);
+    let diff = client.greeting_changed().await.unwrap().unwrap();
+    assert_eq!(diff.added_languages, ["fr", "de"]);
+    assert!(diff.removed_extensions.is_empty());
+}
+
+#[cfg(feature = "cache")]
+#[tokio::test]
+async fn check_cache() {
+    use instant_epp::workflows::availability::CheckCache;
+
+    let _guard = log_to_stdout();
+
+    struct FakeConnector;
+
+    #[async_trait]
+    impl Connector for FakeConnector {
+        type Connection = tokio_test::io::Mock;
+
+        async fn connect(&self, _: Duration) -> Result<Self::Connection, Error> {
+            Ok(build_stream(&[
+                "response/greeting.xml",
+                "request/domain/check.xml",
+                "response/domain/check.xml",
+                "request/domain/check_single.xml",
+                "response/domain/check.xml",
+            ])
+            .build())
+        }
+    }
+
+    let mut client = EppClient::new(FakeConnector, "test".into(), Duration::from_secs(5))
+        .await
+        .unwrap();
+
+    let mut cache = CheckCache::new(Duration::from_secs(60), Duration::from_secs(3600));
+    let names = ["eppdev.com", "eppdev.net"];
+    let results = cache
+        .check_domains(&mut client, &names, CLTRID)
+        .await
+        .unwrap();
+    assert!(results[0].available && !results[0].cached);
+    assert!(!results[1].available && !results[1].cached);
+
+    // Answered from the cache, without sending a command
+    let results = cache
+        .check_domains(&mut client, &["EPPDEV.net", "eppdev.com"], CLTRID)
+        .await
+        .unwrap();
+    assert_eq!(results[0].name, "EPPDEV.net");
+    assert!(!results[0].available && results[0].cached);
+    assert!(results[1].available && results[1].cached);
+
+    cache.invalidate("eppdev.com");
+    let results = cache
+        .check_domains(&mut client, &names, CLTRID)
+        .await
+        .unwrap();
+    assert!(!results[0].cached);
+    assert!(results[1].cached);
+}
+
+#[cfg(feature = "deflate")]
+#[tokio::test]
+async fn deflate() {
+    use flate2::write::DeflateEncoder;
+    use flate2::Compression;
+
+    let _guard = log_to_stdout();
+
+    fn compressed(path: &str) -> Vec<u8> {
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(xml(path).as_bytes()).unwrap();
+        let payload = encoder.finish().unwrap();
+
+        let mut frame = ((payload.len() as u32) + 4).to_be_bytes().to_vec();
+        frame.extend(payload);
+        frame
+    }
+
+    struct FakeConnector;
+
+    #[async_trait]
+    impl Connector for FakeConnector {
+        type Connection = tokio_test::io::Mock;
+
+        async fn connect(&self, _: Duration) -> Result<Self::Connection, Error> {
+            // The greeting is sent uncompressed
+            Ok(build_stream(&["response/greeting.xml"])
+                .write(&compressed("request/domain/check.xml"))
+                .read(&compressed("response/domain/check.xml"))
+                .build())
+        }
+    }
+
+    let mut client = EppClient::new(FakeConnector, "test".into(), Duration::from_secs(5))
+        .await
+        .unwrap();
+    client.set_deflate(true);
+
+    let check = DomainCheck {
+        domains: &["eppdev.com", "eppdev.net"],
+    };
+    let rsp = client.transact(&check, CLTRID).await.unwrap();
+    assert_eq!(rsp.res_data().unwrap().list.len(), 3);
+}
+
+#[cfg(feature = "prometheus")]
+#[tokio::test]
+async fn metrics() {
+    use instant_epp::poll::Poll;
+    use prometheus::{Encoder, TextEncoder};
+
+    let _guard = log_to_stdout();
+
+    struct FakeConnector;
+
+    #[async_trait]
+    impl Connector for FakeConnector {
+        type Connection = tokio_test::io::Mock;
+
+        async fn connect(&self, _: Duration) -> Result<Self::Connection, Error> {
+            Ok(build_stream(&[
+                "response/greeting.xml",
+                "request/domain/check.xml",
+                "response/domain/check.xml",
+                "request/poll/poll.xml",
+                "response/poll/poll_message_only.xml",
+            ])
+            .build())
+        }
+    }
+
+    let mut client = EppClient::new(FakeConnector, "test".into(), Duration::from_secs(5))
+        .await
+        .unwrap();
+
+    let check = DomainCheck {
+        domains: &["eppdev.com", "eppdev.net"],
+    };
+    client.transact(&check, CLTRID).await.unwrap();
+    client.transact(&Poll, CLTRID).await.unwrap();
+
+    let mut buf = Vec::new();
+    let families = client.metrics().registry().gather();
+    TextEncoder::new().encode(&families, &mut buf).unwrap();
+    let text = String::from_utf8(buf).unwrap();
+
+    for line in [
+        r#"epp_connection_state{registry="test",state="ready"} 1"#,
+        r#"epp_connection_state{registry="test",state="closed"} 0"#,
+        r#"epp_requests_in_flight{registry="test"} 0"#,
+        r#"epp_requests_queued{registry="test"} 0"#,
+        r#"epp_poll_queue_depth{registry="test"} 4"#,
+        r#"epp_responses_total{code="1000",command="check",registry="test"} 1"#,
+        r#"epp_responses_total{code="1301",command="poll",registry="test"} 1"#,
+    ] {
+        assert!(text.lines().any(|l| l == line), "{line} not in:\n{text}");
+    }
+}
+
+#[tokio::test]
+async fn malformed_frame_header() {
+    let _guard = log_to_stdout();
+
+    struct FakeConnector;
+
+    #[async_trait]
+    impl Connector for FakeConnector {
+        type Connection = tokio_test::io::Mock;
+
+        async fn connect(&self, _: Duration) -> Result<Self::Connection, Error> {
+            // A length prefix that doesn't even cover the header itself
+            Ok(Builder::new().read(&2u32.to_be_bytes()).build())
+        }
+    }
+
+    let err = EppClient::new(FakeConnector, "test".into(), Duration::from_secs(5))
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(
+        &err,
+        Error::Framing {
+            registry: Some(registry),
+            declared: 2,
+            limit: 4,
+            received: None,
+        } if registry == "test"
+    ));
+    assert_eq!(
+        err.to_string(),
+        "test: frame header announces 2 bytes, less than the minimum of 4"
+    );
+}
+
+#[tokio::test]
+async fn frame_overrun() {
+    let _guard = log_to_stdout();
+
+    struct FakeConnector;
+
+    #[async_trait]
+    impl Connector for FakeConnector {
+        type Connection = tokio_test::io::Mock;
+
+        async fn connect(&self, _: Duration) -> Result<Self::Connection, Error> {
+            // More bytes than the header announces arrive with it
+            let mut frame = 10u32.to_be_bytes().to_vec();
+            frame.extend_from_slice(&[b'x'; 16]);
+            Ok(Builder::new().read(&frame).build())
+        }
+    }
+
+    let err = EppClient::new(FakeConnector, "test".into(), Duration::from_secs(5))
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(
+        err,
+        Error::Framing {
+            declared: 10,
+            received: Some(20),
+            ..
+        }
+    ));
+    assert_eq!(
+        err.to_string(),
+        "test: frame header announces 10 bytes, but 20 were received"
+    );
+}
+
+#[tokio::test]
+async fn unframed_response_too_long() {
+    let _guard = log_to_stdout();
+
+    struct FakeConnector;
+
+    #[async_trait]
+    impl Connector for FakeConnector {
+        type Connection = tokio_test::io::Mock;
+
+        async fn connect(&self, _: Duration) -> Result<Self::Connection, Error> {
+            // A response without header that doesn't end within the maximum frame length
+            let mut response = b"<epp>".to_vec();
+            response.resize(16 * 1024 * 1024, b'x');
+            Ok(Builder::new().read(&response).build())
+        }
+    }
+
+    let err = EppClient::new(FakeConnector, "test".into(), Duration::from_secs(5))
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(
+        err,
+        Error::Framing {
+            declared: 0x3c65_7070,
+            limit: 16_777_216,
+            received: Some(16_777_216),
+            ..
+        }
+    ));
+    assert_eq!(
+        err.to_string(),
+        "test: no end of response without header in 16777216 bytes (limit 16777216)"
+    );
+}
+
+#[tokio::test]
+async fn unframed_response() {
+    let _guard = log_to_stdout();
+
+    struct FakeConnector;
+
+    #[async_trait]
+    impl Connector for FakeConnector {
+        type Connection = tokio_test::io::Mock;
+
+        async fn connect(&self, _: Duration) -> Result<Self::Connection, Error> {
+            let request = xml("request/domain/check.xml");
+            let response = xml("response/domain/check.xml");
+            Ok(
+                build_stream(&["response/greeting.xml", "request/domain/check.xml"])
+                    // The response without its frame header
+                    .read(response.as_bytes())
+                    .write(&len_bytes(&request))
+                    .write(request.as_bytes())
+                    .read(&len_bytes(&response))
+                    .read(response.as_bytes())
+                    .write(&len_bytes(&request))
+                    .write(request.as_bytes())
+                    .read(&[0xff; 4])
+                    .build(),
+            )
+        }
+    }
+
+    let mut client = EppClient::new(FakeConnector, "test".into(), Duration::from_secs(5))
+        .await
+        .unwrap();
+
+    // The response is read up to its end, so the next one is read as usual
+    let check = DomainCheck {
+        domains: &["eppdev.com", "eppdev.net"],
+    };
+    let rsp = client.transact(&check, CLTRID).await.unwrap();
+    assert_eq!(rsp.res_data().unwrap().list.len(), 3);
+    client.transact(&check, CLTRID).await.unwrap();
+
+    // After an oversized frame, the connection can't be used until it is reconnected
+    let err = client.transact(&check, CLTRID).await.unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "test: frame header announces 4294967295 bytes, more than the maximum of 16777216"
+    );
+    let err = client.transact(&check, CLTRID).await.unwrap_err();
+    assert!(matches!(err, Error::Closed));
+}
+
+#[tokio::test]
+async fn shared_default_extension() {
+    let _guard = log_to_stdout();
+
+    struct FakeConnector;
+
+    #[async_trait]
+    impl Connector for FakeConnector {
+        type Connection = tokio_test::io::Mock;
+
+        async fn connect(&self, _: Duration) -> Result<Self::Connection, Error> {
+            Ok(build_stream(&[
+                "response/greeting.xml",
+                "request/extensions/namestore.xml",
+                "response/extensions/namestore.xml",
+                "request/extensions/namestore_net.xml",
+                "response/extensions/namestore.xml",
+                "request/logout.xml",
+                "response/logout.xml",
+            ])
+            .build())
+        }
+    }
+
+    let client = EppClient::new(FakeConnector, "test".into(), Duration::from_secs(5))
+        .await
+        .unwrap();
+
+    let (shared, shutdown) = client.into_shared(CLTRID.to_owned(), Duration::from_secs(5));
+    let com = shared.with_extension(NameStore::new("com"));
+    let net = shared.with_extension(NameStore::new("net")).clone();
+
+    let check = DomainCheck {
+        domains: &["example1.com", "example2.com", "example3.com"],
+    };
+    let rsp = com.transact(&check, CLTRID).await.unwrap();
+    assert_eq!(rsp.extension().unwrap().subproduct, "com");
+
+    let check = DomainCheck {
+        domains: &["example1.net", "example2.net", "example3.net"],
+    };
+    net.transact(&check, CLTRID).await.unwrap();
+    assert!(shared.lock().await.idle() < Duration::from_secs(5));
+
+    // Logs out once all handles are dropped
+    drop((shared, com, net));
+    shutdown.await.unwrap();
+}
+
+#[tokio::test]
+async fn shared_shutdown() {
+    let _guard = log_to_stdout();
+
+    struct FakeConnector;
+
+    #[async_trait]
+    impl Connector for FakeConnector {
+        type Connection = tokio_test::io::Mock;
+
+        async fn connect(&self, _: Duration) -> Result<Self::Connection, Error> {
+            Ok(build_stream(&[
+                "response/greeting.xml",
+                "request/domain/check.xml",
+                "response/domain/check.xml",
+                "request/logout.xml",
+                "response/logout.xml",
+            ])
+            .build())
+        }
+    }
+
+    fn assert_send_sync<T: Send + Sync + 'static>(_: &T) {}
+
+    let client = EppClient::new(FakeConnector, "test".into(), Duration::from_secs(5))
+        .await
+        .unwrap();
+
+    let (shared, shutdown) = client.into_shared(CLTRID.to_owned(), Duration::from_secs(5));
+    assert_send_sync(&shared);
+    let shutdown = tokio::spawn(shutdown);
+
+    let weak = shared.downgrade();
+    let clone = weak.upgrade().unwrap();
+    assert_eq!(shared.strong_count(), 2);
+
+    let check = DomainCheck {
+        domains: &["eppdev.com", "eppdev.net"],
+    };
+    clone.transact(&check, CLTRID).await.unwrap();
+
+    drop(shared);
+    assert_eq!(weak.strong_count(), 1);
+    drop(clone);
+    assert!(weak.upgrade().is_none());
+    let err = weak.transact(&check, CLTRID).await.unwrap_err();
+    assert!(matches!(err, Error::Closed));
+    shutdown.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn greeting_retry() {
+    let _guard = log_to_stdout();
+
+    struct FakeConnector {
+        retries: u32,
+        connects: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Connector for FakeConnector {
+        type Connection = tokio_test::io::Mock;
+
+        async fn connect(&self, _: Duration) -> Result<Self::Connection, Error> {
+            // The server answers the first connection with an error instead of a greeting
+            let greeting = match self.connects.fetch_add(1, Ordering::SeqCst) {
+                0 => "response/error.xml",
+                _ => "response/greeting.xml",
+            };
+
+            Ok(build_stream(&[greeting]).build())
+        }
+
+        fn greeting_retry(&self) -> GreetingRetry {
+            GreetingRetry {
+                retries: self.retries,
+                backoff: Duration::from_millis(1),
+            }
+        }
+    }
+
+    let connector = FakeConnector {
+        retries: 0,
+        connects: AtomicUsize::new(0),
+    };
+    let err = EppClient::new(connector, "test".into(), Duration::from_secs(5))
+        .await
+        .err()
+        .unwrap();
+    match err {
+        Error::UnexpectedGreeting { raw } => assert!(raw.contains("<result code=\"2303\">")),
+        err => panic!("unexpected error: {err}"),
+    }
+
+    let connector = FakeConnector {
+        retries: 2,
+        connects: AtomicUsize::new(0),
+    };
+    let client = EppClient::new(connector, "test".into(), Duration::from_secs(5))
+        .await
+        .unwrap();
+    assert_eq!(client.greeting().unwrap().service_id, "ISPAPI EPP Server");
+}
+
+#[tokio::test]
+async fn circuit_breaker() {
+    let _guard = log_to_stdout();
+
+    #[derive(Clone, Default)]
+    struct FakeConnector {
+        connects: Arc<AtomicUsize>,
+        down: Arc<AtomicBool>,
+    }
+
+    #[async_trait]
+    impl Connector for FakeConnector {
+        type Connection = tokio_test::io::Mock;
+
+        async fn connect(&self, _: Duration) -> Result<Self::Connection, Error> {
+            if self.connects.fetch_add(1, Ordering::SeqCst) == 0 {
+                return Ok(build_stream(&["response/greeting.xml"]).build());
+            }
+
+            match self.down.load(Ordering::SeqCst) {
+                true => Err(io::ErrorKind::ConnectionRefused.into()),
+                false => Ok(build_stream(&[
+                    "response/greeting.xml",
+                    "request/domain/check.xml",
+                    "response/domain/check.xml",
+                ])
+                .build()),
+            }
+        }
+    }
+
+    let connector = FakeConnector::default();
+    let mut client = EppClient::new(connector.clone(), "test".into(), Duration::from_secs(5))
+        .await
+        .unwrap();
+
+    let interval = Duration::from_millis(20);
+    client.set_circuit_breaker(Some(CircuitBreaker::new(2, interval)));
+    connector.down.store(true, Ordering::SeqCst);
+    assert!(matches!(client.reconnect().await, Err(Error::Io(_))));
+    assert!(matches!(client.reconnect().await, Err(Error::Io(_))));
+    assert!(client.circuit_breaker().unwrap().is_open());
+
+    // Fail fast without connecting
+    assert!(matches!(client.reconnect().await, Err(Error::CircuitOpen)));
+    let check = DomainCheck {
+        domains: &["eppdev.com", "eppdev.net"],
+    };
+    let result = client.transact(&check, CLTRID).await;
+    assert!(matches!(result, Err(Error::CircuitOpen)));
+    assert_eq!(connector.connects.load(Ordering::SeqCst), 3);
+
+    // The reconnect after the probe interval closes the circuit
+    connector.down.store(false, Ordering::SeqCst);
+    tokio::time::sleep(interval).await;
+    client.reconnect().await.unwrap();
+    assert!(!client.circuit_breaker().unwrap().is_open());
+    client.transact(&check, CLTRID).await.unwrap();
+}
+
+#[tokio::test]
+async fn reconnect_backoff() {
+    let _guard = log_to_stdout();
+
+    struct FakeConnector {
+        // Connections after the first that fail before one succeeds
+        failures: usize,
+        retries: u32,
+        connects: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Connector for FakeConnector {
+        type Connection = tokio_test::io::Mock;
+
+        async fn connect(&self, _: Duration) -> Result<Self::Connection, Error> {
+            match self.connects.fetch_add(1, Ordering::SeqCst) {
+                n if n > 0 && n <= self.failures => Err(io::ErrorKind::ConnectionRefused.into()),
+                _ => Ok(build_stream(&["response/greeting.xml"]).build()),
+            }
+        }
+
+        fn reconnect_backoff(&self) -> ReconnectBackoff {
+            ReconnectBackoff {
+                retries: self.retries,
+                backoff: Duration::from_millis(1),
+                max_backoff: Some(Duration::from_millis(2)),
+                jitter: true,
+            }
+        }
+    }
+
+    let connector = FakeConnector {
+        failures: 2,
+        retries: 2,
+        connects: AtomicUsize::new(0),
+    };
+    let mut client = EppClient::new(connector, "test".into(), Duration::from_secs(5))
+        .await
+        .unwrap();
+
+    let mut events = client.events();
+    client.reconnect().await.unwrap();
+    let mut reconnecting = 0;
+    while let Ok(event) = events.try_recv() {
+        reconnecting += usize::from(event == ConnectionEvent::Reconnecting);
+    }
+    assert_eq!(reconnecting, 3);
+
+    let connector = FakeConnector {
+        failures: 3,
+        retries: 2,
+        connects: AtomicUsize::new(0),
+    };
+    let mut client = EppClient::new(connector, "test".into(), Duration::from_secs(5))
+        .await
+        .unwrap();
+
+    match client.reconnect().await {
+        Err(Error::ReconnectExhausted { attempts, last }) => {
+            assert_eq!(attempts, 3);
+            assert!(matches!(*last, Error::Io(_)));
+        }
+        result => panic!("unexpected result: {result:?}"),
+    }
+}
+
+#[tokio::test]
+async fn layers() {
+    let _guard = log_to_stdout();
+
+    struct FakeConnector;
+
+    #[async_trait]
+    impl Connector for FakeConnector {
+        type Connection = tokio_test::io::Mock;
+
+        async fn connect(&self, _: Duration) -> Result<Self::Connection, Error> {
+            Ok(build_stream(&[
+                "response/greeting.xml",
+                "request/extensions/namestore.xml",
+                "response/extensions/namestore.xml",
+                "request/extensions/namestore_net.xml",
+                "response/extensions/namestore.xml",
+            ])
+            .build())
+        }
+    }
+
+    struct SubProduct;
+
+    #[async_trait]
+    impl Layer for SubProduct {
+        async fn request(&self, request: &mut Outgoing<'_>) -> Result<(), Error> {
+            if request.command() == "delete" {
+                return Err(Error::Other("deletes are not allowed".into()));
+            }
+
+            if request.object_uris().contains(&domain::XMLNS) && !request.has_extension() {
+                request.attach(&NameStore::new("com"))?;
+            }
+            Ok(())
+        }
+    }
+
+    type Entry = (&'static str, ResultCode, bool);
+
+    #[derive(Clone, Default)]
+    struct Recorder {
+        log: Arc<Mutex<Vec<Entry>>>,
+    }
+
+    #[async_trait]
+    impl Layer for Recorder {
+        async fn response(&self, response: &Incoming<'_>) -> Result<(), Error> {
+            let parsed = response.parse::<DomainCheck, NameStore>()?;
+            let namestore = parsed
+                .extension()
+                .is_some_and(|ext| ext.subproduct == "com");
+            let entry = (response.command(), response.code(), namestore);
+            self.log.lock().unwrap().push(entry);
+            Ok(())
+        }
+    }
+
+    let mut client = EppClient::new(FakeConnector, "test".into(), Duration::from_secs(5))
+        .await
+        .unwrap();
+
+    let recorder = Recorder::default();
+    client.layer(SubProduct);
+    client.layer(recorder.clone());
+
+    // The layer attaches the extension; its response data is only seen by the layers
+    let check = DomainCheck {
+        domains: &["example1.com", "example2.com", "example3.com"],
+    };
+    let rsp = client.transact(&check, CLTRID).await.unwrap();
+    assert_eq!(rsp.res_data().unwrap().list.len(), 2);
+
+    // An extension sent by the caller is left alone
+    let check = DomainCheck {
+        domains: &["example1.net", "example2.net", "example3.net"],
+    };
+    let rsp = client
+        .transact((&check, &NameStore::new("net")), CLTRID)
+        .await
+        .unwrap();
+    assert_eq!(rsp.extension().unwrap().subproduct, "com");
+
+    // A failing layer keeps the request from being sent
+    let delete = DomainDelete::new("eppdev.com");
+    let err = client.transact(&delete, CLTRID).await.unwrap_err();
+    assert_eq!(err.to_string(), "error: deletes are not allowed");
+
+    let log = recorder.log.lock().unwrap().clone();
+    let success = ResultCode::CommandCompletedSuccessfully;
+    assert_eq!(log, [("check", success, true), ("check", success, true)]);
+}
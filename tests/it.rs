@@ -0,0 +1,309 @@
+//! Runs the full command matrix against a real EPP server
+//!
+//! Unlike the tests in `basic.rs`, which compare against golden XML files, these send every
+//! command to an actual server so that its parser validates our serialization. Enable the `it`
+//! feature and point the harness at a server through the environment:
+//!
+//! - `EPP_IT_SERVER`: the server's `host:port`; the tests are skipped when this is unset
+//! - `EPP_IT_USERNAME` and `EPP_IT_PASSWORD`: the registrar login
+//! - `EPP_IT_CA`: optional PEM file with the CA certificate(s) to trust instead of the
+//!   platform's roots, for servers with a self-signed certificate
+//! - `EPP_IT_CERT` and `EPP_IT_KEY`: optional PEM files for TLS client authentication
+//! - `EPP_IT_ZONE`: the zone to create test domains in, defaulting to `test`
+//!
+//! `tests/it/docker-compose.yml` runs a server in a container; see there for details.
+
+use std::env;
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tokio_rustls::rustls::{ClientConfig, RootCertStore};
+
+use instant_epp::client::{EppClient, RustlsConnector};
+use instant_epp::contact::{
+    Address, ContactCheck, ContactCreate, ContactDelete, ContactInfo, ContactUpdate, InfoType,
+    PostalInfo, Voice,
+};
+use instant_epp::domain::update::{DomainAdd, DomainChangeInfo, DomainRemove};
+use instant_epp::domain::{
+    DomainAuthInfo, DomainCheck, DomainContact, DomainCreate, DomainDelete, DomainInfo,
+    DomainRenew, DomainUpdate, HostInfo, HostObj, NameServers, Period,
+};
+use instant_epp::host::update::{HostAdd, HostChangeInfo};
+use instant_epp::host::{self, HostCheck, HostCreate, HostDelete, HostUpdate};
+use instant_epp::login::Login;
+use instant_epp::logout::Logout;
+use instant_epp::poll::Poll;
+use instant_epp::response::ResultCode;
+
+struct Config {
+    server: (String, u16),
+    username: String,
+    password: String,
+    ca: Option<String>,
+    identity: Option<(String, String)>,
+    zone: String,
+}
+
+impl Config {
+    fn from_env() -> Option<Self> {
+        let server = env::var("EPP_IT_SERVER").ok()?;
+        let (host, port) = server
+            .rsplit_once(':')
+            .expect("EPP_IT_SERVER must be host:port");
+
+        Some(Self {
+            server: (host.to_owned(), port.parse().expect("invalid port")),
+            username: env::var("EPP_IT_USERNAME").expect("EPP_IT_USERNAME not set"),
+            password: env::var("EPP_IT_PASSWORD").expect("EPP_IT_PASSWORD not set"),
+            ca: env::var("EPP_IT_CA").ok(),
+            identity: env::var("EPP_IT_CERT")
+                .ok()
+                .map(|cert| (cert, env::var("EPP_IT_KEY").expect("EPP_IT_KEY not set"))),
+            zone: env::var("EPP_IT_ZONE").unwrap_or_else(|_| "test".to_owned()),
+        })
+    }
+
+    fn connector(&self) -> RustlsConnector {
+        let builder = RustlsConnector::builder(self.server.clone()).unwrap();
+        let identity = self.identity.as_ref().map(|(cert, key)| {
+            let certs = CertificateDer::pem_file_iter(cert)
+                .unwrap()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            (certs, PrivateKeyDer::from_pem_file(key).unwrap())
+        });
+
+        let Some(ca) = &self.ca else {
+            return match identity {
+                Some((certs, key)) => builder.client_auth(certs, key).build().unwrap(),
+                None => builder.build().unwrap(),
+            };
+        };
+
+        let mut roots = RootCertStore::empty();
+        for cert in CertificateDer::pem_file_iter(ca).unwrap() {
+            roots.add(cert.unwrap()).unwrap();
+        }
+
+        let config = ClientConfig::builder().with_root_certificates(roots);
+        let config = match identity {
+            Some((certs, key)) => config.with_client_auth_cert(certs, key).unwrap(),
+            None => config.with_no_client_auth(),
+        };
+
+        builder.build_with_config(Arc::new(config))
+    }
+}
+
+#[tokio::test]
+async fn command_matrix() {
+    let Some(config) = Config::from_env() else {
+        eprintln!("EPP_IT_SERVER not set, skipping");
+        return;
+    };
+
+    let mut client = EppClient::new(config.connector(), "it".into(), Duration::from_secs(10))
+        .await
+        .unwrap();
+
+    client.hello().await.unwrap();
+    let rsp = client
+        .transact(
+            &Login::new(&config.username, &config.password, None, None),
+            "it-login",
+        )
+        .await
+        .unwrap();
+    assert_eq!(rsp.result.code, ResultCode::CommandCompletedSuccessfully);
+
+    // Unique names, so that runs against the same server don't collide
+    let suffix = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let contact_id = format!("it{suffix}");
+    let domain = format!("instant-epp-{suffix}.{}", config.zone);
+    let host = format!("ns1.{domain}");
+    let new_host = format!("ns2.{domain}");
+
+    // Contact
+
+    let rsp = client
+        .transact(
+            &ContactCheck {
+                contact_ids: &[&contact_id],
+            },
+            "it-contact-check",
+        )
+        .await
+        .unwrap();
+    assert!(rsp.res_data().unwrap().list[0].id.available);
+
+    let address = Address::new(
+        &["58", "Orchid Road"],
+        "Paris",
+        Some("Paris"),
+        Some("392374"),
+        "FR".parse().unwrap(),
+    );
+    let postal_info = PostalInfo::new(InfoType::International, "John Doe", None, address);
+    let voice = Voice::new("+33.47237942");
+    let create = ContactCreate::new(
+        &contact_id,
+        "contact@example.com",
+        postal_info.clone(),
+        Some(voice.clone()),
+        "it-Passw0rd",
+    );
+    client.transact(&create, "it-contact-create").await.unwrap();
+
+    let rsp = client
+        .transact(
+            &ContactInfo::new(&contact_id).with_auth("it-Passw0rd"),
+            "it-contact-info",
+        )
+        .await
+        .unwrap();
+    assert_eq!(rsp.res_data().unwrap().id, contact_id);
+
+    let mut update = ContactUpdate::new(&contact_id);
+    update.set_info("updated@example.com", postal_info, voice, "it-Passw0rd2");
+    client.transact(&update, "it-contact-update").await.unwrap();
+
+    // Domain
+
+    let rsp = client
+        .transact(
+            &DomainCheck {
+                domains: &[&domain],
+            },
+            "it-domain-check",
+        )
+        .await
+        .unwrap();
+    assert!(rsp.res_data().unwrap().list[0].name.available);
+
+    let contacts = [
+        DomainContact {
+            contact_type: "admin".into(),
+            id: contact_id.as_str().into(),
+        },
+        DomainContact {
+            contact_type: "tech".into(),
+            id: contact_id.as_str().into(),
+        },
+    ];
+    let create = DomainCreate::new(
+        &domain,
+        Period::years(1).unwrap(),
+        None,
+        Some(&contact_id),
+        "it-Passw0rd",
+        Some(&contacts),
+    );
+    client.transact(&create, "it-domain-create").await.unwrap();
+
+    // Host, subordinate to the new domain
+
+    let rsp = client
+        .transact(&HostCheck { hosts: &[&host] }, "it-host-check")
+        .await
+        .unwrap();
+    assert!(rsp.res_data().unwrap().list[0].name.available);
+
+    let addresses = [IpAddr::from([192, 0, 2, 1])];
+    client
+        .transact(&HostCreate::new(&host, Some(&addresses)), "it-host-create")
+        .await
+        .unwrap();
+
+    let rsp = client
+        .transact(&host::HostInfo::new(&host), "it-host-info")
+        .await
+        .unwrap();
+    assert_eq!(rsp.res_data().unwrap().name, host);
+
+    let mut update = HostUpdate::new(&host);
+    let more = [IpAddr::from([0x2001, 0xdb8, 0, 0, 0, 0, 0, 1])];
+    update.add(HostAdd {
+        addresses: Some(&more),
+        statuses: None,
+    });
+    update.info(HostChangeInfo { name: &new_host });
+    client.transact(&update, "it-host-update").await.unwrap();
+
+    // Back to the domain
+
+    let ns = [HostInfo::Obj(HostObj {
+        name: new_host.as_str().into(),
+    })];
+    let mut update = DomainUpdate::new(&domain);
+    update.add(DomainAdd {
+        ns: Some(NameServers {
+            ns: (&ns[..]).into(),
+        }),
+        contacts: None,
+        statuses: None,
+    });
+    update.info(DomainChangeInfo {
+        registrant: None,
+        auth_info: Some(DomainAuthInfo::new("it-Passw0rd2")),
+    });
+    client.transact(&update, "it-domain-update").await.unwrap();
+
+    let rsp = client
+        .transact(
+            &DomainInfo::new(&domain).with_auth("it-Passw0rd2"),
+            "it-domain-info",
+        )
+        .await
+        .unwrap();
+    let info = rsp.res_data().unwrap();
+    assert_eq!(info.name, domain);
+
+    let expiry = info.expiring_at.unwrap().date_naive();
+    let renew = DomainRenew::new(&domain, expiry, Period::years(1).unwrap());
+    client.transact(&renew, "it-domain-renew").await.unwrap();
+
+    // Polling works whether or not the renewal queued any messages
+    let rsp = client.transact(&Poll, "it-poll").await.unwrap();
+    assert!(rsp.result.code.is_success());
+
+    // Clean up in dependency order
+
+    let mut update = DomainUpdate::new(&domain);
+    update.remove(DomainRemove {
+        ns: Some(NameServers {
+            ns: (&ns[..]).into(),
+        }),
+        contacts: None,
+        statuses: None,
+    });
+    client
+        .transact(&update, "it-domain-update-ns")
+        .await
+        .unwrap();
+
+    client
+        .transact(&HostDelete::new(&new_host), "it-host-delete")
+        .await
+        .unwrap();
+    client
+        .transact(&DomainDelete::new(&domain), "it-domain-delete")
+        .await
+        .unwrap();
+    client
+        .transact(&ContactDelete::new(&contact_id), "it-contact-delete")
+        .await
+        .unwrap();
+
+    let rsp = client.transact(&Logout, "it-logout").await.unwrap();
+    assert_eq!(
+        rsp.result.code,
+        ResultCode::CommandCompletedSuccessfullyEndingSession
+    );
+}